@@ -0,0 +1,269 @@
+//! Command-line front-end of `minislang`, compiling Slang files without a Rust driver.
+
+use minislang::shader_slang::{CompileTarget, DebugInfoLevel, OptimizationLevel};
+use minislang::{CompileOptions, Severity, SlangCompiler, target_extension};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "\
+Compiles Slang files with minislang.
+
+Usage: minislang [OPTIONS] <INPUT>...
+
+Each INPUT is either a Slang file, or a directory whose Slang files are compiled recursively. The
+output files replicate the directory hierarchy of the inputs, with the extension of the target.
+
+Options:
+  -t, --target <TARGET>  Compilation target: wgsl, ptx, cuda, metal, hlsl, dxil, or glsl. Can be
+                         repeated to compile for several targets [default: wgsl]
+  -o, --output <DIR>     Output directory [default: .]
+  -I <DIR>               Adds a directory to the search paths of imported modules
+  -D <NAME>[=VALUE]      Defines a preprocessor macro
+  -e, --entry <NAME>     Only compiles the entry point NAME
+  -g, --debug            Disables optimizations and emits debug information
+  -W error               Treats warnings as errors (also --warnings-as-errors)
+  -W no-<CODE>           Silences the warning with the numeric code CODE (also
+                         --disable-warning <CODE>)
+      --min-severity <SEVERITY>
+                         Only reports the diagnostics at least as severe as SEVERITY: note,
+                         warning, or error [default: note]
+      --check            Only checks that the inputs compile, without writing any output
+  -q, --quiet            Only reports errors
+  -h, --help             Prints this help
+
+Exit status: 0 if every input compiled successfully, 1 if some failed, 2 on invalid usage.
+";
+
+struct Args {
+    inputs: Vec<PathBuf>,
+    targets: Vec<CompileTarget>,
+    output: PathBuf,
+    search_paths: Vec<PathBuf>,
+    entry_point: Option<String>,
+    options: CompileOptions,
+    check: bool,
+    quiet: bool,
+}
+
+fn parse_target(name: &str) -> Option<CompileTarget> {
+    match name {
+        "wgsl" => Some(CompileTarget::Wgsl),
+        "ptx" => Some(CompileTarget::Ptx),
+        "cuda" | "cu" => Some(CompileTarget::CudaSource),
+        "metal" => Some(CompileTarget::Metal),
+        "hlsl" => Some(CompileTarget::Hlsl),
+        "dxil" => Some(CompileTarget::Dxil),
+        "glsl" => Some(CompileTarget::Glsl),
+        _ => None,
+    }
+}
+
+fn parse_warning_code(code: &str) -> Result<u32, String> {
+    code.parse()
+        .map_err(|_| format!("invalid warning code `{code}`"))
+}
+
+/// Parses the command-line arguments, or returns `None` if the help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        inputs: vec![],
+        targets: vec![],
+        output: PathBuf::from("."),
+        search_paths: vec![],
+        entry_point: None,
+        options: CompileOptions::default(),
+        check: false,
+        quiet: false,
+    };
+
+    while let Some(arg) = args.next() {
+        // Support both `-I dir` and `-Idir` for the short options taking a value.
+        let (flag, inline_value) = match arg.as_str() {
+            s if s.starts_with("--") => (s.to_string(), None),
+            s if s.len() > 2 && s.starts_with('-') => {
+                (s[..2].to_string(), Some(s[2..].to_string()))
+            }
+            s => (s.to_string(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("missing value for `{name}`"))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-t" | "--target" => {
+                let name = value(&flag)?;
+                let target =
+                    parse_target(&name).ok_or_else(|| format!("unsupported target `{name}`"))?;
+                parsed.targets.push(target);
+            }
+            "-o" | "--output" => parsed.output = value(&flag)?.into(),
+            "-I" => parsed.search_paths.push(value(&flag)?.into()),
+            "-D" => {
+                let define = value(&flag)?;
+                let (name, value) = define.split_once('=').unwrap_or((&define, "1"));
+                parsed.options = parsed.options.macro_define(name, value);
+            }
+            "-e" | "--entry" => parsed.entry_point = Some(value(&flag)?),
+            "-g" | "--debug" => {
+                parsed.options.optimization = OptimizationLevel::None;
+                parsed.options.debug_info = DebugInfoLevel::Maximal;
+            }
+            "-W" => {
+                let warning = value(&flag)?;
+                if warning == "error" {
+                    parsed.options.warnings_as_errors = true;
+                } else if let Some(code) = warning.strip_prefix("no-") {
+                    parsed
+                        .options
+                        .disabled_warnings
+                        .push(parse_warning_code(code)?);
+                } else {
+                    return Err(format!("unknown warning option `-W{warning}`"));
+                }
+            }
+            "--warnings-as-errors" => parsed.options.warnings_as_errors = true,
+            "--disable-warning" => {
+                let code = value(&flag)?;
+                parsed
+                    .options
+                    .disabled_warnings
+                    .push(parse_warning_code(&code)?);
+            }
+            "--min-severity" => {
+                parsed.options.min_severity = match value(&flag)?.as_str() {
+                    "note" => Severity::Note,
+                    "warning" => Severity::Warning,
+                    "error" => Severity::Error,
+                    severity => return Err(format!("unknown severity `{severity}`")),
+                };
+            }
+            "--check" => parsed.check = true,
+            "-q" | "--quiet" => parsed.quiet = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => parsed.inputs.push(arg.into()),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err("no input given".to_string());
+    }
+    if parsed.targets.is_empty() {
+        parsed.targets.push(CompileTarget::Wgsl);
+    }
+    Ok(Some(parsed))
+}
+
+/// The Slang files designated by `input`, paired with their path relative to the output
+/// directory.
+fn input_files(input: &Path) -> Vec<(PathBuf, PathBuf)> {
+    if input.is_dir() {
+        walkdir::WalkDir::new(input)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "slang"))
+            .map(|path| {
+                let relative_path = path
+                    .strip_prefix(input)
+                    .expect("walked paths are always inside the input directory")
+                    .to_path_buf();
+                (path, relative_path)
+            })
+            .collect()
+    } else {
+        let file_name = input.file_name().map(PathBuf::from).unwrap_or_default();
+        vec![(input.to_path_buf(), file_name)]
+    }
+}
+
+fn run(args: Args) -> ExitCode {
+    let compiler = SlangCompiler::new(args.search_paths);
+    let mut failures = 0;
+    let mut successes = 0;
+
+    for input in &args.inputs {
+        if !input.exists() {
+            eprintln!("error: input `{}` doesn’t exist", input.display());
+            failures += 1;
+            continue;
+        }
+
+        for (path, relative_path) in input_files(input) {
+            let Some(module) = path.to_str() else {
+                eprintln!("error: the path `{}` is not valid UTF-8", path.display());
+                failures += 1;
+                continue;
+            };
+
+            for &target in &args.targets {
+                let extension =
+                    target_extension(target).expect("parsed targets are always supported");
+                let target_path = args.output.join(&relative_path).with_extension(extension);
+                let start = Instant::now();
+                let result = compiler
+                    .compile_target_code(module, target, args.entry_point.as_deref(), &args.options)
+                    .and_then(|code| {
+                        if !args.check {
+                            if let Some(parent) = target_path.parent() {
+                                std::fs::create_dir_all(parent)?;
+                            }
+                            std::fs::write(&target_path, code)?;
+                        }
+                        Ok(())
+                    });
+
+                match result {
+                    Ok(()) => {
+                        successes += 1;
+                        if !args.quiet {
+                            let destination = if args.check {
+                                extension.to_string()
+                            } else {
+                                target_path.display().to_string()
+                            };
+                            println!(
+                                "compiled {} into {destination} ({:.2?})",
+                                path.display(),
+                                start.elapsed()
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        eprintln!("error: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    if !args.quiet {
+        println!("{successes} compilation(s) succeeded, {failures} failed");
+    }
+
+    if failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => run(args),
+        Ok(None) => {
+            print!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Helpers for compiling shaders offline, from the `build.rs` script of a downstream crate.
+//!
+//! ```ignore
+//! // build.rs
+//! use minislang::shader_slang::CompileTarget;
+//! use minislang::{CompileOptions, SlangCompiler};
+//!
+//! fn main() {
+//!     let compiler = SlangCompiler::new(vec!["shaders".into()]);
+//!     minislang::build_support::compile_shaders(
+//!         &compiler,
+//!         "shaders",
+//!         &[CompileTarget::Wgsl, CompileTarget::Ptx],
+//!         &CompileOptions::default(),
+//!     )
+//!     .unwrap();
+//! }
+//!
+//! // lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/slang_shaders.rs"));
+//! ```
+
+use crate::codegen::CodegenError;
+use crate::pack::{PackedEntryPoint, PackedModule, PackedTarget, SlangPack};
+use crate::{BuildReport, CompileOptions, SlangCompiler, SlangError, target_extension};
+use shader_slang::CompileTarget;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the Rust module generated by [`compile_shaders`] in `OUT_DIR`.
+pub const GENERATED_MODULE: &str = "slang_shaders.rs";
+
+/// Name of the Rust module generated by [`generate_rust_types`] in `OUT_DIR`.
+pub const GENERATED_TYPES: &str = "slang_types.rs";
+
+/// Name of the entry-point list written by [`write_entry_points`] in `OUT_DIR`.
+pub const ENTRY_POINTS_MANIFEST: &str = "slang_entry_points.txt";
+
+/// Name of the pack of precompiled shaders written by [`write_pack`] in `OUT_DIR`.
+pub const PRECOMPILED_PACK: &str = "slang_shaders.pack";
+
+/// Errors raised by [`compile_shaders`].
+#[derive(thiserror::Error, Debug)]
+pub enum BuildError {
+    #[error("the `OUT_DIR` environment variable isn’t set; is this running from a build script?")]
+    MissingOutDir,
+    #[error("failed to compile some shaders:\n{0}")]
+    Compilation(BuildReport),
+    #[error(transparent)]
+    Slang(#[from] SlangError),
+    #[error(transparent)]
+    Codegen(#[from] CodegenError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Compiles all the Slang files of `src_dir` for each of the `targets`, and generates a Rust
+/// module embedding the results.
+///
+/// The target code is written to `$OUT_DIR/slang/<extension>/`, and the generated module to
+/// `$OUT_DIR/slang_shaders.rs` (see [`GENERATED_MODULE`]), which is meant to be
+/// `include!`-ed by the crate. For each target, it contains a module named after the target’s
+/// file extension (see [`target_extension`]) with:
+/// - one `&[u8]` constant per compiled file, named after its path relative to `src_dir` in
+///   upper snake case (e.g. `FOO_BAR` for `foo/bar.slang`);
+/// - an `ALL` constant listing all the compiled files, as pairs of their path relative to
+///   `src_dir` (without extension) and their code.
+///
+/// This emits `cargo:rerun-if-changed` directives for `src_dir` and for every file the compiled
+/// modules depend on, so that the build script only runs again when shaders change. `compiler`
+/// must be configured (search paths, global macros…) so that the files of `src_dir` can be
+/// compiled.
+///
+/// Panics if two files map to the same constant name, e.g. `foo/bar.slang` and `foo_bar.slang`.
+pub fn compile_shaders(
+    compiler: &SlangCompiler,
+    src_dir: impl AsRef<Path>,
+    targets: &[CompileTarget],
+    options: &CompileOptions,
+) -> Result<PathBuf, BuildError> {
+    let src_dir = src_dir.as_ref();
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").ok_or(BuildError::MissingOutDir)?);
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+
+    let mut dependencies = BTreeSet::new();
+    let mut generated = String::from("// Generated by `minislang::build_support`. Do not edit.\n");
+    for &target in targets {
+        let extension = target_extension(target).ok_or(SlangError::UnsupportedTarget(target))?;
+        let report = compiler.compile_all(
+            target,
+            src_dir,
+            out_dir.join("slang").join(extension),
+            options,
+        )?;
+        if !report.is_success() {
+            return Err(BuildError::Compilation(report));
+        }
+
+        let mut all = String::new();
+        let mut names = HashMap::new();
+        writeln!(generated, "\npub mod {extension} {{").unwrap();
+        for file in &report.files {
+            let module = file.source.to_string_lossy();
+            dependencies.extend(compiler.dependencies(&module, target, options)?);
+
+            let relative_path = file
+                .source
+                .strip_prefix(src_dir)
+                .expect("compiled files are always inside the source directory")
+                .with_extension("");
+            let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+            let name = constant_name(&relative_path);
+            if let Some(previous) = names.insert(name.clone(), relative_path.clone()) {
+                panic!(
+                    "the shaders `{previous}.slang` and `{relative_path}.slang` both map to the \
+                     constant `{name}`, rename one of them"
+                );
+            }
+            writeln!(
+                generated,
+                "    /// The {extension} code compiled from `{relative_path}.slang`."
+            )
+            .unwrap();
+            writeln!(
+                generated,
+                "    pub const {name}: &[u8] = include_bytes!({:?});",
+                file.target.to_string_lossy()
+            )
+            .unwrap();
+            writeln!(all, "        ({relative_path:?}, {name}),").unwrap();
+        }
+        writeln!(
+            generated,
+            "    /// All the compiled files, by path relative to the source directory.\n    \
+             pub const ALL: &[(&str, &[u8])] = &[\n{all}    ];\n}}"
+        )
+        .unwrap();
+    }
+
+    for dependency in dependencies {
+        println!("cargo:rerun-if-changed={}", dependency.display());
+    }
+
+    let generated_path = out_dir.join(GENERATED_MODULE);
+    std::fs::write(&generated_path, generated)?;
+    Ok(generated_path)
+}
+
+/// Lists the entry points of all the Slang files of `src_dir` into
+/// `$OUT_DIR/slang_entry_points.txt` (see [`ENTRY_POINTS_MANIFEST`]).
+///
+/// This list is read by `#[shader(check_entry_points)]` from `slang-hal`’s `derive(Shader)` to
+/// check, at compile time, that each field of the derived struct names an existing entry point.
+/// Each line of the file contains the path of a module relative to `src_dir` (without extension,
+/// as given to `#[shader(module = "…")]`), followed by a colon and the space-separated names of
+/// its entry points.
+///
+/// The files are compiled for `target` in order to be reflected. Like [`compile_shaders`], this
+/// emits `cargo:rerun-if-changed` directives for `src_dir` and the dependencies of its files.
+pub fn write_entry_points(
+    compiler: &SlangCompiler,
+    src_dir: impl AsRef<Path>,
+    target: CompileTarget,
+    options: &CompileOptions,
+) -> Result<PathBuf, BuildError> {
+    let src_dir = src_dir.as_ref();
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").ok_or(BuildError::MissingOutDir)?);
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+
+    let mut dependencies = BTreeSet::new();
+    let mut manifest = String::new();
+    for (source, relative_path) in slang_sources(src_dir) {
+        let module = source
+            .to_str()
+            .ok_or_else(|| SlangError::NonUtf8Path(source.clone()))?;
+        let program = compiler.compile(module, target, None, options)?;
+        dependencies.extend(program.dependencies().iter().cloned());
+
+        let entry_points: Vec<_> = program
+            .reflection()?
+            .entry_points
+            .into_iter()
+            .map(|entry_point| entry_point.name)
+            .collect();
+        writeln!(manifest, "{relative_path}: {}", entry_points.join(" ")).unwrap();
+    }
+
+    for dependency in dependencies {
+        println!("cargo:rerun-if-changed={}", dependency.display());
+    }
+
+    let manifest_path = out_dir.join(ENTRY_POINTS_MANIFEST);
+    std::fs::write(&manifest_path, manifest)?;
+    Ok(manifest_path)
+}
+
+/// Compiles all the Slang files of `src_dir` for each of the `targets`, and bundles the results
+/// with their reflection metadata into `$OUT_DIR/slang_shaders.pack` (see [`PRECOMPILED_PACK`]).
+///
+/// Each file is packed under its path relative to `src_dir`, without extension (as given to
+/// `#[shader(module = "…")]`). The pack is meant to be embedded with `include_bytes!` and read
+/// with [`SlangPack::from_bytes`], e.g. by the `#[shader(precompiled)]` mode of `slang-hal`’s
+/// `derive(Shader)`, so that the shaders can be loaded without the Slang libraries at runtime.
+///
+/// Like [`compile_shaders`], this emits `cargo:rerun-if-changed` directives for `src_dir` and the
+/// dependencies of its files.
+pub fn write_pack(
+    compiler: &SlangCompiler,
+    src_dir: impl AsRef<Path>,
+    targets: &[CompileTarget],
+    options: &CompileOptions,
+) -> Result<PathBuf, BuildError> {
+    let src_dir = src_dir.as_ref();
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").ok_or(BuildError::MissingOutDir)?);
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+
+    let mut dependencies = BTreeSet::new();
+    let mut pack = SlangPack {
+        generator: concat!("minislang ", env!("CARGO_PKG_VERSION")).to_string(),
+        modules: vec![],
+    };
+    for (source, relative_path) in slang_sources(src_dir) {
+        let module = source
+            .to_str()
+            .ok_or_else(|| SlangError::NonUtf8Path(source.clone()))?;
+        let mut packed_targets = vec![];
+        for &target in targets {
+            target_extension(target).ok_or(SlangError::UnsupportedTarget(target))?;
+            let program = compiler.compile(module, target, None, options)?;
+            dependencies.extend(program.dependencies().iter().cloned());
+            packed_targets.push(PackedTarget {
+                target,
+                code: program.target_bytes(0)?,
+                entry_points: program
+                    .reflection()?
+                    .entry_points
+                    .iter()
+                    .map(PackedEntryPoint::from)
+                    .collect(),
+            });
+        }
+        pack.modules.push(PackedModule {
+            name: relative_path,
+            targets: packed_targets,
+        });
+    }
+
+    for dependency in dependencies {
+        println!("cargo:rerun-if-changed={}", dependency.display());
+    }
+
+    let pack_path = out_dir.join(PRECOMPILED_PACK);
+    pack.save(&pack_path)?;
+    Ok(pack_path)
+}
+
+/// The Slang files of `src_dir`, sorted, with their path relative to `src_dir` without extension.
+fn slang_sources(src_dir: &Path) -> Vec<(PathBuf, String)> {
+    let mut sources: Vec<_> = walkdir::WalkDir::new(src_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "slang"))
+        .collect();
+    sources.sort();
+    sources
+        .into_iter()
+        .map(|source| {
+            let relative_path = source
+                .strip_prefix(src_dir)
+                .expect("walked paths are always inside the source directory")
+                .with_extension("");
+            let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+            (source, relative_path)
+        })
+        .collect()
+}
+
+/// Converts a relative file path into an upper-snake-case Rust constant name.
+fn constant_name(relative_path: &str) -> String {
+    let name: String = relative_path
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name == "ALL" {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// Generates Rust structs with the same layout as the Slang structs `type_names` of `module`,
+/// compiled for `target`.
+///
+/// The structs (and the structs they contain) are generated by [`codegen::rust_structs`] with
+/// the given `derives` (e.g. [`codegen::DEFAULT_DERIVES`]) into `$OUT_DIR/slang_types.rs` (see
+/// [`GENERATED_TYPES`]), which is meant to be `include!`-ed by the crate. The types are looked up
+/// in the program linked from the entry points of `module`.
+///
+/// This emits `cargo:rerun-if-changed` directives for the files `module` depends on, so that the
+/// structs are generated again when they change.
+///
+/// [`codegen::rust_structs`]: crate::codegen::rust_structs
+/// [`codegen::DEFAULT_DERIVES`]: crate::codegen::DEFAULT_DERIVES
+pub fn generate_rust_types(
+    compiler: &SlangCompiler,
+    module: &str,
+    type_names: &[&str],
+    target: CompileTarget,
+    options: &CompileOptions,
+    derives: &[&str],
+) -> Result<PathBuf, BuildError> {
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").ok_or(BuildError::MissingOutDir)?);
+    let program = compiler.compile(module, target, None, options)?;
+    for dependency in program.dependencies() {
+        println!("cargo:rerun-if-changed={}", dependency.display());
+    }
+
+    let types = type_names
+        .iter()
+        .map(|name| program.type_reflection(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let generated = format!(
+        "// Generated by `minislang::build_support`. Do not edit.\n\n{}",
+        crate::codegen::rust_structs(&types, derives)?
+    );
+
+    let generated_path = out_dir.join(GENERATED_TYPES);
+    std::fs::write(&generated_path, generated)?;
+    Ok(generated_path)
+}
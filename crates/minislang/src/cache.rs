@@ -0,0 +1,83 @@
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Distinguishes the temporary files written concurrently by the threads of this process.
+static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A 64-bits FNV-1a hasher.
+///
+/// Unlike `DefaultHasher`, its output doesn’t depend on the Rust version, so cache keys stay
+/// valid across toolchain updates.
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Feeds the path and content of every Slang file (source or precompiled module) found recursively in `roots` into
+/// `hasher`, in a deterministic order.
+pub fn hash_sources<'a>(hasher: &mut impl Hasher, roots: impl IntoIterator<Item = &'a Path>) {
+    for root in roots {
+        let mut files: Vec<_> = walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "slang" || ext == "slang-module")
+            })
+            .collect();
+        files.sort();
+
+        for path in files {
+            hash_file(hasher, &path);
+        }
+    }
+}
+
+/// Feeds the path and content of the file at `path` into `hasher`.
+pub fn hash_file(hasher: &mut impl Hasher, path: &Path) {
+    hasher.write(path.as_os_str().as_encoded_bytes());
+    // Unreadable files are hashed as empty: they will fail compilation anyway.
+    let content = std::fs::read(path).unwrap_or_default();
+    hasher.write_usize(content.len());
+    hasher.write(&content);
+}
+
+fn entry_path(dir: &Path, key: u64, name: &str) -> PathBuf {
+    dir.join(format!("{key:016x}.{name}"))
+}
+
+pub fn read(dir: &Path, key: u64, name: &str) -> Option<Vec<u8>> {
+    std::fs::read(entry_path(dir, key, name)).ok()
+}
+
+pub fn write(dir: &Path, key: u64, name: &str, bytes: &[u8]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    // Write to a temporary file first so concurrent readers never observe a partial entry.
+    let path = entry_path(dir, key, name);
+    let tmp_id = NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = dir.join(format!(
+        "{key:016x}.{name}.{}.{tmp_id}.tmp",
+        std::process::id()
+    ));
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(tmp_path, path)
+}
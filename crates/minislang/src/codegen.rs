@@ -0,0 +1,234 @@
+//! Generation of Rust structs matching the layout of Slang structs.
+//!
+//! Each Slang struct is translated into a `#[repr(C)]` Rust struct whose fields have the same
+//! offsets, with explicit `_padN: [u8; N]` fields wherever the target layout leaves gaps (and at
+//! the end of the struct, up to its array stride). Vectors and matrices are translated into
+//! arrays (e.g. `float3` into `[f32; 3]`), and nested structs are generated too.
+//!
+//! Since they don’t have implicit padding, the generated structs can derive `bytemuck::Pod`. Each
+//! of them comes with a compile-time assertion checking its size. Half-precision fields are
+//! generated as `half::f16`, which requires the `half` crate.
+
+use crate::reflection::TypeReflection;
+use shader_slang::{ScalarType, TypeKind};
+use std::fmt::Write;
+
+/// The derives of the generated structs used by default.
+pub const DEFAULT_DERIVES: &[&str] = &[
+    "Copy",
+    "Clone",
+    "Debug",
+    "bytemuck::Pod",
+    "bytemuck::Zeroable",
+];
+
+/// Errors raised while generating Rust structs from Slang types.
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum CodegenError {
+    #[error("the slang type `{0}` isn’t a struct")]
+    NotAStruct(String),
+    #[error("the field `{field}` of `{ty}` has a type that can’t be stored in a Rust struct")]
+    UnsupportedField { ty: String, field: String },
+    #[error("the field `{field}` of `{ty}` has padding between its elements or rows")]
+    PaddedField { ty: String, field: String },
+    #[error("the field `{field}` of `{ty}` overlaps the previous field")]
+    Overlap { ty: String, field: String },
+    #[error("two different slang structs are named `{0}`")]
+    NameConflict(String),
+}
+
+/// Generates the Rust structs matching the layout of the given Slang structs (and of the structs
+/// they contain), with the given `derives`.
+///
+/// The reflection data of the Slang structs is given by
+/// [`SlangProgram::type_reflection`](crate::SlangProgram::type_reflection). Their layout depends
+/// on the compilation target, so the generated structs only match the layout of that target.
+pub fn rust_structs(types: &[TypeReflection], derives: &[&str]) -> Result<String, CodegenError> {
+    let mut generator = Generator {
+        derives: derives.join(", "),
+        generated: vec![],
+        code: String::new(),
+    };
+    for ty in types {
+        generator.generate(ty)?;
+    }
+    Ok(generator.code)
+}
+
+struct Generator {
+    derives: String,
+    /// The name and type of each struct generated so far.
+    generated: Vec<(String, TypeReflection)>,
+    code: String,
+}
+
+impl Generator {
+    /// Generates the struct `ty` if it wasn’t already, and returns its name.
+    fn generate(&mut self, ty: &TypeReflection) -> Result<String, CodegenError> {
+        let name = ty.name.clone().unwrap_or_default();
+        if ty.kind != TypeKind::Struct {
+            return Err(CodegenError::NotAStruct(name));
+        }
+        if let Some((_, generated)) = self.generated.iter().find(|(n, _)| *n == name) {
+            return if generated == ty {
+                Ok(name)
+            } else {
+                Err(CodegenError::NameConflict(name))
+            };
+        }
+        self.generated.push((name.clone(), ty.clone()));
+
+        let mut fields = String::new();
+        let mut cursor = 0;
+        let mut num_pads = 0;
+        let mut pad = |fields: &mut String, len: usize| {
+            writeln!(fields, "    pub _pad{num_pads}: [u8; {len}],").unwrap();
+            num_pads += 1;
+        };
+
+        let mut slang_fields: Vec<_> = ty.fields.iter().collect();
+        slang_fields.sort_by_key(|field| field.offset);
+        for field in slang_fields {
+            let unsupported = || CodegenError::UnsupportedField {
+                ty: name.clone(),
+                field: field.name.clone(),
+            };
+            if field.offset < cursor {
+                return Err(CodegenError::Overlap {
+                    ty: name.clone(),
+                    field: field.name.clone(),
+                });
+            }
+            if field.offset > cursor {
+                pad(&mut fields, field.offset - cursor);
+            }
+
+            let rust_type = self.rust_type(&field.ty).map_err(|e| match e {
+                FieldError::Unsupported => unsupported(),
+                FieldError::Padded => CodegenError::PaddedField {
+                    ty: name.clone(),
+                    field: field.name.clone(),
+                },
+                FieldError::Codegen(e) => e,
+            })?;
+            writeln!(fields, "    pub {}: {rust_type},", field_ident(&field.name)).unwrap();
+            cursor = field.offset + rust_size(&field.ty);
+        }
+        if ty.stride > cursor {
+            pad(&mut fields, ty.stride - cursor);
+        }
+
+        writeln!(
+            self.code,
+            "/// Mirror of the Slang struct `{name}`.\n\
+             #[derive({})]\n\
+             #[repr(C)]\n\
+             pub struct {name} {{\n{fields}}}\n\n\
+             const _: () = assert!(core::mem::size_of::<{name}>() == {});\n",
+            self.derives, ty.stride
+        )
+        .unwrap();
+        Ok(name)
+    }
+
+    /// The Rust type of a field of type `ty`, generating the structs it contains.
+    fn rust_type(&mut self, ty: &TypeReflection) -> Result<String, FieldError> {
+        match ty.kind {
+            TypeKind::Scalar => scalar_type(ty.scalar_type).ok_or(FieldError::Unsupported),
+            TypeKind::Vector => {
+                let scalar = scalar_type(ty.scalar_type).ok_or(FieldError::Unsupported)?;
+                let len = ty.element_count.ok_or(FieldError::Unsupported)?;
+                // NOTE: `size` excludes the trailing padding of vectors (e.g. of `float3` with
+                //       16-byte alignment), which is emitted as a padding field instead.
+                Ok(format!("[{scalar}; {len}]"))
+            }
+            TypeKind::Matrix => {
+                let scalar = scalar_type(ty.scalar_type).ok_or(FieldError::Unsupported)?;
+                let rows = ty.row_count.ok_or(FieldError::Unsupported)? as usize;
+                let columns = ty.column_count.ok_or(FieldError::Unsupported)? as usize;
+                let scalar_size = scalar_size(ty.scalar_type).ok_or(FieldError::Unsupported)?;
+                if ty.size != rows * columns * scalar_size {
+                    return Err(FieldError::Padded);
+                }
+                Ok(format!("[[{scalar}; {columns}]; {rows}]"))
+            }
+            TypeKind::Array => {
+                let element = ty.element_type.as_deref().ok_or(FieldError::Unsupported)?;
+                let len = ty.element_count.filter(|len| *len > 0);
+                let len = len.ok_or(FieldError::Unsupported)?;
+                if element.stride != rust_size(element) {
+                    return Err(FieldError::Padded);
+                }
+                let element = self.rust_type(element)?;
+                Ok(format!("[{element}; {len}]"))
+            }
+            TypeKind::Struct => self.generate(ty).map_err(FieldError::Codegen),
+            _ => Err(FieldError::Unsupported),
+        }
+    }
+}
+
+enum FieldError {
+    Unsupported,
+    Padded,
+    Codegen(CodegenError),
+}
+
+/// The size of the Rust type generated for `ty`.
+fn rust_size(ty: &TypeReflection) -> usize {
+    match ty.kind {
+        // Generated structs are padded up to their stride.
+        TypeKind::Struct => ty.stride,
+        TypeKind::Array => ty.element_type.as_deref().map_or(0, |element| {
+            element.stride * ty.element_count.unwrap_or_default()
+        }),
+        _ => ty.size,
+    }
+}
+
+/// The Rust type matching a Slang scalar type.
+fn scalar_type(ty: Option<ScalarType>) -> Option<String> {
+    let name = match ty? {
+        // Booleans are stored as 32-bit integers in buffers.
+        ScalarType::Bool | ScalarType::Uint32 => "u32",
+        ScalarType::Int32 => "i32",
+        ScalarType::Int64 => "i64",
+        ScalarType::Uint64 => "u64",
+        ScalarType::Float16 => "half::f16",
+        ScalarType::Float32 => "f32",
+        ScalarType::Float64 => "f64",
+        ScalarType::Int8 => "i8",
+        ScalarType::Uint8 => "u8",
+        ScalarType::Int16 => "i16",
+        ScalarType::Uint16 => "u16",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// The size in bytes of a Slang scalar type stored in a buffer.
+fn scalar_size(ty: Option<ScalarType>) -> Option<usize> {
+    match ty? {
+        ScalarType::Int8 | ScalarType::Uint8 => Some(1),
+        ScalarType::Int16 | ScalarType::Uint16 | ScalarType::Float16 => Some(2),
+        ScalarType::Bool | ScalarType::Int32 | ScalarType::Uint32 | ScalarType::Float32 => Some(4),
+        ScalarType::Int64 | ScalarType::Uint64 | ScalarType::Float64 => Some(8),
+        _ => None,
+    }
+}
+
+/// The Rust identifier of a field named `name`, escaping keywords.
+fn field_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override",
+        "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
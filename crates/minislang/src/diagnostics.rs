@@ -0,0 +1,242 @@
+//! Structured view over the diagnostics emitted by the Slang compiler.
+
+use std::fmt;
+
+/// The severity of a single [`Diagnostic`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Additional information attached to a previous diagnostic.
+    Note,
+    /// A warning that doesn’t prevent compilation.
+    Warning,
+    /// An error that prevents compilation.
+    Error,
+    /// An unrecoverable error (including internal compiler errors) that aborted compilation.
+    Fatal,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Note => write!(f, "note"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+            Self::Fatal => write!(f, "fatal error"),
+        }
+    }
+}
+
+/// A single warning or error emitted by the Slang compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The source file the diagnostic refers to, if any.
+    pub file: Option<String>,
+    /// The 1-based line the diagnostic refers to, if any.
+    pub line: Option<u32>,
+    /// The 1-based column the diagnostic refers to, if any.
+    pub column: Option<u32>,
+    /// The severity of the diagnostic.
+    pub severity: Severity,
+    /// The numeric Slang diagnostic code, if any.
+    pub code: Option<u32>,
+    /// The human-readable message of the diagnostic.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}")?;
+            if let Some(line) = self.line {
+                write!(f, ":{line}")?;
+                if let Some(column) = self.column {
+                    write!(f, ":{column}")?;
+                }
+            }
+            write!(f, ": ")?;
+        }
+
+        write!(f, "{}", self.severity)?;
+        if let Some(code) = self.code {
+            write!(f, " {code}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The list of diagnostics emitted by the Slang compiler.
+///
+/// The raw diagnostic text is kept alongside its parsed form, and is what gets displayed. Lines
+/// of the raw text that can’t be attributed to any diagnostic (e.g. the source excerpts Slang
+/// prints after each diagnostic) are only available through [`Diagnostics::text`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+    text: String,
+}
+
+impl Diagnostics {
+    /// Parses the diagnostic text emitted by the Slang compiler.
+    ///
+    /// Slang reports each diagnostic as `file(line): severity code: message`, optionally followed
+    /// by the offending source line and a caret line pointing at the column.
+    pub fn parse(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let mut entries: Vec<Diagnostic> = vec![];
+
+        for line in text.lines() {
+            if let Some(diagnostic) = parse_header(line) {
+                entries.push(diagnostic);
+            } else if let Some(last) = entries.last_mut()
+                && last.column.is_none()
+                && line.trim_start().starts_with('^')
+            {
+                let caret = line.find('^').unwrap_or_default();
+                last.column = Some(line[..caret].chars().count() as u32 + 1);
+            }
+        }
+
+        Self { entries, text }
+    }
+
+    /// The raw diagnostic text, as emitted by the Slang compiler.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// All the parsed diagnostics, in the order they were emitted.
+    pub fn entries(&self) -> &[Diagnostic] {
+        &self.entries
+    }
+
+    /// Iterates through all the parsed diagnostics.
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.entries.iter()
+    }
+
+    /// Iterates through the diagnostics with a [`Severity::Error`] or [`Severity::Fatal`]
+    /// severity.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|d| d.severity >= Severity::Error)
+    }
+
+    /// Iterates through the diagnostics with a [`Severity::Warning`] severity.
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|d| d.severity == Severity::Warning)
+    }
+
+    /// Does this contain any error?
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Is this free of any parsed diagnostic?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes the diagnostics with a severity lower than `min_severity`, along with the lines of
+    /// the raw text following them (e.g. their source excerpts).
+    pub fn with_min_severity(self, min_severity: Severity) -> Self {
+        if self.entries.iter().all(|d| d.severity >= min_severity) {
+            return self;
+        }
+
+        let mut keep = true;
+        let mut text = String::new();
+        for line in self.text.lines() {
+            if let Some(diagnostic) = parse_header(line) {
+                keep = diagnostic.severity >= min_severity;
+            }
+            if keep {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+        Self::parse(text)
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl From<shader_slang::Error> for Diagnostics {
+    fn from(error: shader_slang::Error) -> Self {
+        Self::parse(error.to_string())
+    }
+}
+
+/// Parses a diagnostic header, i.e., `[file(line[,column]): ]severity[ code]: message`.
+fn parse_header(line: &str) -> Option<Diagnostic> {
+    let (file, line_number, column, rest) = match split_location(line) {
+        Some((file, line_number, column, rest)) => {
+            (Some(file.to_string()), line_number, column, rest)
+        }
+        None => (None, None, None, line),
+    };
+    let (severity, rest) = parse_severity(rest)?;
+    let (code, message) = rest.split_once(':')?;
+    let code = code.trim();
+    let code = if code.is_empty() {
+        None
+    } else {
+        Some(code.parse().ok()?)
+    };
+
+    Some(Diagnostic {
+        file,
+        line: line_number,
+        column,
+        severity,
+        code,
+        message: message.trim().to_string(),
+    })
+}
+
+/// Splits `file(line[,column]): rest` into its components.
+fn split_location(line: &str) -> Option<(&str, Option<u32>, Option<u32>, &str)> {
+    let end = line.find("): ")?;
+    let start = line[..end].rfind('(')?;
+    let file = &line[..start];
+    let rest = &line[end + 3..];
+    let mut numbers = line[start + 1..end]
+        .split(',')
+        .map(|n| n.trim().parse::<u32>());
+    let line_number = numbers.next()?.ok()?;
+    let column = numbers.next().transpose().ok()?;
+
+    if file.is_empty() || numbers.next().is_some() {
+        return None;
+    }
+
+    // Slang reports a line of 0 for diagnostics without a meaningful source location.
+    let line_number = (line_number != 0).then_some(line_number);
+    Some((file, line_number, column, rest))
+}
+
+fn parse_severity(rest: &str) -> Option<(Severity, &str)> {
+    const SEVERITIES: [(&str, Severity); 5] = [
+        ("fatal error", Severity::Fatal),
+        ("internal error", Severity::Fatal),
+        ("error", Severity::Error),
+        ("warning", Severity::Warning),
+        ("note", Severity::Note),
+    ];
+
+    SEVERITIES.iter().find_map(|(prefix, severity)| {
+        let rest = rest.strip_prefix(prefix)?;
+        (rest.starts_with(' ') || rest.starts_with(':')).then_some((*severity, rest))
+    })
+}
@@ -1,29 +1,346 @@
 use include_dir::Dir;
 pub use shader_slang;
 use shader_slang::{
-    CompileTarget, CompilerOptions, Downcast, GlobalSession, OptimizationLevel, SessionDesc,
-    TargetDesc,
+    CapabilityID, CompileTarget, CompilerOptions, DebugInfoLevel, Downcast, GlobalSession,
+    LayoutRules, MatrixLayoutMode, OptimizationLevel, ProfileID, SessionDesc, TargetDesc,
 };
 pub use shader_slang_sys;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::CString;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use tempfile::TempDir;
+use std::time::Instant;
 
-mod dir;
+pub use diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use report::{BuildReport, FileReport};
+#[cfg(feature = "watch")]
+pub use watch::{SlangWatcher, WatchEvent, WatchedModule};
+
+pub mod build_support;
+mod cache;
+pub mod codegen;
+mod diagnostics;
+pub mod pack;
+mod precompiled;
+pub mod reflection;
+mod report;
+mod source;
+mod specialize;
+mod support;
+mod vfs;
+#[cfg(feature = "watch")]
+mod watch;
+
+/// Errors raised while compiling Slang modules.
+///
+/// Whenever available, the diagnostics emitted by the Slang compiler are attached to the error.
+#[derive(thiserror::Error, Debug)]
+pub enum SlangError {
+    #[error("failed to create the slang compilation session")]
+    SessionCreation,
+    #[error("failed to load the slang module `{module}`:\n{diagnostics}")]
+    ModuleLoad {
+        module: String,
+        diagnostics: Diagnostics,
+    },
+    #[error("entry point `{entry_point}` not found in the slang module `{module}`")]
+    EntryPointNotFound { module: String, entry_point: String },
+    #[error("type `{type_name}` not found in the slang module `{module}`")]
+    TypeNotFound { module: String, type_name: String },
+    #[error("failed to specialize the slang module `{module}`:\n{diagnostics}")]
+    Specialization {
+        module: String,
+        diagnostics: Diagnostics,
+    },
+    #[error("failed to link the slang module `{module}`:\n{diagnostics}")]
+    Link {
+        module: String,
+        diagnostics: Diagnostics,
+    },
+    #[error("failed to reflect the layout of the slang module `{module}`:\n{diagnostics}")]
+    Layout {
+        module: String,
+        diagnostics: Diagnostics,
+    },
+    #[error("failed to generate the target code of the slang module `{module}`:\n{diagnostics}")]
+    TargetCode {
+        module: String,
+        diagnostics: Diagnostics,
+    },
+    #[error("failed to serialize the slang module `{module}` (error code {code:#x})")]
+    Serialization { module: String, code: i32 },
+    #[error("the target code of the slang module `{module}` is not valid UTF-8")]
+    NonUtf8TargetCode { module: String },
+    #[error("invalid slang pack: {0}")]
+    InvalidPack(String),
+    #[error("the slang pack doesn’t contain the module `{module}` compiled for {target:?}")]
+    NotInPack {
+        module: String,
+        target: CompileTarget,
+    },
+    #[error("unknown slang profile `{0}`")]
+    UnknownProfile(String),
+    #[error("unknown slang capability `{0}`")]
+    UnknownCapability(String),
+    #[error("unsupported compilation target: {0:?}")]
+    UnsupportedTarget(CompileTarget),
+    #[error("the path `{0}` is not valid UTF-8")]
+    NonUtf8Path(PathBuf),
+    #[error("the slang compilation thread panicked")]
+    CompilationPanicked,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl SlangError {
+    /// The diagnostics emitted by the Slang compiler alongside this error, if any.
+    pub fn diagnostics(&self) -> Option<&Diagnostics> {
+        match self {
+            Self::ModuleLoad { diagnostics, .. }
+            | Self::Specialization { diagnostics, .. }
+            | Self::Link { diagnostics, .. }
+            | Self::Layout { diagnostics, .. }
+            | Self::TargetCode { diagnostics, .. } => Some(diagnostics),
+            _ => None,
+        }
+    }
+
+    /// Removes the diagnostics attached to this error with a severity lower than `min_severity`.
+    pub fn with_min_severity(mut self, min_severity: Severity) -> Self {
+        match &mut self {
+            Self::ModuleLoad { diagnostics, .. }
+            | Self::Specialization { diagnostics, .. }
+            | Self::Link { diagnostics, .. }
+            | Self::Layout { diagnostics, .. }
+            | Self::TargetCode { diagnostics, .. } => {
+                *diagnostics = std::mem::take(diagnostics).with_min_severity(min_severity);
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+/// Options controlling the code generated by [`SlangCompiler::compile`] and its variants.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompileOptions {
+    /// The optimization level. Defaults to [`OptimizationLevel::Maximal`].
+    pub optimization: OptimizationLevel,
+    /// The amount of debug information to emit. Defaults to [`DebugInfoLevel::None`].
+    pub debug_info: DebugInfoLevel,
+    /// The default layout of matrices. Defaults to [`MatrixLayoutMode::RowMajor`].
+    pub matrix_layout: MatrixLayoutMode,
+    /// Preprocessor macros defined in addition to the global macros of the compiler (see
+    /// [`SlangCompiler::set_global_macro`]).
+    pub macro_defines: Vec<(String, String)>,
+    /// Types substituted, in order, for the generic parameters of the compiled entry points.
+    ///
+    /// This lets a single generic kernel (e.g. `void add<T : IArithmetic>(…)`) be instantiated for
+    /// different types from the Rust side. Types are given by name (e.g. `float`, `double`, or
+    /// `MyStruct`), and can be built-in types as well as types declared by the compiled module or
+    /// its imports.
+    pub type_arguments: Vec<String>,
+    /// Makes every warning fail the compilation, as an error. Defaults to `false`.
+    pub warnings_as_errors: bool,
+    /// The numeric codes of the warnings to silence (see [`Diagnostic::code`]).
+    pub disabled_warnings: Vec<u32>,
+    /// The diagnostics with a lower severity are removed from the errors returned by the
+    /// compiler. Defaults to [`Severity::Note`], i.e., all the diagnostics are reported.
+    pub min_severity: Severity,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            optimization: OptimizationLevel::Maximal,
+            debug_info: DebugInfoLevel::None,
+            matrix_layout: MatrixLayoutMode::RowMajor,
+            macro_defines: vec![],
+            type_arguments: vec![],
+            warnings_as_errors: false,
+            disabled_warnings: vec![],
+            min_severity: Severity::Note,
+        }
+    }
+}
+
+impl CompileOptions {
+    /// Options suited for development: no optimization (which preserves the names of variables
+    /// and functions in the generated code) and maximal debug information.
+    pub fn debug() -> Self {
+        Self {
+            optimization: OptimizationLevel::None,
+            debug_info: DebugInfoLevel::Maximal,
+            ..Default::default()
+        }
+    }
+
+    /// Defines the preprocessor macro `name` with the given `value`.
+    pub fn macro_define(mut self, name: impl ToString, value: impl ToString) -> Self {
+        self.macro_defines
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Silences the warning with the numeric code `code` (see [`Self::disabled_warnings`]).
+    pub fn disable_warning(mut self, code: u32) -> Self {
+        self.disabled_warnings.push(code);
+        self
+    }
+
+    /// Appends `ty` to the type arguments of the generic entry points (see
+    /// [`Self::type_arguments`]).
+    pub fn type_argument(mut self, ty: impl ToString) -> Self {
+        self.type_arguments.push(ty.to_string());
+        self
+    }
+}
+
+/// The file extension of precompiled Slang modules (see [`SlangCompiler::precompile_module`]).
+pub const PRECOMPILED_MODULE_EXTENSION: &str = "slang-module";
+
+/// The profile and capabilities selected for a compilation target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TargetConfig {
+    target: CompileTarget,
+    profile: Option<String>,
+    capabilities: Vec<String>,
+}
+
+impl Hash for TargetConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.target as i64).hash(state);
+        self.profile.hash(state);
+        self.capabilities.hash(state);
+    }
+}
+
+/// Name of the on-disk cache entries holding target code.
+pub const CODE_CACHE_ENTRY: &str = "code";
 
 // TODO: refactor to a separate crate? Or import slang-hal?
 pub struct SlangCompiler {
     session: GlobalSession,
     search_paths: Vec<PathBuf>,
     global_macros: Vec<(String, String)>,
-    tmp: TempDir,
+    target_configs: Vec<TargetConfig>,
+    downstream_args: Vec<(String, Vec<String>)>,
+    file_system: vfs::MemoryFileSystem,
+    cache_dir: Option<PathBuf>,
+    sessions: RefCell<HashMap<SessionKey, shader_slang::Session>>,
+}
+
+/// The options a compilation session depends on.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SessionKey {
+    target: i64,
+    optimization: OptimizationLevel,
+    debug_info: DebugInfoLevel,
+    matrix_layout: MatrixLayoutMode,
+    macro_defines: Vec<(String, String)>,
+    warnings_as_errors: bool,
+    disabled_warnings: Vec<u32>,
+}
+
+impl SessionKey {
+    fn new(target: CompileTarget, options: &CompileOptions) -> Self {
+        Self {
+            target: target as i64,
+            optimization: options.optimization,
+            debug_info: options.debug_info,
+            matrix_layout: options.matrix_layout,
+            macro_defines: options.macro_defines.clone(),
+            warnings_as_errors: options.warnings_as_errors,
+            disabled_warnings: options.disabled_warnings.clone(),
+        }
+    }
 }
 
 pub struct SlangProgram {
     #[allow(dead_code)]
     session: shader_slang::Session,
     program: shader_slang::ComponentType,
+    module: String,
+    dependencies: Vec<PathBuf>,
+    min_severity: Severity,
+}
+
+impl SlangProgram {
+    /// Generates the code of this program for the target at index `target` of its session.
+    ///
+    /// This shadows [`shader_slang::ComponentType::target_code`] to attach the name of the
+    /// compiled module to any error.
+    pub fn target_code(&self, target: i64) -> Result<shader_slang::Blob, SlangError> {
+        self.program.target_code(target).map_err(|e| {
+            SlangError::TargetCode {
+                module: self.module.clone(),
+                diagnostics: e.into(),
+            }
+            .with_min_severity(self.min_severity)
+        })
+    }
+
+    /// The code of this program for the target at index `target` of its session, as raw bytes.
+    ///
+    /// This is suitable for both binary (e.g. SPIR-V, DXIL) and textual (e.g. WGSL, PTX) targets.
+    pub fn target_bytes(&self, target: i64) -> Result<Vec<u8>, SlangError> {
+        Ok(self.target_code(target)?.as_slice().to_vec())
+    }
+
+    /// The code of this program for the textual target at index `target` of its session.
+    ///
+    /// Fails with [`SlangError::NonUtf8TargetCode`] for binary targets. Use [`Self::target_bytes`]
+    /// for those.
+    pub fn target_text(&self, target: i64) -> Result<String, SlangError> {
+        String::from_utf8(self.target_bytes(target)?).map_err(|_| SlangError::NonUtf8TargetCode {
+            module: self.module.clone(),
+        })
+    }
+
+    /// The files this program was compiled from. See [`SlangCompiler::dependencies`].
+    pub fn dependencies(&self) -> &[PathBuf] {
+        &self.dependencies
+    }
+
+    /// The reflection data of this program, as laid out by its compilation target.
+    pub fn reflection(&self) -> Result<reflection::ProgramReflection, SlangError> {
+        let layout = self.layout()?;
+        Ok(reflection::ProgramReflection {
+            entry_points: layout
+                .entry_points()
+                .map(reflection::EntryPointReflection::new)
+                .collect(),
+        })
+    }
+
+    /// The reflection data of the type named `name`, as laid out by the compilation target.
+    ///
+    /// This gives access to the layout of the structs that aren’t the type of any entry-point
+    /// parameter, e.g. to compare it with the layout of their Rust counterpart.
+    pub fn type_reflection(&self, name: &str) -> Result<reflection::TypeReflection, SlangError> {
+        let layout = self.layout()?;
+        let ty = layout
+            .find_type_by_name(name)
+            .and_then(|ty| layout.type_layout(ty, LayoutRules::Default))
+            .ok_or_else(|| SlangError::TypeNotFound {
+                module: self.module.clone(),
+                type_name: name.to_string(),
+            })?;
+        Ok(reflection::TypeReflection::new(ty))
+    }
+
+    fn layout(&self) -> Result<&shader_slang::reflection::Shader, SlangError> {
+        self.program.layout(0).map_err(|e| {
+            SlangError::Layout {
+                module: self.module.clone(),
+                diagnostics: e.into(),
+            }
+            .with_min_severity(self.min_severity)
+        })
+    }
 }
 
 impl Deref for SlangProgram {
@@ -39,144 +356,698 @@ impl SlangCompiler {
             session: GlobalSession::new().unwrap(),
             search_paths,
             global_macros: Vec::new(),
-            tmp: tempfile::tempdir().unwrap(),
+            target_configs: Vec::new(),
+            downstream_args: Vec::new(),
+            file_system: vfs::MemoryFileSystem::default(),
+            cache_dir: None,
+            sessions: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Enables (or disables, if `None`) the on-disk cache of compiled target code.
+    ///
+    /// When enabled, [`Self::compile_target_code`] (and everything built on top of it) stores the
+    /// generated code in `cache_dir` and reuses it as long as the Slang sources found in the search
+    /// paths (including the directories and precompiled modules registered with [`Self::add_dir`]
+    /// and [`Self::add_precompiled_module`]), the macros, the entry point, and the target are
+    /// unchanged.
+    pub fn set_cache_dir(&mut self, cache_dir: Option<PathBuf>) {
+        self.cache_dir = cache_dir;
+    }
+
+    /// The directory of the on-disk compilation cache, if enabled.
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    /// The key identifying the compilation of `module` in the on-disk cache, or `None` if the
+    /// cache is disabled.
+    ///
+    /// The key is a hash of the Slang version, of all the Slang sources reachable from the search
+    /// paths, of the module file itself and the sources next to it (if `module` is a path), and
+    /// of all the compilation options. Note that computing it reads every Slang file from these
+    /// directories.
+    ///
+    /// This is also `None` if a module resolver is set (see [`Self::set_module_resolver`]).
+    pub fn cache_key(
+        &self,
+        module: &str,
+        target: CompileTarget,
+        entry_point: Option<&str>,
+        options: &CompileOptions,
+    ) -> Option<u64> {
+        self.cache_dir.as_ref()?;
+        if self.file_system.has_resolver() {
+            return None;
+        }
+
+        let mut hasher = cache::StableHasher::default();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        self.session.build_tag_string().hash(&mut hasher);
+        module.hash(&mut hasher);
+        (target as i64).hash(&mut hasher);
+        entry_point.hash(&mut hasher);
+        options.hash(&mut hasher);
+        self.global_macros.hash(&mut hasher);
+        self.target_config(target).hash(&mut hasher);
+        self.downstream_args.hash(&mut hasher);
+        self.search_paths.hash(&mut hasher);
+        cache::hash_sources(&mut hasher, self.search_paths.iter().map(|p| p.as_path()));
+        self.file_system.hash_sources(&mut hasher);
+        let module_path = Path::new(module);
+        if module_path.is_file() {
+            // NOTE: Slang also resolves the imports of a module file from its directory.
+            let dir = module_path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            cache::hash_sources(&mut hasher, [dir]);
+            cache::hash_file(&mut hasher, module_path);
         }
+        Some(hasher.finish())
     }
 
+    /// Reads the cache entry `name` associated to `key`, if it exists.
+    ///
+    /// Besides target code, this lets callers cache any data derived from a compilation (e.g.
+    /// reflection data) under the same key.
+    pub fn read_cache(&self, key: u64, name: &str) -> Option<Vec<u8>> {
+        cache::read(self.cache_dir.as_ref()?, key, name)
+    }
+
+    /// Writes the cache entry `name` associated to `key`.
+    ///
+    /// This is a no-op if the cache is disabled.
+    pub fn write_cache(&self, key: u64, name: &str, bytes: &[u8]) -> Result<(), SlangError> {
+        if let Some(cache_dir) = &self.cache_dir {
+            cache::write(cache_dir, key, name, bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles `module` and returns its target code, reusing the on-disk cache if enabled (see
+    /// [`Self::set_cache_dir`]).
+    pub fn compile_target_code(
+        &self,
+        module: &str,
+        target: CompileTarget,
+        entry_point: Option<&str>,
+        options: &CompileOptions,
+    ) -> Result<Vec<u8>, SlangError> {
+        let key = self.cache_key(module, target, entry_point, options);
+        if let Some(code) = key.and_then(|key| self.read_cache(key, CODE_CACHE_ENTRY)) {
+            return Ok(code);
+        }
+
+        let program = self.compile(module, target, entry_point, options)?;
+        let code = program.target_bytes(0)?;
+        if let Some(key) = key {
+            self.write_cache(key, CODE_CACHE_ENTRY, &code)?;
+        }
+        Ok(code)
+    }
+
+    /// Whether Slang can generate code for `target` on this machine.
+    ///
+    /// This also checks that the downstream compiler required by `target` is available, e.g. a
+    /// C++ compiler for [`CompileTarget::ShaderSharedLibrary`].
+    pub fn supports_target(&self, target: CompileTarget) -> bool {
+        support::check_compile_target_support(&self.session, target)
+    }
+
+    /// The directories searched for imported modules.
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Registers the Slang modules embedded in `dir`, making them importable as if `dir` was one of
+    /// the search paths.
+    ///
+    /// The files are served to Slang straight from memory, without touching the file system.
     pub fn add_dir(&mut self, dir: Dir<'static>) {
-        dir::write_dir_to_disk(&self.tmp, &dir);
+        self.file_system.add_dir(&dir);
+        self.sessions.get_mut().clear();
+    }
+
+    /// Registers the precompiled Slang module `ir` (see [`Self::precompile_module`]), making it
+    /// importable as if it was in one of the search paths.
+    ///
+    /// `name` is the path of the module relative to the search paths, without extension (e.g.
+    /// `linalg/matrix` for `import linalg.matrix;`). Like the directories registered with
+    /// [`Self::add_dir`], the module is served to Slang straight from memory.
+    pub fn add_precompiled_module(&mut self, name: &str, ir: impl Into<Cow<'static, [u8]>>) {
+        let path = format!("{name}.{PRECOMPILED_MODULE_EXTENSION}");
+        self.file_system.add_file(&path, ir.into());
+        self.sessions.get_mut().clear();
+    }
+
+    /// Compiles `module` into Slang IR, which can be shipped instead of its source (e.g. for
+    /// closed-source shader libraries, or to skip the front-end compilation of large modules).
+    ///
+    /// The IR is independent of `target`, which only configures the compilation session. Slang
+    /// picks up precompiled modules named `<module>.slang-module` found in the search paths when
+    /// resolving imports, so the result can either be written next to the module source, or be
+    /// registered with [`Self::add_precompiled_module`].
+    pub fn precompile_module(
+        &self,
+        module: &str,
+        target: CompileTarget,
+        options: &CompileOptions,
+    ) -> Result<Vec<u8>, SlangError> {
+        let session = self.session(target, options)?;
+        let loaded = load_module(&session, module, options)?;
+        precompiled::serialize(&loaded).map_err(|code| SlangError::Serialization {
+            module: module.to_string(),
+            code,
+        })
+    }
+
+    /// Selects the Slang profile used when generating code for `target` (e.g. `sm_6_6` for HLSL
+    /// or `spirv_1_5` for SPIR-V), or resets it to the default profile of the target if `None`.
+    ///
+    /// By default, Slang picks a lowest-common-denominator profile for each target. Fails if Slang
+    /// doesn’t know `profile`.
+    pub fn set_target_profile(
+        &mut self,
+        target: CompileTarget,
+        profile: Option<&str>,
+    ) -> Result<(), SlangError> {
+        if let Some(profile) = profile {
+            self.find_profile(profile)?;
+        }
+        self.target_config_mut(target).profile = profile.map(|p| p.to_string());
+        Ok(())
+    }
+
+    /// Enables the Slang capability `capability` when generating code for `target` (e.g.
+    /// `cuda_sm_8_0` for PTX using tensor-core intrinsics, or `metallib_3_0` for Metal).
+    ///
+    /// Fails if Slang doesn’t know `capability`.
+    pub fn add_target_capability(
+        &mut self,
+        target: CompileTarget,
+        capability: &str,
+    ) -> Result<(), SlangError> {
+        self.find_capability(capability)?;
+        self.target_config_mut(target)
+            .capabilities
+            .push(capability.to_string());
+        Ok(())
+    }
+
+    /// Forwards `args` to the downstream compiler named `compiler` whenever Slang invokes it.
+    ///
+    /// Slang relies on downstream compilers for some targets, e.g. `nvrtc` to generate PTX
+    /// (accepting arguments like `--use_fast_math` or `--gpu-architecture=compute_80`), `dxc` for
+    /// DXIL, or `metal` for Metal libraries. The arguments are appended to the ones given by
+    /// previous calls for the same compiler.
+    pub fn add_downstream_args(
+        &mut self,
+        compiler: &str,
+        args: impl IntoIterator<Item = impl ToString>,
+    ) {
+        self.sessions.get_mut().clear();
+        let args = args.into_iter().map(|arg| arg.to_string());
+        match self.downstream_args.iter_mut().find(|(c, _)| c == compiler) {
+            Some((_, existing)) => existing.extend(args),
+            None => self
+                .downstream_args
+                .push((compiler.to_string(), args.collect())),
+        }
+    }
+
+    fn find_profile(&self, name: &str) -> Result<ProfileID, SlangError> {
+        let profile = self.session.find_profile(name);
+        if profile.is_unknown() {
+            Err(SlangError::UnknownProfile(name.to_string()))
+        } else {
+            Ok(profile)
+        }
+    }
+
+    fn find_capability(&self, name: &str) -> Result<CapabilityID, SlangError> {
+        let capability = self.session.find_capability(name);
+        if capability.is_unknown() {
+            Err(SlangError::UnknownCapability(name.to_string()))
+        } else {
+            Ok(capability)
+        }
+    }
+
+    fn target_config(&self, target: CompileTarget) -> Option<&TargetConfig> {
+        self.target_configs.iter().find(|c| c.target == target)
+    }
+
+    fn target_config_mut(&mut self, target: CompileTarget) -> &mut TargetConfig {
+        self.sessions.get_mut().clear();
+        let index = match self.target_configs.iter().position(|c| c.target == target) {
+            Some(index) => index,
+            None => {
+                self.target_configs.push(TargetConfig {
+                    target,
+                    profile: None,
+                    capabilities: vec![],
+                });
+                self.target_configs.len() - 1
+            }
+        };
+        &mut self.target_configs[index]
+    }
+
+    /// Sets a callback providing the source of the modules (and included files) that can’t be
+    /// found in the search paths nor in the registered directories.
+    ///
+    /// The callback is given the path of the requested file relative to the search paths (e.g.
+    /// `procedural/noise.slang` for `import procedural.noise;`), and returns its content, or `None`
+    /// if it doesn’t provide it. This allows loading shaders from databases, asset pipelines, or
+    /// generating them on the fly. Slang may call it for files that don’t exist (e.g. to look for
+    /// precompiled `.slang-module` files), and only once per file and session: call
+    /// [`Self::clear_session_cache`] if the provided sources change.
+    ///
+    /// Because the content of the resolved files can’t be known in advance, the on-disk cache is
+    /// disabled while a resolver is set.
+    pub fn set_module_resolver(
+        &mut self,
+        resolver: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) {
+        self.file_system.set_resolver(Some(Box::new(resolver)));
+        self.sessions.get_mut().clear();
+    }
+
+    /// Removes the callback set with [`Self::set_module_resolver`].
+    pub fn clear_module_resolver(&mut self) {
+        self.file_system.set_resolver(None);
+        self.sessions.get_mut().clear();
     }
 
     pub fn set_global_macro(&mut self, name: impl ToString, value: impl ToString) {
+        self.sessions.get_mut().clear();
         self.global_macros
             .push((name.to_string(), value.to_string()));
     }
 
-    pub fn compile(
+    /// The files `module` depends on, i.e., its own source file and all the files it transitively
+    /// imports or includes.
+    ///
+    /// This only loads the module, without linking nor generating code. Build scripts can use it
+    /// to emit accurate `cargo:rerun-if-changed` directives, and hot-reloading systems to only
+    /// watch the relevant files. Files embedded with [`Self::add_dir`] are not on disk and are
+    /// omitted.
+    pub fn dependencies(
         &self,
         module: &str,
         target: CompileTarget,
-        entry_point: Option<&str>,
-        macro_defines: &[(String, String)],
-    ) -> SlangProgram {
-        let (linked_program, session) = {
-            let search_paths: Vec<_> = self
-                .search_paths
-                .iter()
-                .map(|p| p.as_ref())
-                .chain(std::iter::once(self.tmp.path()))
-                .map(|path| CString::new(path.as_os_str().as_encoded_bytes()).unwrap())
-                .collect();
+        options: &CompileOptions,
+    ) -> Result<Vec<PathBuf>, SlangError> {
+        let session = self.session(target, options)?;
+        let module = load_module(&session, module, options)?;
+        Ok(on_disk_dependencies(&module))
+    }
 
-            // All compiler options are available through this builder.
-            let mut session_options = CompilerOptions::default()
-                .optimization(OptimizationLevel::Maximal)
-                .matrix_layout_row(true)
-                .macro_define("SLANG_CUDA_STRUCTURED_BUFFER_NO_COUNT", "true");
+    /// Drops the compilation sessions cached by this compiler, along with the modules they loaded.
+    ///
+    /// Sessions are reused across compilations with the same target and session-level options
+    /// (i.e., everything but [`CompileOptions::type_arguments`] and
+    /// [`CompileOptions::min_severity`]), so that a module and its imports are only parsed once
+    /// even when compiling its entry points separately. As a consequence, changes made on disk to
+    /// already-loaded modules are ignored until this is called.
+    pub fn clear_session_cache(&self) {
+        self.sessions.borrow_mut().clear();
+    }
 
-            for (macro_name, macro_val) in macro_defines.iter().chain(&self.global_macros) {
-                session_options = session_options.macro_define(macro_name, macro_val);
-            }
+    /// The compilation session for `target` and `options`, reused from previous compilations if
+    /// possible.
+    fn session(
+        &self,
+        target: CompileTarget,
+        options: &CompileOptions,
+    ) -> Result<shader_slang::Session, SlangError> {
+        let key = SessionKey::new(target, options);
+        if let Some(session) = self.sessions.borrow().get(&key) {
+            return Ok(session.clone());
+        }
 
-            let target_desc = TargetDesc::default().format(target);
+        let session = self.create_session(target, options)?;
+        self.sessions.borrow_mut().insert(key, session.clone());
+        Ok(session)
+    }
 
-            let targets = [target_desc];
-            let search_paths_ptr: Vec<_> = search_paths.iter().map(|path| path.as_ptr()).collect();
+    fn create_session(
+        &self,
+        target: CompileTarget,
+        options: &CompileOptions,
+    ) -> Result<shader_slang::Session, SlangError> {
+        let search_paths: Vec<_> = self
+            .search_paths
+            .iter()
+            .map(|p| p.as_os_str())
+            .chain(std::iter::once(vfs::VIRTUAL_ROOT.as_ref()))
+            .map(|path| CString::new(path.as_encoded_bytes()).unwrap())
+            .collect();
 
-            let session_desc = SessionDesc::default()
-                .targets(&targets)
-                .search_paths(&search_paths_ptr)
-                .options(&session_options);
+        // All compiler options are available through this builder.
+        let mut session_options = CompilerOptions::default()
+            .optimization(options.optimization)
+            .debug_information(options.debug_info)
+            .macro_define("SLANG_CUDA_STRUCTURED_BUFFER_NO_COUNT", "true");
 
-            let session = self
-                .session
-                .create_session(&session_desc)
-                .expect("failed to create session");
+        match options.matrix_layout {
+            MatrixLayoutMode::RowMajor => session_options = session_options.matrix_layout_row(true),
+            MatrixLayoutMode::ColumnMajor => {
+                session_options = session_options.matrix_layout_column(true)
+            }
+            _ => {}
+        }
 
-            let module = session.load_module(module).unwrap();
+        for (macro_name, macro_val) in options.macro_defines.iter().chain(&self.global_macros) {
+            session_options = session_options.macro_define(macro_name, macro_val);
+        }
 
-            let entry_points: Vec<_> = module
-                .entry_points()
-                .filter(|e| {
-                    entry_point.is_none() || Some(e.function_reflection().name()) == entry_point
-                })
-                .map(|e| e.downcast().clone())
+        if options.warnings_as_errors {
+            session_options = session_options.warnings_as_errors("all");
+        }
+        if !options.disabled_warnings.is_empty() {
+            let codes: Vec<_> = options
+                .disabled_warnings
+                .iter()
+                .map(|code| code.to_string())
                 .collect();
-            let program = session
-                .create_composite_component_type(&entry_points)
-                .unwrap();
-            let linked_program = program.link().unwrap();
-            (linked_program, session)
-        };
+            session_options = session_options.disable_warnings(&codes.join(","));
+        }
 
-        SlangProgram {
-            program: linked_program,
-            session,
+        let mut target_desc = TargetDesc::default().format(target);
+        if let Some(config) = self.target_config(target) {
+            if let Some(profile) = &config.profile {
+                target_desc = target_desc.profile(self.find_profile(profile)?);
+            }
+            for capability in &config.capabilities {
+                session_options = session_options.capability(self.find_capability(capability)?);
+            }
         }
+
+        let targets = [target_desc];
+        let search_paths_ptr: Vec<_> = search_paths.iter().map(|path| path.as_ptr()).collect();
+
+        let session_desc = SessionDesc::default()
+            .targets(&targets)
+            .search_paths(&search_paths_ptr)
+            .options(&session_options);
+        let mut raw_session_desc = *session_desc;
+        raw_session_desc.fileSystem = self.file_system.as_raw();
+
+        // `CompilerOptions` can’t express downstream arguments, so they are appended to its raw
+        // option entries. Slang expects the arguments to be separated by new lines.
+        let downstream_args: Vec<_> = self
+            .downstream_args
+            .iter()
+            .map(|(compiler, args)| {
+                (
+                    CString::new(compiler.as_str()).unwrap(),
+                    CString::new(args.join("\n")).unwrap(),
+                )
+            })
+            .collect();
+        let mut option_entries = if raw_session_desc.compilerOptionEntryCount == 0 {
+            vec![]
+        } else {
+            // SAFETY: the entries point into `session_options`, which outlives this scope.
+            unsafe {
+                std::slice::from_raw_parts(
+                    raw_session_desc.compilerOptionEntries,
+                    raw_session_desc.compilerOptionEntryCount as usize,
+                )
+            }
+            .to_vec()
+        };
+        option_entries.extend(downstream_args.iter().map(|(compiler, args)| {
+            shader_slang_sys::slang_CompilerOptionEntry {
+                name: shader_slang::CompilerOptionName::DownstreamArgs,
+                value: shader_slang_sys::slang_CompilerOptionValue {
+                    kind: shader_slang_sys::slang_CompilerOptionValueKind::String,
+                    intValue0: 0,
+                    intValue1: 0,
+                    stringValue0: compiler.as_ptr(),
+                    stringValue1: args.as_ptr(),
+                },
+            }
+        }));
+        raw_session_desc.compilerOptionEntries = option_entries.as_mut_ptr();
+        raw_session_desc.compilerOptionEntryCount = option_entries.len() as _;
+        // SAFETY: `SessionDesc` is a `#[repr(transparent)]` wrapper around `slang_SessionDesc`,
+        //         and `raw_session_desc` only points to data outliving this scope.
+        let session_desc: &SessionDesc =
+            unsafe { &*(&raw const raw_session_desc).cast::<SessionDesc>() };
+
+        self.session
+            .create_session(session_desc)
+            .ok_or(SlangError::SessionCreation)
     }
 
+    pub fn compile(
+        &self,
+        module: &str,
+        target: CompileTarget,
+        entry_point: Option<&str>,
+        options: &CompileOptions,
+    ) -> Result<SlangProgram, SlangError> {
+        let session = self.session(target, options)?;
+        let loaded = load_module(&session, module, options)?;
+        link(session, &loaded, module, entry_point, options)
+    }
+
+    /// Compiles the module `name` from its in-memory `source`, e.g. for kernels generated at
+    /// runtime.
+    ///
+    /// The module can import the modules of the search paths, but can’t be imported by them. It
+    /// is compiled in a dedicated session, so that compiling different sources under the same
+    /// `name` doesn’t return stale results.
+    pub fn compile_str(
+        &self,
+        name: &str,
+        source: &str,
+        target: CompileTarget,
+        entry_point: Option<&str>,
+        options: &CompileOptions,
+    ) -> Result<SlangProgram, SlangError> {
+        let session = self.create_session(target, options)?;
+        let loaded = source::load_module_from_source(&session, name, source).map_err(|e| {
+            SlangError::ModuleLoad {
+                module: name.to_string(),
+                diagnostics: e.into(),
+            }
+            .with_min_severity(options.min_severity)
+        })?;
+        link(session, &loaded, name, entry_point, options)
+    }
+
+    /// Compiles all the entry points of `module` and writes the resulting target code, as raw
+    /// bytes, to `target_file`.
+    ///
+    /// This works for both binary (e.g. SPIR-V, DXIL) and textual (e.g. WGSL, PTX) targets.
     pub fn compile_to(
         &self,
         target: CompileTarget,
         module: &str,
         target_file: impl AsRef<Path>,
-        macro_defines: &[(String, String)],
-    ) {
-        let program = self.compile(module, target, None, macro_defines);
-        let code = program
-            .program
-            .target_code(0)
-            .expect("failed to link target code");
-        std::fs::write(target_file, code.as_str().unwrap()).unwrap();
+        options: &CompileOptions,
+    ) -> Result<(), SlangError> {
+        let code = self.compile_target_code(module, target, None, options)?;
+        std::fs::write(target_file, code)?;
+        Ok(())
+    }
+
+    /// Compiles all the entry points of each of the `modules` for each of the `targets`, and
+    /// bundles the resulting code with the reflection metadata needed to run it.
+    ///
+    /// The modules are packed under the names they are given here, which are the names to pass
+    /// to [`SlangPack::module`](pack::SlangPack::module). Supports the same targets as
+    /// [`Self::compile_all`].
+    pub fn pack(
+        &self,
+        modules: &[&str],
+        targets: &[CompileTarget],
+        options: &CompileOptions,
+    ) -> Result<pack::SlangPack, SlangError> {
+        let mut packed_modules = vec![];
+        for &module in modules {
+            let mut packed_targets = vec![];
+            for &target in targets {
+                target_extension(target).ok_or(SlangError::UnsupportedTarget(target))?;
+                let program = self.compile(module, target, None, options)?;
+                packed_targets.push(pack::PackedTarget {
+                    target,
+                    code: program.target_bytes(0)?,
+                    entry_points: program
+                        .reflection()?
+                        .entry_points
+                        .iter()
+                        .map(pack::PackedEntryPoint::from)
+                        .collect(),
+                });
+            }
+            packed_modules.push(pack::PackedModule {
+                name: module.to_string(),
+                targets: packed_targets,
+            });
+        }
+
+        Ok(pack::SlangPack {
+            generator: concat!("minislang ", env!("CARGO_PKG_VERSION")).to_string(),
+            modules: packed_modules,
+        })
     }
 
     /// Traverses the `src_dir` directory recursively and compile slang files it contains into the
     /// `target_dir`, replicating the same directory hierarchy.
+    ///
+    /// Supports the WGSL, PTX, CUDA, Metal, HLSL, DXIL, and GLSL targets. Generating DXIL requires
+    /// the `dxc` compiler (and its `dxil` library) to be available to Slang. GLSL is emitted for the
+    /// default Slang GLSL profile (`#version 450`); targeting OpenGL ES 3.1 requires selecting a
+    /// GLSL ES profile.
+    ///
+    /// Every Slang file is compiled even if some of them fail: the outcome of each file is
+    /// recorded in the returned [`BuildReport`]. This only fails if `target` isn’t supported.
     pub fn compile_all(
         &self,
         target: CompileTarget,
         src_dir: impl AsRef<Path>,
         target_dir: impl AsRef<Path>,
-        macro_defines: &[(String, String)],
-    ) {
+        options: &CompileOptions,
+    ) -> Result<BuildReport, SlangError> {
         use walkdir::WalkDir;
 
+        let extension = target_extension(target).ok_or(SlangError::UnsupportedTarget(target))?;
         let src_dir = src_dir.as_ref();
+        let mut report = BuildReport::default();
         for entry in WalkDir::new(src_dir)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.metadata().unwrap().is_file())
+            .filter(|e| e.file_type().is_file())
         {
             let path = entry.path();
 
-            if path.extension().unwrap().to_str().unwrap() == "slang" {
-                let path_with_modified_ext = path.with_extension(target_extension(target));
-                let target_path = path_with_modified_ext.to_str().unwrap();
-                let target_path = target_path.replace(
-                    src_dir.to_str().unwrap(),
-                    target_dir.as_ref().to_str().unwrap(),
-                );
-                let target_path = PathBuf::from(target_path);
-                let target_parent_dir = target_path.parent().unwrap();
-
-                println!(
-                    "Compiling {} into {}.",
-                    path.display(),
-                    target_path.display()
-                );
-                std::fs::create_dir_all(target_parent_dir).unwrap();
-                self.compile_to(target, path.to_str().unwrap(), target_path, macro_defines);
+            if path.extension().is_some_and(|ext| ext == "slang") {
+                let relative_path = path
+                    .strip_prefix(src_dir)
+                    .expect("walked paths are always inside the source directory");
+                let target_path = target_dir
+                    .as_ref()
+                    .join(relative_path)
+                    .with_extension(extension);
+
+                let start = Instant::now();
+                let result = self.compile_file(target, path, &target_path, options);
+
+                report.files.push(FileReport {
+                    source: path.to_path_buf(),
+                    target: target_path,
+                    duration: start.elapsed(),
+                    error: result.err(),
+                });
             }
         }
+
+        Ok(report)
     }
+
+    fn compile_file(
+        &self,
+        target: CompileTarget,
+        path: &Path,
+        target_path: &Path,
+        options: &CompileOptions,
+    ) -> Result<(), SlangError> {
+        if let Some(target_parent_dir) = target_path.parent() {
+            std::fs::create_dir_all(target_parent_dir)?;
+        }
+        let module = path
+            .to_str()
+            .ok_or_else(|| SlangError::NonUtf8Path(path.to_path_buf()))?;
+        self.compile_to(target, module, target_path, options)
+    }
+}
+
+/// Links the entry points of `module` (or only `entry_point` if specified) into a program.
+fn link(
+    session: shader_slang::Session,
+    module: &shader_slang::Module,
+    module_name: &str,
+    entry_point: Option<&str>,
+    options: &CompileOptions,
+) -> Result<SlangProgram, SlangError> {
+    let entry_points: Vec<_> = module
+        .entry_points()
+        .filter(|e| entry_point.is_none() || Some(e.function_reflection().name()) == entry_point)
+        .map(|e| e.downcast().clone())
+        .collect();
+
+    if let Some(entry_point) = entry_point
+        && entry_points.is_empty()
+    {
+        return Err(SlangError::EntryPointNotFound {
+            module: module_name.to_string(),
+            entry_point: entry_point.to_string(),
+        });
+    }
+
+    let link_error = |e: shader_slang::Error| {
+        SlangError::Link {
+            module: module_name.to_string(),
+            diagnostics: e.into(),
+        }
+        .with_min_severity(options.min_severity)
+    };
+    let mut program = session
+        .create_composite_component_type(&entry_points)
+        .map_err(link_error)?;
+    if !options.type_arguments.is_empty() {
+        program = specialize::specialize(&program, module_name, &options.type_arguments)
+            .map_err(|e| e.with_min_severity(options.min_severity))?;
+    }
+    let linked_program = program.link().map_err(link_error)?;
+
+    Ok(SlangProgram {
+        program: linked_program,
+        session,
+        module: module_name.to_string(),
+        dependencies: on_disk_dependencies(module),
+        min_severity: options.min_severity,
+    })
+}
+
+fn load_module(
+    session: &shader_slang::Session,
+    module: &str,
+    options: &CompileOptions,
+) -> Result<shader_slang::Module, SlangError> {
+    session.load_module(module).map_err(|e| {
+        SlangError::ModuleLoad {
+            module: module.to_string(),
+            diagnostics: e.into(),
+        }
+        .with_min_severity(options.min_severity)
+    })
+}
+
+fn on_disk_dependencies(module: &shader_slang::Module) -> Vec<PathBuf> {
+    module
+        .dependency_file_paths()
+        .filter(|path| !vfs::is_virtual(path))
+        .map(PathBuf::from)
+        .collect()
 }
 
-fn target_extension(target: CompileTarget) -> &'static str {
+/// The file extension of the code generated for `target`, or `None` if it isn’t supported by
+/// [`SlangCompiler::compile_all`].
+pub fn target_extension(target: CompileTarget) -> Option<&'static str> {
     match target {
-        CompileTarget::Wgsl => "wgsl",
-        CompileTarget::Ptx => "ptx",
-        CompileTarget::CudaSource => "cu",
-        CompileTarget::Metal => "metal",
-        _ => todo!(),
+        CompileTarget::Wgsl => Some("wgsl"),
+        CompileTarget::Ptx => Some("ptx"),
+        CompileTarget::CudaSource => Some("cu"),
+        CompileTarget::Metal => Some("metal"),
+        CompileTarget::Hlsl => Some("hlsl"),
+        CompileTarget::Dxil => Some("dxil"),
+        CompileTarget::Glsl => Some("glsl"),
+        _ => None,
     }
 }
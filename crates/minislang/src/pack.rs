@@ -0,0 +1,486 @@
+//! Packaged shader artifacts (`.slangpack` files), bundling the compiled code of several modules
+//! for several targets with the reflection metadata needed to run them.
+//!
+//! A pack is laid out as follows, with all integers in little-endian order and all strings
+//! prefixed by their length as a `u32`:
+//! - the magic bytes `SLANGPAK` followed by the format version ([`PACK_FORMAT_VERSION`]) as a
+//!   `u32`;
+//! - the manifest: the version of `minislang` that generated the pack, then the list of modules,
+//!   each with the list of targets it was compiled for (identified by their file extension, see
+//!   [`target_extension`]), along with the size of their code and the reflection metadata of their
+//!   entry points;
+//! - the code of every module and target, concatenated in manifest order.
+
+use crate::reflection::{Binding, EntryPointReflection, ParameterReflection, TypeReflection};
+use crate::{SlangError, target_extension};
+use shader_slang::{
+    CompileTarget, ParameterCategory, ResourceAccess, ResourceShape, ScalarType, TypeKind,
+};
+use std::path::Path;
+
+/// The file extension of shader packs.
+pub const PACK_EXTENSION: &str = "slangpack";
+
+/// The version of the pack format written by [`SlangPack::to_bytes`].
+pub const PACK_FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 8] = b"SLANGPAK";
+
+/// A bundle of compiled modules, generated by [`SlangCompiler::pack`](crate::SlangCompiler::pack).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlangPack {
+    /// The version of `minislang` that generated this pack.
+    pub generator: String,
+    /// The modules of this pack.
+    pub modules: Vec<PackedModule>,
+}
+
+/// A module of a [`SlangPack`], compiled for one or more targets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedModule {
+    /// The name the module was packed under.
+    pub name: String,
+    /// The code of the module for each target it was compiled for.
+    pub targets: Vec<PackedTarget>,
+}
+
+/// The code of a [`PackedModule`] for a single target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedTarget {
+    /// The compilation target.
+    pub target: CompileTarget,
+    /// The target code of all the entry points of the module.
+    pub code: Vec<u8>,
+    /// The reflection metadata of the entry points, as laid out by this target.
+    pub entry_points: Vec<PackedEntryPoint>,
+}
+
+/// The reflection metadata of an entry point needed to bind its arguments and launch it.
+///
+/// This is a digest of [`EntryPointReflection`], only keeping its resource bindings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedEntryPoint {
+    /// The name of the entry point.
+    pub name: String,
+    /// The thread-group size declared with `[numthreads(…)]`.
+    pub thread_group_size: [u32; 3],
+    /// Binding of the constant buffer holding the parameters passed by value, if the target needs
+    /// one.
+    pub value_buffer: Option<Binding>,
+    /// The parameters of the entry point that aren’t system values, in declaration order.
+    pub arguments: Vec<PackedArgument>,
+}
+
+/// A parameter of a [`PackedEntryPoint`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedArgument {
+    /// The name of the parameter.
+    pub name: String,
+    /// The binding of the parameter. Only meaningful for resource parameters.
+    pub binding: Binding,
+    /// The kind of resource bound to the parameter, or `None` if it is passed by value.
+    pub resource: Option<PackedResource>,
+}
+
+/// The kind of resource bound to a [`PackedArgument`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PackedResource {
+    /// A `ConstantBuffer<T>`, with `size` the size of `T` in bytes.
+    ConstantBuffer { size: u64 },
+    /// A `StructuredBuffer<T>` or `RWStructuredBuffer<T>`, with `element_size` the size of `T` in
+    /// bytes, including its trailing padding (i.e. the stride of the buffer’s elements).
+    StructuredBuffer { read_only: bool, element_size: u64 },
+    /// A `ByteAddressBuffer` or `RWByteAddressBuffer`.
+    ByteAddressBuffer { read_only: bool },
+    /// A `SamplerState`.
+    Sampler,
+    /// A `Texture2D<T>` or `RWTexture2D<T>`, with the type of its texels if it is a 32-bit (or
+    /// half-precision float) scalar or vector.
+    Texture2d {
+        read_only: bool,
+        texel: Option<TexelType>,
+    },
+    /// Any other resource, whose binding type can’t be inferred.
+    Other,
+}
+
+/// The scalar type of the texels of a [`PackedResource::Texture2d`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TexelType {
+    Float,
+    Sint,
+    Uint,
+}
+
+impl SlangPack {
+    /// The module packed under `name`, if any.
+    pub fn module(&self, name: &str) -> Option<&PackedModule> {
+        self.modules.iter().find(|m| m.name == name)
+    }
+
+    /// The code and metadata of the module `name` for `target`, if any.
+    pub fn target(&self, name: &str, target: CompileTarget) -> Option<&PackedTarget> {
+        self.module(name)?.target(target)
+    }
+
+    /// Serializes this pack into the `.slangpack` format.
+    ///
+    /// Fails with [`SlangError::InvalidPack`] if a target can’t be packed, i.e., if it has no
+    /// file extension (see [`target_extension`]).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SlangError> {
+        let mut out = MAGIC.to_vec();
+        write_u32(&mut out, PACK_FORMAT_VERSION);
+        write_str(&mut out, &self.generator);
+        write_u32(&mut out, self.modules.len() as u32);
+        for module in &self.modules {
+            write_str(&mut out, &module.name);
+            write_u32(&mut out, module.targets.len() as u32);
+            for target in &module.targets {
+                let extension = target_extension(target.target).ok_or_else(|| {
+                    SlangError::InvalidPack(format!(
+                        "the target {:?} of the module `{}` can’t be packed",
+                        target.target, module.name
+                    ))
+                })?;
+                write_str(&mut out, extension);
+                out.extend_from_slice(&(target.code.len() as u64).to_le_bytes());
+                write_u32(&mut out, target.entry_points.len() as u32);
+                for entry_point in &target.entry_points {
+                    entry_point.write(&mut out);
+                }
+            }
+        }
+
+        for target in self.modules.iter().flat_map(|m| &m.targets) {
+            out.extend_from_slice(&target.code);
+        }
+        Ok(out)
+    }
+
+    /// Deserializes a pack serialized with [`Self::to_bytes`].
+    ///
+    /// Fails with [`SlangError::InvalidPack`] if `bytes` is malformed or was written with another
+    /// version of the pack format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SlangError> {
+        let invalid = |reason: &str| SlangError::InvalidPack(reason.to_string());
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len()) != Some(MAGIC) {
+            return Err(invalid("not a slang pack"));
+        }
+        let version = reader.u32().ok_or_else(|| invalid("truncated header"))?;
+        if version != PACK_FORMAT_VERSION {
+            return Err(SlangError::InvalidPack(format!(
+                "unsupported format version {version} (expected {PACK_FORMAT_VERSION})"
+            )));
+        }
+
+        let (mut pack, code_lengths) = reader
+            .manifest()
+            .ok_or_else(|| invalid("malformed manifest"))?;
+        let targets = pack.modules.iter_mut().flat_map(|m| &mut m.targets);
+        for (target, len) in targets.zip(code_lengths) {
+            let code = usize::try_from(len)
+                .ok()
+                .and_then(|len| reader.take(len))
+                .ok_or_else(|| invalid("truncated code"))?;
+            target.code = code.to_vec();
+        }
+        if !reader.0.is_empty() {
+            return Err(invalid("trailing data"));
+        }
+
+        Ok(pack)
+    }
+
+    /// Writes this pack to the file at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SlangError> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Reads a pack from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SlangError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+impl PackedModule {
+    /// The code and metadata of this module for `target`, if it was packed for it.
+    pub fn target(&self, target: CompileTarget) -> Option<&PackedTarget> {
+        self.targets.iter().find(|t| t.target == target)
+    }
+}
+
+impl PackedTarget {
+    /// The entry point named `name`, if it exists.
+    pub fn entry_point(&self, name: &str) -> Option<&PackedEntryPoint> {
+        self.entry_points.iter().find(|e| e.name == name)
+    }
+}
+
+impl From<&EntryPointReflection> for PackedEntryPoint {
+    fn from(entry_point: &EntryPointReflection) -> Self {
+        Self {
+            name: entry_point.name.clone(),
+            thread_group_size: entry_point.thread_group_size,
+            value_buffer: entry_point.value_buffer,
+            arguments: entry_point.arguments().map(PackedArgument::from).collect(),
+        }
+    }
+}
+
+impl From<&ParameterReflection> for PackedArgument {
+    fn from(param: &ParameterReflection) -> Self {
+        Self {
+            name: param.name.clone(),
+            binding: param.binding,
+            resource: (param.category != ParameterCategory::Uniform)
+                .then(|| PackedResource::from(&param.ty)),
+        }
+    }
+}
+
+impl From<&TypeReflection> for PackedResource {
+    fn from(ty: &TypeReflection) -> Self {
+        let element = ty.element_type.as_ref();
+        let element_size = element.map_or(0, |elt| elt.size) as u64;
+        match ty.kind {
+            TypeKind::ConstantBuffer => Self::ConstantBuffer { size: element_size },
+            TypeKind::SamplerState => Self::Sampler,
+            TypeKind::Resource => {
+                let Some(resource) = ty.resource else {
+                    return Self::Other;
+                };
+                let read_only = resource.access == ResourceAccess::Read;
+                match resource.shape {
+                    ResourceShape::SlangStructuredBuffer => Self::StructuredBuffer {
+                        read_only,
+                        element_size: element.map_or(0, |elt| elt.stride) as u64,
+                    },
+                    ResourceShape::SlangByteAddressBuffer => Self::ByteAddressBuffer { read_only },
+                    ResourceShape::SlangTexture2d => Self::Texture2d {
+                        read_only,
+                        texel: match resource.result_type {
+                            Some(ScalarType::Float16 | ScalarType::Float32) => {
+                                Some(TexelType::Float)
+                            }
+                            Some(ScalarType::Int32) => Some(TexelType::Sint),
+                            Some(ScalarType::Uint32) => Some(TexelType::Uint),
+                            _ => None,
+                        },
+                    },
+                    _ => Self::Other,
+                }
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+impl PackedEntryPoint {
+    /// Serializes this entry point alone, e.g. for caching its reflection data.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write(&mut out);
+        out
+    }
+
+    /// Deserializes an entry point serialized with [`Self::to_bytes`].
+    ///
+    /// Returns `None` if `bytes` is malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        let entry_point = reader.entry_point()?;
+        reader.0.is_empty().then_some(entry_point)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        write_str(out, &self.name);
+        self.thread_group_size
+            .iter()
+            .for_each(|dim| write_u32(out, *dim));
+        out.push(self.value_buffer.is_some() as u8);
+        if let Some(binding) = self.value_buffer {
+            write_binding(out, binding);
+        }
+        write_u32(out, self.arguments.len() as u32);
+        for arg in &self.arguments {
+            write_str(out, &arg.name);
+            write_binding(out, arg.binding);
+            match arg.resource {
+                None => out.push(0),
+                Some(PackedResource::ConstantBuffer { size }) => {
+                    out.push(1);
+                    out.extend_from_slice(&size.to_le_bytes());
+                }
+                Some(PackedResource::StructuredBuffer {
+                    read_only,
+                    element_size,
+                }) => {
+                    out.extend_from_slice(&[2, read_only as u8]);
+                    out.extend_from_slice(&element_size.to_le_bytes());
+                }
+                Some(PackedResource::ByteAddressBuffer { read_only }) => {
+                    out.extend_from_slice(&[3, read_only as u8]);
+                }
+                Some(PackedResource::Sampler) => out.push(4),
+                Some(PackedResource::Texture2d { read_only, texel }) => {
+                    let texel = match texel {
+                        None => 0,
+                        Some(TexelType::Float) => 1,
+                        Some(TexelType::Sint) => 2,
+                        Some(TexelType::Uint) => 3,
+                    };
+                    out.extend_from_slice(&[5, read_only as u8, texel]);
+                }
+                Some(PackedResource::Other) => out.push(6),
+            }
+        }
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, val: u32) {
+    out.extend_from_slice(&val.to_le_bytes());
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_u32(out, s.len() as u32);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_binding(out: &mut Vec<u8>, binding: Binding) {
+    write_u32(out, binding.space);
+    write_u32(out, binding.index);
+}
+
+/// The inverse of [`target_extension`].
+fn target_from_extension(extension: &str) -> Option<CompileTarget> {
+    [
+        CompileTarget::Wgsl,
+        CompileTarget::Ptx,
+        CompileTarget::CudaSource,
+        CompileTarget::Metal,
+        CompileTarget::Hlsl,
+        CompileTarget::Dxil,
+        CompileTarget::Glsl,
+    ]
+    .into_iter()
+    .find(|target| target_extension(*target) == Some(extension))
+}
+
+/// Cursor over the bytes of a serialized pack.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (head, tail) = self.0.split_at_checked(len)?;
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        self.u8().map(|b| b != 0)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn binding(&mut self) -> Option<Binding> {
+        Some(Binding {
+            space: self.u32()?,
+            index: self.u32()?,
+        })
+    }
+
+    /// Reads the manifest, returning the pack without its code, and the size of the code of each
+    /// target in manifest order.
+    fn manifest(&mut self) -> Option<(SlangPack, Vec<u64>)> {
+        let generator = self.string()?;
+        let mut code_lengths = vec![];
+        let mut modules = vec![];
+        for _ in 0..self.u32()? {
+            let name = self.string()?;
+            let mut targets = vec![];
+            for _ in 0..self.u32()? {
+                let target = target_from_extension(&self.string()?)?;
+                code_lengths.push(self.u64()?);
+                let mut entry_points = vec![];
+                for _ in 0..self.u32()? {
+                    entry_points.push(self.entry_point()?);
+                }
+                targets.push(PackedTarget {
+                    target,
+                    code: vec![],
+                    entry_points,
+                });
+            }
+            modules.push(PackedModule { name, targets });
+        }
+
+        Some((SlangPack { generator, modules }, code_lengths))
+    }
+
+    fn entry_point(&mut self) -> Option<PackedEntryPoint> {
+        let name = self.string()?;
+        let thread_group_size = [self.u32()?, self.u32()?, self.u32()?];
+        let value_buffer = match self.u8()? {
+            0 => None,
+            _ => Some(self.binding()?),
+        };
+        let mut arguments = vec![];
+        for _ in 0..self.u32()? {
+            let name = self.string()?;
+            let binding = self.binding()?;
+            let resource = match self.u8()? {
+                0 => None,
+                1 => Some(PackedResource::ConstantBuffer { size: self.u64()? }),
+                2 => Some(PackedResource::StructuredBuffer {
+                    read_only: self.bool()?,
+                    element_size: self.u64()?,
+                }),
+                3 => Some(PackedResource::ByteAddressBuffer {
+                    read_only: self.bool()?,
+                }),
+                4 => Some(PackedResource::Sampler),
+                5 => Some(PackedResource::Texture2d {
+                    read_only: self.bool()?,
+                    texel: match self.u8()? {
+                        0 => None,
+                        1 => Some(TexelType::Float),
+                        2 => Some(TexelType::Sint),
+                        3 => Some(TexelType::Uint),
+                        _ => return None,
+                    },
+                }),
+                6 => Some(PackedResource::Other),
+                _ => return None,
+            };
+            arguments.push(PackedArgument {
+                name,
+                binding,
+                resource,
+            });
+        }
+
+        Some(PackedEntryPoint {
+            name,
+            thread_group_size,
+            value_buffer,
+            arguments,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+//! Serialization of Slang modules into precompiled IR (`.slang-module` files).
+//!
+//! The `shader-slang` bindings don’t expose `IModule::serialize`, so it is called through the raw
+//! vtable.
+
+use shader_slang::{Blob, Module};
+use shader_slang_sys as sys;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+/// Serializes the IR of `module`, or returns the Slang error code on failure.
+pub fn serialize(module: &Module) -> Result<Vec<u8>, sys::SlangResult> {
+    let mut blob = std::ptr::null_mut();
+    // SAFETY: `Module` is a `#[repr(transparent)]` wrapper around a non-null pointer to a COM
+    //         object, whose first field is its vtable pointer.
+    let result = unsafe {
+        let this: *mut c_void = std::mem::transmute_copy(module);
+        let vtable = &**(this as *const *const sys::IModuleVtable);
+        (vtable.serialize)(this, &mut blob)
+    };
+
+    // SAFETY: the returned blob comes with a reference owned by the caller, which is released when
+    //         dropping its `#[repr(transparent)]` wrapper.
+    let blob = NonNull::new(blob)
+        .map(|ptr| unsafe { std::mem::transmute::<NonNull<sys::ISlangBlob>, Blob>(ptr) });
+    match blob {
+        Some(blob) if result >= 0 => Ok(blob.as_slice().to_vec()),
+        _ => Err(result),
+    }
+}
@@ -0,0 +1,217 @@
+//! Owned, typed reflection data of compiled Slang programs.
+//!
+//! This mirrors the subset of Slang’s reflection API needed to bind resources and to validate
+//! Rust-side types against shader layouts, without exposing the raw `shader-slang` reflection
+//! handles (which borrow from the program they come from).
+
+use shader_slang::reflection::{EntryPoint, TypeLayout, VariableLayout};
+use shader_slang::{ParameterCategory, ResourceAccess, ResourceShape, ScalarType, TypeKind};
+
+/// Reflection data of a compiled [`SlangProgram`](crate::SlangProgram).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramReflection {
+    /// The entry points of the program.
+    pub entry_points: Vec<EntryPointReflection>,
+}
+
+impl ProgramReflection {
+    /// The entry point named `name`, if it exists.
+    pub fn entry_point(&self, name: &str) -> Option<&EntryPointReflection> {
+        self.entry_points.iter().find(|e| e.name == name)
+    }
+}
+
+/// A binding slot of a shader resource.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Binding {
+    /// Binding space (aka. binding group).
+    pub space: u32,
+    /// Binding index.
+    pub index: u32,
+}
+
+/// Reflection data of an entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryPointReflection {
+    /// The name of the entry point.
+    pub name: String,
+    /// The thread-group size declared with `[numthreads(…)]`.
+    pub thread_group_size: [u32; 3],
+    /// Binding of the constant buffer holding the parameters passed by value, if the target needs
+    /// one.
+    pub value_buffer: Option<Binding>,
+    /// The parameters of the entry point, in declaration order.
+    ///
+    /// This includes system-value parameters (see [`ParameterReflection::semantic`]).
+    pub parameters: Vec<ParameterReflection>,
+}
+
+/// Reflection data of an entry-point parameter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterReflection {
+    /// The name of the parameter.
+    pub name: String,
+    /// The kind of resource consumed by the parameter. Parameters passed by value are
+    /// [`ParameterCategory::Uniform`].
+    pub category: ParameterCategory,
+    /// The binding of the parameter. Only meaningful for resource parameters.
+    pub binding: Binding,
+    /// Byte offset of the parameter in the value buffer. Only meaningful for parameters passed by
+    /// value.
+    pub offset: usize,
+    /// The system-value semantic of the parameter (e.g. `SV_DispatchThreadID`), if any.
+    pub semantic: Option<String>,
+    /// The type of the parameter.
+    pub ty: TypeReflection,
+}
+
+/// Reflection data of a resource type (buffer, texture, sampler…).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceReflection {
+    /// The shape of the resource.
+    pub shape: ResourceShape,
+    /// How the resource can be accessed by the shader.
+    pub access: ResourceAccess,
+    /// The scalar type of the values read from the resource (e.g. the texel type of a texture).
+    pub result_type: Option<ScalarType>,
+}
+
+/// Reflection data of a type, as laid out by the compilation target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeReflection {
+    /// The kind of type.
+    pub kind: TypeKind,
+    /// The name of the type, if it has one (e.g. for structs).
+    pub name: Option<String>,
+    /// The size of the type, in bytes, when stored in a buffer.
+    pub size: usize,
+    /// The alignment of the type, in bytes, when stored in a buffer.
+    pub alignment: usize,
+    /// The stride of the type, in bytes, when stored in an array.
+    pub stride: usize,
+    /// The scalar type of scalars, vectors, and matrices.
+    pub scalar_type: Option<ScalarType>,
+    /// The number of rows of matrices (and vectors).
+    pub row_count: Option<u32>,
+    /// The number of columns of matrices (and vectors).
+    pub column_count: Option<u32>,
+    /// The number of elements of arrays and vectors. Unsized arrays have zero elements.
+    pub element_count: Option<usize>,
+    /// The element type of arrays, constant buffers, and structured buffers.
+    pub element_type: Option<Box<TypeReflection>>,
+    /// The fields of structs, in declaration order.
+    pub fields: Vec<FieldReflection>,
+    /// Details about resource types.
+    pub resource: Option<ResourceReflection>,
+}
+
+/// Reflection data of a struct field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldReflection {
+    /// The name of the field.
+    pub name: String,
+    /// Byte offset of the field from the start of the struct.
+    pub offset: usize,
+    /// The type of the field.
+    pub ty: TypeReflection,
+}
+
+impl EntryPointReflection {
+    pub(crate) fn new(entry_point: &EntryPoint) -> Self {
+        let value_buffer = entry_point.has_default_constant_buffer().then(|| {
+            let layout = entry_point.var_layout();
+            Binding {
+                space: layout.binding_space(),
+                index: layout.binding_index(),
+            }
+        });
+        let parameters = entry_point
+            .parameters()
+            .filter_map(ParameterReflection::new)
+            .collect();
+
+        Self {
+            name: entry_point.name().to_string(),
+            thread_group_size: entry_point.compute_thread_group_size().map(|e| e as u32),
+            value_buffer,
+            parameters,
+        }
+    }
+
+    /// The parameters of this entry point that aren’t system values.
+    pub fn arguments(&self) -> impl Iterator<Item = &ParameterReflection> {
+        self.parameters.iter().filter(|p| p.semantic.is_none())
+    }
+}
+
+impl ParameterReflection {
+    fn new(param: &VariableLayout) -> Option<Self> {
+        Some(Self {
+            name: param.variable()?.name().to_string(),
+            category: param.category(),
+            binding: Binding {
+                space: param.binding_space(),
+                index: param.binding_index(),
+            },
+            offset: param.offset(ParameterCategory::Uniform),
+            semantic: param.semantic_name().map(|s| s.to_string()),
+            ty: TypeReflection::new(param.type_layout()),
+        })
+    }
+}
+
+impl TypeReflection {
+    pub(crate) fn new(ty: &TypeLayout) -> Self {
+        let kind = ty.kind();
+        let has_element_type = match kind {
+            TypeKind::Array | TypeKind::ConstantBuffer | TypeKind::ParameterBlock => true,
+            TypeKind::Resource => ty.resource_shape() == Some(ResourceShape::SlangStructuredBuffer),
+            _ => false,
+        };
+        let fields = if kind == TypeKind::Struct {
+            ty.fields()
+                .map(|field| FieldReflection {
+                    name: field.name().unwrap_or_default().to_string(),
+                    offset: field.offset(ParameterCategory::Uniform),
+                    ty: Self::new(field.type_layout()),
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let resource = if kind == TypeKind::Resource {
+            ty.resource_shape()
+                .zip(ty.resource_access())
+                .map(|(shape, access)| ResourceReflection {
+                    shape,
+                    access,
+                    result_type: ty.resource_result_type().map(|t| t.scalar_type()),
+                })
+        } else {
+            None
+        };
+
+        Self {
+            kind,
+            name: ty.name().map(|name| name.to_string()),
+            size: ty.size(ParameterCategory::Uniform),
+            alignment: ty.alignment(ParameterCategory::Uniform).max(0) as usize,
+            stride: ty.stride(ParameterCategory::Uniform),
+            scalar_type: matches!(kind, TypeKind::Scalar | TypeKind::Vector | TypeKind::Matrix)
+                .then(|| ty.scalar_type())
+                .flatten(),
+            row_count: matches!(kind, TypeKind::Vector | TypeKind::Matrix)
+                .then(|| ty.row_count())
+                .flatten(),
+            column_count: matches!(kind, TypeKind::Vector | TypeKind::Matrix)
+                .then(|| ty.column_count())
+                .flatten(),
+            element_count: matches!(kind, TypeKind::Array | TypeKind::Vector)
+                .then(|| ty.element_count())
+                .flatten(),
+            element_type: has_element_type.then(|| Box::new(Self::new(ty.element_type_layout()))),
+            fields,
+            resource,
+        }
+    }
+}
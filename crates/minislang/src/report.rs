@@ -0,0 +1,89 @@
+//! Machine-readable results of batch compilations.
+
+use crate::{Diagnostics, SlangError};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The result of compiling a single file with
+/// [`SlangCompiler::compile_all`](crate::SlangCompiler::compile_all).
+#[derive(Debug)]
+pub struct FileReport {
+    /// The path of the compiled Slang file.
+    pub source: PathBuf,
+    /// The path the target code was (or would have been) written to.
+    pub target: PathBuf,
+    /// The time spent compiling and writing the file.
+    pub duration: Duration,
+    /// The error that prevented this file from being compiled, if any.
+    pub error: Option<SlangError>,
+}
+
+impl FileReport {
+    /// Was this file compiled and written successfully?
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The diagnostics emitted by the Slang compiler for this file, if it failed to compile.
+    pub fn diagnostics(&self) -> Option<&Diagnostics> {
+        self.error.as_ref()?.diagnostics()
+    }
+}
+
+/// The per-file results of [`SlangCompiler::compile_all`](crate::SlangCompiler::compile_all).
+///
+/// A file failing to compile doesn’t prevent the others from being compiled: failures are
+/// recorded here instead.
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    /// The report of every Slang file found, in traversal order.
+    pub files: Vec<FileReport>,
+}
+
+impl BuildReport {
+    /// Were all the files compiled successfully?
+    pub fn is_success(&self) -> bool {
+        self.files.iter().all(FileReport::is_success)
+    }
+
+    /// Iterates through the files compiled successfully.
+    pub fn succeeded(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|f| f.is_success())
+    }
+
+    /// Iterates through the files that failed to compile.
+    pub fn failed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|f| !f.is_success())
+    }
+
+    /// The total time spent compiling all the files.
+    pub fn duration(&self) -> Duration {
+        self.files.iter().map(|f| f.duration).sum()
+    }
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            match &file.error {
+                None => writeln!(
+                    f,
+                    "compiled {} into {} ({:.2?})",
+                    file.source.display(),
+                    file.target.display(),
+                    file.duration
+                )?,
+                Some(error) => writeln!(f, "failed to compile {}: {error}", file.source.display())?,
+            }
+        }
+
+        let failed = self.failed().count();
+        write!(
+            f,
+            "{} file(s) compiled, {failed} failed ({:.2?})",
+            self.files.len() - failed,
+            self.duration()
+        )
+    }
+}
@@ -0,0 +1,60 @@
+//! Loading of Slang modules from in-memory source strings.
+//!
+//! The `shader-slang` bindings don’t expose `ISession::loadModuleFromSourceString`, so it is
+//! called through the raw vtable.
+
+use shader_slang::{Blob, Module, Session};
+use shader_slang_sys as sys;
+use std::ffi::{CString, c_void};
+use std::ptr::NonNull;
+
+const SLANG_FAIL: sys::SlangResult = 0x80004005_u32 as sys::SlangResult;
+
+/// Loads the module `name` from `source` into `session`.
+pub fn load_module_from_source(
+    session: &Session,
+    name: &str,
+    source: &str,
+) -> Result<Module, shader_slang::Error> {
+    let c_name = CString::new(name).map_err(|_| shader_slang::Error::Code(SLANG_FAIL))?;
+    let c_path = CString::new(format!("{name}.slang")).unwrap();
+    let c_source = CString::new(source).map_err(|_| shader_slang::Error::Code(SLANG_FAIL))?;
+
+    let mut diagnostics = std::ptr::null_mut();
+    // SAFETY: `Session` is a `#[repr(transparent)]` wrapper around a non-null pointer to a COM
+    //         object, whose first field is its vtable pointer.
+    let module = unsafe {
+        let this: *mut c_void = std::mem::transmute_copy(session);
+        let vtable = &**(this as *const *const sys::ISessionVtable);
+        (vtable.loadModuleFromSourceString)(
+            this,
+            c_name.as_ptr(),
+            c_path.as_ptr(),
+            c_source.as_ptr(),
+            &mut diagnostics,
+        )
+    };
+
+    // SAFETY: the returned diagnostics come with a reference owned by the caller, which is
+    //         released when dropping their `#[repr(transparent)]` wrapper.
+    let diagnostics = NonNull::new(diagnostics)
+        .map(|ptr| unsafe { std::mem::transmute::<NonNull<sys::ISlangBlob>, Blob>(ptr) });
+    match NonNull::new(module) {
+        // SAFETY: unlike the other objects, the module is owned by the session, so a reference
+        //         must be added for the wrapper to release.
+        Some(ptr) => Ok(unsafe { module_from_borrowed(ptr) }),
+        None => Err(match diagnostics {
+            Some(blob) => shader_slang::Error::Blob(blob),
+            None => shader_slang::Error::Code(SLANG_FAIL),
+        }),
+    }
+}
+
+/// Wraps a module pointer borrowed from its session, adding a reference to it.
+unsafe fn module_from_borrowed(ptr: NonNull<sys::slang_IModule>) -> Module {
+    let module = unsafe { std::mem::transmute::<NonNull<sys::slang_IModule>, Module>(ptr) };
+    // Cloning adds the reference released when dropping `module`.
+    let owned = module.clone();
+    std::mem::forget(module);
+    owned
+}
@@ -0,0 +1,88 @@
+//! Specialization of generic entry points with type arguments.
+//!
+//! The `shader-slang` bindings don’t expose `IComponentType::specialize`, so it is called
+//! through the raw vtable.
+
+use crate::SlangError;
+use shader_slang::reflection::Type;
+use shader_slang::{Blob, ComponentType};
+use shader_slang_sys as sys;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+/// `slang::SpecializationArg::Kind::Type`.
+const SPECIALIZATION_ARG_KIND_TYPE: i32 = 1;
+
+/// Layout-compatible with `slang::SpecializationArg` when its kind is a type.
+#[repr(C)]
+struct SpecializationArg {
+    kind: i32,
+    ty: *const Type,
+}
+
+/// Specializes the generic parameters of `program`, in order, with the types named
+/// `type_arguments`.
+///
+/// The types are looked up by name in the (unspecialized) layout of `program`, so they can be
+/// built-in types as well as types declared by the module or its imports.
+pub fn specialize(
+    program: &ComponentType,
+    module: &str,
+    type_arguments: &[String],
+) -> Result<ComponentType, SlangError> {
+    let layout = program.layout(0).map_err(|e| SlangError::Layout {
+        module: module.to_string(),
+        diagnostics: e.into(),
+    })?;
+    let args = type_arguments
+        .iter()
+        .map(|name| {
+            let ty = layout
+                .find_type_by_name(name)
+                .ok_or_else(|| SlangError::TypeNotFound {
+                    module: module.to_string(),
+                    type_name: name.clone(),
+                })?;
+            Ok(SpecializationArg {
+                kind: SPECIALIZATION_ARG_KIND_TYPE,
+                ty,
+            })
+        })
+        .collect::<Result<Vec<_>, SlangError>>()?;
+
+    let mut specialized = std::ptr::null_mut();
+    let mut diagnostics = std::ptr::null_mut();
+    // SAFETY: `ComponentType` is a `#[repr(transparent)]` wrapper around a non-null pointer to a
+    //         COM object, whose first field is its vtable pointer.
+    let result = unsafe {
+        let this: *mut c_void = std::mem::transmute_copy(program);
+        let vtable = &**(this as *const *const sys::IComponentTypeVtable);
+        (vtable.specialize)(
+            this,
+            args.as_ptr().cast(),
+            args.len() as sys::SlangInt,
+            &mut specialized,
+            &mut diagnostics,
+        )
+    };
+
+    // SAFETY: the returned objects come with a reference owned by the caller, which is released
+    //         when dropping their `#[repr(transparent)]` wrappers.
+    let diagnostics = NonNull::new(diagnostics)
+        .map(|ptr| unsafe { std::mem::transmute::<NonNull<sys::ISlangBlob>, Blob>(ptr) });
+    match NonNull::new(specialized) {
+        Some(ptr) if result >= 0 => Ok(unsafe {
+            std::mem::transmute::<NonNull<sys::slang_IComponentType>, ComponentType>(ptr)
+        }),
+        _ => {
+            let error = match diagnostics {
+                Some(blob) => shader_slang::Error::Blob(blob),
+                None => shader_slang::Error::Code(result),
+            };
+            Err(SlangError::Specialization {
+                module: module.to_string(),
+                diagnostics: error.into(),
+            })
+        }
+    }
+}
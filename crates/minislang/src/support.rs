@@ -0,0 +1,21 @@
+//! Checks of the targets supported by the Slang installation.
+//!
+//! The `shader-slang` bindings don’t expose `IGlobalSession::checkCompileTargetSupport`, so it is
+//! called through the raw vtable.
+
+use shader_slang::{CompileTarget, GlobalSession};
+use shader_slang_sys as sys;
+use std::ffi::c_void;
+
+/// Whether `session` can generate code for `target`, including the downstream compiler it
+/// needs (e.g. a C++ compiler for the CPU targets).
+pub fn check_compile_target_support(session: &GlobalSession, target: CompileTarget) -> bool {
+    // SAFETY: `GlobalSession` is a `#[repr(transparent)]` wrapper around a non-null pointer to a
+    //         COM object, whose first field is its vtable pointer.
+    let result = unsafe {
+        let this: *mut c_void = std::mem::transmute_copy(session);
+        let vtable = &**(this as *const *const sys::IGlobalSessionVtable);
+        (vtable.checkCompileTargetSupport)(this, target)
+    };
+    result >= 0
+}
@@ -0,0 +1,388 @@
+//! In-memory implementation of Slang’s `ISlangFileSystem`.
+//!
+//! This serves the directories registered with [`SlangCompiler::add_dir`](crate::SlangCompiler::add_dir)
+//! and the modules registered with
+//! [`SlangCompiler::add_precompiled_module`](crate::SlangCompiler::add_precompiled_module) straight
+//! from memory, without writing them to disk, and falls back to the OS file system for
+//! everything else (e.g. the user’s search paths). In-memory files that weren’t registered can
+//! also be provided on demand by a user callback (see
+//! [`SlangCompiler::set_module_resolver`](crate::SlangCompiler::set_module_resolver)).
+
+use include_dir::{Dir, DirEntry};
+use shader_slang::UUID;
+use shader_slang_sys as sys;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, c_char, c_void};
+use std::hash::Hasher;
+use std::ptr::NonNull;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The search path under which the in-memory files are exposed to Slang.
+pub const VIRTUAL_ROOT: &str = "__minislang_embedded__";
+
+const SLANG_OK: sys::SlangResult = 0;
+const SLANG_E_NO_INTERFACE: sys::SlangResult = 0x80004002_u32 as sys::SlangResult;
+const SLANG_E_NOT_FOUND: sys::SlangResult = 0x82000005_u32 as sys::SlangResult;
+
+const fn uuid(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> UUID {
+    UUID {
+        data1,
+        data2,
+        data3,
+        data4,
+    }
+}
+
+const UNKNOWN_IID: UUID = uuid(0, 0, 0, [0xc0, 0, 0, 0, 0, 0, 0, 0x46]);
+const CASTABLE_IID: UUID = uuid(
+    0x87ede0e1,
+    0x4852,
+    0x44b0,
+    [0x8b, 0xf2, 0xcb, 0x31, 0x87, 0x4d, 0xe2, 0x39],
+);
+const FILE_SYSTEM_IID: UUID = uuid(
+    0x003a09fc,
+    0x3a4d,
+    0x4ba0,
+    [0xad, 0x60, 0x1f, 0xd8, 0x63, 0xa9, 0x15, 0xab],
+);
+const BLOB_IID: UUID = uuid(
+    0x8ba5fb08,
+    0x5195,
+    0x40e2,
+    [0xac, 0x58, 0x0d, 0x98, 0x9c, 0x3a, 0x01, 0x02],
+);
+
+/*
+ * COM objects implemented on the Rust side. Their first field is the vtable pointer, as expected
+ * by Slang.
+ */
+
+#[repr(C)]
+struct FileSystemVtable {
+    query_interface: unsafe extern "C" fn(*mut c_void, *const UUID, *mut *mut c_void) -> i32,
+    add_ref: unsafe extern "C" fn(*mut c_void) -> u32,
+    release: unsafe extern "C" fn(*mut c_void) -> u32,
+    cast_as: unsafe extern "C" fn(*mut c_void, *const UUID) -> *mut c_void,
+    load_file: unsafe extern "C" fn(*mut c_void, *const c_char, *mut *mut c_void) -> i32,
+}
+
+#[repr(C)]
+struct BlobVtable {
+    query_interface: unsafe extern "C" fn(*mut c_void, *const UUID, *mut *mut c_void) -> i32,
+    add_ref: unsafe extern "C" fn(*mut c_void) -> u32,
+    release: unsafe extern "C" fn(*mut c_void) -> u32,
+    get_buffer_pointer: unsafe extern "C" fn(*mut c_void) -> *const c_void,
+    get_buffer_size: unsafe extern "C" fn(*mut c_void) -> usize,
+}
+
+/// A callback providing the content of in-memory files that weren’t registered explicitly.
+pub type Resolver = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+#[repr(C)]
+struct FileSystemObject {
+    vtable: &'static FileSystemVtable,
+    ref_count: AtomicU32,
+    files: RwLock<BTreeMap<String, Cow<'static, [u8]>>>,
+    resolver: RwLock<Option<Resolver>>,
+}
+
+#[repr(C)]
+struct BlobObject {
+    vtable: &'static BlobVtable,
+    ref_count: AtomicU32,
+    data: Cow<'static, [u8]>,
+}
+
+static FILE_SYSTEM_VTABLE: FileSystemVtable = FileSystemVtable {
+    query_interface: fs_query_interface,
+    add_ref: add_ref::<FileSystemObject>,
+    release: release::<FileSystemObject>,
+    cast_as: fs_cast_as,
+    load_file: fs_load_file,
+};
+
+static BLOB_VTABLE: BlobVtable = BlobVtable {
+    query_interface: blob_query_interface,
+    add_ref: add_ref::<BlobObject>,
+    release: release::<BlobObject>,
+    get_buffer_pointer: blob_get_buffer_pointer,
+    get_buffer_size: blob_get_buffer_size,
+};
+
+trait RefCounted {
+    fn ref_count(&self) -> &AtomicU32;
+}
+
+impl RefCounted for FileSystemObject {
+    fn ref_count(&self) -> &AtomicU32 {
+        &self.ref_count
+    }
+}
+
+impl RefCounted for BlobObject {
+    fn ref_count(&self) -> &AtomicU32 {
+        &self.ref_count
+    }
+}
+
+unsafe extern "C" fn add_ref<T: RefCounted>(this: *mut c_void) -> u32 {
+    let this = unsafe { &*(this as *const T) };
+    this.ref_count().fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "C" fn release<T: RefCounted>(this: *mut c_void) -> u32 {
+    let remaining = unsafe { &*(this as *const T) }
+        .ref_count()
+        .fetch_sub(1, Ordering::Release)
+        - 1;
+    if remaining == 0 {
+        std::sync::atomic::fence(Ordering::Acquire);
+        // SAFETY: the object was allocated with `Box::into_raw` and this was its last reference.
+        drop(unsafe { Box::from_raw(this as *mut T) });
+    }
+    remaining
+}
+
+/// Writes `this` into `out` (with an added reference) if `iid` is one of `supported`.
+unsafe fn query_interface<T: RefCounted>(
+    this: *mut c_void,
+    iid: *const UUID,
+    out: *mut *mut c_void,
+    supported: &[UUID],
+) -> sys::SlangResult {
+    let iid = unsafe { &*iid };
+    if supported.contains(iid) {
+        unsafe {
+            add_ref::<T>(this);
+            *out = this;
+        }
+        SLANG_OK
+    } else {
+        unsafe { *out = std::ptr::null_mut() };
+        SLANG_E_NO_INTERFACE
+    }
+}
+
+unsafe extern "C" fn fs_query_interface(
+    this: *mut c_void,
+    iid: *const UUID,
+    out: *mut *mut c_void,
+) -> sys::SlangResult {
+    let supported = [UNKNOWN_IID, CASTABLE_IID, FILE_SYSTEM_IID];
+    unsafe { query_interface::<FileSystemObject>(this, iid, out, &supported) }
+}
+
+unsafe extern "C" fn fs_cast_as(this: *mut c_void, iid: *const UUID) -> *mut c_void {
+    // NOTE: unlike `queryInterface`, `castAs` doesn’t add a reference.
+    let iid = unsafe { &*iid };
+    if [UNKNOWN_IID, CASTABLE_IID, FILE_SYSTEM_IID].contains(iid) {
+        this
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+unsafe extern "C" fn fs_load_file(
+    this: *mut c_void,
+    path: *const c_char,
+    out_blob: *mut *mut c_void,
+) -> sys::SlangResult {
+    let this = unsafe { &*(this as *const FileSystemObject) };
+    let path = unsafe { CStr::from_ptr(path) }.to_string_lossy();
+
+    let data = match virtual_path(&path) {
+        Some(path) => {
+            let files = this.files.read().unwrap_or_else(|e| e.into_inner());
+            files.get(&path).cloned().or_else(|| this.resolve(&path))
+        }
+        None => std::fs::read(&*path).ok().map(Cow::Owned),
+    };
+
+    match data {
+        Some(data) => {
+            let blob = Box::new(BlobObject {
+                vtable: &BLOB_VTABLE,
+                ref_count: AtomicU32::new(1),
+                data,
+            });
+            unsafe { *out_blob = Box::into_raw(blob) as *mut c_void };
+            SLANG_OK
+        }
+        None => {
+            unsafe { *out_blob = std::ptr::null_mut() };
+            SLANG_E_NOT_FOUND
+        }
+    }
+}
+
+impl FileSystemObject {
+    fn resolve(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        let resolver = self.resolver.read().unwrap_or_else(|e| e.into_inner());
+        let resolver = resolver.as_ref()?;
+        // Unwinding through Slang’s C++ frames is undefined behavior.
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| resolver(path)))
+            .ok()
+            .flatten()
+            .map(|source| Cow::Owned(source.into_bytes()))
+    }
+}
+
+unsafe extern "C" fn blob_query_interface(
+    this: *mut c_void,
+    iid: *const UUID,
+    out: *mut *mut c_void,
+) -> sys::SlangResult {
+    unsafe { query_interface::<BlobObject>(this, iid, out, &[UNKNOWN_IID, BLOB_IID]) }
+}
+
+unsafe extern "C" fn blob_get_buffer_pointer(this: *mut c_void) -> *const c_void {
+    let this = unsafe { &*(this as *const BlobObject) };
+    this.data.as_ptr() as *const c_void
+}
+
+unsafe extern "C" fn blob_get_buffer_size(this: *mut c_void) -> usize {
+    let this = unsafe { &*(this as *const BlobObject) };
+    this.data.len()
+}
+
+/// Does `path` point to an in-memory file?
+pub fn is_virtual(path: &str) -> bool {
+    virtual_path(path).is_some()
+}
+
+/// The normalized path of an in-memory file, relative to [`VIRTUAL_ROOT`], or `None` if `path`
+/// doesn’t point into it.
+fn virtual_path(path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let relative = path
+        .trim_start_matches("./")
+        .strip_prefix(VIRTUAL_ROOT)?
+        .strip_prefix('/')?;
+    Some(normalize(relative))
+}
+
+/// Resolves the `.` and `..` components of a `/`-separated relative path.
+fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = vec![];
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
+/// An in-memory file system handed over to Slang sessions.
+pub struct MemoryFileSystem(NonNull<FileSystemObject>);
+
+// SAFETY: the file system object is reference-counted atomically and its file table is behind a
+//         lock.
+unsafe impl Send for MemoryFileSystem {}
+unsafe impl Sync for MemoryFileSystem {}
+
+impl Default for MemoryFileSystem {
+    fn default() -> Self {
+        let object = Box::new(FileSystemObject {
+            vtable: &FILE_SYSTEM_VTABLE,
+            ref_count: AtomicU32::new(1),
+            files: RwLock::new(BTreeMap::new()),
+            resolver: RwLock::new(None),
+        });
+        // SAFETY: `Box::into_raw` never returns null.
+        Self(unsafe { NonNull::new_unchecked(Box::into_raw(object)) })
+    }
+}
+
+impl Drop for MemoryFileSystem {
+    fn drop(&mut self) {
+        unsafe { release::<FileSystemObject>(self.0.as_ptr() as *mut c_void) };
+    }
+}
+
+impl MemoryFileSystem {
+    fn object(&self) -> &FileSystemObject {
+        unsafe { self.0.as_ref() }
+    }
+
+    /// The pointer to the `ISlangFileSystem` to give to Slang.
+    ///
+    /// Slang adds its own reference to the file system, so it can outlive `self`.
+    pub fn as_raw(&self) -> *mut sys::ISlangFileSystem {
+        self.0.as_ptr() as *mut sys::ISlangFileSystem
+    }
+
+    /// Registers all the files of `dir`, recursively, at their path relative to `dir`.
+    pub fn add_dir(&self, dir: &Dir<'static>) {
+        let mut files = self
+            .object()
+            .files
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        add_dir_recursive(&mut files, dir);
+    }
+
+    /// Registers a single file at `path`, relative to [`VIRTUAL_ROOT`].
+    pub fn add_file(&self, path: &str, data: Cow<'static, [u8]>) {
+        let mut files = self
+            .object()
+            .files
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        files.insert(normalize(&path.replace('\\', "/")), data);
+    }
+
+    /// Sets the callback providing the content of the in-memory files that weren’t registered
+    /// explicitly, given their path relative to [`VIRTUAL_ROOT`].
+    pub fn set_resolver(&self, resolver: Option<Resolver>) {
+        *self
+            .object()
+            .resolver
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = resolver;
+    }
+
+    /// Does this have a resolver?
+    pub fn has_resolver(&self) -> bool {
+        self.object()
+            .resolver
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// Feeds the path and content of every registered Slang file (source or precompiled module)
+    /// into `hasher`.
+    pub fn hash_sources(&self, hasher: &mut impl Hasher) {
+        let files = self
+            .object()
+            .files
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        for (path, content) in files.iter() {
+            if path.ends_with(".slang") || path.ends_with(".slang-module") {
+                hasher.write(path.as_bytes());
+                hasher.write_usize(content.len());
+                hasher.write(content);
+            }
+        }
+    }
+}
+
+fn add_dir_recursive(files: &mut BTreeMap<String, Cow<'static, [u8]>>, dir: &Dir<'static>) {
+    for entry in dir.entries() {
+        match entry {
+            DirEntry::Dir(dir) => add_dir_recursive(files, dir),
+            DirEntry::File(file) => {
+                let path = normalize(&file.path().to_string_lossy().replace('\\', "/"));
+                files.insert(path, Cow::Borrowed(file.contents()));
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Recompilation of Slang modules on file changes, as a building block for shader hot-reloading.
+
+use crate::{CompileOptions, SlangCompiler, SlangError};
+use notify::{EventKind, RecursiveMode, Watcher};
+use shader_slang::CompileTarget;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often the background thread checks whether the watcher was dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// File events received within this delay of each other are processed together, so that editors
+/// saving files in several steps don’t trigger several rebuilds.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(50);
+
+/// A module recompiled by a [`SlangWatcher`] whenever one of its dependencies changes.
+#[derive(Clone, Debug)]
+pub struct WatchedModule {
+    /// The module name or path, as given to [`SlangCompiler::compile`].
+    pub module: String,
+    /// The compilation target.
+    pub target: CompileTarget,
+    /// The entry point to compile, or `None` for all of them.
+    pub entry_point: Option<String>,
+    /// The compilation options.
+    pub options: CompileOptions,
+}
+
+impl WatchedModule {
+    /// A module compiled for `target` with all its entry points and the default options.
+    pub fn new(module: impl ToString, target: CompileTarget) -> Self {
+        Self {
+            module: module.to_string(),
+            target,
+            entry_point: None,
+            options: CompileOptions::default(),
+        }
+    }
+}
+
+/// An event emitted by a [`SlangWatcher`].
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// `module` was recompiled after one of its dependencies changed.
+    Rebuilt {
+        /// The module name or path, as given in its [`WatchedModule`].
+        module: String,
+        /// The new target code of the module.
+        code: Vec<u8>,
+    },
+    /// Recompiling `module` failed after one of its dependencies changed.
+    Failed {
+        /// The module name or path, as given in its [`WatchedModule`].
+        module: String,
+        /// The compilation error.
+        error: SlangError,
+    },
+    /// Watching the file system failed.
+    WatchError(notify::Error),
+}
+
+/// Watches the search paths of a [`SlangCompiler`] and recompiles modules in the background when
+/// the files they depend on change.
+///
+/// Only the modules depending on a modified file (see [`SlangCompiler::dependencies`]) are
+/// recompiled. Dropping the watcher stops the background thread.
+pub struct SlangWatcher {
+    events: Receiver<WatchEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SlangWatcher {
+    /// Starts watching the files `modules` depend on.
+    ///
+    /// Because the Slang compiler can’t be shared across threads, `make_compiler` is called from
+    /// the background thread to create the compiler used for recompilation. It must be configured
+    /// the same way as the compiler used by the application (search paths, embedded directories,
+    /// global macros).
+    pub fn new(
+        make_compiler: impl FnOnce() -> SlangCompiler + Send + 'static,
+        modules: Vec<WatchedModule>,
+    ) -> Self {
+        let (events_tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("slang-watcher".to_string())
+            .spawn(move || run(make_compiler(), modules, events_tx, thread_stop))
+            .expect("failed to spawn the slang watcher thread");
+
+        Self {
+            events,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// The channel receiving the events of this watcher.
+    pub fn events(&self) -> &Receiver<WatchEvent> {
+        &self.events
+    }
+
+    /// Iterates through the events received since the last call, without blocking.
+    pub fn try_iter(&self) -> impl Iterator<Item = WatchEvent> + '_ {
+        self.events.try_iter()
+    }
+}
+
+impl Drop for SlangWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    // Deleted files can’t be canonicalized, but still need to be matched against dependencies.
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+struct WatchState {
+    watcher: notify::RecommendedWatcher,
+    recursive_roots: Vec<PathBuf>,
+    watched_dirs: HashSet<PathBuf>,
+    events: Sender<WatchEvent>,
+}
+
+impl WatchState {
+    fn watch(&mut self, dir: PathBuf, mode: RecursiveMode) {
+        if mode == RecursiveMode::NonRecursive
+            && self
+                .recursive_roots
+                .iter()
+                .any(|root| dir.starts_with(root))
+        {
+            return;
+        }
+
+        if self.watched_dirs.insert(dir.clone()) {
+            if let Err(e) = self.watcher.watch(&dir, mode) {
+                let _ = self.events.send(WatchEvent::WatchError(e));
+            }
+            if mode == RecursiveMode::Recursive {
+                self.recursive_roots.push(dir);
+            }
+        }
+    }
+
+    /// Watches the directories of `module`’s dependencies, and returns them, or `None` if they
+    /// can’t be determined.
+    fn dependencies(
+        &mut self,
+        compiler: &SlangCompiler,
+        module: &WatchedModule,
+    ) -> Option<HashSet<PathBuf>> {
+        let dependencies: HashSet<_> = compiler
+            .dependencies(&module.module, module.target, &module.options)
+            .ok()?
+            .iter()
+            .map(|path| canonicalize(path))
+            .collect();
+
+        for dependency in &dependencies {
+            if let Some(dir) = dependency.parent() {
+                self.watch(dir.to_path_buf(), RecursiveMode::NonRecursive);
+            }
+        }
+
+        Some(dependencies)
+    }
+}
+
+fn run(
+    compiler: SlangCompiler,
+    modules: Vec<WatchedModule>,
+    events: Sender<WatchEvent>,
+    stop: Arc<AtomicBool>,
+) {
+    let (fs_events_tx, fs_events) = mpsc::channel();
+    let watcher = match notify::recommended_watcher(fs_events_tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            let _ = events.send(WatchEvent::WatchError(e));
+            return;
+        }
+    };
+    let mut state = WatchState {
+        watcher,
+        recursive_roots: vec![],
+        watched_dirs: HashSet::new(),
+        events: events.clone(),
+    };
+
+    for search_path in compiler.search_paths() {
+        state.watch(canonicalize(search_path), RecursiveMode::Recursive);
+    }
+
+    let mut dependencies: Vec<_> = modules
+        .iter()
+        .map(|module| state.dependencies(&compiler, module))
+        .collect();
+
+    while !stop.load(Ordering::Relaxed) {
+        let event = match fs_events.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+
+        let mut changed = HashSet::new();
+        let mut collect = |event: notify::Result<notify::Event>| match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                changed.extend(event.paths.iter().map(|path| canonicalize(path)))
+            }
+            Ok(_) => {}
+            Err(e) => {
+                let _ = events.send(WatchEvent::WatchError(e));
+            }
+        };
+        collect(event);
+        while let Ok(event) = fs_events.recv_timeout(DEBOUNCE_DELAY) {
+            collect(event);
+        }
+
+        if changed.is_empty() {
+            continue;
+        }
+        // The cached sessions hold the previous version of the modified modules.
+        compiler.clear_session_cache();
+
+        for (module, module_dependencies) in modules.iter().zip(dependencies.iter_mut()) {
+            // Rebuild on any change if the dependencies are unknown (e.g. after a failed load).
+            let affected = module_dependencies
+                .as_ref()
+                .is_none_or(|deps| !deps.is_disjoint(&changed));
+            if !affected {
+                continue;
+            }
+
+            let event = match compiler.compile_target_code(
+                &module.module,
+                module.target,
+                module.entry_point.as_deref(),
+                &module.options,
+            ) {
+                Ok(code) => WatchEvent::Rebuilt {
+                    module: module.module.clone(),
+                    code,
+                },
+                Err(error) => WatchEvent::Failed {
+                    module: module.module.clone(),
+                    error,
+                },
+            };
+            *module_dependencies = state.dependencies(&compiler, module);
+
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -2,115 +2,618 @@
 
 extern crate proc_macro;
 
-use darling::FromDeriveInput;
+use darling::ast::{Data, Fields, Style};
+use darling::{FromDeriveInput, FromField};
 use proc_macro::TokenStream;
-use quote::{ToTokens, quote};
-use syn::{Data, DataStruct};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use std::collections::BTreeMap;
 
 #[derive(FromDeriveInput, Clone)]
-#[darling(attributes(shader))]
+#[darling(attributes(shader), supports(struct_any))]
 struct DeriveShadersParams {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: Data<(), ShaderField>,
     pub module: String,
+    /// Preprocessor macros defined when compiling the module.
+    #[darling(default)]
+    pub defines: BTreeMap<String, String>,
+    /// Whether to check that the entry points of the fields exist in the module, from the list
+    /// written by `minislang::build_support::write_entry_points`.
+    #[darling(default)]
+    pub check_entry_points: bool,
+    /// Whether to load the module from the pack written by `minislang::build_support::write_pack`
+    /// instead of compiling it.
+    #[darling(default)]
+    pub precompiled: bool,
+}
+
+#[derive(FromField, Clone)]
+#[darling(attributes(shader))]
+struct ShaderField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    /// The name of the entry point loaded into this field, if it differs from the field’s name.
+    /// Required for tuple-struct fields.
+    entry_point: Option<String>,
+    /// Whether this field is an `Option<GpuFunction<B>>` set to `None` if its entry point fails
+    /// to load, instead of failing `from_backend`.
+    #[darling(default)]
+    optional: bool,
+    /// Whether this field is a `LazyGpuFunction<B>`, compiled on first use.
+    #[darling(default)]
+    lazy: bool,
+}
+
+#[derive(FromDeriveInput, Clone)]
+#[darling(attributes(shader), supports(struct_any))]
+struct DeriveShaderArgsParams {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: Data<(), ShaderArgsField>,
+}
+
+#[derive(FromField, Clone)]
+#[darling(attributes(shader))]
+struct ShaderArgsField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    /// The name of the shader parameter bound to this field, if it differs from the field’s name.
+    /// Required for tuple-struct fields.
+    name: Option<String>,
+    /// Whether this field is bound by value (it must implement `DeviceValue` and `Pod`), instead
+    /// of through its `ShaderArgs` implementation.
+    #[darling(default)]
+    value: bool,
+}
+
+#[derive(FromDeriveInput, Clone)]
+#[darling(supports(struct_named, struct_newtype, struct_tuple))]
+struct DeriveDeviceValueParams {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: Data<(), DeviceValueField>,
+}
+
+#[derive(FromField, Clone)]
+struct DeviceValueField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
 }
 
+/// The name a field is matched against: the one given by its attribute if any, or the field’s name
+/// otherwise.
+fn field_name(
+    ident: &Option<syn::Ident>,
+    ty: &syn::Type,
+    attribute: &Option<String>,
+    attribute_name: &str,
+) -> darling::Result<String> {
+    match (ident, attribute) {
+        (_, Some(name)) => Ok(name.clone()),
+        (None, None) => Err(darling::Error::custom(format!(
+            "tuple-struct fields need a `#[shader({attribute_name} = \"…\")]` attribute"
+        ))
+        .with_span(ty)),
+        (Some(ident), None) => Ok(ident.to_string()),
+    }
+}
+
+/// The backend type parameter of the derived struct: the one bound by `Backend`, or else the one
+/// named `B`.
+///
+/// If the struct doesn’t have any (e.g. unit structs), a `B: Backend` parameter is added to
+/// `impl_generics`, so that the generated impl is generic over the backend.
+fn backend_param(struct_generics: &syn::Generics, impl_generics: &mut syn::Generics) -> syn::Ident {
+    let is_backend_bound = |bound: &syn::TypeParamBound| {
+        matches!(bound, syn::TypeParamBound::Trait(t)
+            if t.path.segments.last().is_some_and(|s| s.ident == "Backend"))
+    };
+    let existing = struct_generics
+        .type_params()
+        .find(|param| param.bounds.iter().any(is_backend_bound))
+        .or_else(|| {
+            struct_generics
+                .type_params()
+                .find(|param| param.ident == "B")
+        });
+    if let Some(param) = existing {
+        return param.ident.clone();
+    }
+
+    impl_generics
+        .params
+        .push(syn::parse_quote! { B: slang_hal::backend::Backend });
+    format_ident!("B")
+}
+
+/// Name of the file written in `OUT_DIR` by `minislang::build_support::write_entry_points`.
+const ENTRY_POINTS_MANIFEST: &str = "slang_entry_points.txt";
+
+/// Reads the entry points of `module` from the list written in `OUT_DIR` by the build script.
+///
+/// Returns the path of that list, so that the generated code can depend on it, along with the
+/// entry points.
+fn module_entry_points(module: &str, span: &syn::Ident) -> darling::Result<(String, Vec<String>)> {
+    let missing_manifest = || {
+        darling::Error::custom(format!(
+            "`check_entry_points` requires a build script calling \
+             `minislang::build_support::write_entry_points` to list the entry points in \
+             `$OUT_DIR/{ENTRY_POINTS_MANIFEST}`"
+        ))
+        .with_span(span)
+    };
+    let out_dir = std::env::var("OUT_DIR").map_err(|_| missing_manifest())?;
+    let path = std::path::Path::new(&out_dir).join(ENTRY_POINTS_MANIFEST);
+    let manifest = std::fs::read_to_string(&path).map_err(|_| missing_manifest())?;
+    let modules = manifest.lines().filter_map(|line| line.split_once(':'));
+    let Some((_, entry_points)) = modules.clone().find(|(name, _)| name.trim() == module) else {
+        let available: Vec<_> = modules.map(|(name, _)| name.trim()).collect();
+        return Err(darling::Error::custom(format!(
+            "the slang module `{module}` doesn’t exist; available modules: {}",
+            available.join(", ")
+        ))
+        .with_span(span));
+    };
+    let entry_points = entry_points
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    Ok((path.to_string_lossy().into_owned(), entry_points))
+}
+
+/// Builds `Self` from the expressions initializing each of its `fields`, in order.
+fn construct(fields: &Fields<TokenStream2>, idents: &[Option<syn::Ident>]) -> TokenStream2 {
+    let values = &fields.fields;
+    match fields.style {
+        Style::Struct => quote! { Self { #(#idents: #values,)* } },
+        Style::Tuple => quote! { Self(#(#values,)*) },
+        Style::Unit => quote! { Self },
+    }
+}
+
+/// Implements `Shader` by loading each field from the entry point of the same name in the Slang
+/// module given by `#[shader(module = "…")]`.
+///
+/// Fields of type `Option<GpuFunction<B>>` marked with `#[shader(optional)]` are set to `None` if
+/// their entry point doesn’t exist or fails to compile for the backend, e.g. for kernels only
+/// supported by some backends.
+///
+/// Fields of type `LazyGpuFunction<B>` marked with `#[shader(lazy)]` are only compiled when they
+/// are first used.
+///
+/// Preprocessor macros can be defined for compiling the module with
+/// `#[shader(defines(NAME = "value", …))]`.
+///
+/// Use `#[shader(entry_point = "…")]` on a field to load another entry point, e.g. when Rust and
+/// Slang follow different naming conventions. This attribute is required on tuple-struct fields.
+///
+/// `Shader::from_backend_async` is implemented too, compiling all the non-lazy entry points on a
+/// background thread, as well as `Shader::from_registry`, sharing the compiled module with the
+/// other shaders of a `ShaderRegistry`.
+///
+/// With `#[shader(precompiled)]`, the module is loaded from the pack embedded in the crate, as
+/// written by a build script calling `minislang::build_support::write_pack`, instead of being
+/// compiled. The struct then gets a `from_precompiled(backend)` method that doesn’t need the
+/// Slang compiler. Lazy fields and `defines` aren’t supported in this mode.
+///
+/// With `#[shader(check_entry_points)]`, the entry point of each field (except optional ones) is
+/// checked to exist in the module at compile time, from the list written by a build script calling
+/// `minislang::build_support::write_entry_points`.
 #[proc_macro_derive(Shader, attributes(shader))]
 pub fn derive_shader(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
-    let struct_identifier = &input.ident;
-
     let derive_shaders = match DeriveShadersParams::from_derive_input(&input) {
         Ok(v) => v,
         Err(e) => {
             return e.write_errors().into();
         }
     };
+    let struct_identifier = &derive_shaders.ident;
+    let mut generics = derive_shaders.generics.clone();
+    let backend = backend_param(&derive_shaders.generics, &mut generics);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = derive_shaders.generics.split_for_impl();
 
-    match &input.data {
-        Data::Struct(DataStruct { fields, .. }) => {
-            /*
-             * Field attributes.
-             */
-            let mut kernels_to_build = vec![];
-            let slang_path = derive_shaders.module.replace("::", "/");
+    let fields = derive_shaders
+        .data
+        .take_struct()
+        .expect("only structs are supported");
+    let slang_path = derive_shaders.module.replace("::", "/");
+    let mut errors = darling::Error::accumulator();
+    let known_entry_points = if derive_shaders.check_entry_points {
+        errors.handle(module_entry_points(&slang_path, struct_identifier))
+    } else {
+        None
+    };
+    let idents: Vec<_> = fields.iter().map(|field| field.ident.clone()).collect();
+    let kernels_to_build = fields.clone().map(|field| {
+        let entry_point = errors
+            .handle(field_name(
+                &field.ident,
+                &field.ty,
+                &field.entry_point,
+                "entry_point",
+            ))
+            .unwrap_or_default();
+        if let Some((_, known)) = &known_entry_points
+            && !field.optional
+            && !known.contains(&entry_point)
+        {
+            let error = darling::Error::custom(format!(
+                "entry point `{entry_point}` not found in the slang module `{slang_path}`; \
+                 available entry points: {}",
+                known.join(", ")
+            ));
+            errors.push(match &field.ident {
+                Some(ident) => error.with_span(ident),
+                None => error.with_span(&field.ty),
+            });
+        }
+        let function = quote! {
+            slang_hal::function::GpuFunction::from_file_with_options(backend, compiler, #slang_path, #entry_point, &options)
+        };
+        if field.lazy && field.optional {
+            errors.push(
+                darling::Error::custom("`lazy` and `optional` can’t be combined")
+                    .with_span(&field.ty),
+            );
+        }
+        if field.lazy && derive_shaders.precompiled {
+            errors.push(
+                darling::Error::custom("`lazy` fields can’t be loaded from precompiled shaders")
+                    .with_span(&field.ty),
+            );
+        }
+        if field.lazy {
+            quote! {
+                slang_hal::function::LazyGpuFunction::new(#slang_path, #entry_point, options.clone())
+            }
+        } else if field.optional {
+            quote! { #function.ok() }
+        } else {
+            quote! { #function? }
+        }
+    });
+    if derive_shaders.precompiled && !derive_shaders.defines.is_empty() {
+        errors.push(darling::Error::custom(
+            "`defines` can’t be combined with `precompiled`; pass them to the compile options of \
+             `write_pack` instead",
+        ));
+    }
+    if let Err(e) = errors.finish() {
+        return e.write_errors().into();
+    }
+    let kernels_from_registry = fields.clone().map(|field| {
+        let entry_point = field_name(&field.ident, &field.ty, &field.entry_point, "entry_point")
+            .unwrap_or_default();
+        let function = quote! {
+            registry.function(backend, compiler, #slang_path, #entry_point, &options)
+        };
+        if field.lazy {
+            quote! {
+                slang_hal::function::LazyGpuFunction::new(#slang_path, #entry_point, options.clone())
+            }
+        } else if field.optional {
+            quote! { #function.ok() }
+        } else {
+            quote! { #function? }
+        }
+    });
+    // Recompiles the struct when the entry points change.
+    let manifest_dependency = known_entry_points.map(|(path, _)| {
+        quote! { const _: &[u8] = include_bytes!(#path); }
+    });
+    if derive_shaders.precompiled {
+        let kernels_from_pack = fields.map(|field| {
+            let entry_point = field_name(&field.ident, &field.ty, &field.entry_point, "entry_point")
+                .unwrap_or_default();
+            let function = quote! {
+                slang_hal::function::GpuFunction::from_pack(backend, &pack, #slang_path, #entry_point)
+            };
+            if field.optional {
+                quote! { #function.ok() }
+            } else {
+                quote! { #function? }
+            }
+        });
+        let from_pack = construct(&kernels_from_pack, &idents);
+        // The inherent impl can’t have the backend parameter added to the `Shader` impl, so it is
+        // a parameter of the method instead.
+        let (struct_impl_generics, _, struct_where_clause) =
+            derive_shaders.generics.split_for_impl();
+        let method_generics = (generics.params.len() != derive_shaders.generics.params.len())
+            .then(|| quote! { <#backend: slang_hal::backend::Backend> });
 
-            for field in fields.iter() {
-                let ident = field
-                    .ident
-                    .as_ref()
-                    .expect("unnamed fields not supported")
-                    .into_token_stream();
+        return quote! {
+            #manifest_dependency
 
-                kernels_to_build.push(quote! {
-                    #ident: GpuFunction::from_file(backend, compiler, #slang_path, stringify!(#ident))?,
-                });
+            #[automatically_derived]
+            impl #struct_impl_generics #struct_identifier #ty_generics #struct_where_clause {
+                /// Loads all the functions of this shader from the pack of precompiled shaders
+                /// embedded in the crate, without the Slang compiler.
+                pub fn from_precompiled #method_generics(backend: &#backend) -> Result<Self, <#backend as slang_hal::backend::Backend>::Error> {
+                    let pack = slang_hal::re_exports::minislang::pack::SlangPack::from_bytes(
+                        include_bytes!(concat!(env!("OUT_DIR"), "/slang_shaders.pack")),
+                    )?;
+                    Ok(#from_pack)
+                }
             }
 
-            let from_backend = quote! {
-                Ok(Self {
-                    #(
-                        #kernels_to_build
-                    )*
-                })
-            };
+            #[automatically_derived]
+            impl #impl_generics slang_hal::shader::Shader<#backend> for #struct_identifier #ty_generics #where_clause {
+                fn from_backend(backend: &#backend, _compiler: &slang_hal::re_exports::minislang::SlangCompiler) -> Result<Self, <#backend as slang_hal::backend::Backend>::Error> {
+                    Self::from_precompiled(backend)
+                }
+
+                fn from_backend_async<'__a>(
+                    backend: &'__a #backend,
+                    _make_compiler: impl FnOnce() -> slang_hal::re_exports::minislang::SlangCompiler + Send + 'static,
+                ) -> impl core::future::Future<Output = Result<Self, <#backend as slang_hal::backend::Backend>::Error>> + '__a {
+                    async move { Self::from_precompiled(backend) }
+                }
+
+                fn modules() -> Vec<String> {
+                    vec![#slang_path.to_string()]
+                }
+            }
+        }
+        .into();
+    }
 
+    let from_backend = construct(&kernels_to_build, &idents);
+    let from_registry = construct(&kernels_from_registry, &idents);
+
+    // The async constructor compiles every non-lazy entry point on a background thread, then
+    // loads the result bound to each field.
+    let mut compiled_entry_points = vec![];
+    let mut compiled_idents = vec![];
+    let kernels_to_load = fields.map(|field| {
+        let entry_point = field_name(&field.ident, &field.ty, &field.entry_point, "entry_point")
+            .unwrap_or_default();
+        if field.lazy {
+            return quote! {
+                slang_hal::function::LazyGpuFunction::new(#slang_path, #entry_point, options.clone())
+            };
+        }
+        let compiled = format_ident!("__compiled_{}", compiled_idents.len());
+        compiled_entry_points.push(entry_point);
+        compiled_idents.push(compiled.clone());
+        if field.optional {
             quote! {
-                #[automatically_derived]
-                impl<B: Backend> slang_hal::shader::Shader<B> for #struct_identifier<B> {
-                    fn from_backend(backend: &B, compiler: &slang_hal::re_exports::minislang::SlangCompiler) -> Result<Self, B::Error> {
-                        #from_backend
-                    }
+                #compiled.ok().and_then(|c| slang_hal::function::GpuFunction::from_compiled(backend, c).ok())
+            }
+        } else {
+            quote! { slang_hal::function::GpuFunction::from_compiled(backend, #compiled?)? }
+        }
+    });
+    let from_backend_async = construct(&kernels_to_load, &idents);
+    let (define_names, define_values): (Vec<_>, Vec<_>) = derive_shaders.defines.iter().unzip();
+
+    quote! {
+        #manifest_dependency
+
+        #[automatically_derived]
+        impl #impl_generics slang_hal::shader::Shader<#backend> for #struct_identifier #ty_generics #where_clause {
+            fn from_backend(backend: &#backend, compiler: &slang_hal::re_exports::minislang::SlangCompiler) -> Result<Self, <#backend as slang_hal::backend::Backend>::Error> {
+                let options = slang_hal::re_exports::minislang::CompileOptions::default()
+                    #(.macro_define(#define_names, #define_values))*;
+                Ok(#from_backend)
+            }
+
+            fn from_registry(
+                backend: &#backend,
+                compiler: &slang_hal::re_exports::minislang::SlangCompiler,
+                registry: &slang_hal::registry::ShaderRegistry<#backend>,
+            ) -> Result<Self, <#backend as slang_hal::backend::Backend>::Error> {
+                let options = slang_hal::re_exports::minislang::CompileOptions::default()
+                    #(.macro_define(#define_names, #define_values))*;
+                Ok(#from_registry)
+            }
+
+            fn from_backend_async<'__a>(
+                backend: &'__a #backend,
+                make_compiler: impl FnOnce() -> slang_hal::re_exports::minislang::SlangCompiler + Send + 'static,
+            ) -> impl core::future::Future<Output = Result<Self, <#backend as slang_hal::backend::Backend>::Error>> + '__a {
+                async move {
+                    let options = slang_hal::re_exports::minislang::CompileOptions::default()
+                        #(.macro_define(#define_names, #define_values))*;
+                    let [#(#compiled_idents,)*] = slang_hal::function::compile_in_background(
+                        make_compiler,
+                        slang_hal::backend::Backend::compile_target(backend),
+                        [#((#slang_path.to_string(), #compiled_entry_points.to_string()),)*],
+                        options.clone(),
+                    )
+                    .await;
+                    Ok(#from_backend_async)
                 }
             }
+
+            fn modules() -> Vec<String> {
+                vec![#slang_path.to_string()]
+            }
         }
-        _ => unimplemented!(),
     }
     .into()
 }
 
-#[proc_macro_derive(ShaderArgs)]
+/// Implements `ShaderArgs` by binding each field to the shader parameter of the same name.
+///
+/// Use `#[shader(name = "…")]` on a field to bind it to another parameter. This attribute is
+/// required on tuple-struct fields.
+///
+/// Fields marked with `#[shader(value)]` are passed by value to the kernel (staged into the
+/// uniform buffer of the dispatch where needed, see `Dispatch::write_value`), e.g. for
+/// plain-old-data structs that don’t implement `ShaderArgs`.
+#[proc_macro_derive(ShaderArgs, attributes(shader))]
 pub fn derive_shader_args(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
-    let struct_identifier = &input.ident;
-
-    match &input.data {
-        Data::Struct(DataStruct { fields, .. }) => {
-            /*
-             * Field attributes.
-             */
-            let mut fields_to_match = vec![];
+    let derive_args = match DeriveShaderArgsParams::from_derive_input(&input) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.write_errors().into();
+        }
+    };
+    let struct_identifier = &derive_args.ident;
+    let mut generics = derive_args.generics.clone();
+    let backend = backend_param(&derive_args.generics, &mut generics);
+    // NOTE: the lifetimes of the generated impl are given unusual names so they don’t clash with
+    //       the struct’s own lifetimes.
+    generics.params.insert(0, syn::parse_quote! { '__args });
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = derive_args.generics.split_for_impl();
 
-            for field in fields.iter() {
-                let ident = field
-                    .ident
-                    .as_ref()
-                    .expect("unnamed fields not supported")
-                    .into_token_stream();
+    let fields = derive_args
+        .data
+        .take_struct()
+        .expect("only structs are supported");
+    let mut errors = darling::Error::accumulator();
+    let mut names = vec![];
+    let fields_to_match: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter_map(|(i, field)| {
+            let name = errors.handle(field_name(&field.ident, &field.ty, &field.name, "name"))?;
+            names.push(name.clone());
+            let member = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! { #index }
+                }
+            };
+            if field.value {
+                Some((
+                    quote! { #name => dispatch.write_value(binding, name, &self.#member), },
+                    quote! { #name => None, },
+                ))
+            } else {
+                Some((
+                    quote! { #name => self.#member.write_arg(binding, name, dispatch), },
+                    quote! { #name => self.#member.arg_info(name), },
+                ))
+            }
+        })
+        .collect();
+    let (fields_to_match, infos_to_match): (Vec<_>, Vec<_>) = fields_to_match.into_iter().unzip();
+    if let Err(e) = errors.finish() {
+        return e.write_errors().into();
+    }
 
-                fields_to_match.push(quote! {
-                    stringify!(#ident) => self.#ident.write_arg(binding, name, dispatch)?,
-                });
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics slang_hal::shader::ShaderArgs<'__args, #backend> for #struct_identifier #ty_generics #where_clause {
+            fn write_arg<'__dispatch>(&'__args self, binding: slang_hal::backend::ShaderBinding, name: &str, dispatch: &mut <#backend as slang_hal::backend::Backend>::Dispatch<'__dispatch>) -> Result<(), slang_hal::shader::ShaderArgsError>
+            where '__args: '__dispatch {
+                use slang_hal::backend::Dispatch;
+                match name {
+                    #(
+                        #fields_to_match
+                    )*
+                    _ => Err(slang_hal::shader::ShaderArgsError::ArgNotFound(name.to_owned())),
+                }
             }
 
-            quote! {
-                #[automatically_derived]
-                // TODO: don't hard-code the lifetime requirement?
-                impl<'b, B: Backend> slang_hal::shader::ShaderArgs<'b, B> for #struct_identifier<'_, B> {
-                    fn write_arg<'a>(&'b self, binding: slang_hal::backend::ShaderBinding, name: &str, dispatch: &mut B::Dispatch<'a>) -> Result<(), slang_hal::shader::ShaderArgsError>
-                    where 'b: 'a {
-                        use slang_hal::backend::Dispatch;
-                        match name {
-                            #(
-                                #fields_to_match
-                            )*
-                            _ => return Err(slang_hal::shader::ShaderArgsError::ArgNotFound(name.to_owned())),
-                        }
-
-                        Ok(())
-                    }
+            fn arg_info(&self, name: &str) -> Option<slang_hal::shader::ArgInfo> {
+                match name {
+                    #(
+                        #infos_to_match
+                    )*
+                    _ => None,
                 }
             }
+
+            fn arg_names(&self) -> Vec<String> {
+                vec![#(#names.to_string()),*]
+            }
+        }
+    }
+    .into()
+}
+
+/// Implements `DeviceValue` for a `#[repr(C)]` (or `#[repr(transparent)]`) struct whose fields all
+/// implement `DeviceValue`.
+///
+/// This also describes the layout of the struct, which can be compared to the layout of its Slang
+/// counterpart with `slang_hal::layout::check_layout`.
+#[proc_macro_derive(DeviceValue)]
+pub fn derive_device_value(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    let derive_value = match DeriveDeviceValueParams::from_derive_input(&input) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.write_errors().into();
+        }
+    };
+    let struct_identifier = &derive_value.ident;
+
+    // The Rust layout of other representations isn’t specified, so it can’t match Slang’s.
+    let has_stable_repr = input.attrs.iter().any(|attr| {
+        attr.path().is_ident("repr")
+            && attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                )
+                .is_ok_and(|reprs| {
+                    reprs.iter().any(|repr| {
+                        repr.path().is_ident("C") || repr.path().is_ident("transparent")
+                    })
+                })
+    });
+    if !has_stable_repr {
+        return syn::Error::new_spanned(
+            struct_identifier,
+            "`DeviceValue` can only be derived for `#[repr(C)]` or `#[repr(transparent)]` structs",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let fields = derive_value
+        .data
+        .take_struct()
+        .expect("only structs are supported");
+    let mut generics = derive_value.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for field in fields.iter() {
+        let ty = &field.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote! { #ty: slang_hal::backend::DeviceValue });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_layouts = fields.iter().enumerate().map(|(i, field)| {
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = syn::Index::from(i);
+                quote! { #index }
+            }
+        };
+        quote! {
+            slang_hal::layout::FieldLayout {
+                name: stringify!(#member),
+                offset: core::mem::offset_of!(Self, #member),
+                size: core::mem::size_of::<#ty>(),
+            }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        // SAFETY: the struct has a stable representation and only contains device values.
+        unsafe impl #impl_generics slang_hal::backend::DeviceValue for #struct_identifier #ty_generics #where_clause {
+            fn struct_layout() -> Option<slang_hal::layout::StructLayout> {
+                Some(slang_hal::layout::StructLayout {
+                    name: stringify!(#struct_identifier),
+                    size: core::mem::size_of::<Self>(),
+                    fields: vec![#(#field_layouts),*],
+                })
+            }
         }
-        _ => unimplemented!(),
     }
-        .into()
+    .into()
 }
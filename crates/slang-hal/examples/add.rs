@@ -70,7 +70,7 @@ async fn compute_sum_on_gpu<B: Backend>(
     // Dispatch the operation on the gpu.
     let add = GpuAdd::from_backend(backend, compiler)?;
     let mut encoder = backend.begin_encoding();
-    let mut pass = encoder.begin_pass();
+    let mut pass = encoder.begin_pass("add");
     add.launch(backend, &mut pass, &a, &b)?;
     drop(pass);
     backend.submit(encoder)?;
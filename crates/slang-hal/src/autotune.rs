@@ -0,0 +1,237 @@
+//! Selection of the block dimensions of a kernel by benchmarking it on the device.
+//!
+//! The best thread-group size of a kernel depends on the device: sizes tuned for a discrete GPU
+//! are often slow on integrated GPUs, and vice versa. An [`Autotuner`] compiles a kernel with
+//! each candidate block dimensions, times a representative launch of each variant, and keeps the
+//! fastest one. The kernel’s thread-group size must be set from macros, by default
+//! `[numthreads(BLOCK_DIM_X, BLOCK_DIM_Y, BLOCK_DIM_Z)]`.
+//!
+//! The selected block dimensions are stored in the on-disk cache of the [`SlangCompiler`] (see
+//! [`SlangCompiler::set_cache_dir`]), so the benchmark only runs again if the kernel’s sources,
+//! the candidates, or the device change.
+
+use crate::backend::{Backend, Encoder, UnsupportedError};
+use crate::function::GpuFunction;
+use minislang::{CompileOptions, SlangCompiler};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// The block dimensions tried by [`Autotuner::new`], for 1D kernels.
+pub const DEFAULT_CANDIDATES: &[[u32; 3]] = &[
+    [32, 1, 1],
+    [64, 1, 1],
+    [128, 1, 1],
+    [256, 1, 1],
+    [512, 1, 1],
+];
+
+/// The macros setting the block dimensions, used by [`Autotuner::new`].
+pub const DEFAULT_MACROS: [&str; 3] = ["BLOCK_DIM_X", "BLOCK_DIM_Y", "BLOCK_DIM_Z"];
+
+/// Benchmarks a kernel with several block dimensions and selects the fastest.
+///
+/// ```ignore
+/// let autotuner = Autotuner::new(&device_info.name);
+/// let function = autotuner
+///     .tune(&backend, &compiler, "particles.slang", "integrate", &options, |function, pass| {
+///         function.launch(&backend, pass, &args, [num_particles, 1, 1])
+///     })
+///     .await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Autotuner {
+    device: String,
+    candidates: Vec<[u32; 3]>,
+    macros: [String; 3],
+    iterations: usize,
+}
+
+impl Autotuner {
+    /// An autotuner trying the [`DEFAULT_CANDIDATES`], set through the [`DEFAULT_MACROS`].
+    ///
+    /// `device` identifies the device in the on-disk cache, e.g. its
+    /// [`DeviceInfo::name`](crate::backend::DeviceInfo::name), so that the configurations
+    /// selected for different devices don’t replace each other.
+    pub fn new(device: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            candidates: DEFAULT_CANDIDATES.to_vec(),
+            macros: DEFAULT_MACROS.map(str::to_string),
+            iterations: 10,
+        }
+    }
+
+    /// Sets the block dimensions to try.
+    pub fn with_candidates(mut self, candidates: Vec<[u32; 3]>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    /// Sets the names of the macros defined to the block dimensions along each axis.
+    pub fn with_macros(mut self, macros: [&str; 3]) -> Self {
+        self.macros = macros.map(str::to_string);
+        self
+    }
+
+    /// Sets the number of timed launches of each candidate (after a warm-up launch).
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Compiles and loads the entry point `entry_point_name` of the shader at `path` with the
+    /// fastest block dimensions, benchmarking the candidates unless the result is cached.
+    ///
+    /// `run` records a representative launch of the given variant of the kernel into the given
+    /// pass. It must adapt the launch to the variant’s block dimensions, which
+    /// [`GpuFunction::launch`] does. Candidates exceeding the device limits (see
+    /// [`Backend::limits`]) or failing to compile are skipped. The launches are timed with
+    /// timestamp queries if the backend supports them, or from the host otherwise.
+    pub async fn tune<B: Backend>(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        options: &CompileOptions,
+        mut run: impl FnMut(&GpuFunction<B>, &mut B::Pass) -> Result<(), B::Error>,
+    ) -> Result<GpuFunction<B>, B::Error> {
+        let cache_key = compiler.cache_key(
+            path,
+            backend.compile_target(),
+            Some(entry_point_name),
+            options,
+        );
+        let cache_entry = self.cache_entry::<B>();
+        let cached = cache_key
+            .and_then(|key| compiler.read_cache(key, &cache_entry))
+            .and_then(|bytes| Some(bytemuck::pod_read_unaligned(bytes.get(..12)?)));
+        if let Some(block_dim) = cached {
+            return self.load(
+                backend,
+                compiler,
+                path,
+                entry_point_name,
+                options,
+                block_dim,
+            );
+        }
+
+        let limits = backend.limits()?;
+        let mut best: Option<(u64, GpuFunction<B>)> = None;
+        let mut last_error = None;
+        for block_dim in &self.candidates {
+            let fits = (0..3).all(|i| block_dim[i] <= limits.max_workgroup_size[i])
+                && block_dim.iter().product::<u32>() <= limits.max_invocations_per_workgroup;
+            if !fits {
+                continue;
+            }
+
+            let loaded = self.load(
+                backend,
+                compiler,
+                path,
+                entry_point_name,
+                options,
+                *block_dim,
+            );
+            let function = match loaded {
+                Ok(function) => function,
+                Err(e) => {
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            let time_ns = match self.benchmark(backend, &function, &mut run).await {
+                Ok(time_ns) => time_ns,
+                Err(e) => {
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            log::debug!("{entry_point_name} with block dim {block_dim:?}: {time_ns} ns");
+            if best.as_ref().is_none_or(|(best_ns, _)| time_ns < *best_ns) {
+                best = Some((time_ns, function));
+            }
+        }
+
+        let Some((_, function)) = best else {
+            return Err(last_error.unwrap_or_else(|| {
+                UnsupportedError::new::<B>("block dimensions within the device limits").into()
+            }));
+        };
+        if let Some(key) = cache_key {
+            compiler.write_cache(key, &cache_entry, bytemuck::bytes_of(&function.block_dim()))?;
+        }
+        Ok(function)
+    }
+
+    /// The name of the cache entry holding the block dimensions selected by this autotuner.
+    fn cache_entry<B: Backend>(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        B::NAME.hash(&mut hasher);
+        self.device.hash(&mut hasher);
+        self.candidates.hash(&mut hasher);
+        self.macros.hash(&mut hasher);
+        format!("autotune-{:016x}", hasher.finish())
+    }
+
+    fn load<B: Backend>(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        options: &CompileOptions,
+        block_dim: [u32; 3],
+    ) -> Result<GpuFunction<B>, B::Error> {
+        let options = (0..3).fold(options.clone(), |options, i| {
+            options.macro_define(&self.macros[i], block_dim[i])
+        });
+        GpuFunction::from_file_with_options(backend, compiler, path, entry_point_name, &options)
+    }
+
+    /// The average duration of a launch recorded by `run`, in nanoseconds.
+    async fn benchmark<B: Backend>(
+        &self,
+        backend: &B,
+        function: &GpuFunction<B>,
+        run: &mut impl FnMut(&GpuFunction<B>, &mut B::Pass) -> Result<(), B::Error>,
+    ) -> Result<u64, B::Error> {
+        let label = function.entry_point();
+
+        // NOTE: the first launch may include one-time costs, e.g. pipeline creation.
+        let mut encoder = backend.begin_encoding();
+        run(function, &mut encoder.begin_pass(label))?;
+        backend.submit_async(encoder)?.await?;
+
+        match backend.create_timestamps(self.iterations) {
+            Ok(mut timestamps) => {
+                let mut encoder = backend.begin_encoding();
+                for _ in 0..self.iterations {
+                    run(
+                        function,
+                        &mut encoder.begin_timed_pass(label, &mut timestamps)?,
+                    )?;
+                }
+                backend.submit(encoder)?;
+                let passes = backend.resolve_timestamps(&mut timestamps).await?;
+                let total: u64 = passes.iter().map(|pass| pass.duration_ns()).sum();
+                Ok(total / passes.len().max(1) as u64)
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Err(_) => {
+                let start = std::time::Instant::now();
+                let mut encoder = backend.begin_encoding();
+                for _ in 0..self.iterations {
+                    run(function, &mut encoder.begin_pass(label))?;
+                }
+                backend.submit_async(encoder)?.await?;
+                Ok(start.elapsed().as_nanos() as u64 / self.iterations as u64)
+            }
+            // NOTE: `std::time::Instant` isn’t available on the web.
+            #[cfg(target_arch = "wasm32")]
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,1549 @@
+//! Type-erased backend, for selecting the backend at runtime.
+
+use crate::ShaderArgs;
+use crate::backend::{
+    Backend, Buffer, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid,
+    EncaseType, Encoder, Fence, FunctionOptions, MemoryInfo, PassTimestamps, Sampler,
+    SamplerDescriptor, ShaderBinding, SlangCompiled, SubmissionFuture, Texture, Timestamps,
+    UnsupportedError, WebGpu,
+};
+use crate::profiler::ProfilingReport;
+use crate::shader::{ArgInfo, ShaderArgsError};
+use bytemuck::Pod;
+use encase::ShaderType;
+use minislang::shader_slang::CompileTarget;
+use std::ops::{Range, RangeBounds};
+use wgpu::{BufferUsages, TextureFormat, TextureUsages};
+
+#[cfg(feature = "cpu")]
+use crate::backend::Cpu;
+#[cfg(feature = "cuda")]
+use crate::backend::Cuda;
+#[cfg(all(feature = "dx12", target_os = "windows"))]
+use crate::backend::Dx12;
+
+/// Matches several type-erased values at once, expecting all of them to come from the same
+/// backend.
+///
+/// The `$body` is evaluated with each `$var` bound to the value contained by the corresponding
+/// enum variant. The `$mismatch` expression is evaluated if the values come from different
+/// backends.
+macro_rules! match_any {
+    ($($ty:ident($val:expr) => $var:ident),+; $body:expr; else $mismatch:expr) => {
+        match ($($val,)+) {
+            ($($ty::WebGpu($var),)+) => $body,
+            #[cfg(feature = "cuda")]
+            ($($ty::Cuda($var),)+) => $body,
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            ($($ty::Dx12($var),)+) => $body,
+            #[cfg(feature = "cpu")]
+            ($($ty::Cpu($var),)+) => $body,
+            #[allow(unreachable_patterns)]
+            _ => $mismatch,
+        }
+    };
+}
+
+/// Converts a type-erased dispatch grid into the dispatch grid of a specific backend.
+macro_rules! downcast_grid {
+    ($grid:expr, $variant:ident) => {
+        match $grid {
+            DispatchGrid::Direct(grid) => DispatchGrid::Direct(grid),
+            DispatchGrid::Indirect(AnyBuffer::$variant(grid), offset) => {
+                DispatchGrid::Indirect(grid, offset)
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        }
+    };
+}
+
+/// A backend selected at runtime.
+///
+/// This allows the application to choose its backend dynamically (e.g. from a configuration
+/// file or a command-line flag) without having to make all its code generic over the backend.
+/// All the resources created by an `AnyBackend` are type-erased too (see [`AnyBuffer`],
+/// [`AnyEncoder`], etc.) and must only be used with the backend that created them. Mixing
+/// resources from different backends results in an [`AnyBackendError::BackendMismatch`] error,
+/// or a panic for operations that can’t fail.
+pub enum AnyBackend {
+    WebGpu(WebGpu),
+    #[cfg(feature = "cuda")]
+    Cuda(Cuda),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(Dx12),
+    #[cfg(feature = "cpu")]
+    Cpu(Cpu),
+}
+
+impl AnyBackend {
+    /// Initializes the first backend available on this machine.
+    ///
+    /// The candidates are tried in this order:
+    /// 1. [`Cuda`], if the `cuda` feature is enabled and a CUDA device exists.
+    /// 2. [`WebGpu`], if a GPU adapter can be found.
+    /// 3. [`Cpu`], if the `cpu` feature is enabled and Slang finds a C++ compiler to build its
+    ///    kernels with.
+    ///
+    /// If none of them can be initialized, the returned error lists why each candidate failed.
+    pub async fn auto() -> Result<Self, BackendSelectionError> {
+        let mut failures = vec![];
+
+        #[cfg(feature = "cuda")]
+        {
+            // NOTE: cudarc panics if the CUDA driver library can’t be found.
+            match std::panic::catch_unwind(Cuda::new) {
+                Ok(Ok(cuda)) => return Ok(Self::Cuda(cuda)),
+                Ok(Err(e)) => failures.push((Cuda::NAME, e.to_string())),
+                Err(panic) => failures.push((Cuda::NAME, panic_message(panic))),
+            }
+        }
+        #[cfg(not(feature = "cuda"))]
+        failures.push(("cuda", "the `cuda` feature isn’t enabled".to_string()));
+
+        match WebGpu::default().await {
+            Ok(webgpu) => return Ok(Self::WebGpu(webgpu)),
+            Err(e) => failures.push((WebGpu::NAME, e.to_string())),
+        }
+
+        // NOTE: the CPU kernels are compiled by a C++ compiler Slang looks for at runtime, so
+        //       without it the backend would only fail once the first module is loaded.
+        #[cfg(feature = "cpu")]
+        if minislang::SlangCompiler::new(vec![]).supports_target(Cpu::TARGET) {
+            return Ok(Self::Cpu(Cpu::new()));
+        } else {
+            failures.push((Cpu::NAME, "Slang found no C++ compiler".to_string()));
+        }
+        #[cfg(not(feature = "cpu"))]
+        failures.push(("cpu", "the `cpu` feature isn’t enabled".to_string()));
+
+        Err(BackendSelectionError { failures })
+    }
+
+    /// The name of the selected backend.
+    pub fn name(&self) -> &'static str {
+        match_any!(AnyBackend(self) => b; name_of(b); else unreachable!())
+    }
+}
+
+fn name_of<B: Backend>(_: &B) -> &'static str {
+    B::NAME
+}
+
+#[cfg(feature = "cuda")]
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = panic.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "initialization panicked".to_string()
+    }
+}
+
+/// Error returned by [`AnyBackend::auto`] if none of the candidate backends could be initialized.
+#[derive(Debug)]
+pub struct BackendSelectionError {
+    /// The name of each candidate backend, and the reason why it couldn’t be initialized.
+    pub failures: Vec<(&'static str, String)>,
+}
+
+impl std::fmt::Display for BackendSelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no backend could be initialized:")?;
+        for (name, reason) in &self.failures {
+            write!(f, "\n- {name}: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BackendSelectionError {}
+
+impl From<WebGpu> for AnyBackend {
+    fn from(backend: WebGpu) -> Self {
+        Self::WebGpu(backend)
+    }
+}
+
+#[cfg(feature = "cuda")]
+impl From<Cuda> for AnyBackend {
+    fn from(backend: Cuda) -> Self {
+        Self::Cuda(backend)
+    }
+}
+
+#[cfg(all(feature = "dx12", target_os = "windows"))]
+impl From<Dx12> for AnyBackend {
+    fn from(backend: Dx12) -> Self {
+        Self::Dx12(backend)
+    }
+}
+
+#[cfg(feature = "cpu")]
+impl From<Cpu> for AnyBackend {
+    fn from(backend: Cpu) -> Self {
+        Self::Cpu(backend)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AnyBackendError {
+    #[error(transparent)]
+    ShaderArg(#[from] ShaderArgsError),
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error("attempted to mix resources from different backends")]
+    BackendMismatch,
+    #[error(transparent)]
+    WebGpu(#[from] <WebGpu as Backend>::Error),
+    #[cfg(feature = "cuda")]
+    #[error(transparent)]
+    Cuda(#[from] <Cuda as Backend>::Error),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    #[error(transparent)]
+    Dx12(#[from] <Dx12 as Backend>::Error),
+    #[cfg(feature = "cpu")]
+    #[error(transparent)]
+    Cpu(#[from] <Cpu as Backend>::Error),
+}
+
+pub enum AnyBuffer<T: DeviceValue> {
+    WebGpu(<WebGpu as Backend>::Buffer<T>),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Buffer<T>),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Buffer<T>),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Buffer<T>),
+}
+
+pub enum AnyTexture {
+    WebGpu(<WebGpu as Backend>::Texture),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Texture),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Texture),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Texture),
+}
+
+pub enum AnyFence {
+    WebGpu(<WebGpu as Backend>::Fence),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Fence),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Fence),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Fence),
+}
+
+/// Timestamp queries of an [`AnyBackend`].
+///
+/// Only the WebGpu and CUDA backends support timestamp queries.
+pub enum AnyTimestamps {
+    WebGpu(<WebGpu as Backend>::Timestamps),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Timestamps),
+}
+
+/// Command bundles of an [`AnyBackend`].
+///
+/// Only the WebGpu and CUDA backends support command bundles.
+pub enum AnyBundle {
+    WebGpu(<WebGpu as Backend>::Bundle),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Bundle),
+}
+
+pub enum AnySampler {
+    WebGpu(<WebGpu as Backend>::Sampler),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Sampler),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Sampler),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Sampler),
+}
+
+pub enum AnyBufferSlice<'b, T: DeviceValue> {
+    WebGpu(<WebGpu as Backend>::BufferSlice<'b, T>),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::BufferSlice<'b, T>),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::BufferSlice<'b, T>),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::BufferSlice<'b, T>),
+}
+
+pub enum AnyEncoder {
+    WebGpu(<WebGpu as Backend>::Encoder),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Encoder),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Encoder),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Encoder),
+}
+
+#[allow(clippy::large_enum_variant)]
+pub enum AnyPass {
+    WebGpu(<WebGpu as Backend>::Pass),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Pass),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Pass),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Pass),
+}
+
+pub enum AnyModule {
+    WebGpu(<WebGpu as Backend>::Module),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Module),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Module),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Module),
+}
+
+pub enum AnyFunction {
+    WebGpu(<WebGpu as Backend>::Function),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Function),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Function),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Function),
+}
+
+#[allow(clippy::large_enum_variant)]
+pub enum AnyDispatch<'a> {
+    WebGpu(<WebGpu as Backend>::Dispatch<'a>),
+    #[cfg(feature = "cuda")]
+    Cuda(<Cuda as Backend>::Dispatch<'a>),
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    Dx12(<Dx12 as Backend>::Dispatch<'a>),
+    #[cfg(feature = "cpu")]
+    Cpu(<Cpu as Backend>::Dispatch<'a>),
+}
+
+#[async_trait::async_trait]
+impl Backend for AnyBackend {
+    const NAME: &'static str = "any";
+    // NOTE: this is only a placeholder, the actual target depends on the selected backend. See
+    //       `AnyBackend::compile_target`.
+    const TARGET: CompileTarget = CompileTarget::Wgsl;
+
+    type Error = AnyBackendError;
+    type Buffer<T: DeviceValue> = AnyBuffer<T>;
+    type Texture = AnyTexture;
+    type Sampler = AnySampler;
+    type Timestamps = AnyTimestamps;
+    type Bundle = AnyBundle;
+    type Fence = AnyFence;
+    type BufferSlice<'b, T: DeviceValue> = AnyBufferSlice<'b, T>;
+    type Encoder = AnyEncoder;
+    type Pass = AnyPass;
+    type Module = AnyModule;
+    type Function = AnyFunction;
+    type Dispatch<'a> = AnyDispatch<'a>;
+
+    fn compile_target(&self) -> CompileTarget {
+        match_any!(AnyBackend(self) => b; b.compile_target(); else unreachable!())
+    }
+
+    #[cfg(feature = "cuda")]
+    fn as_cuda(&self) -> Option<&Cuda> {
+        match_any!(AnyBackend(self) => b; b.as_cuda(); else unreachable!())
+    }
+    fn as_webgpu(&self) -> Option<&WebGpu> {
+        match_any!(AnyBackend(self) => b; b.as_webgpu(); else unreachable!())
+    }
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    fn as_dx12(&self) -> Option<&Dx12> {
+        match_any!(AnyBackend(self) => b; b.as_dx12(); else unreachable!())
+    }
+    #[cfg(feature = "cpu")]
+    fn as_cpu(&self) -> Option<&Cpu> {
+        match_any!(AnyBackend(self) => b; b.as_cpu(); else unreachable!())
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        match_any!(AnyBackend(self) => b; Ok(b.limits()?); else unreachable!())
+    }
+
+    fn memory_info(&self) -> Result<MemoryInfo, Self::Error> {
+        match_any!(AnyBackend(self) => b; Ok(b.memory_info()?); else unreachable!())
+    }
+
+    /*
+     * Module/function loading.
+     */
+    fn load_module(&self, data: &str) -> Result<Self::Module, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyModule::WebGpu(b.load_module(data)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyModule::Cuda(b.load_module(data)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyModule::Dx12(b.load_module(data)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyModule::Cpu(b.load_module(data)?),
+        })
+    }
+
+    fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyModule::WebGpu(b.load_module_bytes(bytes)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyModule::Cuda(b.load_module_bytes(bytes)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyModule::Dx12(b.load_module_bytes(bytes)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyModule::Cpu(b.load_module_bytes(bytes)?),
+        })
+    }
+
+    fn load_compiled_module(
+        &self,
+        bytes: &[u8],
+        compiled: SlangCompiled,
+    ) -> Result<Self::Module, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyModule::WebGpu(b.load_compiled_module(bytes, compiled)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyModule::Cuda(b.load_compiled_module(bytes, compiled)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyModule::Dx12(b.load_compiled_module(bytes, compiled)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyModule::Cpu(b.load_compiled_module(bytes, compiled)?),
+        })
+    }
+
+    fn load_function(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+    ) -> Result<Self::Function, Self::Error> {
+        Ok(match (self, module) {
+            (Self::WebGpu(b), AnyModule::WebGpu(m)) => {
+                AnyFunction::WebGpu(b.load_function(m, entry_point)?)
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyModule::Cuda(m)) => {
+                AnyFunction::Cuda(b.load_function(m, entry_point)?)
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(b), AnyModule::Dx12(m)) => {
+                AnyFunction::Dx12(b.load_function(m, entry_point)?)
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(b), AnyModule::Cpu(m)) => AnyFunction::Cpu(b.load_function(m, entry_point)?),
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        })
+    }
+
+    fn load_function_with_options(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+        options: FunctionOptions<'_>,
+    ) -> Result<Self::Function, Self::Error> {
+        Ok(match (self, module) {
+            (Self::WebGpu(b), AnyModule::WebGpu(m)) => {
+                AnyFunction::WebGpu(b.load_function_with_options(m, entry_point, options)?)
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyModule::Cuda(m)) => {
+                AnyFunction::Cuda(b.load_function_with_options(m, entry_point, options)?)
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(b), AnyModule::Dx12(m)) => {
+                AnyFunction::Dx12(b.load_function_with_options(m, entry_point, options)?)
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(b), AnyModule::Cpu(m)) => {
+                AnyFunction::Cpu(b.load_function_with_options(m, entry_point, options)?)
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        })
+    }
+
+    fn occupancy_block_size(&self, function: &Self::Function) -> Result<Option<u32>, Self::Error> {
+        Ok(match (self, function) {
+            (Self::WebGpu(b), AnyFunction::WebGpu(f)) => b.occupancy_block_size(f)?,
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyFunction::Cuda(f)) => b.occupancy_block_size(f)?,
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(b), AnyFunction::Dx12(f)) => b.occupancy_block_size(f)?,
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(b), AnyFunction::Cpu(f)) => b.occupancy_block_size(f)?,
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        })
+    }
+
+    /*
+     * Kernel dispatch.
+     */
+    fn begin_encoding(&self) -> Self::Encoder {
+        match self {
+            Self::WebGpu(b) => AnyEncoder::WebGpu(b.begin_encoding()),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyEncoder::Cuda(b.begin_encoding()),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyEncoder::Dx12(b.begin_encoding()),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyEncoder::Cpu(b.begin_encoding()),
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => Self::WebGpu(b.create_queue()?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => Self::Cuda(b.create_queue()?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => Self::Dx12(b.create_queue()?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => Self::Cpu(b.create_queue()?),
+        })
+    }
+
+    fn begin_dispatch<'a>(
+        &'a self,
+        pass: &'a mut Self::Pass,
+        function: &'a Self::Function,
+        label: &'a str,
+    ) -> Self::Dispatch<'a> {
+        match (self, pass, function) {
+            (Self::WebGpu(b), AnyPass::WebGpu(p), AnyFunction::WebGpu(f)) => {
+                AnyDispatch::WebGpu(b.begin_dispatch(p, f, label))
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyPass::Cuda(p), AnyFunction::Cuda(f)) => {
+                AnyDispatch::Cuda(b.begin_dispatch(p, f, label))
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(b), AnyPass::Dx12(p), AnyFunction::Dx12(f)) => {
+                AnyDispatch::Dx12(b.begin_dispatch(p, f, label))
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(b), AnyPass::Cpu(p), AnyFunction::Cpu(f)) => {
+                AnyDispatch::Cpu(b.begin_dispatch(p, f, label))
+            }
+            #[allow(unreachable_patterns)]
+            _ => panic!("{}", AnyBackendError::BackendMismatch),
+        }
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        match_any!(AnyBackend(self) => b; Ok(b.synchronize()?); else unreachable!())
+    }
+
+    fn submit(&self, encoder: Self::Encoder) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyEncoder(encoder) => e;
+            Ok(b.submit(e)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyEncoder(encoder) => e;
+            {
+                let future = b.submit_async(e)?;
+                Ok(SubmissionFuture::new(async move { Ok(future.await?) }))
+            };
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyFence::WebGpu(b.create_fence()?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyFence::Cuda(b.create_fence()?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyFence::Dx12(b.create_fence()?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyFence::Cpu(b.create_fence()?),
+        })
+    }
+
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyFence(fence) => f;
+            Ok(b.signal_fence(f)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyFence(fence) => f;
+            Ok(b.wait_fence(f)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    /*
+     * Buffer handling.
+     */
+    fn init_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyBuffer::WebGpu(b.init_buffer(data, usage)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyBuffer::Cuda(b.init_buffer(data, usage)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyBuffer::Dx12(b.init_buffer(data, usage)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyBuffer::Cpu(b.init_buffer(data, usage)?),
+        })
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyBuffer::WebGpu(b.init_buffer_bytes::<T>(bytes, usage)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyBuffer::Cuda(b.init_buffer_bytes::<T>(bytes, usage)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyBuffer::Dx12(b.init_buffer_bytes::<T>(bytes, usage)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyBuffer::Cpu(b.init_buffer_bytes::<T>(bytes, usage)?),
+        })
+    }
+
+    fn init_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyBuffer::WebGpu(b.init_buffer_encased(data, usage)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyBuffer::Cuda(b.init_buffer_encased(data, usage)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyBuffer::Dx12(b.init_buffer_encased(data, usage)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyBuffer::Cpu(b.init_buffer_encased(data, usage)?),
+        })
+    }
+
+    unsafe fn uninit_buffer<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        unsafe {
+            Ok(match self {
+                Self::WebGpu(b) => AnyBuffer::WebGpu(b.uninit_buffer::<T>(len, usage)?),
+                #[cfg(feature = "cuda")]
+                Self::Cuda(b) => AnyBuffer::Cuda(b.uninit_buffer::<T>(len, usage)?),
+                #[cfg(all(feature = "dx12", target_os = "windows"))]
+                Self::Dx12(b) => AnyBuffer::Dx12(b.uninit_buffer::<T>(len, usage)?),
+                #[cfg(feature = "cpu")]
+                Self::Cpu(b) => AnyBuffer::Cpu(b.uninit_buffer::<T>(len, usage)?),
+            })
+        }
+    }
+
+    unsafe fn uninit_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        unsafe {
+            Ok(match self {
+                Self::WebGpu(b) => AnyBuffer::WebGpu(b.uninit_buffer_encased::<T>(len, usage)?),
+                #[cfg(feature = "cuda")]
+                Self::Cuda(b) => AnyBuffer::Cuda(b.uninit_buffer_encased::<T>(len, usage)?),
+                #[cfg(all(feature = "dx12", target_os = "windows"))]
+                Self::Dx12(b) => AnyBuffer::Dx12(b.uninit_buffer_encased::<T>(len, usage)?),
+                #[cfg(feature = "cpu")]
+                Self::Cpu(b) => AnyBuffer::Cpu(b.uninit_buffer_encased::<T>(len, usage)?),
+            })
+        }
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_buffer(buf, data)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_buffer_encased(buf, data)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_buffer_at(buf, offset, data)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_buffer_encased_at(buf, offset, data)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_buffer_with::<T>(buf, byte_offset, len, write)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.read_buffer(buf, data).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.read_buffer_encased(buf, data).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.slow_read_buffer(buf, data).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.read_buffer_range(buf, range, out).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.slow_read_range(buf, range, out).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.fill_buffer(buf, value, range)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn clear_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.clear_buffer::<T>(buf)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    /*
+     * Uniform handling.
+     */
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyBuffer::WebGpu(b.init_uniform(value)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyBuffer::Cuda(b.init_uniform(value)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyBuffer::Dx12(b.init_uniform(value)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyBuffer::Cpu(b.init_uniform(value)?),
+        })
+    }
+
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyBuffer(buffer) => buf;
+            Ok(b.write_uniform(buf, value)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    /*
+     * Texture handling.
+     */
+    fn init_texture<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyTexture::WebGpu(b.init_texture(data, size, format, usage)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyTexture::Cuda(b.init_texture(data, size, format, usage)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnyTexture::Dx12(b.init_texture(data, size, format, usage)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnyTexture::Cpu(b.init_texture(data, size, format, usage)?),
+        })
+    }
+
+    unsafe fn uninit_texture(
+        &self,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        unsafe {
+            Ok(match self {
+                Self::WebGpu(b) => AnyTexture::WebGpu(b.uninit_texture(size, format, usage)?),
+                #[cfg(feature = "cuda")]
+                Self::Cuda(b) => AnyTexture::Cuda(b.uninit_texture(size, format, usage)?),
+                #[cfg(all(feature = "dx12", target_os = "windows"))]
+                Self::Dx12(b) => AnyTexture::Dx12(b.uninit_texture(size, format, usage)?),
+                #[cfg(feature = "cpu")]
+                Self::Cpu(b) => AnyTexture::Cpu(b.uninit_texture(size, format, usage)?),
+            })
+        }
+    }
+
+    fn write_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &mut Self::Texture,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyTexture(texture) => tex;
+            Ok(b.write_texture(tex, data)?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    async fn read_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &Self::Texture,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        match_any!(
+            AnyBackend(self) => b, AnyTexture(texture) => tex;
+            Ok(b.read_texture(tex, data).await?);
+            else Err(AnyBackendError::BackendMismatch)
+        )
+    }
+
+    fn create_sampler(&self, desc: &SamplerDescriptor) -> Result<Self::Sampler, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnySampler::WebGpu(b.create_sampler(desc)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnySampler::Cuda(b.create_sampler(desc)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => AnySampler::Dx12(b.create_sampler(desc)?),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => AnySampler::Cpu(b.create_sampler(desc)?),
+        })
+    }
+
+    fn create_timestamps(&self, capacity: usize) -> Result<Self::Timestamps, Self::Error> {
+        Ok(match self {
+            Self::WebGpu(b) => AnyTimestamps::WebGpu(b.create_timestamps(capacity)?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyTimestamps::Cuda(b.create_timestamps(capacity)?),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(b) => match b.create_timestamps(capacity)? {},
+            #[cfg(feature = "cpu")]
+            Self::Cpu(b) => match b.create_timestamps(capacity)? {},
+        })
+    }
+
+    async fn resolve_timestamps(
+        &self,
+        timestamps: &mut Self::Timestamps,
+    ) -> Result<Vec<PassTimestamps>, Self::Error> {
+        match (self, timestamps) {
+            (Self::WebGpu(b), AnyTimestamps::WebGpu(ts)) => Ok(b.resolve_timestamps(ts).await?),
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyTimestamps::Cuda(ts)) => Ok(b.resolve_timestamps(ts).await?),
+            #[allow(unreachable_patterns)]
+            _ => Err(AnyBackendError::BackendMismatch),
+        }
+    }
+
+    fn enable_profiling(&mut self, capacity: usize) -> Result<(), Self::Error> {
+        match_any!(AnyBackend(self) => b; Ok(b.enable_profiling(capacity)?); else unreachable!())
+    }
+
+    fn disable_profiling(&mut self) {
+        match_any!(AnyBackend(self) => b; b.disable_profiling(); else unreachable!())
+    }
+
+    async fn profiling_report(&self) -> Result<ProfilingReport, Self::Error> {
+        match_any!(AnyBackend(self) => b; Ok(b.profiling_report().await?); else unreachable!())
+    }
+
+    fn record_bundle(
+        &self,
+        record: &mut dyn FnMut(&Self, &mut Self::Encoder) -> Result<(), Self::Error>,
+    ) -> Result<Self::Bundle, Self::Error> {
+        // NOTE: the errors of `record` can’t be converted to the wrapped backend’s error, so they
+        //       are stashed and returned once the recording ended.
+        let mut error = None;
+        let bundle = match self {
+            Self::WebGpu(b) => AnyBundle::WebGpu(b.record_bundle(&mut |_, encoder| {
+                let mut any_encoder =
+                    AnyEncoder::WebGpu(std::mem::replace(encoder, b.begin_encoding()));
+                error = record(self, &mut any_encoder).err();
+                *encoder = match any_encoder {
+                    AnyEncoder::WebGpu(e) => e,
+                    #[allow(unreachable_patterns)]
+                    _ => unreachable!(),
+                };
+                Ok(())
+            })?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(b) => AnyBundle::Cuda(b.record_bundle(&mut |recorder, encoder| {
+                let mut any_encoder = AnyEncoder::Cuda(encoder.clone());
+                error = record(&Self::Cuda(recorder.clone()), &mut any_encoder).err();
+                Ok(())
+            })?),
+            #[allow(unreachable_patterns)]
+            _ => return Err(UnsupportedError::new::<Self>("command bundles").into()),
+        };
+        match error {
+            Some(e) => Err(e),
+            None => Ok(bundle),
+        }
+    }
+
+    fn replay_bundle(
+        &self,
+        encoder: &mut Self::Encoder,
+        bundle: &Self::Bundle,
+    ) -> Result<(), Self::Error> {
+        match (self, encoder, bundle) {
+            (Self::WebGpu(b), AnyEncoder::WebGpu(e), AnyBundle::WebGpu(bundle)) => {
+                Ok(b.replay_bundle(e, bundle)?)
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(b), AnyEncoder::Cuda(e), AnyBundle::Cuda(bundle)) => {
+                Ok(b.replay_bundle(e, bundle)?)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(AnyBackendError::BackendMismatch),
+        }
+    }
+}
+
+impl Encoder<AnyBackend> for AnyEncoder {
+    fn begin_pass(&mut self, label: &str) -> AnyPass {
+        match self {
+            Self::WebGpu(e) => AnyPass::WebGpu(e.begin_pass(label)),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(e) => AnyPass::Cuda(e.begin_pass(label)),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(e) => AnyPass::Dx12(e.begin_pass(label)),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(e) => AnyPass::Cpu(e.begin_pass(label)),
+        }
+    }
+
+    fn begin_timed_pass(
+        &mut self,
+        label: &str,
+        timestamps: &mut AnyTimestamps,
+    ) -> Result<AnyPass, AnyBackendError> {
+        match (self, timestamps) {
+            (Self::WebGpu(e), AnyTimestamps::WebGpu(ts)) => Ok(AnyPass::WebGpu(
+                Encoder::<WebGpu>::begin_timed_pass(e, label, ts)?,
+            )),
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(e), AnyTimestamps::Cuda(ts)) => Ok(AnyPass::Cuda(
+                Encoder::<Cuda>::begin_timed_pass(e, label, ts)?,
+            )),
+            #[allow(unreachable_patterns)]
+            _ => Err(AnyBackendError::BackendMismatch),
+        }
+    }
+
+    fn push_debug_group(&mut self, label: &str) {
+        match self {
+            Self::WebGpu(e) => Encoder::<WebGpu>::push_debug_group(e, label),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(e) => Encoder::<Cuda>::push_debug_group(e, label),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(e) => Encoder::<Dx12>::push_debug_group(e, label),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(e) => Encoder::<Cpu>::push_debug_group(e, label),
+        }
+    }
+
+    fn pop_debug_group(&mut self) {
+        match self {
+            Self::WebGpu(e) => Encoder::<WebGpu>::pop_debug_group(e),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(e) => Encoder::<Cuda>::pop_debug_group(e),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(e) => Encoder::<Dx12>::pop_debug_group(e),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(e) => Encoder::<Cpu>::pop_debug_group(e),
+        }
+    }
+
+    fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
+        &mut self,
+        source: &AnyBuffer<T>,
+        source_offset: usize,
+        target: &mut AnyBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), AnyBackendError> {
+        match (self, source, target) {
+            (Self::WebGpu(e), AnyBuffer::WebGpu(src), AnyBuffer::WebGpu(dst)) => {
+                Encoder::<WebGpu>::copy_buffer_to_buffer::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(e), AnyBuffer::Cuda(src), AnyBuffer::Cuda(dst)) => {
+                Encoder::<Cuda>::copy_buffer_to_buffer::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(e), AnyBuffer::Dx12(src), AnyBuffer::Dx12(dst)) => {
+                Encoder::<Dx12>::copy_buffer_to_buffer::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(e), AnyBuffer::Cpu(src), AnyBuffer::Cpu(dst)) => {
+                Encoder::<Cpu>::copy_buffer_to_buffer::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        }
+        Ok(())
+    }
+
+    fn copy_buffer_to_buffer_encased<T: DeviceValue + ShaderType>(
+        &mut self,
+        source: &AnyBuffer<T>,
+        source_offset: usize,
+        target: &mut AnyBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), AnyBackendError> {
+        match (self, source, target) {
+            (Self::WebGpu(e), AnyBuffer::WebGpu(src), AnyBuffer::WebGpu(dst)) => {
+                Encoder::<WebGpu>::copy_buffer_to_buffer_encased::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(e), AnyBuffer::Cuda(src), AnyBuffer::Cuda(dst)) => {
+                Encoder::<Cuda>::copy_buffer_to_buffer_encased::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(e), AnyBuffer::Dx12(src), AnyBuffer::Dx12(dst)) => {
+                Encoder::<Dx12>::copy_buffer_to_buffer_encased::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(e), AnyBuffer::Cpu(src), AnyBuffer::Cpu(dst)) => {
+                Encoder::<Cpu>::copy_buffer_to_buffer_encased::<T>(
+                    e,
+                    src,
+                    source_offset,
+                    dst,
+                    target_offset,
+                    copy_len,
+                )?
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(AnyBackendError::BackendMismatch),
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Dispatch<'a, AnyBackend> for AnyDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        match self {
+            Self::WebGpu(d) => d.write_value(binding, name, value),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.write_value(binding, name, value),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.write_value(binding, name, value),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.write_value(binding, name, value),
+        }
+    }
+
+    fn set_value_buffer_binding(&mut self, binding: ShaderBinding) {
+        match self {
+            Self::WebGpu(d) => d.set_value_buffer_binding(binding),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.set_value_buffer_binding(binding),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.set_value_buffer_binding(binding),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.set_value_buffer_binding(binding),
+        }
+    }
+
+    fn set_dynamic_shared_memory(&mut self, bytes: u32) {
+        match self {
+            Self::WebGpu(d) => d.set_dynamic_shared_memory(bytes),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.set_dynamic_shared_memory(bytes),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.set_dynamic_shared_memory(bytes),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.set_dynamic_shared_memory(bytes),
+        }
+    }
+
+    fn array_element_binding(&self, binding: ShaderBinding, index: usize) -> ShaderBinding {
+        match self {
+            Self::WebGpu(d) => d.array_element_binding(binding, index),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.array_element_binding(binding, index),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.array_element_binding(binding, index),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.array_element_binding(binding, index),
+        }
+    }
+
+    fn bind_array(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        len: usize,
+    ) -> Result<(), ShaderArgsError> {
+        match self {
+            Self::WebGpu(d) => d.bind_array(binding, name, len),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.bind_array(binding, name, len),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.bind_array(binding, name, len),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.bind_array(binding, name, len),
+        }
+    }
+
+    fn launch<'b>(
+        self,
+        grid: impl Into<DispatchGrid<'b, AnyBackend>>,
+        block_dim: [u32; 3],
+    ) -> Result<(), AnyBackendError> {
+        let grid = grid.into();
+        match self {
+            Self::WebGpu(d) => d.launch(downcast_grid!(grid, WebGpu), block_dim)?,
+            #[cfg(feature = "cuda")]
+            Self::Cuda(d) => d.launch(downcast_grid!(grid, Cuda), block_dim)?,
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(d) => d.launch(downcast_grid!(grid, Dx12), block_dim)?,
+            #[cfg(feature = "cpu")]
+            Self::Cpu(d) => d.launch(downcast_grid!(grid, Cpu), block_dim)?,
+        }
+        Ok(())
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, AnyBackend> for AnyBuffer<T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut AnyDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match_any!(
+            AnyBuffer(self) => buf, AnyDispatch(dispatch) => d;
+            buf.write_arg(binding, name, d);
+            else Err(ShaderArgsError::BackendMismatch(name.to_owned()))
+        )
+    }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        match self {
+            Self::WebGpu(buf) => ShaderArgs::<WebGpu>::arg_info(buf, name),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(buf) => ShaderArgs::<Cuda>::arg_info(buf, name),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(buf) => ShaderArgs::<Dx12>::arg_info(buf, name),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(buf) => ShaderArgs::<Cpu>::arg_info(buf, name),
+        }
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, AnyBackend> for AnyBufferSlice<'_, T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut AnyDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match_any!(
+            AnyBufferSlice(self) => slice, AnyDispatch(dispatch) => d;
+            slice.write_arg(binding, name, d);
+            else Err(ShaderArgsError::BackendMismatch(name.to_owned()))
+        )
+    }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        match self {
+            Self::WebGpu(slice) => ShaderArgs::<WebGpu>::arg_info(slice, name),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(slice) => ShaderArgs::<Cuda>::arg_info(slice, name),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(slice) => ShaderArgs::<Dx12>::arg_info(slice, name),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(slice) => ShaderArgs::<Cpu>::arg_info(slice, name),
+        }
+    }
+}
+
+impl<'b> ShaderArgs<'b, AnyBackend> for AnyTexture {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut AnyDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        // NOTE: the backend can’t be inferred from the dispatch type since some backends share
+        //       the same texture type (`UnsupportedTexture`).
+        match (self, dispatch) {
+            (Self::WebGpu(tex), AnyDispatch::WebGpu(d)) => {
+                ShaderArgs::<WebGpu>::write_arg(tex, binding, name, d)
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(tex), AnyDispatch::Cuda(d)) => {
+                ShaderArgs::<Cuda>::write_arg(tex, binding, name, d)
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(tex), AnyDispatch::Dx12(d)) => {
+                ShaderArgs::<Dx12>::write_arg(tex, binding, name, d)
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(tex), AnyDispatch::Cpu(d)) => {
+                ShaderArgs::<Cpu>::write_arg(tex, binding, name, d)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(ShaderArgsError::BackendMismatch(name.to_owned())),
+        }
+    }
+}
+
+impl Texture<AnyBackend> for AnyTexture {
+    fn size(&self) -> [u32; 2] {
+        match self {
+            Self::WebGpu(tex) => Texture::<WebGpu>::size(tex),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(tex) => Texture::<Cuda>::size(tex),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(tex) => Texture::<Dx12>::size(tex),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(tex) => Texture::<Cpu>::size(tex),
+        }
+    }
+
+    fn format(&self) -> TextureFormat {
+        match self {
+            Self::WebGpu(tex) => Texture::<WebGpu>::format(tex),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(tex) => Texture::<Cuda>::format(tex),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(tex) => Texture::<Dx12>::format(tex),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(tex) => Texture::<Cpu>::format(tex),
+        }
+    }
+}
+
+impl<'b> ShaderArgs<'b, AnyBackend> for AnySampler {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut AnyDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        // NOTE: see the `ShaderArgs` implementation of `AnyTexture`.
+        match (self, dispatch) {
+            (Self::WebGpu(s), AnyDispatch::WebGpu(d)) => {
+                ShaderArgs::<WebGpu>::write_arg(s, binding, name, d)
+            }
+            #[cfg(feature = "cuda")]
+            (Self::Cuda(s), AnyDispatch::Cuda(d)) => {
+                ShaderArgs::<Cuda>::write_arg(s, binding, name, d)
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            (Self::Dx12(s), AnyDispatch::Dx12(d)) => {
+                ShaderArgs::<Dx12>::write_arg(s, binding, name, d)
+            }
+            #[cfg(feature = "cpu")]
+            (Self::Cpu(s), AnyDispatch::Cpu(d)) => {
+                ShaderArgs::<Cpu>::write_arg(s, binding, name, d)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(ShaderArgsError::BackendMismatch(name.to_owned())),
+        }
+    }
+}
+
+impl Fence<AnyBackend> for AnyFence {
+    fn is_signaled(&self) -> bool {
+        match self {
+            Self::WebGpu(f) => Fence::<WebGpu>::is_signaled(f),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(f) => Fence::<Cuda>::is_signaled(f),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(f) => Fence::<Dx12>::is_signaled(f),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(f) => Fence::<Cpu>::is_signaled(f),
+        }
+    }
+}
+
+impl Timestamps<AnyBackend> for AnyTimestamps {
+    fn capacity(&self) -> usize {
+        match self {
+            Self::WebGpu(ts) => Timestamps::<WebGpu>::capacity(ts),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(ts) => Timestamps::<Cuda>::capacity(ts),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::WebGpu(ts) => Timestamps::<WebGpu>::len(ts),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(ts) => Timestamps::<Cuda>::len(ts),
+        }
+    }
+}
+
+impl Sampler<AnyBackend> for AnySampler {
+    fn descriptor(&self) -> SamplerDescriptor {
+        match self {
+            Self::WebGpu(s) => Sampler::<WebGpu>::descriptor(s),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(s) => Sampler::<Cuda>::descriptor(s),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(s) => Sampler::<Dx12>::descriptor(s),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(s) => Sampler::<Cpu>::descriptor(s),
+        }
+    }
+}
+
+impl<T: DeviceValue> Buffer<AnyBackend, T> for AnyBuffer<T> {
+    fn len(&self) -> usize {
+        match self {
+            Self::WebGpu(buf) => Buffer::<WebGpu, T>::len(buf),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(buf) => Buffer::<Cuda, T>::len(buf),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(buf) => Buffer::<Dx12, T>::len(buf),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(buf) => Buffer::<Cpu, T>::len(buf),
+        }
+    }
+
+    fn slice(&self, range: impl RangeBounds<usize>) -> AnyBufferSlice<'_, T> {
+        match self {
+            Self::WebGpu(buf) => AnyBufferSlice::WebGpu(Buffer::<WebGpu, T>::slice(buf, range)),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(buf) => AnyBufferSlice::Cuda(Buffer::<Cuda, T>::slice(buf, range)),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(buf) => AnyBufferSlice::Dx12(Buffer::<Dx12, T>::slice(buf, range)),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(buf) => AnyBufferSlice::Cpu(Buffer::<Cpu, T>::slice(buf, range)),
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        match self {
+            Self::WebGpu(buf) => Buffer::<WebGpu, T>::size_bytes(buf),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(buf) => Buffer::<Cuda, T>::size_bytes(buf),
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(buf) => Buffer::<Dx12, T>::size_bytes(buf),
+            #[cfg(feature = "cpu")]
+            Self::Cpu(buf) => Buffer::<Cpu, T>::size_bytes(buf),
+        }
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<AnyBufferSlice<'_, U>, BufferCastError> {
+        Ok(match self {
+            Self::WebGpu(buf) => AnyBufferSlice::WebGpu(Buffer::<WebGpu, T>::reinterpret::<U>(
+                buf,
+                byte_offset,
+                len,
+            )?),
+            #[cfg(feature = "cuda")]
+            Self::Cuda(buf) => {
+                AnyBufferSlice::Cuda(Buffer::<Cuda, T>::reinterpret::<U>(buf, byte_offset, len)?)
+            }
+            #[cfg(all(feature = "dx12", target_os = "windows"))]
+            Self::Dx12(buf) => {
+                AnyBufferSlice::Dx12(Buffer::<Dx12, T>::reinterpret::<U>(buf, byte_offset, len)?)
+            }
+            #[cfg(feature = "cpu")]
+            Self::Cpu(buf) => {
+                AnyBufferSlice::Cpu(Buffer::<Cpu, T>::reinterpret::<U>(buf, byte_offset, len)?)
+            }
+        })
+    }
+}
@@ -0,0 +1,963 @@
+use crate::ShaderArgs;
+use crate::backend::{
+    Backend, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid, EncaseType,
+    Encoder, Fence, ShaderBinding, SlangCompiled, SubmissionFuture, UnsupportedBundle,
+    UnsupportedError, UnsupportedSampler, UnsupportedTexture, UnsupportedTimestamps, buffer_range,
+    elements_len, repeat_bytes, view_bytes,
+};
+use crate::shader::{ArgInfo, ShaderArgsError};
+use bytemuck::Pod;
+use encase::{ShaderType, StorageBuffer};
+use minislang::shader_slang;
+use std::cell::UnsafeCell;
+use std::ffi::c_void;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::ops::{Range, RangeBounds};
+use std::sync::Arc;
+use wgpu::BufferUsages;
+
+/// Signature of the compute entry-points generated by Slang for the CPU targets.
+type CpuKernel = unsafe extern "C" fn(*mut ComputeVaryingInput, *mut c_void, *mut c_void);
+
+/// Mirror of the `ComputeVaryingInput` struct from Slang’s C++ prelude.
+#[repr(C)]
+struct ComputeVaryingInput {
+    start_group_id: [u32; 3],
+    end_group_id: [u32; 3],
+}
+
+/// Mirror of the `StructuredBuffer<T>`/`RWStructuredBuffer<T>` structs from Slang’s C++ prelude.
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CpuStructuredBuffer {
+    data: u64,
+    count: u64,
+}
+
+/// A backend running the kernels on CPU threads.
+///
+/// Slang modules are compiled into a shared library (this requires a C++ compiler to be
+/// available to Slang at runtime) which is then loaded and called directly. Since loading a
+/// library runs native code, only the libraries compiled by Slang from this crate (including
+/// the ones read from the cache set with [`SlangCompiler::set_cache_dir`]) are trusted, and
+/// [`Backend::load_module_bytes`] fails with an [`UnsupportedError`]. Other libraries, e.g.
+/// compiled ahead of time, must be loaded with the unsafe [`Cpu::load_library`].
+///
+/// [`SlangCompiler::set_cache_dir`]: minislang::SlangCompiler::set_cache_dir Each dispatch is
+/// executed synchronously, with its workgroups split across [`Cpu::num_threads`] threads.
+///
+/// This backend is mostly useful for running tests on machines without GPUs, and for debugging
+/// kernels deterministically with a single thread. Workgroup barriers aren’t supported by the
+/// Slang CPU targets so kernels relying on shared memory synchronization won’t work here. This
+/// backend reports no shared memory in its [`DeviceLimits`], and the [`utils`](crate::utils)
+/// relying on it (reductions, prefix sums, radix sorts, and histograms) fail with an
+/// [`UnsupportedError`] instead of returning wrong results.
+#[derive(Clone)]
+pub struct Cpu {
+    /// The number of threads dispatches are split across.
+    pub num_threads: usize,
+}
+
+impl Cpu {
+    /// Initializes a CPU backend using all the available cores.
+    pub fn new() -> Self {
+        let num_threads = std::thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1);
+        Self::with_num_threads(num_threads)
+    }
+
+    /// Initializes a CPU backend splitting dispatches across `num_threads` threads.
+    ///
+    /// Use `1` for a fully deterministic execution.
+    pub fn with_num_threads(num_threads: usize) -> Self {
+        Self {
+            num_threads: num_threads.max(1),
+        }
+    }
+
+    /// Loads a shared library compiled by Slang for the [`Backend::TARGET`] of this backend, e.g.
+    /// ahead of time by a build script.
+    ///
+    /// Use [`GpuFunction::from_precompiled_module`](crate::function::GpuFunction::from_precompiled_module)
+    /// to load its functions.
+    ///
+    /// # Safety
+    /// Loading a shared library runs its initialization code, and its entry points are then
+    /// called as compute kernels. `bytes` must be a library compiled by Slang, from a trusted
+    /// source.
+    pub unsafe fn load_library(&self, bytes: &[u8]) -> Result<CpuModule, CpuBackendError> {
+        // The library has to be written to disk before it can be loaded.
+        let mut file = tempfile::Builder::new()
+            .suffix(std::env::consts::DLL_SUFFIX)
+            .tempfile()?;
+        file.write_all(bytes)?;
+        let path = file.into_temp_path();
+        // SAFETY: forwarded to the caller.
+        let library = unsafe { libloading::Library::new(&*path)? };
+        Ok(CpuModule {
+            library: Arc::new(library),
+            _file: Arc::new(path),
+        })
+    }
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CpuBackendError {
+    #[error(transparent)]
+    ShaderArg(#[from] ShaderArgsError),
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
+    BytemuckPod(#[from] bytemuck::PodCastError),
+    #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Library(#[from] libloading::Error),
+    #[error(
+        "the dispatch of {grid:?} workgroups of {block_dim:?} threads exceeds the 32-bit thread ids"
+    )]
+    GridTooLarge { grid: [u32; 3], block_dim: [u32; 3] },
+    #[error(
+        "the access of {len} bytes at byte {start} is out of the bounds of the buffer ({size} bytes)"
+    )]
+    OutOfBounds {
+        start: usize,
+        len: usize,
+        size: usize,
+    },
+}
+
+/// A 16-bytes aligned chunk of memory, so that buffers can be reinterpreted as any primitive
+/// type by the kernels.
+#[derive(Copy, Clone)]
+#[repr(C, align(16))]
+struct CpuChunk([u8; 16]);
+
+/// A buffer allocated in host memory.
+pub struct CpuBuffer<T> {
+    // NOTE: the kernels write into the buffers through shared references, same as on the GPU.
+    chunks: Box<[UnsafeCell<CpuChunk>]>,
+    len_bytes: usize,
+    stride: usize,
+    // Uniform buffers are passed to kernels as plain pointers instead of structured buffers.
+    uniform: bool,
+    _phantom: PhantomData<T>,
+}
+
+// SAFETY: concurrent accesses to the buffer content only happen from the kernels, with the
+//         same data-race rules as on the GPU.
+unsafe impl<T> Send for CpuBuffer<T> {}
+unsafe impl<T> Sync for CpuBuffer<T> {}
+
+impl<T> CpuBuffer<T> {
+    fn new(len_bytes: usize, stride: usize, usage: BufferUsages) -> Self {
+        let chunks = (0..len_bytes.div_ceil(size_of::<CpuChunk>()))
+            .map(|_| UnsafeCell::new(CpuChunk([0; 16])))
+            .collect();
+        Self {
+            chunks,
+            len_bytes,
+            stride,
+            uniform: usage.contains(BufferUsages::UNIFORM),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        // UnsafeCell<T> has the same layout as T.
+        self.chunks.as_ptr() as *mut u8
+    }
+
+    /// The content of this buffer, as bytes.
+    ///
+    /// # Safety
+    /// No kernel must be writing into this buffer while the returned slice is alive.
+    pub unsafe fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.as_ptr(), self.len_bytes) }
+    }
+
+    /// The content of this buffer, as mutable bytes.
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.as_ptr(), self.len_bytes) }
+    }
+
+    /// The size of this buffer, in bytes.
+    pub fn size(&self) -> usize {
+        self.len_bytes
+    }
+
+    /// The range of the `len` bytes starting at `start`, or an error if they are out of bounds.
+    fn byte_range(&self, start: usize, len: usize) -> Result<Range<usize>, CpuBackendError> {
+        let size = self.len_bytes;
+        match start.checked_add(len) {
+            Some(end) if end <= size => Ok(start..end),
+            _ => Err(CpuBackendError::OutOfBounds { start, len, size }),
+        }
+    }
+
+    fn view<U>(
+        &self,
+        offset_bytes: usize,
+        len_bytes: usize,
+        stride: usize,
+    ) -> CpuBufferSlice<'_, U> {
+        CpuBufferSlice {
+            data: self.as_ptr().wrapping_add(offset_bytes),
+            len_bytes,
+            stride,
+            uniform: self.uniform,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A range of elements of a [`CpuBuffer`], possibly reinterpreted as another type.
+pub struct CpuBufferSlice<'b, T> {
+    // Pointer to the first byte of the slice.
+    data: *mut u8,
+    len_bytes: usize,
+    stride: usize,
+    uniform: bool,
+    _phantom: PhantomData<&'b T>,
+}
+
+// SAFETY: same as for `CpuBuffer`.
+unsafe impl<T> Send for CpuBufferSlice<'_, T> {}
+unsafe impl<T> Sync for CpuBufferSlice<'_, T> {}
+
+impl<T> Clone for CpuBufferSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CpuBufferSlice<'_, T> {}
+
+/// A shared library compiled by Slang.
+#[derive(Clone)]
+pub struct CpuModule {
+    library: Arc<libloading::Library>,
+    // NOTE: must be dropped after the library.
+    _file: Arc<tempfile::TempPath>,
+}
+
+/// A compute kernel loaded from a [`CpuModule`].
+pub struct CpuFunction {
+    kernel: CpuKernel,
+    _module: CpuModule,
+}
+
+/// The CPU backend executes everything immediately so encoders don’t record anything.
+pub struct CpuEncoder;
+
+/// The CPU backend executes everything immediately so passes don’t record anything.
+pub struct CpuPass;
+
+/// The CPU backend executes everything immediately so fences are always signaled.
+pub struct CpuFence;
+
+impl Fence<Cpu> for CpuFence {
+    fn is_signaled(&self) -> bool {
+        true
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for Cpu {
+    const NAME: &'static str = "cpu";
+    const TARGET: shader_slang::CompileTarget = shader_slang::CompileTarget::ShaderSharedLibrary;
+
+    type Error = CpuBackendError;
+    type Buffer<T: DeviceValue> = CpuBuffer<T>;
+    type Texture = UnsupportedTexture;
+    type Sampler = UnsupportedSampler;
+    type Timestamps = UnsupportedTimestamps;
+    type Bundle = UnsupportedBundle;
+    type Fence = CpuFence;
+    type BufferSlice<'b, T: DeviceValue> = CpuBufferSlice<'b, T>;
+    type Encoder = CpuEncoder;
+    type Pass = CpuPass;
+    type Module = CpuModule;
+    type Function = CpuFunction;
+    type Dispatch<'a> = CpuDispatch<'a>;
+
+    fn as_cpu(&self) -> Option<&Cpu> {
+        Some(self)
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        // Workgroups are executed by the thread splitting the dispatch, so only the Slang CPU
+        // targets restrict the kernels.
+        Ok(DeviceLimits {
+            max_workgroup_count: [u32::MAX; 3],
+            max_workgroup_size: [u32::MAX; 3],
+            max_invocations_per_workgroup: u32::MAX,
+            max_storage_buffers_per_stage: u32::MAX,
+            // NOTE: shared memory is useless without workgroup barriers, which the CPU targets
+            //       don’t support.
+            max_shared_memory_size: 0,
+            shader_f16: false,
+            shader_f64: true,
+            shader_int64_atomics: false,
+            subgroups: false,
+        })
+    }
+
+    /*
+     * Module/function loading.
+     */
+    fn load_module_bytes(&self, _bytes: &[u8]) -> Result<Self::Module, Self::Error> {
+        // NOTE: loading a shared library runs its initializers, so libraries of unknown origin
+        //       must go through the unsafe `Cpu::load_library`.
+        Err(UnsupportedError::new::<Self>("shared libraries not compiled by Slang").into())
+    }
+
+    fn load_compiled_module(
+        &self,
+        bytes: &[u8],
+        _compiled: SlangCompiled,
+    ) -> Result<Self::Module, Self::Error> {
+        // SAFETY: the library was compiled by Slang from this crate.
+        unsafe { self.load_library(bytes) }
+    }
+
+    fn load_function(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+    ) -> Result<Self::Function, Self::Error> {
+        // SAFETY: the libraries are either compiled by Slang, whose entry points all have the
+        //         `CpuKernel` signature, or trusted to be by the caller of `Cpu::load_library`.
+        let kernel = unsafe { *module.library.get::<CpuKernel>(entry_point.as_bytes())? };
+        Ok(CpuFunction {
+            kernel,
+            _module: module.clone(),
+        })
+    }
+
+    /*
+     * Kernel dispatch.
+     */
+    fn begin_encoding(&self) -> Self::Encoder {
+        CpuEncoder
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        // Everything is executed synchronously so there is nothing to overlap.
+        Ok(self.clone())
+    }
+
+    fn begin_dispatch<'a>(
+        &'a self,
+        _pass: &'a mut Self::Pass,
+        function: &'a Self::Function,
+        _label: &'a str,
+    ) -> Self::Dispatch<'a> {
+        CpuDispatch {
+            backend: self,
+            function,
+            params: vec![],
+        }
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        // Everything is executed synchronously.
+        Ok(())
+    }
+
+    fn submit(&self, _encoder: Self::Encoder) -> Result<(), Self::Error> {
+        // Everything is executed synchronously.
+        Ok(())
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        // Everything is executed synchronously.
+        self.submit(encoder)?;
+        Ok(SubmissionFuture::ready())
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        Ok(CpuFence)
+    }
+
+    fn signal_fence(&self, _fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        // Everything is executed synchronously.
+        Ok(())
+    }
+
+    fn wait_fence(&self, _fence: &Self::Fence) -> Result<(), Self::Error> {
+        // Everything is executed synchronously.
+        Ok(())
+    }
+
+    /*
+     * Buffer handling.
+     */
+    fn init_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let mut buffer = CpuBuffer::new(bytes.len(), size_of::<T>(), usage);
+        buffer.bytes_mut().copy_from_slice(bytes);
+        Ok(buffer)
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        elements_len::<T>(bytes.len())?;
+        let mut buffer = CpuBuffer::new(bytes.len(), size_of::<T>(), usage);
+        buffer.bytes_mut().copy_from_slice(bytes);
+        Ok(buffer)
+    }
+
+    fn init_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let mut bytes = vec![]; // TODO PERF: can we avoid the allocation somehow?
+        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
+        bytes_buffer.write(data).unwrap();
+
+        let mut buffer = CpuBuffer::new(bytes.len(), T::min_size().get() as usize, usage);
+        buffer.bytes_mut().copy_from_slice(&bytes);
+        Ok(buffer)
+    }
+
+    unsafe fn uninit_buffer<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        Ok(CpuBuffer::new(len * size_of::<T>(), size_of::<T>(), usage))
+    }
+
+    unsafe fn uninit_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let stride = T::min_size().get() as usize;
+        Ok(CpuBuffer::new(len * stride, stride, usage))
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_at(buffer, 0, data)
+    }
+
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let range = buffer.byte_range(offset.saturating_mul(buffer.stride), bytes.len())?;
+        buffer.bytes_mut()[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let stride = buffer.stride;
+        self.write_buffer_with(
+            buffer,
+            offset.saturating_mul(stride),
+            data.len() * stride,
+            |view| StorageBuffer::new(view).write(data).unwrap(),
+        )
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        let range = buffer.byte_range(byte_offset, len)?;
+        write(&mut buffer.bytes_mut()[range]);
+        Ok(())
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(data)?;
+        // SAFETY: all the dispatches are finished since they are executed synchronously.
+        let bytes = unsafe { buffer.bytes() };
+        let len = out.len().min(bytes.len());
+        out[..len].copy_from_slice(&bytes[..len]);
+        Ok(())
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        // SAFETY: all the dispatches are finished since they are executed synchronously.
+        let bytes = unsafe { buffer.bytes() };
+        let mut result = vec![];
+        let encase_buffer = StorageBuffer::new(&bytes);
+        encase_buffer.read(&mut result).unwrap(); // TODO: propagate error
+        data[..result.len()].copy_from_slice(&result);
+        Ok(())
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_buffer(buffer, data).await
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, crate::backend::Buffer::<Cpu, T>::len(buffer));
+        let sz = size_of::<T>();
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        // SAFETY: all the dispatches are finished since they are executed synchronously.
+        let bytes = unsafe { buffer.bytes() };
+        out.copy_from_slice(&bytes[range.start * sz..range.end * sz]);
+        Ok(())
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_buffer_range(buffer, range, out).await
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, crate::backend::Buffer::<Cpu, T>::len(buffer));
+        let sz = size_of::<T>();
+        buffer.bytes_mut()[range.start * sz..range.end * sz]
+            .copy_from_slice(&repeat_bytes(&value, range.len()));
+        Ok(())
+    }
+}
+
+impl Encoder<Cpu> for CpuEncoder {
+    fn begin_pass(&mut self, _label: &str) -> CpuPass {
+        CpuPass
+    }
+
+    fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
+        &mut self,
+        source: &CpuBuffer<T>,
+        source_offset: usize,
+        target: &mut CpuBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), CpuBackendError> {
+        let sz = size_of::<T>();
+        copy_bytes(
+            source,
+            source_offset.saturating_mul(sz),
+            target,
+            target_offset.saturating_mul(sz),
+            copy_len.saturating_mul(sz),
+        )
+    }
+
+    fn copy_buffer_to_buffer_encased<T: DeviceValue + ShaderType>(
+        &mut self,
+        source: &CpuBuffer<T>,
+        source_offset: usize,
+        target: &mut CpuBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), CpuBackendError> {
+        let sz = T::min_size().get() as usize;
+        copy_bytes(
+            source,
+            source_offset.saturating_mul(sz),
+            target,
+            target_offset.saturating_mul(sz),
+            copy_len.saturating_mul(sz),
+        )
+    }
+}
+
+fn copy_bytes<T>(
+    source: &CpuBuffer<T>,
+    source_offset: usize,
+    target: &mut CpuBuffer<T>,
+    target_offset: usize,
+    len: usize,
+) -> Result<(), CpuBackendError> {
+    let source_range = source.byte_range(source_offset, len)?;
+    let target_range = target.byte_range(target_offset, len)?;
+    // SAFETY: no kernel is running since they are executed synchronously.
+    let source = unsafe { &source.bytes()[source_range] };
+    target.bytes_mut()[target_range].copy_from_slice(source);
+    Ok(())
+}
+
+pub struct CpuDispatch<'a> {
+    backend: &'a Cpu,
+    function: &'a CpuFunction,
+    // The uniform entry-point parameters. Stored as `u64` for alignment.
+    params: Vec<u64>,
+}
+
+impl CpuDispatch<'_> {
+    fn push_buffer<T>(&mut self, binding: ShaderBinding, slice: &CpuBufferSlice<T>) {
+        // NOTE: for the CPU targets, the binding index of a uniform entry-point parameter is
+        //       its byte offset in the entry-point parameters struct.
+        let data = slice.data as u64;
+        if slice.uniform {
+            self.write_param(binding.index as usize, data);
+        } else {
+            let arg = CpuStructuredBuffer {
+                data,
+                count: (slice.len_bytes / slice.stride.max(1)) as u64,
+            };
+            self.write_param(binding.index as usize, arg);
+        }
+    }
+
+    /// Writes `value` into the entry-point parameters at the given byte offset.
+    fn write_param<T: Pod>(&mut self, offset: usize, value: T) {
+        let end = (offset + size_of::<T>()).div_ceil(size_of::<u64>());
+        if self.params.len() < end {
+            self.params.resize(end, 0);
+        }
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut self.params);
+        bytes[offset..offset + size_of::<T>()].copy_from_slice(bytemuck::bytes_of(&value));
+    }
+}
+
+impl<'a> Dispatch<'a, Cpu> for CpuDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        _name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        self.write_param(binding.index as usize, *value);
+        Ok(())
+    }
+
+    fn array_element_binding(&self, binding: ShaderBinding, index: usize) -> ShaderBinding {
+        // NOTE: the binding index is a byte offset, and arrays of buffers are stored inline in
+        //       the entry-point parameters.
+        ShaderBinding {
+            space: binding.space,
+            index: binding.index + (index * size_of::<CpuStructuredBuffer>()) as u32,
+        }
+    }
+
+    fn launch<'b>(
+        mut self,
+        grid: impl Into<DispatchGrid<'b, Cpu>>,
+        block_dim: [u32; 3],
+    ) -> Result<(), CpuBackendError> {
+        let grid = match grid.into() {
+            DispatchGrid::Direct(grid) => grid,
+            DispatchGrid::Indirect(grid, offset) => {
+                let len = size_of::<[u32; 3]>();
+                let range = grid.byte_range(offset.saturating_mul(len), len)?;
+                // SAFETY: no kernel is running since they are executed synchronously.
+                let bytes = unsafe { grid.bytes() };
+                bytemuck::pod_read_unaligned(&bytes[range])
+            }
+        };
+
+        if grid.contains(&0) {
+            return Ok(());
+        }
+
+        // NOTE: the kernels compute the global thread ids in 32 bits.
+        if (0..3).any(|i| grid[i] as u64 * block_dim[i] as u64 > u32::MAX as u64 + 1) {
+            return Err(CpuBackendError::GridTooLarge { grid, block_dim });
+        }
+
+        // Split the workgroups along the largest grid axis.
+        let axis = (0..3).max_by_key(|i| grid[*i]).unwrap();
+        let num_threads = self.backend.num_threads.min(grid[axis] as usize) as u32;
+        let groups_per_thread = grid[axis].div_ceil(num_threads) as u64;
+
+        let kernel = self.function.kernel;
+        // NOTE: raw pointers aren’t `Send` so we pass the address instead.
+        let params = self.params.as_mut_ptr() as usize;
+
+        let run = move |thread_id: u32| {
+            let mut start_group_id = [0; 3];
+            let mut end_group_id = grid;
+            let group_id = |thread_id: u32| {
+                (thread_id as u64 * groups_per_thread).min(grid[axis] as u64) as u32
+            };
+            start_group_id[axis] = group_id(thread_id);
+            end_group_id[axis] = group_id(thread_id + 1);
+            let mut varying = ComputeVaryingInput {
+                start_group_id,
+                end_group_id,
+            };
+            // SAFETY: the kernel was loaded from the library generated by Slang, and the
+            //         parameters were written according to its reflection data.
+            unsafe {
+                kernel(&mut varying, params as *mut c_void, std::ptr::null_mut());
+            }
+        };
+
+        if num_threads == 1 {
+            run(0);
+        } else {
+            std::thread::scope(|s| {
+                for thread_id in 0..num_threads {
+                    s.spawn(move || run(thread_id));
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Cpu> for CpuBuffer<T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut CpuDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.push_buffer(binding, &self.view::<T>(0, self.len_bytes, self.stride));
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(self.stride),
+            size_bytes: Some(self.len_bytes),
+            writable: Some(!self.uniform),
+        })
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Cpu> for CpuBufferSlice<'_, T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut CpuDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.push_buffer(binding, self);
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(self.stride),
+            size_bytes: Some(self.len_bytes),
+            writable: Some(!self.uniform),
+        })
+    }
+}
+
+impl<T: DeviceValue> crate::backend::Buffer<Cpu, T> for CpuBuffer<T> {
+    fn len(&self) -> usize {
+        self.len_bytes / self.stride.max(1)
+    }
+
+    fn slice(&self, range: impl RangeBounds<usize>) -> CpuBufferSlice<'_, T> {
+        // NOTE: the slice is handed to the kernels as a raw pointer and length, so its bounds
+        //       must be checked here.
+        let range = buffer_range(range, crate::backend::Buffer::<Cpu, T>::len(self));
+        self.view(
+            range.start * self.stride,
+            range.len() * self.stride,
+            self.stride,
+        )
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.len_bytes
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<CpuBufferSlice<'_, U>, BufferCastError> {
+        let range = view_bytes::<U>(self.len_bytes, byte_offset, len)?;
+        Ok(self.view(range.start, range.len(), size_of::<U>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Buffer;
+    use crate::function::GpuFunction;
+    use crate::utils::{Reduce, ReduceOp};
+    use minislang::SlangCompiler;
+
+    const ADD_ASSIGN: &str = r#"
+[shader("compute")]
+[numthreads(64, 1, 1)]
+func add_assign(
+    uint3 invocation_id: SV_DispatchThreadID,
+    RWStructuredBuffer<float> a,
+    StructuredBuffer<float> b,
+) {
+    let thread_id = invocation_id.x;
+    if (thread_id < a.getCount()) {
+        a[thread_id] += b[thread_id];
+    }
+}
+"#;
+
+    fn load_add_assign(backend: &Cpu) -> GpuFunction<Cpu> {
+        let dir = std::env::temp_dir().join(format!("slang-hal-cpu-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cpu_add_assign.slang"), ADD_ASSIGN).unwrap();
+        let compiler = SlangCompiler::new(vec![dir]);
+        GpuFunction::from_file(backend, &compiler, "cpu_add_assign", "add_assign").unwrap()
+    }
+
+    #[test]
+    fn kernel_runs_on_threads() {
+        let backend = Cpu::with_num_threads(2);
+        let add_assign = load_add_assign(&backend);
+
+        let len = 150;
+        let a = (0..len).map(|i| i as f32).collect::<Vec<_>>();
+        let b = (0..len).map(|i| i as f32 * 10.0).collect::<Vec<_>>();
+        let a_buf = backend.init_buffer(&a, BufferUsages::STORAGE).unwrap();
+        let b_buf = backend.init_buffer(&b, BufferUsages::STORAGE).unwrap();
+
+        let mut encoder = backend.begin_encoding();
+        let mut pass = encoder.begin_pass("add_assign");
+        add_assign
+            .dispatch(&backend, &mut pass)
+            .arg("a", &a_buf)
+            .arg("b", &b_buf)
+            .threads([len, 1, 1])
+            .launch()
+            .unwrap();
+
+        // The thread ids of this grid don’t fit in 32 bits.
+        let result = add_assign
+            .dispatch(&backend, &mut pass)
+            .arg("a", &a_buf)
+            .arg("b", &b_buf)
+            .grid([u32::MAX, 1, 1])
+            .launch();
+        assert!(matches!(result, Err(CpuBackendError::GridTooLarge { .. })));
+        drop(pass);
+        backend.submit(encoder).unwrap();
+
+        let result = futures::executor::block_on(backend.slow_read_vec(&a_buf)).unwrap();
+        let expected = (0..len).map(|i| i as f32 * 11.0).collect::<Vec<_>>();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds_slices_are_rejected() {
+        let backend = Cpu::new();
+        let buffer = backend
+            .init_buffer(&[0u32; 10], BufferUsages::STORAGE)
+            .unwrap();
+        let _ = buffer.slice(5..1000);
+    }
+
+    #[test]
+    fn out_of_bounds_accesses_fail() {
+        let backend = Cpu::new();
+        let mut a = backend
+            .init_buffer(&[0u32; 4], BufferUsages::STORAGE)
+            .unwrap();
+        let b = backend
+            .init_buffer(&[1u32; 8], BufferUsages::STORAGE)
+            .unwrap();
+
+        let result = backend.write_buffer_at(&mut a, 3, &[1, 2]);
+        assert!(matches!(
+            result,
+            Err(CpuBackendError::OutOfBounds {
+                start: 12,
+                len: 8,
+                size: 16,
+            })
+        ));
+        let result = backend.write_buffer_at(&mut a, usize::MAX, &[1]);
+        assert!(matches!(result, Err(CpuBackendError::OutOfBounds { .. })));
+        let result = backend.write_buffer_with(&mut a, 8, 12, |_| unreachable!());
+        assert!(matches!(result, Err(CpuBackendError::OutOfBounds { .. })));
+
+        let mut encoder = backend.begin_encoding();
+        let result = encoder.copy_buffer_to_buffer(&b, 2, &mut a, 0, 6);
+        assert!(matches!(result, Err(CpuBackendError::OutOfBounds { .. })));
+        assert_eq!(unsafe { a.bytes() }, [0; 16]);
+    }
+
+    #[test]
+    fn untrusted_libraries_are_rejected() {
+        let result = Cpu::new().load_module_bytes(b"not a library");
+        assert!(matches!(result, Err(CpuBackendError::Unsupported(_))));
+    }
+
+    #[test]
+    fn shared_memory_utils_are_rejected() {
+        let backend = Cpu::new();
+        let compiler = SlangCompiler::new(vec![]);
+        let result = Reduce::<Cpu, f32>::new(&backend, &compiler, ReduceOp::Sum);
+        assert!(matches!(result, Err(CpuBackendError::Unsupported(_))));
+    }
+}
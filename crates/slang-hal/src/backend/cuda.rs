@@ -1,17 +1,30 @@
 use crate::ShaderArgs;
 use crate::backend::{
-    Backend, DeviceValue, Dispatch, DispatchGrid, EncaseType, Encoder, ShaderBinding,
+    Backend, BufferCastError, DeviceInfo, DeviceLimits, DeviceValue, Dispatch, DispatchGrid,
+    EncaseType, Encoder, Fence, MemoryInfo, PassTimestamps, SamplerDescriptor, ShaderBinding,
+    SubmissionFuture, TextureDataError, Timestamps as _, UnsupportedError, buffer_range,
+    check_texture_data, elements_len, texture_bytes_len, view_bytes,
 };
-use crate::shader::ShaderArgsError;
+use crate::profiler::{Profiler, ProfilingReport};
+use crate::shader::{ArgInfo, ShaderArgsError};
 use bytemuck::Pod;
-use cudarc::driver::safe::{CudaFunction, CudaSlice, CudaStream, DeviceRepr, LaunchArgs};
-use cudarc::driver::{CudaContext, CudaModule, CudaView, CudaViewMut, LaunchConfig, PushKernelArg};
-use cudarc::nvrtc::Ptx;
+use cudarc::driver::safe::{
+    CudaFunction, CudaSlice, CudaStream, DeviceRepr, DriverError, LaunchArgs, UnifiedSlice,
+    ValidAsZeroBits,
+};
+use cudarc::driver::{
+    CudaContext, CudaEvent, CudaGraph, CudaModule, CudaView, CudaViewMut, DevicePtr, DevicePtrMut,
+    LaunchConfig, PushKernelArg, sys,
+};
+use cudarc::nvrtc::{CompileOptions, Ptx};
 use minislang::shader_slang;
-use std::ffi::{CStr, FromBytesWithNulError};
-use std::ops::RangeBounds;
-use std::sync::Arc;
-use wgpu::{Buffer, BufferSlice, BufferUsages};
+use std::ffi::{CStr, CString, FromBytesWithNulError, c_char, c_int};
+use std::io::Write;
+use std::ops::{Deref, DerefMut, Range, RangeBounds};
+use std::sync::{Arc, Mutex, OnceLock};
+use wgpu::{
+    AddressMode, Buffer, BufferSlice, BufferUsages, FilterMode, TextureFormat, TextureUsages,
+};
 
 #[cfg(feature = "cublas")]
 use cudarc::cublas::safe::CudaBlas;
@@ -27,11 +40,33 @@ pub struct Cuda {
     // TODO: add a more comprehensive bitmask for enabled features?
     #[cfg(feature = "cublas")]
     pub cublas_enabled: bool,
+    /// Pinned staging memory for large transfers, reused by the buffer operations.
+    staging: Arc<Mutex<Option<PinnedVec<u8>>>>,
+    /// Streams of submitted encoders, reused by [`Backend::begin_encoding`].
+    encoder_streams: Arc<Mutex<Vec<Arc<CudaStream>>>>,
+    /// If set, shaders are compiled to CUDA source then to PTX with NVRTC.
+    nvrtc: Option<Arc<NvrtcOptions>>,
+    /// The options applied by [`Backend::load_module_bytes`].
+    module_options: CudaModuleOptions,
+    /// The profiling state, if profiling is enabled.
+    profiler: Option<Arc<Profiler<Cuda>>>,
 }
 
+/// Transfers of at least this many bytes go through pinned staging memory.
+const PINNED_STAGING_MIN_BYTES: usize = 1 << 20;
+
 impl Cuda {
+    /// Initializes the backend on the first CUDA device.
     pub fn new() -> Result<Self, CudaBackendError> {
-        let ctxt = CudaContext::new(0)?;
+        Self::with_device(0)
+    }
+
+    /// Initializes the backend on the CUDA device with the given ordinal, as listed by
+    /// [`Cuda::enumerate_devices`].
+    pub fn with_device(index: usize) -> Result<Self, CudaBackendError> {
+        let ctxt = CudaContext::new(index)?;
+        // Let the thread sleep instead of spinning while waiting for the device.
+        ctxt.set_blocking_synchronize()?;
         let stream = ctxt.default_stream();
         #[cfg(feature = "cublas")]
         let cublas = Arc::new(CudaBlas::new(stream.clone())?);
@@ -42,8 +77,607 @@ impl Cuda {
             cublas,
             #[cfg(feature = "cublas")]
             cublas_enabled: cfg!(feature = "cublas"),
+            staging: Arc::default(),
+            encoder_streams: Arc::default(),
+            nvrtc: None,
+            module_options: CudaModuleOptions::default(),
+            profiler: None,
+        })
+    }
+
+    /// Initializes one backend per CUDA device.
+    ///
+    /// Modules are tied to the device they were loaded on, so a shader has to be instantiated
+    /// (with [`Shader::from_backend`](crate::Shader::from_backend)) for each backend to run on
+    /// all of them. Use [`Cuda::copy_buffer_to_peer`] to move data between devices.
+    pub fn all_devices() -> Result<Vec<Self>, CudaBackendError> {
+        (0..CudaContext::device_count()? as usize)
+            .map(Self::with_device)
+            .collect()
+    }
+
+    /// Initializes the backend on the first CUDA device for which `predicate` returns `true`.
+    ///
+    /// Returns `Ok(None)` if no device matches.
+    pub fn with_device_matching(
+        mut predicate: impl FnMut(&DeviceInfo) -> bool,
+    ) -> Result<Option<Self>, CudaBackendError> {
+        match Self::enumerate_devices()?
+            .iter()
+            .find(|info| predicate(info))
+        {
+            Some(info) => Self::with_device(info.index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Lists the CUDA devices, in the order expected by [`Cuda::with_device`].
+    ///
+    /// This doesn’t create any CUDA context.
+    pub fn enumerate_devices() -> Result<Vec<DeviceInfo>, CudaBackendError> {
+        use cudarc::driver::result::device;
+        use sys::CUdevice_attribute::*;
+
+        let count = CudaContext::device_count()?;
+        (0..count)
+            .map(|ordinal| {
+                let dev = device::get(ordinal)?;
+                // SAFETY: `dev` was returned by `device::get`.
+                let (major, minor, integrated, memory) = unsafe {
+                    (
+                        device::get_attribute(dev, CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?,
+                        device::get_attribute(dev, CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?,
+                        device::get_attribute(dev, CU_DEVICE_ATTRIBUTE_INTEGRATED)?,
+                        device::total_mem(dev)?,
+                    )
+                };
+                Ok(DeviceInfo {
+                    index: ordinal as usize,
+                    name: device::get_name(dev)?,
+                    device_type: if integrated != 0 {
+                        wgpu::DeviceType::IntegratedGpu
+                    } else {
+                        wgpu::DeviceType::DiscreteGpu
+                    },
+                    api: format!("CUDA sm_{major}{minor}"),
+                    memory: Some(memory as u64),
+                })
+            })
+            .collect()
+    }
+
+    /// Allocates `len` zeroed elements of page-locked host memory.
+    pub fn alloc_pinned_vec<T: Pod>(&self, len: usize) -> Result<PinnedVec<T>, CudaBackendError> {
+        self.ctxt.bind_to_thread()?;
+        // NOTE: unlike `CudaContext::alloc_pinned`, this doesn’t make the memory write-combined
+        //       so it can be read efficiently from the host.
+        let num_bytes = (len * size_of::<T>()).max(1);
+        // SAFETY: the memory is zeroed right after, which is a valid `T` since it is `Pod`.
+        let ptr = unsafe {
+            let ptr = cudarc::driver::result::malloc_host(num_bytes, 0)?;
+            std::ptr::write_bytes(ptr as *mut u8, 0, num_bytes);
+            ptr as *mut T
+        };
+        Ok(PinnedVec {
+            ptr,
+            len,
+            event: self
+                .ctxt
+                .new_event(Some(sys::CUevent_flags::CU_EVENT_BLOCKING_SYNC))?,
         })
     }
+
+    /// Copies `data` into a new page-locked host allocation.
+    pub fn pinned_vec_from_slice<T: Pod>(
+        &self,
+        data: &[T],
+    ) -> Result<PinnedVec<T>, CudaBackendError> {
+        let mut result = self.alloc_pinned_vec(data.len())?;
+        result.as_mut_slice()?.copy_from_slice(data);
+        Ok(result)
+    }
+
+    /// Copies `data` into `buffer`, starting at the element `offset`.
+    ///
+    /// Unlike [`Backend::write_buffer_at`], the copy is asynchronous: it is executed in order with
+    /// the other operations of this backend’s stream, and `data` can’t be accessed again until it
+    /// completes (see [`PinnedVec::as_slice`]).
+    pub fn write_buffer_pinned<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut <Self as Backend>::Buffer<T>,
+        offset: usize,
+        data: &PinnedVec<T>,
+    ) -> Result<(), CudaBackendError> {
+        let mut target = buffer.slice_mut(offset..offset + data.len());
+        let (dst, _record) = target.device_ptr_mut(&self.stream);
+        // SAFETY: `dst` points to `data.len()` elements, and `data` isn’t accessed from the host
+        //         before the copy completes.
+        unsafe { self.memcpy_htod_pinned(data, data.len() * size_of::<T>(), dst) }
+    }
+
+    /// Copies the elements of `buffer` within `range` into `out`.
+    ///
+    /// Unlike [`Backend::read_buffer_range`], the copy is asynchronous: [`PinnedVec::as_slice`]
+    /// waits for it to complete.
+    pub fn read_buffer_pinned<T: DeviceValue + Pod>(
+        &self,
+        buffer: &<Self as Backend>::Buffer<T>,
+        range: impl RangeBounds<usize>,
+        out: &mut PinnedVec<T>,
+    ) -> Result<(), CudaBackendError> {
+        let range = buffer_range(range, buffer.len());
+        assert!(out.len() >= range.len(), "the output is too small");
+        let len = range.len();
+        let source = buffer.slice(range);
+        let (src, _record) = source.device_ptr(&self.stream);
+        // SAFETY: `src` points to `len` elements, and `out` isn’t accessed from the host before
+        //         the copy completes.
+        unsafe { self.memcpy_dtoh_pinned(src, len * size_of::<T>(), out) }
+    }
+
+    /// Enqueues the copy of the first `num_bytes` bytes of `src` to `dst`.
+    ///
+    /// # Safety
+    /// `dst` must point to at least `num_bytes` bytes of device memory.
+    unsafe fn memcpy_htod_pinned<T: Pod>(
+        &self,
+        src: &PinnedVec<T>,
+        num_bytes: usize,
+        dst: sys::CUdeviceptr,
+    ) -> Result<(), CudaBackendError> {
+        assert!(num_bytes <= src.len() * size_of::<T>());
+        // Wait for other copies involving `src` on other streams.
+        self.stream.wait(&src.event)?;
+        // SAFETY: `src` has at least `num_bytes` initialized bytes.
+        let bytes = unsafe { std::slice::from_raw_parts(src.ptr as *const u8, num_bytes) };
+        unsafe {
+            cudarc::driver::result::memcpy_htod_async(dst, bytes, self.stream.cu_stream())?;
+        }
+        Ok(src.event.record(&self.stream)?)
+    }
+
+    /// Enqueues the copy of `num_bytes` bytes from `src` to the start of `dst`.
+    ///
+    /// # Safety
+    /// `src` must point to at least `num_bytes` bytes of device memory.
+    unsafe fn memcpy_dtoh_pinned<T: Pod>(
+        &self,
+        src: sys::CUdeviceptr,
+        num_bytes: usize,
+        dst: &mut PinnedVec<T>,
+    ) -> Result<(), CudaBackendError> {
+        assert!(num_bytes <= dst.len() * size_of::<T>());
+        // Wait for other copies involving `dst` on other streams.
+        self.stream.wait(&dst.event)?;
+        // SAFETY: `dst` has at least `num_bytes` bytes, and any bytes are valid since `T: Pod`.
+        let bytes = unsafe { std::slice::from_raw_parts_mut(dst.ptr as *mut u8, num_bytes) };
+        unsafe {
+            cudarc::driver::result::memcpy_dtoh_async(bytes, src, self.stream.cu_stream())?;
+        }
+        Ok(dst.event.record(&self.stream)?)
+    }
+
+    /// Runs `f` with staging memory of at least `num_bytes` bytes.
+    fn with_staging<R>(
+        &self,
+        num_bytes: usize,
+        f: impl FnOnce(&mut PinnedVec<u8>) -> Result<R, CudaBackendError>,
+    ) -> Result<R, CudaBackendError> {
+        let mut staging = self.staging.lock().unwrap();
+        if staging.as_ref().is_none_or(|s| s.len() < num_bytes) {
+            // Free the previous staging memory before allocating the new one.
+            *staging = None;
+            *staging = Some(self.alloc_pinned_vec(num_bytes.next_power_of_two())?);
+        }
+        f(staging.as_mut().unwrap())
+    }
+
+    /// Whether this device can access the memory of `peer`’s device directly, through
+    /// peer-to-peer transfers.
+    pub fn can_access_peer(&self, peer: &Cuda) -> Result<bool, CudaBackendError> {
+        let mut can_access = 0;
+        // SAFETY: both devices are valid since they have a context.
+        unsafe {
+            sys::cuDeviceCanAccessPeer(
+                &mut can_access,
+                self.ctxt.cu_device(),
+                peer.ctxt.cu_device(),
+            )
+            .result()?;
+        }
+        Ok(can_access != 0)
+    }
+
+    /// Enables peer-to-peer transfers from this device to the memory of `peer`’s device.
+    ///
+    /// This requires [`Cuda::can_access_peer`] to be `true`, and does nothing if peer access
+    /// was already enabled.
+    pub fn enable_peer_access(&self, peer: &Cuda) -> Result<(), CudaBackendError> {
+        self.ctxt.bind_to_thread()?;
+        // SAFETY: `peer.ctxt` is a valid context.
+        match unsafe { sys::cuCtxEnablePeerAccess(peer.ctxt.cu_ctx(), 0) }.result() {
+            Ok(()) | Err(DriverError(sys::CUresult::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED)) => {
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Enqueues the copy of `copy_len` elements of `source`, allocated by this backend, to
+    /// `target`, allocated by another [`Cuda`] backend (possibly on another device).
+    ///
+    /// The copy goes directly through the peer-to-peer interconnect if enabled with
+    /// [`Cuda::enable_peer_access`], and is staged through host memory by the driver otherwise.
+    /// It is ordered after the work previously enqueued on both backends, and before the work
+    /// enqueued after it.
+    pub fn copy_buffer_to_peer<T: DeviceValue + Pod>(
+        &self,
+        source: &<Self as Backend>::Buffer<T>,
+        source_offset: usize,
+        target: &mut <Self as Backend>::Buffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), CudaBackendError> {
+        let target_stream = target.stream().clone();
+        let target_ctxt = target_stream.context().clone();
+        let source = source.slice(source_offset..source_offset + copy_len);
+        let mut target = target.slice_mut(target_offset..target_offset + copy_len);
+
+        // NOTE: events can only be recorded on streams of their own context, so the target is
+        //       accessed through its own stream, synchronized with ours.
+        self.stream.wait(&target_stream.record_event(None)?)?;
+        let (src, _record_src) = source.device_ptr(&self.stream);
+        let (dst, _record_dst) = target.device_ptr_mut(&target_stream);
+        // SAFETY: both pointers point to `copy_len` elements in their respective contexts.
+        unsafe {
+            sys::cuMemcpyPeerAsync(
+                dst,
+                target_ctxt.cu_ctx(),
+                src,
+                self.ctxt.cu_ctx(),
+                copy_len * size_of::<T>(),
+                self.stream.cu_stream(),
+            )
+            .result()?;
+        }
+        // Make the target’s stream wait for the copy before `_record_dst` records its event.
+        target_stream.wait(&self.stream.record_event(None)?)?;
+        Ok(())
+    }
+
+    /// Sets whether shaders are compiled at runtime with NVRTC.
+    ///
+    /// If `Some`, [`Backend::compile_target`] becomes [`CompileTarget::CudaSource`] instead of
+    /// [`CompileTarget::Ptx`], and the CUDA source emitted by Slang is compiled with NVRTC for
+    /// the exact architecture of this device when its module is loaded. The driver’s JIT
+    /// compilation of the generic PTX emitted by Slang sometimes results in worse register
+    /// allocation.
+    ///
+    /// This only affects the shaders compiled after this call.
+    ///
+    /// [`CompileTarget::CudaSource`]: shader_slang::CompileTarget::CudaSource
+    /// [`CompileTarget::Ptx`]: shader_slang::CompileTarget::Ptx
+    pub fn set_nvrtc(&mut self, options: Option<NvrtcOptions>) {
+        self.nvrtc = options.map(Arc::new);
+    }
+
+    /// The NVRTC options set with [`Cuda::set_nvrtc`], if any.
+    pub fn nvrtc(&self) -> Option<&NvrtcOptions> {
+        self.nvrtc.as_deref()
+    }
+
+    /// Sets the options applied when loading the modules of the shaders compiled after this call.
+    ///
+    /// To load only some shaders with different options (e.g. to cap the register usage of a
+    /// few kernels), set them on a clone of this backend, which shares the same device and
+    /// stream.
+    pub fn set_module_options(&mut self, options: CudaModuleOptions) {
+        self.module_options = options;
+    }
+
+    /// The options applied when loading modules, see [`Cuda::set_module_options`].
+    pub fn module_options(&self) -> CudaModuleOptions {
+        self.module_options
+    }
+
+    /// Loads a module from the PTX emitted by Slang (or its CUDA source when compiling with
+    /// NVRTC), with the given options instead of [`Cuda::module_options`].
+    pub fn load_module_bytes_with_options(
+        &self,
+        bytes: &[u8],
+        options: &CudaModuleOptions,
+    ) -> Result<Arc<CudaModule>, CudaBackendError> {
+        if let Some(nvrtc) = &self.nvrtc {
+            let source = String::from_utf8_lossy(bytes.strip_suffix(&[0]).unwrap_or(bytes));
+            let ptx = self.compile_cuda_source(&source, nvrtc, options)?;
+            return Ok(self.ctxt.load_module(ptx)?);
+        }
+
+        let ptx = CStr::from_bytes_with_nul(bytes)?;
+        if options.max_registers.is_none() && options.target.is_none() && !options.fast_math {
+            return Ok(self
+                .ctxt
+                .load_module(Ptx::from_src(ptx.to_string_lossy()))?);
+        }
+        if options.fast_math {
+            return Err(UnsupportedError::new::<Self>("fast math without NVRTC").into());
+        }
+
+        // NOTE: cudarc can’t load a module from a binary image in memory, so the cubin compiled
+        //       by the JIT is loaded from a temporary file.
+        let cubin = self.jit_ptx(ptx, options)?;
+        let mut file = tempfile::Builder::new().suffix(".cubin").tempfile()?;
+        file.write_all(&cubin)?;
+        file.flush()?;
+        Ok(self.ctxt.load_module(Ptx::from_file(file.path()))?)
+    }
+
+    /// Compiles PTX to a cubin with the driver’s JIT compiler, applying the register and target
+    /// options.
+    fn jit_ptx(
+        &self,
+        ptx: &CStr,
+        options: &CudaModuleOptions,
+    ) -> Result<Vec<u8>, CudaBackendError> {
+        use std::ffi::c_void;
+        use sys::CUjit_option::*;
+
+        // NOTE: JIT option values are integers cast to pointers.
+        let mut keys = vec![];
+        let mut values: Vec<*mut c_void> = vec![];
+        if let Some(max_registers) = options.max_registers {
+            keys.push(CU_JIT_MAX_REGISTERS);
+            values.push(max_registers as usize as *mut c_void);
+        }
+        if let Some([major, minor]) = options.target {
+            // `CUjit_target` values are `10 * major + minor`.
+            keys.push(CU_JIT_TARGET);
+            values.push((10 * major + minor) as usize as *mut c_void);
+        }
+
+        self.ctxt.bind_to_thread()?;
+        // SAFETY: the option arrays have the same length, and the linker state is destroyed
+        //         once the cubin was copied.
+        unsafe {
+            let mut state = std::ptr::null_mut();
+            sys::cuLinkCreate_v2(
+                keys.len() as u32,
+                keys.as_mut_ptr(),
+                values.as_mut_ptr(),
+                &mut state,
+            )
+            .result()?;
+            let cubin = (|| {
+                let ptx = ptx.to_bytes_with_nul();
+                sys::cuLinkAddData_v2(
+                    state,
+                    sys::CUjitInputType::CU_JIT_INPUT_PTX,
+                    ptx.as_ptr() as *mut c_void,
+                    ptx.len(),
+                    c"slang".as_ptr(),
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                )
+                .result()?;
+                let mut cubin = std::ptr::null_mut();
+                let mut size = 0;
+                sys::cuLinkComplete(state, &mut cubin, &mut size).result()?;
+                // The cubin is owned by the linker state.
+                Ok::<_, DriverError>(std::slice::from_raw_parts(cubin as *const u8, size).to_vec())
+            })();
+            sys::cuLinkDestroy(state).result()?;
+            Ok(cubin?)
+        }
+    }
+
+    fn compile_cuda_source(
+        &self,
+        source: &str,
+        nvrtc: &NvrtcOptions,
+        options: &CudaModuleOptions,
+    ) -> Result<Ptx, CudaBackendError> {
+        use sys::CUdevice_attribute::*;
+
+        let [major, minor] = match options.target {
+            Some(target) => target,
+            None => [
+                self.ctxt
+                    .attribute(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?
+                    as u32,
+                self.ctxt
+                    .attribute(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?
+                    as u32,
+            ],
+        };
+        let mut nvrtc_options = vec![format!("--gpu-architecture=compute_{major}{minor}")];
+        if let Some(max_registers) = options.max_registers {
+            nvrtc_options.push(format!("--maxrregcount={max_registers}"));
+        }
+        if options.fast_math {
+            nvrtc_options.push("--use_fast_math".to_string());
+        }
+        nvrtc_options.extend(nvrtc.options.iter().cloned());
+        let opts = CompileOptions {
+            include_paths: nvrtc.include_paths.clone(),
+            options: nvrtc_options,
+            ..Default::default()
+        };
+        Ok(cudarc::nvrtc::compile_ptx_with_opts(source, opts)?)
+    }
+
+    /// Whether this device supports managed memory, i.e., [`CudaManagedBuffer`]s.
+    pub fn supports_managed_memory(&self) -> Result<bool, CudaBackendError> {
+        Ok(self
+            .ctxt
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MANAGED_MEMORY)?
+            != 0)
+    }
+
+    /// Allocates a buffer in unified memory, initialized with `data`.
+    ///
+    /// Fails with `CUDA_ERROR_NOT_PERMITTED` if the device doesn’t support managed memory.
+    pub fn managed_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+    ) -> Result<CudaManagedBuffer<T>, CudaBackendError> {
+        // SAFETY: the whole buffer is initialized right after.
+        let mut buffer = CudaManagedBuffer(unsafe { self.ctxt.alloc_unified(data.len(), true)? });
+        buffer.as_mut_slice()?.copy_from_slice(data);
+        Ok(buffer)
+    }
+
+    /// Allocates a buffer of `len` zeroed elements in unified memory.
+    ///
+    /// Fails with `CUDA_ERROR_NOT_PERMITTED` if the device doesn’t support managed memory.
+    pub fn managed_buffer_zeroed<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+    ) -> Result<CudaManagedBuffer<T>, CudaBackendError> {
+        // SAFETY: the whole buffer is initialized right after.
+        let mut buffer = CudaManagedBuffer(unsafe { self.ctxt.alloc_unified(len, true)? });
+        buffer.as_mut_slice()?.fill(T::zeroed());
+        Ok(buffer)
+    }
+}
+
+/// A buffer in CUDA unified (managed) memory, accessible from both the host and the device.
+///
+/// Unlike [`Cuda`] buffers, its content can be accessed directly from the host with
+/// [`CudaManagedBuffer::as_slice`] and [`CudaManagedBuffer::as_mut_slice`], without explicit
+/// copies: the driver migrates the memory pages on demand. It can be bound as a shader argument
+/// like any other CUDA buffer.
+pub struct CudaManagedBuffer<T: DeviceValue>(UnifiedSlice<ForceDeviceRepr<T>>);
+
+impl<T: DeviceValue + Pod> CudaManagedBuffer<T> {
+    /// The number of elements of this buffer.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether this buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Waits for the device operations using this buffer to complete, then returns its content.
+    pub fn as_slice(&self) -> Result<&[T], CudaBackendError> {
+        Ok(bytemuck::cast_slice(self.0.as_slice()?))
+    }
+
+    /// Waits for the device operations using this buffer to complete, then returns its content.
+    pub fn as_mut_slice(&mut self) -> Result<&mut [T], CudaBackendError> {
+        Ok(bytemuck::cast_slice_mut(self.0.as_mut_slice()?))
+    }
+
+    /// The underlying `cudarc` unified memory slice.
+    pub fn inner(&self) -> &UnifiedSlice<ForceDeviceRepr<T>> {
+        &self.0
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Cuda> for CudaManagedBuffer<T> {
+    #[inline]
+    fn write_arg<'a>(
+        &'b self,
+        _binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut <Cuda as Backend>::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.arg(&self.0);
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(size_of::<T>()),
+            size_bytes: Some(self.0.len() * size_of::<T>()),
+            writable: None,
+        })
+    }
+}
+
+/// Options for loading the modules of shaders on CUDA, see [`Cuda::set_module_options`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CudaModuleOptions {
+    /// The maximum number of registers each thread of the module’s kernels can use.
+    ///
+    /// Lowering it can restore the occupancy of kernels limited by their register usage, at the
+    /// cost of register spilling.
+    pub max_registers: Option<u32>,
+    /// The compute capability to compile for, as `[major, minor]`, instead of the device’s.
+    pub target: Option<[u32; 2]>,
+    /// Whether to use faster but less accurate floating-point operations.
+    ///
+    /// This is only supported when compiling with NVRTC (see [`Cuda::set_nvrtc`]), since the
+    /// driver can’t apply it to the PTX emitted by Slang.
+    pub fast_math: bool,
+}
+
+/// Options for compiling shaders with NVRTC, see [`Cuda::set_nvrtc`].
+#[derive(Clone, Debug, Default)]
+pub struct NvrtcOptions {
+    /// Directories searched for the headers included by the CUDA source (e.g. the `include`
+    /// directory of the CUDA toolkit, for `cuda_fp16.h`).
+    pub include_paths: Vec<String>,
+    /// Additional options given to NVRTC as-is (e.g. `--use_fast_math`).
+    pub options: Vec<String>,
+}
+
+/// Page-locked host memory, for fast transfers with [`Cuda`] buffers.
+///
+/// Transfers from pageable host memory go through an intermediate copy by the driver, which
+/// can halve their bandwidth. Transfers with [`Cuda::write_buffer_pinned`] and
+/// [`Cuda::read_buffer_pinned`] are asynchronous, so the content of a `PinnedVec` is only
+/// accessible through [`PinnedVec::as_slice`] and [`PinnedVec::as_mut_slice`], which wait for
+/// them to complete.
+pub struct PinnedVec<T: Pod> {
+    ptr: *mut T,
+    len: usize,
+    /// Recorded after each copy involving this memory.
+    event: CudaEvent,
+}
+
+// SAFETY: the memory is owned by the `PinnedVec`.
+unsafe impl<T: Pod + Send> Send for PinnedVec<T> {}
+unsafe impl<T: Pod + Sync> Sync for PinnedVec<T> {}
+
+impl<T: Pod> PinnedVec<T> {
+    /// The number of elements of this allocation.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether this allocation is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Waits for pending transfers involving this memory, then returns its content.
+    pub fn as_slice(&self) -> Result<&[T], CudaBackendError> {
+        self.event.synchronize()?;
+        // SAFETY: the memory is initialized, and no transfer is pending.
+        Ok(unsafe { std::slice::from_raw_parts(self.ptr, self.len) })
+    }
+
+    /// Waits for pending transfers involving this memory, then returns its content.
+    pub fn as_mut_slice(&mut self) -> Result<&mut [T], CudaBackendError> {
+        self.event.synchronize()?;
+        // SAFETY: the memory is initialized, and no transfer is pending.
+        Ok(unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) })
+    }
+}
+
+impl<T: Pod> Drop for PinnedVec<T> {
+    fn drop(&mut self) {
+        let ctxt = self.event.context().clone();
+        ctxt.record_err(self.event.synchronize());
+        // SAFETY: `ptr` was allocated with `malloc_host` and no transfer is pending.
+        ctxt.record_err(unsafe { cudarc::driver::result::free_host(self.ptr as _) });
+    }
 }
 
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -55,17 +689,35 @@ pub enum CudaBackendError {
     #[error(transparent)]
     ShaderArg(#[from] ShaderArgsError),
     #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
     CudaDriver(#[from] cudarc::driver::DriverError),
     #[error(transparent)]
     BytemuckPod(#[from] bytemuck::PodCastError),
     #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error(transparent)]
+    TextureData(#[from] TextureDataError),
+    #[error(transparent)]
     PtxRead(#[from] FromBytesWithNulError),
     #[cfg(feature = "cublas")]
     #[error(transparent)]
     Cublas(#[from] cudarc::cublas::result::CublasError),
+    #[error("Failed to wait for a submission: {0}")]
+    SubmissionWait(async_channel::RecvError),
+    #[error(transparent)]
+    Nvrtc(#[from] cudarc::nvrtc::CompileError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("all the timestamp queries of this set are already used")]
+    TimestampsFull,
 }
 
 unsafe impl<T: DeviceValue> DeviceRepr for ForceDeviceRepr<T> {}
+// SAFETY: any bit pattern is valid for `Pod` types.
+unsafe impl<T: DeviceValue + Pod> ValidAsZeroBits for ForceDeviceRepr<T> {}
 
 #[async_trait::async_trait]
 impl Backend for Cuda {
@@ -74,23 +726,65 @@ impl Backend for Cuda {
 
     type Error = CudaBackendError;
     type Buffer<T: DeviceValue> = CudaSlice<ForceDeviceRepr<T>>;
+    type Texture = CudaTexture;
+    type Sampler = CudaSampler;
+    type Timestamps = CudaTimestamps;
+    type Bundle = CudaBundle;
+    type Fence = CudaFence;
     type BufferSlice<'b, T: DeviceValue> = CudaView<'b, ForceDeviceRepr<T>>;
     type Encoder = Cuda;
     type Function = CudaFunction;
-    type Pass = Cuda;
+    type Pass = CudaPass;
     type Module = Arc<CudaModule>;
-    type Dispatch<'a> = LaunchArgs<'a>;
+    type Dispatch<'a> = CudaDispatch<'a>;
 
     fn as_cuda(&self) -> Option<&Cuda> {
         Some(self)
     }
 
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        use sys::CUdevice_attribute::*;
+        let attr = |attrib| Ok::<_, CudaBackendError>(self.ctxt.attribute(attrib)? as u32);
+        let cc = (
+            attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?,
+            attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?,
+        );
+        Ok(DeviceLimits {
+            max_workgroup_count: [
+                attr(CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_X)?,
+                attr(CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_Y)?,
+                attr(CU_DEVICE_ATTRIBUTE_MAX_GRID_DIM_Z)?,
+            ],
+            max_workgroup_size: [
+                attr(CU_DEVICE_ATTRIBUTE_MAX_BLOCK_DIM_X)?,
+                attr(CU_DEVICE_ATTRIBUTE_MAX_BLOCK_DIM_Y)?,
+                attr(CU_DEVICE_ATTRIBUTE_MAX_BLOCK_DIM_Z)?,
+            ],
+            max_invocations_per_workgroup: attr(CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK)?,
+            // Buffers are passed as raw pointers in the kernel parameters.
+            max_storage_buffers_per_stage: u32::MAX,
+            max_shared_memory_size: attr(CU_DEVICE_ATTRIBUTE_MAX_SHARED_MEMORY_PER_BLOCK)?,
+            // Arithmetic on `half` requires compute capability 5.3.
+            shader_f16: cc >= (5, 3),
+            shader_f64: true,
+            shader_int64_atomics: true,
+            subgroups: true,
+        })
+    }
+
     /*
      * Module/function loading.
      */
+    fn compile_target(&self) -> shader_slang::CompileTarget {
+        if self.nvrtc.is_some() {
+            shader_slang::CompileTarget::CudaSource
+        } else {
+            Self::TARGET
+        }
+    }
+
     fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
-        let c_str = CStr::from_bytes_with_nul(bytes)?.to_string_lossy();
-        Ok(self.ctxt.load_module(Ptx::from_src(c_str))?)
+        self.load_module_bytes_with_options(bytes, &self.module_options)
     }
 
     fn load_function(
@@ -101,29 +795,152 @@ impl Backend for Cuda {
         Ok(module.load_function(entry_point)?)
     }
 
+    fn memory_info(&self) -> Result<MemoryInfo, Self::Error> {
+        self.ctxt.bind_to_thread()?;
+        let (free, total) = cudarc::driver::result::mem_get_info()?;
+        Ok(MemoryInfo {
+            used: Some((total - free) as u64),
+            free: Some(free as u64),
+            total: Some(total as u64),
+        })
+    }
+
+    fn occupancy_block_size(&self, function: &Self::Function) -> Result<Option<u32>, Self::Error> {
+        extern "C" fn no_dynamic_shared_memory(_block_size: c_int) -> usize {
+            0
+        }
+
+        let (_min_grid_size, block_size) =
+            function.occupancy_max_potential_block_size(no_dynamic_shared_memory, 0, 0, None)?;
+        Ok(Some(block_size))
+    }
+
     /*
      * Kernel dispatch.
      */
     fn begin_encoding(&self) -> Self::Encoder {
-        self.clone()
+        // Each encoder records its commands on its own stream so they can overlap with the work
+        // enqueued on the backend’s stream (e.g. transfers) until the encoder is submitted.
+        let stream = self.encoder_streams.lock().unwrap().pop();
+        let stream = stream.map(Ok).unwrap_or_else(|| self.ctxt.new_stream());
+        let stream = stream.and_then(|stream| {
+            // The encoder must observe everything enqueued on the backend so far.
+            stream.wait(&self.stream.record_event(None)?)?;
+            Ok(stream)
+        });
+
+        match stream {
+            Ok(stream) => Self {
+                stream,
+                ..self.clone()
+            },
+            // NOTE: `begin_encoding` can’t fail, so fall back to recording on the backend’s
+            //       stream directly.
+            Err(_) => self.clone(),
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        let stream = self.ctxt.new_stream()?;
+        Ok(Self {
+            ctxt: self.ctxt.clone(),
+            #[cfg(feature = "cublas")]
+            cublas: Arc::new(CudaBlas::new(stream.clone())?),
+            #[cfg(feature = "cublas")]
+            cublas_enabled: self.cublas_enabled,
+            stream,
+            staging: Arc::default(),
+            encoder_streams: Arc::default(),
+            nvrtc: self.nvrtc.clone(),
+            module_options: self.module_options,
+            profiler: self.profiler.clone(),
+        })
     }
 
     fn begin_dispatch<'a>(
         &'a self,
-        _pass: &'a mut Self::Pass,
+        pass: &'a mut Self::Pass,
         function: &'a Self::Function,
+        label: &'a str,
     ) -> Self::Dispatch<'a> {
-        self.stream.launch_builder(function)
+        // NOTE: kernels are launched from `LaunchArgs` which we can’t hook into, so dispatches
+        //       are identified by an instantaneous marker instead of a range.
+        nvtx_mark(label);
+        CudaDispatch {
+            args: pass.stream.launch_builder(function),
+            function,
+            shared_memory_bytes: 0,
+            stream: &pass.stream,
+            label,
+            profiler: self.profiler.as_deref(),
+        }
     }
 
-    fn submit(&self, _encoder: Self::Encoder) -> Result<(), Self::Error> {
-        // No real action to perform here?
+    fn submit(&self, encoder: Self::Encoder) -> Result<(), Self::Error> {
+        // The kernels were already launched on the encoder’s stream. Make any later work on the
+        // backend’s stream wait for them.
+        if !Arc::ptr_eq(&encoder.stream, &self.stream) {
+            self.stream.wait(&encoder.stream.record_event(None)?)?;
+            self.encoder_streams
+                .lock()
+                .unwrap()
+                .push(encoder.stream.clone());
+        }
         Ok(())
     }
 
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        self.submit(encoder)?;
+        // Kernels were already launched on the stream, so we just need to know when the stream
+        // reaches this point. A host function enqueued on the stream notifies the future, so
+        // awaiting it doesn’t block the thread.
+        let event = self.stream.record_event(None)?;
+        let (sender, receiver) = async_channel::bounded(1);
+
+        unsafe extern "C" fn notify(data: *mut std::ffi::c_void) {
+            // SAFETY: `data` was created by `Box::into_raw` below, and this is called once.
+            let sender = unsafe { Box::from_raw(data as *mut async_channel::Sender<()>) };
+            let _ = sender.force_send(());
+        }
+
+        let data = Box::into_raw(Box::new(sender));
+        // SAFETY: `notify` doesn’t call any CUDA function, and takes ownership of `data`.
+        unsafe {
+            if let Err(e) = cudarc::driver::result::stream::launch_host_function(
+                self.stream.cu_stream(),
+                notify,
+                data as *mut std::ffi::c_void,
+            ) {
+                drop(Box::from_raw(data));
+                return Err(e.into());
+            }
+        }
+
+        Ok(SubmissionFuture::new(async move {
+            receiver
+                .recv()
+                .await
+                .map_err(CudaBackendError::SubmissionWait)?;
+            // NOTE: the event is complete at this point; this reports errors raised by the
+            //       submission’s kernels.
+            event.synchronize()?;
+            Ok(())
+        }))
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        Ok(CudaFence {
+            event: self.ctxt.new_event(None)?,
+        })
+    }
+
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        Ok(fence.event.record(&self.stream)?)
+    }
+
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error> {
+        Ok(self.stream.wait(&fence.event)?)
+    }
+
     fn synchronize(&self) -> Result<(), Self::Error> {
-        // TODO: doesn’t sound like the best place to set this flag.
-        self.ctxt.set_blocking_synchronize()?;
         Ok(self.stream.synchronize()?)
     }
 
@@ -139,6 +956,20 @@ impl Backend for Cuda {
         Ok(self.stream.memcpy_stod(wrapped)?)
     }
 
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        _usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let len = elements_len::<T>(bytes.len())?;
+        // SAFETY: the whole buffer is initialized right after.
+        let mut buffer = unsafe { self.stream.alloc(len)? };
+        // SAFETY: `u8` can be written with any bytes.
+        let mut view = unsafe { buffer.transmute_mut::<u8>(bytes.len()).unwrap() };
+        self.stream.memcpy_htod(bytes, &mut view)?;
+        Ok(buffer)
+    }
+
     fn init_buffer_encased<T: DeviceValue + EncaseType>(
         &self,
         data: &[T],
@@ -171,8 +1002,7 @@ impl Backend for Cuda {
         buffer: &mut Self::Buffer<T>,
         data: &[T],
     ) -> Result<(), Self::Error> {
-        let wrapped: &[ForceDeviceRepr<T>] = bytemuck::try_cast_slice(data)?;
-        Ok(self.stream.memcpy_htod(wrapped, buffer)?)
+        self.write_buffer_at(buffer, 0, data)
     }
 
     fn write_buffer_encased<T: DeviceValue + EncaseType>(
@@ -180,9 +1010,66 @@ impl Backend for Cuda {
         buffer: &mut Self::Buffer<T>,
         data: &[T],
     ) -> Result<(), Self::Error> {
-        // let wrapped: &[ForceDeviceRepr<T>] = bytemuck::try_cast_slice(data)?;
-        // Ok(self.stream.memcpy_htod(wrapped, buffer)?)
-        todo!()
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let wrapped: &[ForceDeviceRepr<T>] = bytemuck::try_cast_slice(data)?;
+        let mut target = buffer.slice_mut(offset..offset + data.len());
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        if bytes.len() >= PINNED_STAGING_MIN_BYTES {
+            return self.with_staging(bytes.len(), |staging| {
+                staging.as_mut_slice()?[..bytes.len()].copy_from_slice(bytes);
+                let (dst, _record) = target.device_ptr_mut(&self.stream);
+                // SAFETY: `dst` points to `bytes.len()` bytes, and the staging memory isn’t
+                //         accessed before the copy completes.
+                unsafe { self.memcpy_htod_pinned(staging, bytes.len(), dst) }
+            });
+        }
+        Ok(self.stream.memcpy_htod(wrapped, &mut target)?)
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        _buffer: &mut Self::Buffer<T>,
+        _offset: usize,
+        _data: &[T],
+    ) -> Result<(), Self::Error> {
+        // NOTE: encased buffers aren’t supported yet (see `init_buffer_encased`).
+        Err(UnsupportedError::new::<Self>("encased buffers").into())
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        if len == 0 {
+            write(&mut []);
+            return Ok(());
+        }
+
+        let size_bytes = buffer.len() * size_of::<T>();
+        // SAFETY: any byte pattern is valid for `u8`.
+        let mut bytes = unsafe { buffer.transmute_mut::<u8>(size_bytes).unwrap() };
+        let mut target = bytes.slice_mut(byte_offset..byte_offset + len);
+        self.with_staging(len, |staging| {
+            // NOTE: the staging memory is reused, so it is zeroed before being exposed.
+            let view = &mut staging.as_mut_slice()?[..len];
+            view.fill(0);
+            write(view);
+            let (dst, _record) = target.device_ptr_mut(&self.stream);
+            // SAFETY: `dst` points to `len` bytes, and the staging memory isn’t accessed before
+            //         the copy completes.
+            unsafe { self.memcpy_htod_pinned(staging, len, dst) }
+        })
     }
 
     async fn read_buffer<T: DeviceValue + Pod>(
@@ -190,10 +1077,7 @@ impl Backend for Cuda {
         buffer: &Self::Buffer<T>,
         data: &mut [T],
     ) -> Result<(), Self::Error> {
-        let wrapped: &mut [ForceDeviceRepr<T>] = bytemuck::try_cast_slice_mut(data)?;
-        Ok(self
-            .stream
-            .memcpy_dtoh(buffer, &mut wrapped[..buffer.len()])?)
+        self.read_buffer_range(buffer, 0..buffer.len(), data).await
     }
 
     async fn read_buffer_encased<T: DeviceValue + EncaseType>(
@@ -215,11 +1099,310 @@ impl Backend for Cuda {
     ) -> Result<(), Self::Error> {
         self.read_buffer(buffer, data).await
     }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, buffer.len());
+        let wrapped: &mut [ForceDeviceRepr<T>] = bytemuck::try_cast_slice_mut(out)?;
+        let len = range.len();
+        let num_bytes = len * size_of::<T>();
+        if num_bytes >= PINNED_STAGING_MIN_BYTES {
+            let source = buffer.slice(range);
+            return self.with_staging(num_bytes, |staging| {
+                {
+                    let (src, _record) = source.device_ptr(&self.stream);
+                    // SAFETY: `src` points to `num_bytes` bytes, and the staging memory isn’t
+                    //         accessed before the copy completes.
+                    unsafe { self.memcpy_dtoh_pinned(src, num_bytes, staging)? };
+                }
+                let bytes = &staging.as_slice()?[..num_bytes];
+                bytemuck::cast_slice_mut::<_, u8>(&mut wrapped[..len]).copy_from_slice(bytes);
+                Ok(())
+            });
+        }
+        Ok(self
+            .stream
+            .memcpy_dtoh(&buffer.slice(range), &mut wrapped[..len])?)
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_buffer_range(buffer, range, out).await
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        let sz = size_of::<T>();
+        let range = buffer_range(range, buffer.len());
+        if range.is_empty() || sz == 0 {
+            return Ok(());
+        }
+
+        let bytes = bytemuck::bytes_of(&value);
+        let len = range.len();
+        let mut view = buffer.slice_mut(range);
+        let (ptr, _record) = view.device_ptr_mut(&self.stream);
+        let stream = self.stream.cu_stream();
+        self.ctxt.bind_to_thread()?;
+
+        // SAFETY: all the memsets stay within the bounds of `view`.
+        unsafe {
+            if bytes.iter().all(|b| *b == bytes[0]) {
+                sys::cuMemsetD8Async(ptr, bytes[0], len * sz, stream).result()?;
+            } else {
+                // Fill each word of the values with a 2D memset, using the value size as pitch.
+                let word = [4, 2, 1].into_iter().find(|w| sz % w == 0).unwrap();
+                for (i, w) in bytes.chunks_exact(word).enumerate() {
+                    let dst = ptr + (i * word) as u64;
+                    match word {
+                        4 => {
+                            let w = u32::from_ne_bytes(w.try_into().unwrap());
+                            sys::cuMemsetD2D32Async(dst, sz, w, 1, len, stream)
+                        }
+                        2 => {
+                            let w = u16::from_ne_bytes(w.try_into().unwrap());
+                            sys::cuMemsetD2D16Async(dst, sz, w, 1, len, stream)
+                        }
+                        _ => sys::cuMemsetD2D8Async(dst, sz, w[0], 1, len, stream),
+                    }
+                    .result()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Texture handling.
+     */
+    fn init_texture<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        // SAFETY: the texture is initialized right away.
+        let mut texture = unsafe { self.uninit_texture(size, format, usage)? };
+        self.write_texture(&mut texture, data)?;
+        Ok(texture)
+    }
+
+    unsafe fn uninit_texture(
+        &self,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        CudaTexture::new(self, size, format, usage)
+    }
+
+    fn write_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &mut Self::Texture,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        check_texture_data(
+            bytes.len(),
+            texture_bytes_len::<Self>(texture.size, texture.format)?,
+        )?;
+        let row_len = texture.row_len();
+        let copy = sys::CUDA_MEMCPY2D {
+            srcMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_HOST,
+            srcHost: bytes.as_ptr() as *const _,
+            srcPitch: row_len,
+            dstMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_ARRAY,
+            dstArray: texture.array,
+            WidthInBytes: row_len,
+            Height: texture.size[1] as usize,
+            ..unsafe { std::mem::zeroed() }
+        };
+        self.ctxt.bind_to_thread()?;
+        // SAFETY: `bytes` is large enough, and it outlives the copy since we synchronize the
+        //         stream right after.
+        unsafe {
+            sys::cuMemcpy2DAsync_v2(&copy, self.stream.cu_stream()).result()?;
+        }
+        Ok(self.stream.synchronize()?)
+    }
+
+    async fn read_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &Self::Texture,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let bytes: &mut [u8] = bytemuck::try_cast_slice_mut(data)?;
+        check_texture_data(
+            bytes.len(),
+            texture_bytes_len::<Self>(texture.size, texture.format)?,
+        )?;
+        let row_len = texture.row_len();
+        let copy = sys::CUDA_MEMCPY2D {
+            srcMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_ARRAY,
+            srcArray: texture.array,
+            dstMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_HOST,
+            dstHost: bytes.as_mut_ptr() as *mut _,
+            dstPitch: row_len,
+            WidthInBytes: row_len,
+            Height: texture.size[1] as usize,
+            ..unsafe { std::mem::zeroed() }
+        };
+        self.ctxt.bind_to_thread()?;
+        // SAFETY: `bytes` is large enough, and it outlives the copy since we synchronize the
+        //         stream right after.
+        unsafe {
+            sys::cuMemcpy2DAsync_v2(&copy, self.stream.cu_stream()).result()?;
+        }
+        Ok(self.stream.synchronize()?)
+    }
+
+    fn create_sampler(&self, desc: &SamplerDescriptor) -> Result<Self::Sampler, Self::Error> {
+        Ok(CudaSampler {
+            desc: *desc,
+            placeholder: 0,
+        })
+    }
+
+    fn create_timestamps(&self, capacity: usize) -> Result<Self::Timestamps, Self::Error> {
+        // Each pass records one event when it begins, and one when it ends.
+        let events = (0..2 * capacity)
+            .map(|_| {
+                self.ctxt
+                    .new_event(Some(sys::CUevent_flags::CU_EVENT_DEFAULT))
+                    .map(Arc::new)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(CudaTimestamps { events, len: 0 })
+    }
+
+    async fn resolve_timestamps(
+        &self,
+        timestamps: &mut Self::Timestamps,
+    ) -> Result<Vec<PassTimestamps>, Self::Error> {
+        let len = std::mem::take(&mut timestamps.len);
+        let events = &timestamps.events[..2 * len];
+        // NOTE: `elapsed_ms` waits for both events to complete.
+        let to_ns = |event: &CudaEvent| -> Result<u64, CudaBackendError> {
+            Ok((events[0].elapsed_ms(event)? as f64 * 1.0e6) as u64)
+        };
+        events
+            .chunks_exact(2)
+            .map(|e| {
+                Ok(PassTimestamps {
+                    begin_ns: to_ns(&e[0])?,
+                    end_ns: to_ns(&e[1])?,
+                })
+            })
+            .collect()
+    }
+
+    fn enable_profiling(&mut self, capacity: usize) -> Result<(), Self::Error> {
+        let timestamps = self.create_timestamps(capacity)?;
+        self.profiler = Some(Arc::new(Profiler::new(Some(timestamps))));
+        Ok(())
+    }
+
+    fn disable_profiling(&mut self) {
+        self.profiler = None;
+    }
+
+    async fn profiling_report(&self) -> Result<ProfilingReport, Self::Error> {
+        match &self.profiler {
+            Some(profiler) => profiler.report(self).await,
+            None => Ok(ProfilingReport::default()),
+        }
+    }
+
+    fn record_bundle(
+        &self,
+        record: &mut dyn FnMut(&Self, &mut Self::Encoder) -> Result<(), Self::Error>,
+    ) -> Result<CudaBundle, Self::Error> {
+        // NOTE: the commands are captured into a CUDA graph on a dedicated stream. The captured
+        //       launches aren’t executed, so they aren’t profiled.
+        let stream = self.ctxt.new_stream()?;
+        let recorder = Self {
+            stream: stream.clone(),
+            profiler: None,
+            ..self.clone()
+        };
+        stream.begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)?;
+        let result = record(&recorder, &mut recorder.clone());
+        let graph = stream.end_capture(
+            sys::CUgraphInstantiate_flags::CUDA_GRAPH_INSTANTIATE_FLAG_AUTO_FREE_ON_LAUNCH,
+        )?;
+        result?;
+        Ok(CudaBundle { graph, stream })
+    }
+
+    fn replay_bundle(
+        &self,
+        encoder: &mut Self::Encoder,
+        bundle: &CudaBundle,
+    ) -> Result<(), Self::Error> {
+        let Some(graph) = &bundle.graph else {
+            return Ok(());
+        };
+        // NOTE: the graph is launched on the stream it was captured on, ordered with the
+        //       encoder’s stream through events.
+        bundle.stream.wait(&encoder.stream.record_event(None)?)?;
+        graph.launch()?;
+        encoder.stream.wait(&bundle.stream.record_event(None)?)?;
+        Ok(())
+    }
 }
 
 impl Encoder<Cuda> for Cuda {
-    fn begin_pass(&mut self) -> <Self as Backend>::Pass {
-        self.clone()
+    fn begin_pass(&mut self, label: &str) -> <Self as Backend>::Pass {
+        CudaPass {
+            stream: self.stream.clone(),
+            end: None,
+            range: nvtx_range_push(label),
+        }
+    }
+
+    fn begin_timed_pass(
+        &mut self,
+        label: &str,
+        timestamps: &mut CudaTimestamps,
+    ) -> Result<CudaPass, CudaBackendError> {
+        let (begin, end) = timestamps.next_events()?;
+        begin.record(&self.stream)?;
+        Ok(CudaPass {
+            stream: self.stream.clone(),
+            end: Some(end),
+            range: nvtx_range_push(label),
+        })
+    }
+
+    fn push_debug_group(&mut self, label: &str) {
+        // NOTE: always push a range so it matches the next `pop_debug_group`.
+        if let Some(nvtx) = nvtx() {
+            let label = nvtx_label(label);
+            unsafe {
+                (nvtx.range_push)(label.as_ptr());
+            }
+        }
+    }
+
+    fn pop_debug_group(&mut self) {
+        if let Some(nvtx) = nvtx() {
+            unsafe {
+                (nvtx.range_pop)();
+            }
+        }
     }
 
     fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
@@ -252,7 +1435,63 @@ impl Encoder<Cuda> for Cuda {
     }
 }
 
-impl<'a> Dispatch<'a, Cuda> for LaunchArgs<'a> {
+/// A kernel launch being recorded on CUDA.
+///
+/// This dereferences to cudarc’s [`LaunchArgs`], so kernel arguments can be pushed to it directly.
+pub struct CudaDispatch<'a> {
+    args: LaunchArgs<'a>,
+    function: &'a CudaFunction,
+    shared_memory_bytes: u32,
+    stream: &'a CudaStream,
+    label: &'a str,
+    profiler: Option<&'a Profiler<Cuda>>,
+}
+
+impl<'a> Deref for CudaDispatch<'a> {
+    type Target = LaunchArgs<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.args
+    }
+}
+
+impl DerefMut for CudaDispatch<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.args
+    }
+}
+
+/// Kernels must opt in to use more than this amount of dynamic shared memory.
+const DEFAULT_MAX_DYNAMIC_SHARED_MEMORY_BYTES: u32 = 48 * 1024;
+
+impl<'a> Dispatch<'a, Cuda> for CudaDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        _binding: ShaderBinding,
+        _name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        // NOTE: CUDA kernels take values as regular kernel arguments.
+        let value: &ForceDeviceRepr<T> = bytemuck::cast_ref(value);
+        self.arg(value);
+        Ok(())
+    }
+
+    fn set_dynamic_shared_memory(&mut self, bytes: u32) {
+        self.shared_memory_bytes = bytes;
+    }
+
+    fn bind_array(
+        &mut self,
+        _binding: ShaderBinding,
+        name: &str,
+        _len: usize,
+    ) -> Result<(), ShaderArgsError> {
+        // NOTE: Slang passes arrays of buffers as a single kernel argument, but the kernel
+        //       arguments of their elements were already pushed separately.
+        Err(ShaderArgsError::UnsupportedArray(name.to_owned()))
+    }
+
     fn launch<'b>(
         mut self,
         grid: impl Into<DispatchGrid<'b, Cuda>>,
@@ -260,19 +1499,47 @@ impl<'a> Dispatch<'a, Cuda> for LaunchArgs<'a> {
     ) -> Result<(), CudaBackendError> {
         match grid.into() {
             DispatchGrid::Direct(grid_dim) => {
+                if self.shared_memory_bytes > DEFAULT_MAX_DYNAMIC_SHARED_MEMORY_BYTES {
+                    self.function.set_attribute(
+                        sys::CUfunction_attribute::CU_FUNC_ATTRIBUTE_MAX_DYNAMIC_SHARED_SIZE_BYTES,
+                        self.shared_memory_bytes as i32,
+                    )?;
+                }
+
                 let config = LaunchConfig {
                     grid_dim: (grid_dim[0], grid_dim[1], grid_dim[2]),
                     block_dim: (block_dim[0], block_dim[1], block_dim[2]),
-                    shared_mem_bytes: 0,
+                    shared_mem_bytes: self.shared_memory_bytes,
                 };
 
-                // TODO: safety?
-                unsafe {
-                    LaunchArgs::launch(&mut self, config)?;
+                let args = &mut self.args;
+                let stream = self.stream;
+                let mut launch = |timestamps: Option<&mut CudaTimestamps>| {
+                    let events = timestamps.map(|ts| ts.next_events()).transpose()?;
+                    if let Some((begin, _)) = &events {
+                        begin.record(stream)?;
+                    }
+                    // TODO: safety?
+                    unsafe {
+                        LaunchArgs::launch(args, config)?;
+                    }
+                    if let Some((_, end)) = &events {
+                        end.record(stream)?;
+                    }
+                    Ok::<_, CudaBackendError>(())
+                };
+
+                match self.profiler {
+                    Some(profiler) => {
+                        profiler.record(self.label, Some(grid_dim), block_dim, launch)?
+                    }
+                    None => launch(None)?,
                 }
             }
-            DispatchGrid::Indirect(grid_indirect) => {
-                todo!("Indirect dispatch needs to be emulated on cuda.")
+            DispatchGrid::Indirect(..) => {
+                // NOTE: CUDA has no indirect launches, they would have to be emulated, e.g. by
+                //       reading the grid back or with dynamic parallelism.
+                return Err(UnsupportedError::new::<Cuda>("indirect dispatches").into());
             }
         }
         Ok(())
@@ -293,6 +1560,14 @@ impl<'b, T: DeviceValue> ShaderArgs<'b, Cuda> for CudaSlice<ForceDeviceRepr<T>>
         dispatch.arg(self);
         Ok(())
     }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(size_of::<T>()),
+            size_bytes: Some(self.len() * size_of::<T>()),
+            writable: None,
+        })
+    }
 }
 
 impl<'b, T: DeviceValue> ShaderArgs<'b, Cuda> for CudaView<'_, T> {
@@ -309,6 +1584,14 @@ impl<'b, T: DeviceValue> ShaderArgs<'b, Cuda> for CudaView<'_, T> {
         dispatch.arg(&*self);
         Ok(())
     }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(size_of::<T>()),
+            size_bytes: Some(self.len() * size_of::<T>()),
+            writable: None,
+        })
+    }
 }
 
 impl<T: DeviceValue> crate::backend::Buffer<Cuda, T> for CudaSlice<ForceDeviceRepr<T>> {
@@ -319,4 +1602,429 @@ impl<T: DeviceValue> crate::backend::Buffer<Cuda, T> for CudaSlice<ForceDeviceRe
     fn slice(&self, range: impl RangeBounds<usize>) -> <Cuda as Backend>::BufferSlice<'_, T> {
         self.slice(range)
     }
+
+    fn size_bytes(&self) -> usize {
+        self.num_bytes()
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<CudaView<'_, ForceDeviceRepr<U>>, BufferCastError> {
+        let range = view_bytes::<U>(self.num_bytes(), byte_offset, len)?;
+        // SAFETY: `U` is `Pod` so any bytes are a valid `U`, and `view_bytes` checked that the
+        //         view is in bounds and aligned (allocations are at least 256-bytes aligned).
+        unsafe {
+            let bytes = self
+                .transmute::<ForceDeviceRepr<u8>>(self.num_bytes())
+                .unwrap();
+            Ok(bytes.slice(range).transmute(len).unwrap())
+        }
+    }
+}
+
+/// A 2D texture created by the [`Cuda`] backend.
+///
+/// The texture is stored in a CUDA array. It is bound to kernels as a surface object if it was
+/// created with the `STORAGE_BINDING` usage (for `RWTexture2D` parameters), and as a texture
+/// object otherwise (for `Texture2D` parameters).
+pub struct CudaTexture {
+    ctxt: Arc<CudaContext>,
+    array: sys::CUarray,
+    tex: sys::CUtexObject,
+    surf: Option<sys::CUsurfObject>,
+    size: [u32; 2],
+    format: TextureFormat,
+}
+
+// SAFETY: the CUDA handles can be used from any thread as long as the context is bound to it.
+unsafe impl Send for CudaTexture {}
+unsafe impl Sync for CudaTexture {}
+
+impl CudaTexture {
+    fn new(
+        cuda: &Cuda,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self, CudaBackendError> {
+        let (array_format, num_channels, _) = cuda_array_format(format)?;
+        let storage = usage.contains(TextureUsages::STORAGE_BINDING);
+        let array_desc = sys::CUDA_ARRAY3D_DESCRIPTOR {
+            Width: size[0] as usize,
+            Height: size[1] as usize,
+            Depth: 0,
+            Format: array_format,
+            NumChannels: num_channels,
+            Flags: if storage {
+                sys::CUDA_ARRAY3D_SURFACE_LDST
+            } else {
+                0
+            },
+        };
+
+        cuda.ctxt.bind_to_thread()?;
+
+        // SAFETY: the descriptors are fully initialized, and the created handles are destroyed
+        //         when the texture is dropped.
+        unsafe {
+            let mut texture = Self {
+                ctxt: cuda.ctxt.clone(),
+                array: std::ptr::null_mut(),
+                tex: 0,
+                surf: None,
+                size,
+                format,
+            };
+            sys::cuArray3DCreate_v2(&mut texture.array, &array_desc).result()?;
+            texture.tex = texture.create_tex_object(&SamplerDescriptor::default())?;
+
+            if storage {
+                let mut surf = 0;
+                sys::cuSurfObjectCreate(&mut surf, &texture.resource_desc()).result()?;
+                texture.surf = Some(surf);
+            }
+
+            Ok(texture)
+        }
+    }
+
+    /// Sets the filtering and addressing modes used when sampling this texture.
+    ///
+    /// CUDA doesn’t have standalone samplers: these modes are a property of the texture object
+    /// bound to `Texture2D` parameters. By default, textures use the modes of
+    /// [`SamplerDescriptor::default`]. Note that linear filtering is only supported by
+    /// floating-point and normalized formats.
+    pub fn set_sampler(&mut self, desc: &SamplerDescriptor) -> Result<(), CudaBackendError> {
+        self.ctxt.bind_to_thread()?;
+        // SAFETY: the array is valid, and the old texture object isn’t referenced anymore once
+        //         replaced.
+        unsafe {
+            let tex = self.create_tex_object(desc)?;
+            let _ = sys::cuTexObjectDestroy(self.tex);
+            self.tex = tex;
+        }
+        Ok(())
+    }
+
+    fn resource_desc(&self) -> sys::CUDA_RESOURCE_DESC {
+        // SAFETY: the descriptor is a plain C struct, valid when zeroed.
+        let mut res_desc: sys::CUDA_RESOURCE_DESC = unsafe { std::mem::zeroed() };
+        res_desc.resType = sys::CUresourcetype::CU_RESOURCE_TYPE_ARRAY;
+        res_desc.res.array.hArray = self.array;
+        res_desc
+    }
+
+    unsafe fn create_tex_object(
+        &self,
+        desc: &SamplerDescriptor,
+    ) -> Result<sys::CUtexObject, CudaBackendError> {
+        let (_, _, read_as_integer) = cuda_array_format(self.format)?;
+        // SAFETY: the descriptor is a plain C struct, valid when zeroed.
+        let mut tex_desc: sys::CUDA_TEXTURE_DESC = unsafe { std::mem::zeroed() };
+        tex_desc.addressMode = [
+            cuda_address_mode(desc.address_mode_u),
+            cuda_address_mode(desc.address_mode_v),
+            sys::CUaddress_mode::CU_TR_ADDRESS_MODE_CLAMP,
+        ];
+        // NOTE: CUDA doesn’t distinguish between minification and magnification filters.
+        tex_desc.filterMode = match desc.mag_filter {
+            FilterMode::Nearest => sys::CUfilter_mode::CU_TR_FILTER_MODE_POINT,
+            FilterMode::Linear => sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR,
+        };
+        if read_as_integer {
+            tex_desc.flags = sys::CU_TRSF_READ_AS_INTEGER;
+        }
+
+        let mut tex = 0;
+        unsafe {
+            sys::cuTexObjectCreate(&mut tex, &self.resource_desc(), &tex_desc, std::ptr::null())
+                .result()?;
+        }
+        Ok(tex)
+    }
+
+    /// The texture object, for reading the texture with `Texture2D` parameters.
+    pub fn tex_object(&self) -> sys::CUtexObject {
+        self.tex
+    }
+
+    /// The surface object, if the texture was created with the `STORAGE_BINDING` usage.
+    pub fn surf_object(&self) -> Option<sys::CUsurfObject> {
+        self.surf
+    }
+
+    fn row_len(&self) -> usize {
+        // NOTE: the format was validated when creating the texture.
+        texture_bytes_len::<Cuda>([self.size[0], 1], self.format).unwrap()
+    }
+}
+
+impl Drop for CudaTexture {
+    fn drop(&mut self) {
+        // NOTE: errors are ignored since there is nothing we can do about them here.
+        let _ = self.ctxt.bind_to_thread();
+        unsafe {
+            if let Some(surf) = self.surf {
+                let _ = sys::cuSurfObjectDestroy(surf);
+            }
+            if self.tex != 0 {
+                let _ = sys::cuTexObjectDestroy(self.tex);
+            }
+            if !self.array.is_null() {
+                let _ = sys::cuArrayDestroy(self.array);
+            }
+        }
+    }
+}
+
+impl crate::backend::Texture<Cuda> for CudaTexture {
+    fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.format
+    }
+}
+
+impl<'b> ShaderArgs<'b, Cuda> for CudaTexture {
+    #[inline]
+    fn write_arg<'a>(
+        &'b self,
+        _binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut <Cuda as Backend>::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match &self.surf {
+            Some(surf) => dispatch.arg(surf),
+            None => dispatch.arg(&self.tex),
+        };
+        Ok(())
+    }
+}
+
+/// A compute pass of the [`Cuda`] backend.
+///
+/// Kernels are launched on the stream as soon as they are dispatched, so the pass only records
+/// the end of passes started with [`Encoder::begin_timed_pass`], and closes the NVTX range of
+/// labeled passes, when it is dropped.
+pub struct CudaPass {
+    stream: Arc<CudaStream>,
+    end: Option<Arc<CudaEvent>>,
+    /// Whether an NVTX range was pushed when the pass began.
+    range: bool,
+}
+
+impl Drop for CudaPass {
+    fn drop(&mut self) {
+        if let Some(end) = &self.end {
+            // NOTE: errors are ignored since there is nothing we can do about them here. They
+            //       will be reported when the timestamps are resolved.
+            let _ = end.record(&self.stream);
+        }
+        if self.range
+            && let Some(nvtx) = nvtx()
+        {
+            unsafe {
+                (nvtx.range_pop)();
+            }
+        }
+    }
+}
+
+/// A sequence of commands recorded with [`Backend::record_bundle`] into a CUDA graph.
+///
+/// Replaying the bundle launches the whole graph at once, instead of each of its kernels.
+pub struct CudaBundle {
+    /// The captured graph, or `None` if no command was recorded.
+    graph: Option<CudaGraph>,
+    /// The stream the graph was captured on, which it is launched on.
+    stream: Arc<CudaStream>,
+}
+
+/// A fence of the [`Cuda`] backend, implemented with a CUDA event.
+///
+/// Waiting for the fence makes the stream wait for the event on the device, so it doesn’t block
+/// the current thread.
+pub struct CudaFence {
+    event: CudaEvent,
+}
+
+impl CudaFence {
+    /// The underlying CUDA event.
+    pub fn event(&self) -> &CudaEvent {
+        &self.event
+    }
+}
+
+impl Fence<Cuda> for CudaFence {
+    fn is_signaled(&self) -> bool {
+        // NOTE: this is also `true` for events that were never recorded.
+        self.event.is_complete()
+    }
+}
+
+/// A set of timestamp queries, implemented with CUDA events.
+pub struct CudaTimestamps {
+    events: Vec<Arc<CudaEvent>>,
+    len: usize,
+}
+
+impl CudaTimestamps {
+    /// Reserves the next pair of events, timing the beginning and end of a pass or dispatch.
+    ///
+    /// Fails if all the events of this set are already used.
+    fn next_events(&mut self) -> Result<(Arc<CudaEvent>, Arc<CudaEvent>), CudaBackendError> {
+        if self.len >= self.capacity() {
+            return Err(CudaBackendError::TimestampsFull);
+        }
+        let index = 2 * self.len;
+        self.len += 1;
+        Ok((self.events[index].clone(), self.events[index + 1].clone()))
+    }
+}
+
+impl crate::backend::Timestamps<Cuda> for CudaTimestamps {
+    fn capacity(&self) -> usize {
+        self.events.len() / 2
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A texture sampler created by the [`Cuda`] backend.
+///
+/// Slang’s CUDA target doesn’t use `SamplerState` parameters, so binding this sampler only
+/// pushes a placeholder argument. The sampling modes of a texture are set with
+/// [`CudaTexture::set_sampler`] instead.
+pub struct CudaSampler {
+    desc: SamplerDescriptor,
+    placeholder: u64,
+}
+
+impl crate::backend::Sampler<Cuda> for CudaSampler {
+    fn descriptor(&self) -> SamplerDescriptor {
+        self.desc
+    }
+}
+
+impl<'b> ShaderArgs<'b, Cuda> for CudaSampler {
+    #[inline]
+    fn write_arg<'a>(
+        &'b self,
+        _binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut <Cuda as Backend>::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.arg(&self.placeholder);
+        Ok(())
+    }
+}
+
+fn cuda_address_mode(mode: AddressMode) -> sys::CUaddress_mode {
+    match mode {
+        AddressMode::ClampToEdge => sys::CUaddress_mode::CU_TR_ADDRESS_MODE_CLAMP,
+        AddressMode::Repeat => sys::CUaddress_mode::CU_TR_ADDRESS_MODE_WRAP,
+        AddressMode::MirrorRepeat => sys::CUaddress_mode::CU_TR_ADDRESS_MODE_MIRROR,
+        AddressMode::ClampToBorder => sys::CUaddress_mode::CU_TR_ADDRESS_MODE_BORDER,
+    }
+}
+
+/// The CUDA array format, channel count, and whether texture reads return integers, for the
+/// given texture format.
+fn cuda_array_format(
+    format: TextureFormat,
+) -> Result<(sys::CUarray_format, u32, bool), UnsupportedError> {
+    use sys::CUarray_format::*;
+    Ok(match format {
+        TextureFormat::R32Float => (CU_AD_FORMAT_FLOAT, 1, false),
+        TextureFormat::Rg32Float => (CU_AD_FORMAT_FLOAT, 2, false),
+        TextureFormat::Rgba32Float => (CU_AD_FORMAT_FLOAT, 4, false),
+        TextureFormat::R16Float => (CU_AD_FORMAT_HALF, 1, false),
+        TextureFormat::Rgba16Float => (CU_AD_FORMAT_HALF, 4, false),
+        TextureFormat::R32Uint => (CU_AD_FORMAT_UNSIGNED_INT32, 1, true),
+        TextureFormat::Rg32Uint => (CU_AD_FORMAT_UNSIGNED_INT32, 2, true),
+        TextureFormat::Rgba32Uint => (CU_AD_FORMAT_UNSIGNED_INT32, 4, true),
+        TextureFormat::R32Sint => (CU_AD_FORMAT_SIGNED_INT32, 1, true),
+        TextureFormat::Rgba32Sint => (CU_AD_FORMAT_SIGNED_INT32, 4, true),
+        TextureFormat::R8Unorm => (CU_AD_FORMAT_UNSIGNED_INT8, 1, false),
+        TextureFormat::Rgba8Unorm => (CU_AD_FORMAT_UNSIGNED_INT8, 4, false),
+        TextureFormat::Rgba8Uint => (CU_AD_FORMAT_UNSIGNED_INT8, 4, true),
+        _ => return Err(UnsupportedError::new::<Cuda>("textures with this format")),
+    })
+}
+
+/// The NVTX functions used for labeling CUDA work in Nsight captures.
+struct Nvtx {
+    _library: libloading::Library,
+    range_push: unsafe extern "C" fn(*const c_char) -> c_int,
+    range_pop: unsafe extern "C" fn() -> c_int,
+    mark: unsafe extern "C" fn(*const c_char),
+}
+
+/// Loads the NVTX library shipped with the CUDA toolkit, if it is available.
+///
+/// Debug labels are silently ignored if it isn’t.
+fn nvtx() -> Option<&'static Nvtx> {
+    static NVTX: OnceLock<Option<Nvtx>> = OnceLock::new();
+    NVTX.get_or_init(|| {
+        const NAMES: &[&str] = &[
+            "libnvToolsExt.so.1",
+            "libnvToolsExt.so",
+            "nvToolsExt64_1.dll",
+            "libnvToolsExt.dylib",
+        ];
+        NAMES.iter().find_map(|name| unsafe {
+            let library = libloading::Library::new(name).ok()?;
+            let range_push = *library.get(b"nvtxRangePushA\0").ok()?;
+            let range_pop = *library.get(b"nvtxRangePop\0").ok()?;
+            let mark = *library.get(b"nvtxMarkA\0").ok()?;
+            Some(Nvtx {
+                _library: library,
+                range_push,
+                range_pop,
+                mark,
+            })
+        })
+    })
+    .as_ref()
+}
+
+fn nvtx_label(label: &str) -> CString {
+    // NOTE: interior nul bytes would truncate the label anyway.
+    CString::new(label.replace('\0', "")).unwrap()
+}
+
+/// Pushes an NVTX range labeled with `label`, unless it is empty. Returns `true` if a range was
+/// pushed.
+fn nvtx_range_push(label: &str) -> bool {
+    match nvtx() {
+        Some(nvtx) if !label.is_empty() => {
+            let label = nvtx_label(label);
+            unsafe {
+                (nvtx.range_push)(label.as_ptr());
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+fn nvtx_mark(label: &str) {
+    if let Some(nvtx) = nvtx().filter(|_| !label.is_empty()) {
+        let label = nvtx_label(label);
+        unsafe {
+            (nvtx.mark)(label.as_ptr());
+        }
+    }
 }
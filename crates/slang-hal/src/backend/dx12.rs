@@ -0,0 +1,1545 @@
+use crate::ShaderArgs;
+use crate::backend::{
+    Backend, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid, EncaseType,
+    Encoder, Fence, ShaderBinding, SubmissionFuture, UnsupportedBundle, UnsupportedError,
+    UnsupportedSampler, UnsupportedTexture, UnsupportedTimestamps, buffer_range, elements_len,
+    repeat_bytes, uniform_bytes, view_bytes,
+};
+use crate::shader::{ArgInfo, ShaderArgsError};
+use bytemuck::Pod;
+use encase::{ShaderType, StorageBuffer};
+use minislang::shader_slang;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, Range, RangeBounds};
+use std::sync::{Arc, Mutex};
+use wgpu::BufferUsages;
+use windows::Win32::Foundation::{BOOLEAN, CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+use windows::Win32::Graphics::Direct3D::Dxc::{
+    CLSID_DxcUtils, DxcBuffer, DxcCreateInstance, IDxcUtils,
+};
+use windows::Win32::Graphics::Direct3D::{
+    D3D_FEATURE_LEVEL_11_0, D3D_SHADER_INPUT_TYPE, D3D_SIT_BYTEADDRESS, D3D_SIT_CBUFFER,
+    D3D_SIT_STRUCTURED, D3D_SIT_UAV_APPEND_STRUCTURED, D3D_SIT_UAV_CONSUME_STRUCTURED,
+    D3D_SIT_UAV_RWBYTEADDRESS, D3D_SIT_UAV_RWSTRUCTURED, D3D_SIT_UAV_RWSTRUCTURED_WITH_COUNTER,
+    ID3DBlob,
+};
+use windows::Win32::Graphics::Direct3D12::*;
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT_UNKNOWN, DXGI_SAMPLE_DESC};
+use windows::Win32::System::Threading::{
+    CreateEventW, INFINITE, RegisterWaitForSingleObject, UnregisterWaitEx, WT_EXECUTEONLYONCE,
+};
+use windows::core::Interface;
+
+/// Wrapper making a D3D12 COM object `Send + Sync`.
+///
+/// All the D3D12 interfaces we use here are free-threaded so this is fine as long as we don’t
+/// record into the same command list from multiple threads simultaneously (which is prevented
+/// by the `&mut` requirements of the encoders and passes).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Dx12Object<T>(pub T);
+
+unsafe impl<T> Send for Dx12Object<T> {}
+unsafe impl<T> Sync for Dx12Object<T> {}
+
+impl<T> Deref for Dx12Object<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// A Direct3D 12 compute backend.
+///
+/// Slang modules are compiled to DXIL (this requires `dxcompiler.dll` and `dxil.dll` to be
+/// available next to the executable or in the `PATH`), and every resource is bound through
+/// root descriptors built from the DXIL reflection data. Because of this, only structured
+/// buffers, byte-address buffers, and constant buffers are supported as kernel parameters.
+pub struct Dx12 {
+    device: Dx12Object<ID3D12Device>,
+    queue: Dx12Object<ID3D12CommandQueue>,
+    fence: Dx12Object<ID3D12Fence>,
+    dxc_utils: Dx12Object<IDxcUtils>,
+    dispatch_indirect_signature: Dx12Object<ID3D12CommandSignature>,
+    submissions: Mutex<Dx12Submissions>,
+}
+
+#[derive(Default)]
+struct Dx12Submissions {
+    last_fence_value: u64,
+    // Keeps the command allocators, lists, and referenced resources alive until the gpu is done
+    // with them.
+    in_flight: Vec<(u64, Dx12Encoder)>,
+}
+
+impl Dx12 {
+    /// Creates a D3D12 device on the default adapter, as well as its compute queue.
+    pub fn new() -> Result<Self, Dx12BackendError> {
+        unsafe {
+            let mut device: Option<ID3D12Device> = None;
+            D3D12CreateDevice(None, D3D_FEATURE_LEVEL_11_0, &mut device)?;
+            let device = device.ok_or(Dx12BackendError::NoDevice)?;
+            Self::from_device(device)
+        }
+    }
+
+    /// Initializes the backend from an existing D3D12 device.
+    ///
+    /// This lets slang-hal kernels be dispatched alongside an existing D3D12 renderer and
+    /// share its buffers.
+    pub fn from_device(device: ID3D12Device) -> Result<Self, Dx12BackendError> {
+        unsafe {
+            let queue: ID3D12CommandQueue =
+                device.CreateCommandQueue(&D3D12_COMMAND_QUEUE_DESC {
+                    Type: D3D12_COMMAND_LIST_TYPE_COMPUTE,
+                    Priority: D3D12_COMMAND_QUEUE_PRIORITY_NORMAL.0,
+                    Flags: D3D12_COMMAND_QUEUE_FLAG_NONE,
+                    NodeMask: 0,
+                })?;
+            let fence: ID3D12Fence = device.CreateFence(0, D3D12_FENCE_FLAG_NONE)?;
+            let dxc_utils: IDxcUtils = DxcCreateInstance(&CLSID_DxcUtils)?;
+
+            let indirect_arg = D3D12_INDIRECT_ARGUMENT_DESC {
+                Type: D3D12_INDIRECT_ARGUMENT_TYPE_DISPATCH,
+                ..Default::default()
+            };
+            let mut dispatch_indirect_signature: Option<ID3D12CommandSignature> = None;
+            device.CreateCommandSignature(
+                &D3D12_COMMAND_SIGNATURE_DESC {
+                    ByteStride: size_of::<[u32; 3]>() as u32,
+                    NumArgumentDescs: 1,
+                    pArgumentDescs: &indirect_arg,
+                    NodeMask: 0,
+                },
+                None,
+                &mut dispatch_indirect_signature,
+            )?;
+
+            Ok(Self {
+                device: Dx12Object(device),
+                queue: Dx12Object(queue),
+                fence: Dx12Object(fence),
+                dxc_utils: Dx12Object(dxc_utils),
+                dispatch_indirect_signature: Dx12Object(
+                    dispatch_indirect_signature.ok_or(Dx12BackendError::NoDevice)?,
+                ),
+                submissions: Mutex::new(Dx12Submissions::default()),
+            })
+        }
+    }
+
+    /// The D3D12 device.
+    pub fn device(&self) -> &ID3D12Device {
+        &self.device
+    }
+
+    /// The D3D12 compute queue all the work is submitted to.
+    pub fn queue(&self) -> &ID3D12CommandQueue {
+        &self.queue
+    }
+
+    fn create_buffer(
+        &self,
+        size: u64,
+        usage: BufferUsages,
+    ) -> Result<Dx12Buffer, Dx12BackendError> {
+        let heap = Dx12Heap::from_usage(usage);
+        let (heap_type, initial_state, flags) = match heap {
+            Dx12Heap::Default => (
+                D3D12_HEAP_TYPE_DEFAULT,
+                D3D12_RESOURCE_STATE_COMMON,
+                D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS,
+            ),
+            Dx12Heap::Upload => (
+                D3D12_HEAP_TYPE_UPLOAD,
+                D3D12_RESOURCE_STATE_GENERIC_READ,
+                D3D12_RESOURCE_FLAG_NONE,
+            ),
+            Dx12Heap::Readback => (
+                D3D12_HEAP_TYPE_READBACK,
+                D3D12_RESOURCE_STATE_COPY_DEST,
+                D3D12_RESOURCE_FLAG_NONE,
+            ),
+        };
+
+        let desc = D3D12_RESOURCE_DESC {
+            Dimension: D3D12_RESOURCE_DIMENSION_BUFFER,
+            Alignment: 0,
+            // D3D12 doesn’t support zero-sized resources.
+            Width: size.max(4),
+            Height: 1,
+            DepthOrArraySize: 1,
+            MipLevels: 1,
+            Format: DXGI_FORMAT_UNKNOWN,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Layout: D3D12_TEXTURE_LAYOUT_ROW_MAJOR,
+            Flags: flags,
+        };
+        let heap_props = D3D12_HEAP_PROPERTIES {
+            Type: heap_type,
+            ..Default::default()
+        };
+
+        let mut resource: Option<ID3D12Resource> = None;
+        unsafe {
+            self.device.CreateCommittedResource(
+                &heap_props,
+                D3D12_HEAP_FLAG_NONE,
+                &desc,
+                initial_state,
+                None,
+                &mut resource,
+            )?;
+        }
+
+        Ok(Dx12Buffer {
+            resource: Dx12Object(resource.ok_or(Dx12BackendError::NoDevice)?),
+            size,
+            heap,
+        })
+    }
+
+    fn write_bytes(
+        &self,
+        buffer: &Dx12Buffer,
+        offset: u64,
+        bytes: &[u8],
+    ) -> Result<(), Dx12BackendError> {
+        self.write_bytes_with(buffer, offset, bytes.len(), |view| {
+            view.copy_from_slice(bytes)
+        })
+    }
+
+    /// Writes `len` bytes into `buffer` at the byte `offset`, by letting `write` fill them
+    /// directly in mapped memory (either `buffer` itself or an upload staging buffer).
+    fn write_bytes_with(
+        &self,
+        buffer: &Dx12Buffer,
+        offset: u64,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Dx12BackendError> {
+        buffer.check_bounds(offset, len as u64)?;
+        if len == 0 {
+            write(&mut []);
+            return Ok(());
+        }
+
+        match buffer.heap {
+            Dx12Heap::Upload | Dx12Heap::Readback => unsafe {
+                // NOTE: for readback heaps this is slow, but valid.
+                let mut ptr = std::ptr::null_mut();
+                buffer.resource.Map(0, None, Some(&mut ptr))?;
+                // NOTE: the view holds the current content of the buffer.
+                let view =
+                    std::slice::from_raw_parts_mut((ptr as *mut u8).add(offset as usize), len);
+                write(view);
+                buffer.resource.Unmap(0, None);
+                Ok(())
+            },
+            Dx12Heap::Default => {
+                let staging = self.create_buffer(len as u64, BufferUsages::MAP_WRITE)?;
+                self.write_bytes_with(&staging, 0, len, write)?;
+                let mut encoder = self.begin_encoding();
+                encoder.copy_bytes(&staging, 0, buffer, offset, len as u64);
+                // NOTE: the staging buffer is released once the copy completes, with the rest of
+                //       the submission, so we don’t have to wait for it here.
+                encoder.retained.lock().unwrap().push(staging);
+                self.submit(encoder)
+            }
+        }
+    }
+
+    fn read_bytes(
+        &self,
+        buffer: &Dx12Buffer,
+        offset: u64,
+        out: &mut [u8],
+    ) -> Result<(), Dx12BackendError> {
+        if buffer.heap == Dx12Heap::Default {
+            return Err(Dx12BackendError::NotMappable);
+        }
+
+        // Make sure any pending copy into the buffer is finished.
+        self.synchronize()?;
+
+        let available = buffer
+            .size
+            .checked_sub(offset)
+            .ok_or(Dx12BackendError::OutOfBounds {
+                start: offset,
+                len: out.len() as u64,
+                size: buffer.size,
+            })?;
+        let len = out.len().min(available as usize);
+        let offset = offset as usize;
+        unsafe {
+            let read_range = D3D12_RANGE {
+                Begin: offset,
+                End: offset + len,
+            };
+            let mut ptr = std::ptr::null_mut();
+            buffer.resource.Map(0, Some(&read_range), Some(&mut ptr))?;
+            std::ptr::copy_nonoverlapping((ptr as *const u8).add(offset), out.as_mut_ptr(), len);
+            buffer.resource.Unmap(0, Some(&D3D12_RANGE::default()));
+        }
+        Ok(())
+    }
+
+    fn reflect_resources(
+        &self,
+        bytecode: &[u8],
+    ) -> Result<Vec<Dx12ResourceBinding>, Dx12BackendError> {
+        let buffer = DxcBuffer {
+            Ptr: bytecode.as_ptr() as *const _,
+            Size: bytecode.len(),
+            Encoding: 0,
+        };
+        let mut reflection = std::ptr::null_mut();
+        let reflection: ID3D12ShaderReflection = unsafe {
+            self.dxc_utils.CreateReflection(
+                &buffer,
+                &ID3D12ShaderReflection::IID,
+                &mut reflection,
+            )?;
+            ID3D12ShaderReflection::from_raw(reflection)
+        };
+
+        let mut shader_desc = D3D12_SHADER_DESC::default();
+        unsafe { reflection.GetDesc(&mut shader_desc)? };
+
+        let mut result = vec![];
+        for i in 0..shader_desc.BoundResources {
+            let mut desc = D3D12_SHADER_INPUT_BIND_DESC::default();
+            unsafe { reflection.GetResourceBindingDesc(i, &mut desc)? };
+            let name = unsafe { desc.Name.to_string() }.unwrap_or_default();
+            let kind = Dx12ResourceKind::from_input_type(desc.Type)
+                .ok_or_else(|| Dx12BackendError::UnsupportedResource(name.clone()))?;
+            result.push(Dx12ResourceBinding {
+                name,
+                kind,
+                register: desc.BindPoint,
+                space: desc.Space,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn retire_completed_submissions(&self, submissions: &mut Dx12Submissions) {
+        let completed = unsafe { self.fence.GetCompletedValue() };
+        submissions
+            .in_flight
+            .retain(|(fence_value, _)| *fence_value > completed);
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Dx12BackendError {
+    #[error(transparent)]
+    ShaderArg(#[from] ShaderArgsError),
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
+    Windows(#[from] windows::core::Error),
+    #[error(transparent)]
+    BytemuckPod(#[from] bytemuck::PodCastError),
+    #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error("no suitable D3D12 device found")]
+    NoDevice,
+    #[error("failed to serialize root signature: {0}")]
+    RootSignature(String),
+    #[error(
+        "unsupported shader resource `{0}`: only structured, byte-address, and constant buffers are supported"
+    )]
+    UnsupportedResource(String),
+    #[error(
+        "the buffer isn’t mappable: it must be created with `BufferUsages::MAP_READ` or `BufferUsages::MAP_WRITE`"
+    )]
+    NotMappable,
+    #[error("Failed to wait for a submission: {0}")]
+    SubmissionWait(async_channel::RecvError),
+    #[error(
+        "the access of {len} bytes at byte {start} is out of the bounds of the buffer ({size} bytes)"
+    )]
+    OutOfBounds { start: u64, len: u64, size: u64 },
+}
+
+/// A wait for a fence to reach a value, notifying a channel from a thread-pool callback instead
+/// of blocking a thread.
+struct Dx12FenceWait {
+    event: HANDLE,
+    wait: HANDLE,
+    // Boxed so that the pointer given to the callback stays valid when `self` moves.
+    sender: Box<async_channel::Sender<()>>,
+}
+
+// SAFETY: the handles are only used to unregister the wait and to close the event, which can be
+//         done from any thread.
+unsafe impl Send for Dx12FenceWait {}
+
+impl Dx12FenceWait {
+    fn new(
+        fence: &ID3D12Fence,
+        value: u64,
+        sender: async_channel::Sender<()>,
+    ) -> Result<Self, Dx12BackendError> {
+        unsafe extern "system" fn notify(data: *mut c_void, _timed_out: BOOLEAN) {
+            // SAFETY: `data` points to the sender of the `Dx12FenceWait`, which unregisters the
+            //         wait (and waits for this callback to return) before dropping it.
+            let sender = unsafe { &*(data as *const async_channel::Sender<()>) };
+            let _ = sender.force_send(());
+        }
+
+        unsafe {
+            let mut this = Self {
+                event: CreateEventW(None, false, false, None)?,
+                wait: HANDLE::default(),
+                sender: Box::new(sender),
+            };
+            fence.SetEventOnCompletion(value, this.event)?;
+            let data = &*this.sender as *const async_channel::Sender<()> as *const c_void;
+            RegisterWaitForSingleObject(
+                &mut this.wait,
+                this.event,
+                Some(notify),
+                Some(data),
+                INFINITE,
+                WT_EXECUTEONLYONCE,
+            )?;
+            Ok(this)
+        }
+    }
+}
+
+impl Drop for Dx12FenceWait {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.wait.is_invalid() {
+                // NOTE: with `INVALID_HANDLE_VALUE`, this waits for the callback to return if it
+                //       is running, so the sender can be dropped safely afterwards.
+                let _ = UnregisterWaitEx(self.wait, INVALID_HANDLE_VALUE);
+            }
+            let _ = CloseHandle(self.event);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Dx12Heap {
+    Default,
+    Upload,
+    Readback,
+}
+
+impl Dx12Heap {
+    fn from_usage(usage: BufferUsages) -> Self {
+        if usage.contains(BufferUsages::MAP_READ) {
+            Dx12Heap::Readback
+        } else if usage.contains(BufferUsages::MAP_WRITE) {
+            Dx12Heap::Upload
+        } else {
+            Dx12Heap::Default
+        }
+    }
+}
+
+/// A D3D12 buffer resource.
+pub struct Dx12Buffer {
+    resource: Dx12Object<ID3D12Resource>,
+    size: u64,
+    heap: Dx12Heap,
+}
+
+impl Dx12Buffer {
+    /// The underlying D3D12 resource.
+    pub fn resource(&self) -> &ID3D12Resource {
+        &self.resource
+    }
+
+    /// The size of this buffer, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn check_bounds(&self, start: u64, len: u64) -> Result<(), Dx12BackendError> {
+        match start.checked_add(len) {
+            Some(end) if end <= self.size => Ok(()),
+            _ => Err(Dx12BackendError::OutOfBounds {
+                start,
+                len,
+                size: self.size,
+            }),
+        }
+    }
+}
+
+/// A byte range of a [`Dx12Buffer`].
+#[derive(Copy, Clone)]
+pub struct Dx12BufferSlice<'b> {
+    buffer: &'b Dx12Buffer,
+    offset: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Dx12ResourceKind {
+    ConstantBuffer,
+    ShaderResource,
+    UnorderedAccess,
+}
+
+impl Dx12ResourceKind {
+    fn from_input_type(ty: D3D_SHADER_INPUT_TYPE) -> Option<Self> {
+        match ty {
+            D3D_SIT_CBUFFER => Some(Self::ConstantBuffer),
+            D3D_SIT_STRUCTURED | D3D_SIT_BYTEADDRESS => Some(Self::ShaderResource),
+            D3D_SIT_UAV_RWSTRUCTURED
+            | D3D_SIT_UAV_RWBYTEADDRESS
+            | D3D_SIT_UAV_APPEND_STRUCTURED
+            | D3D_SIT_UAV_CONSUME_STRUCTURED
+            | D3D_SIT_UAV_RWSTRUCTURED_WITH_COUNTER => Some(Self::UnorderedAccess),
+            _ => None,
+        }
+    }
+
+    fn root_parameter_type(self) -> D3D12_ROOT_PARAMETER_TYPE {
+        match self {
+            Self::ConstantBuffer => D3D12_ROOT_PARAMETER_TYPE_CBV,
+            Self::ShaderResource => D3D12_ROOT_PARAMETER_TYPE_SRV,
+            Self::UnorderedAccess => D3D12_ROOT_PARAMETER_TYPE_UAV,
+        }
+    }
+
+    fn resource_state(self) -> D3D12_RESOURCE_STATES {
+        match self {
+            Self::ConstantBuffer => D3D12_RESOURCE_STATE_VERTEX_AND_CONSTANT_BUFFER,
+            Self::ShaderResource => D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE,
+            Self::UnorderedAccess => D3D12_RESOURCE_STATE_UNORDERED_ACCESS,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Dx12ResourceBinding {
+    name: String,
+    kind: Dx12ResourceKind,
+    register: u32,
+    space: u32,
+}
+
+/// A compiled DXIL module.
+#[derive(Clone)]
+pub struct Dx12Module {
+    bytecode: Arc<Vec<u8>>,
+}
+
+/// A compute pipeline, with the root signature derived from its DXIL reflection.
+pub struct Dx12Function {
+    pipeline: Dx12Object<ID3D12PipelineState>,
+    root_signature: Dx12Object<ID3D12RootSignature>,
+    // The i-th resource is bound to the i-th root parameter.
+    resources: Vec<Dx12ResourceBinding>,
+}
+
+impl Dx12Function {
+    fn root_parameter_index(&self, binding: ShaderBinding, name: &str) -> Option<usize> {
+        let mut candidates = self
+            .resources
+            .iter()
+            .enumerate()
+            .filter(|(_, res)| res.space == binding.space && res.register == binding.index);
+        let first = candidates.next()?;
+        let Some(second) = candidates.next() else {
+            return Some(first.0);
+        };
+
+        // HLSL has separate register sets for SRVs and UAVs so `t0` and `u0` can both exist.
+        // Disambiguate with the resource names. Slang suffixes the names of the emitted HLSL
+        // globals with `_<number>` so we strip it.
+        let strip_suffix = |s: &str| {
+            s.rsplit_once('_')
+                .filter(|(_, suffix)| suffix.chars().all(|c| c.is_ascii_digit()))
+                .map(|(prefix, _)| prefix.to_string())
+                .unwrap_or_else(|| s.to_string())
+        };
+        [first, second]
+            .into_iter()
+            .chain(candidates)
+            .find(|(_, res)| res.name == name || strip_suffix(&res.name) == name)
+            .map(|(id, _)| id)
+    }
+}
+
+/// Tracks the state of the resources used by a command list.
+#[derive(Default)]
+struct Dx12ResourceStates {
+    states: HashMap<usize, D3D12_RESOURCE_STATES>,
+    // Keep every resource referenced by the command list alive until its execution completes.
+    referenced: Vec<Dx12Object<ID3D12Resource>>,
+}
+
+impl Dx12ResourceStates {
+    fn transition(
+        &mut self,
+        list: &ID3D12GraphicsCommandList,
+        buffer: &Dx12Buffer,
+        new_state: D3D12_RESOURCE_STATES,
+    ) {
+        if buffer.heap != Dx12Heap::Default {
+            // Upload and readback heaps have a fixed state.
+            return;
+        }
+
+        let key = buffer.resource.as_raw() as usize;
+        let old_state = match self.states.insert(key, new_state) {
+            Some(state) => state,
+            None => {
+                self.referenced.push(buffer.resource.clone());
+                // Buffers always decay to the common state at the end of a command list
+                // execution.
+                D3D12_RESOURCE_STATE_COMMON
+            }
+        };
+
+        let barrier = if old_state == new_state {
+            if new_state != D3D12_RESOURCE_STATE_UNORDERED_ACCESS {
+                return;
+            }
+
+            D3D12_RESOURCE_BARRIER {
+                Type: D3D12_RESOURCE_BARRIER_TYPE_UAV,
+                Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+                Anonymous: D3D12_RESOURCE_BARRIER_0 {
+                    UAV: ManuallyDrop::new(D3D12_RESOURCE_UAV_BARRIER {
+                        pResource: unsafe { std::mem::transmute_copy(&buffer.resource.0) },
+                    }),
+                },
+            }
+        } else {
+            D3D12_RESOURCE_BARRIER {
+                Type: D3D12_RESOURCE_BARRIER_TYPE_TRANSITION,
+                Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+                Anonymous: D3D12_RESOURCE_BARRIER_0 {
+                    Transition: ManuallyDrop::new(D3D12_RESOURCE_TRANSITION_BARRIER {
+                        // NOTE: transmute_copy so we don’t increment the reference count
+                        //       (`ManuallyDrop` would never decrement it).
+                        pResource: unsafe { std::mem::transmute_copy(&buffer.resource.0) },
+                        Subresource: D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
+                        StateBefore: old_state,
+                        StateAfter: new_state,
+                    }),
+                },
+            }
+        };
+
+        unsafe { list.ResourceBarrier(&[barrier]) };
+    }
+}
+
+/// A D3D12 command list with its allocator.
+pub struct Dx12Encoder {
+    // The allocator must outlive the execution of the command list.
+    _allocator: Dx12Object<ID3D12CommandAllocator>,
+    list: Dx12Object<ID3D12GraphicsCommandList>,
+    states: Arc<Mutex<Dx12ResourceStates>>,
+    // Buffers created while recording (e.g. for the values passed to dispatches) that must
+    // outlive the execution of the command list.
+    retained: Arc<Mutex<Vec<Dx12Buffer>>>,
+}
+
+impl Dx12Encoder {
+    /// The D3D12 command list commands are recorded into.
+    pub fn command_list(&self) -> &ID3D12GraphicsCommandList {
+        &self.list
+    }
+
+    fn copy_bytes(
+        &mut self,
+        source: &Dx12Buffer,
+        source_offset: u64,
+        target: &Dx12Buffer,
+        target_offset: u64,
+        len: u64,
+    ) {
+        let mut states = self.states.lock().unwrap();
+        states.transition(&self.list, source, D3D12_RESOURCE_STATE_COPY_SOURCE);
+        states.transition(&self.list, target, D3D12_RESOURCE_STATE_COPY_DEST);
+        unsafe {
+            self.list.CopyBufferRegion(
+                &target.resource.0,
+                target_offset,
+                &source.resource.0,
+                source_offset,
+                len,
+            );
+        }
+    }
+}
+
+/// A fence of the [`Dx12`] backend.
+///
+/// Waiting for the fence makes the queue wait on the device, so it doesn’t block the current
+/// thread.
+pub struct Dx12Fence {
+    fence: Dx12Object<ID3D12Fence>,
+    /// The value the fence reaches once signaled.
+    value: u64,
+}
+
+impl Dx12Fence {
+    /// The underlying D3D12 fence.
+    pub fn fence(&self) -> &ID3D12Fence {
+        &self.fence
+    }
+}
+
+impl Fence<Dx12> for Dx12Fence {
+    fn is_signaled(&self) -> bool {
+        unsafe { self.fence.GetCompletedValue() >= self.value }
+    }
+}
+
+/// A compute pass.
+///
+/// D3D12 doesn’t have the concept of compute passes, so this just records dispatches into the
+/// command list of the encoder it was created from. Labeled passes are enclosed in a PIX event
+/// that ends when the pass is dropped.
+pub struct Dx12Pass {
+    list: Dx12Object<ID3D12GraphicsCommandList>,
+    states: Arc<Mutex<Dx12ResourceStates>>,
+    retained: Arc<Mutex<Vec<Dx12Buffer>>>,
+    /// Whether a PIX event was opened when the pass began.
+    event: bool,
+}
+
+impl Drop for Dx12Pass {
+    fn drop(&mut self) {
+        if self.event {
+            unsafe { self.list.EndEvent() }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for Dx12 {
+    const NAME: &'static str = "dx12";
+    const TARGET: shader_slang::CompileTarget = shader_slang::CompileTarget::Dxil;
+
+    type Error = Dx12BackendError;
+    type Buffer<T: DeviceValue> = Dx12Buffer;
+    type Texture = UnsupportedTexture;
+    type Sampler = UnsupportedSampler;
+    type Timestamps = UnsupportedTimestamps;
+    type Bundle = UnsupportedBundle;
+    type Fence = Dx12Fence;
+    type BufferSlice<'b, T: DeviceValue> = Dx12BufferSlice<'b>;
+    type Encoder = Dx12Encoder;
+    type Pass = Dx12Pass;
+    type Module = Dx12Module;
+    type Function = Dx12Function;
+    type Dispatch<'a> = Dx12Dispatch<'a>;
+
+    fn as_dx12(&self) -> Option<&Dx12> {
+        Some(self)
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        let mut options = D3D12_FEATURE_DATA_D3D12_OPTIONS::default();
+        let mut options1 = D3D12_FEATURE_DATA_D3D12_OPTIONS1::default();
+        let mut options4 = D3D12_FEATURE_DATA_D3D12_OPTIONS4::default();
+        let mut shader_model = D3D12_FEATURE_DATA_SHADER_MODEL {
+            HighestShaderModel: D3D_SHADER_MODEL_6_6,
+        };
+        unsafe {
+            check_feature_support(&self.device, D3D12_FEATURE_D3D12_OPTIONS, &mut options)?;
+            check_feature_support(&self.device, D3D12_FEATURE_D3D12_OPTIONS1, &mut options1)?;
+            check_feature_support(&self.device, D3D12_FEATURE_D3D12_OPTIONS4, &mut options4)?;
+            check_feature_support(&self.device, D3D12_FEATURE_SHADER_MODEL, &mut shader_model)?;
+        }
+
+        Ok(DeviceLimits {
+            max_workgroup_count: [D3D12_CS_DISPATCH_MAX_THREAD_GROUPS_PER_DIMENSION; 3],
+            max_workgroup_size: [
+                D3D12_CS_THREAD_GROUP_MAX_X,
+                D3D12_CS_THREAD_GROUP_MAX_Y,
+                D3D12_CS_THREAD_GROUP_MAX_Z,
+            ],
+            max_invocations_per_workgroup: D3D12_CS_THREAD_GROUP_MAX_THREADS_PER_GROUP,
+            // Every resource is bound through a root descriptor, costing two of the 64 DWORDs
+            // available in a root signature.
+            max_storage_buffers_per_stage: D3D12_MAX_ROOT_COST / 2,
+            max_shared_memory_size: D3D12_CS_TGSM_REGISTER_COUNT * 4,
+            shader_f16: options4.Native16BitShaderOpsSupported.as_bool(),
+            shader_f64: options.DoublePrecisionFloatShaderOps.as_bool(),
+            // 64-bit atomics on raw and structured buffers are required by shader model 6.6.
+            shader_int64_atomics: shader_model.HighestShaderModel.0 >= D3D_SHADER_MODEL_6_6.0,
+            subgroups: options1.WaveOps.as_bool(),
+        })
+    }
+
+    /*
+     * Module/function loading.
+     */
+    fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
+        Ok(Dx12Module {
+            bytecode: Arc::new(bytes.to_vec()),
+        })
+    }
+
+    fn load_function(
+        &self,
+        module: &Self::Module,
+        _entry_point: &str,
+    ) -> Result<Self::Function, Self::Error> {
+        // NOTE: the DXIL emitted by Slang for a single entry-point contains only that
+        //       entry-point, so we don’t need to select it here.
+        let resources = self.reflect_resources(&module.bytecode)?;
+
+        /*
+         * Root signature: one root descriptor per resource.
+         */
+        let parameters: Vec<_> = resources
+            .iter()
+            .map(|res| D3D12_ROOT_PARAMETER {
+                ParameterType: res.kind.root_parameter_type(),
+                Anonymous: D3D12_ROOT_PARAMETER_0 {
+                    Descriptor: D3D12_ROOT_DESCRIPTOR {
+                        ShaderRegister: res.register,
+                        RegisterSpace: res.space,
+                    },
+                },
+                ShaderVisibility: D3D12_SHADER_VISIBILITY_ALL,
+            })
+            .collect();
+        let root_desc = D3D12_ROOT_SIGNATURE_DESC {
+            NumParameters: parameters.len() as u32,
+            pParameters: parameters.as_ptr(),
+            NumStaticSamplers: 0,
+            pStaticSamplers: std::ptr::null(),
+            Flags: D3D12_ROOT_SIGNATURE_FLAG_NONE,
+        };
+
+        let mut blob: Option<ID3DBlob> = None;
+        let mut error_blob: Option<ID3DBlob> = None;
+        let serialized = unsafe {
+            D3D12SerializeRootSignature(
+                &root_desc,
+                D3D_ROOT_SIGNATURE_VERSION_1,
+                &mut blob,
+                Some(&mut error_blob),
+            )
+        };
+        if let Err(e) = serialized {
+            let message = error_blob
+                .map(|err| unsafe { String::from_utf8_lossy(blob_bytes(&err)).into_owned() })
+                .unwrap_or_else(|| e.to_string());
+            return Err(Dx12BackendError::RootSignature(message));
+        }
+        let blob = blob.ok_or_else(|| Dx12BackendError::RootSignature(String::new()))?;
+        let root_signature: ID3D12RootSignature =
+            unsafe { self.device.CreateRootSignature(0, blob_bytes(&blob))? };
+
+        /*
+         * Create the pipeline.
+         */
+        let pipeline_desc = D3D12_COMPUTE_PIPELINE_STATE_DESC {
+            pRootSignature: unsafe { std::mem::transmute_copy(&root_signature) },
+            CS: D3D12_SHADER_BYTECODE {
+                pShaderBytecode: module.bytecode.as_ptr() as *const _,
+                BytecodeLength: module.bytecode.len(),
+            },
+            NodeMask: 0,
+            CachedPSO: D3D12_CACHED_PIPELINE_STATE::default(),
+            Flags: D3D12_PIPELINE_STATE_FLAG_NONE,
+        };
+        let pipeline: ID3D12PipelineState =
+            unsafe { self.device.CreateComputePipelineState(&pipeline_desc)? };
+
+        Ok(Dx12Function {
+            pipeline: Dx12Object(pipeline),
+            root_signature: Dx12Object(root_signature),
+            resources,
+        })
+    }
+
+    /*
+     * Kernel dispatch.
+     */
+    fn begin_encoding(&self) -> Self::Encoder {
+        // TODO: recycle command allocators instead of creating new ones every time.
+        let allocator: ID3D12CommandAllocator = unsafe {
+            self.device
+                .CreateCommandAllocator(D3D12_COMMAND_LIST_TYPE_COMPUTE)
+                .expect("failed to create a D3D12 command allocator")
+        };
+        let list: ID3D12GraphicsCommandList = unsafe {
+            self.device
+                .CreateCommandList(0, D3D12_COMMAND_LIST_TYPE_COMPUTE, &allocator, None)
+                .expect("failed to create a D3D12 command list")
+        };
+        Dx12Encoder {
+            _allocator: Dx12Object(allocator),
+            list: Dx12Object(list),
+            states: Arc::new(Mutex::new(Dx12ResourceStates::default())),
+            retained: Arc::default(),
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        unsafe {
+            let queue: ID3D12CommandQueue =
+                self.device.CreateCommandQueue(&D3D12_COMMAND_QUEUE_DESC {
+                    Type: D3D12_COMMAND_LIST_TYPE_COMPUTE,
+                    Priority: D3D12_COMMAND_QUEUE_PRIORITY_NORMAL.0,
+                    Flags: D3D12_COMMAND_QUEUE_FLAG_NONE,
+                    NodeMask: 0,
+                })?;
+            let fence: ID3D12Fence = self.device.CreateFence(0, D3D12_FENCE_FLAG_NONE)?;
+            Ok(Self {
+                device: self.device.clone(),
+                queue: Dx12Object(queue),
+                fence: Dx12Object(fence),
+                dxc_utils: self.dxc_utils.clone(),
+                dispatch_indirect_signature: self.dispatch_indirect_signature.clone(),
+                submissions: Mutex::new(Dx12Submissions::default()),
+            })
+        }
+    }
+
+    fn begin_dispatch<'a>(
+        &'a self,
+        pass: &'a mut Self::Pass,
+        function: &'a Self::Function,
+        label: &'a str,
+    ) -> Self::Dispatch<'a> {
+        Dx12Dispatch {
+            backend: self,
+            pass,
+            function,
+            label,
+            args: SmallVec::default(),
+            values: vec![],
+            value_buffer: None,
+        }
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        let mut submissions = self.submissions.lock().unwrap();
+        unsafe {
+            if self.fence.GetCompletedValue() < submissions.last_fence_value {
+                // With a null event handle, this blocks until the fence value is reached.
+                self.fence
+                    .SetEventOnCompletion(submissions.last_fence_value, HANDLE::default())?;
+            }
+        }
+        self.retire_completed_submissions(&mut submissions);
+        Ok(())
+    }
+
+    fn submit(&self, encoder: Self::Encoder) -> Result<(), Self::Error> {
+        unsafe {
+            encoder.list.Close()?;
+            self.queue
+                .ExecuteCommandLists(&[Some(encoder.list.0.cast::<ID3D12CommandList>()?)]);
+        }
+
+        let mut submissions = self.submissions.lock().unwrap();
+        submissions.last_fence_value += 1;
+        let fence_value = submissions.last_fence_value;
+        unsafe { self.queue.Signal(&self.fence.0, fence_value)? };
+        self.retire_completed_submissions(&mut submissions);
+        submissions.in_flight.push((fence_value, encoder));
+        Ok(())
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        self.submit(encoder)?;
+        let fence_value = self.submissions.lock().unwrap().last_fence_value;
+        if unsafe { self.fence.GetCompletedValue() } >= fence_value {
+            return Ok(SubmissionFuture::ready());
+        }
+
+        // The fence signals an event once the submission completes, and a thread-pool wait on
+        // that event notifies the future, so awaiting it doesn’t block the thread.
+        let (sender, receiver) = async_channel::bounded(1);
+        let wait = Dx12FenceWait::new(&self.fence, fence_value, sender)?;
+        Ok(SubmissionFuture::new(async move {
+            let result = receiver.recv().await;
+            drop(wait);
+            result.map_err(Dx12BackendError::SubmissionWait)
+        }))
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        let fence: ID3D12Fence = unsafe { self.device.CreateFence(0, D3D12_FENCE_FLAG_NONE)? };
+        Ok(Dx12Fence {
+            fence: Dx12Object(fence),
+            value: 0,
+        })
+    }
+
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        fence.value += 1;
+        unsafe { self.queue.Signal(&fence.fence.0, fence.value)? };
+        Ok(())
+    }
+
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error> {
+        unsafe { self.queue.Wait(&fence.fence.0, fence.value)? };
+        Ok(())
+    }
+
+    /*
+     * Buffer handling.
+     */
+    fn init_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let buffer = self.create_buffer(bytes.len() as u64, usage)?;
+        self.write_bytes(&buffer, 0, bytes)?;
+        Ok(buffer)
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        elements_len::<T>(bytes.len())?;
+        let buffer = self.create_buffer(bytes.len() as u64, usage)?;
+        self.write_bytes(&buffer, 0, bytes)?;
+        Ok(buffer)
+    }
+
+    fn init_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let len = data.len() * T::min_size().get() as usize;
+        let buffer = self.create_buffer(len as u64, usage)?;
+        self.write_bytes_with(&buffer, 0, len, |view| {
+            StorageBuffer::new(view).write(data).unwrap()
+        })?;
+        Ok(buffer)
+    }
+
+    unsafe fn uninit_buffer<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        self.create_buffer(size_of::<T>() as u64 * len as u64, usage)
+    }
+
+    unsafe fn uninit_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        self.create_buffer(T::min_size().get() * len as u64, usage)
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_at(buffer, 0, data)
+    }
+
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let offset = (offset * size_of::<T>()) as u64;
+        self.write_bytes(buffer, offset, bytemuck::try_cast_slice(data)?)
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let sz = T::min_size().get();
+        self.write_bytes_with(
+            buffer,
+            offset as u64 * sz,
+            data.len() * sz as usize,
+            |view| StorageBuffer::new(view).write(data).unwrap(),
+        )
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        self.write_bytes_with(buffer, byte_offset as u64, len, write)
+    }
+
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        // NOTE: the std140-like layout of `encase` matches HLSL’s constant buffer packing rules
+        //       for all the types it supports.
+        let bytes = uniform_bytes(value);
+        let buffer = self.create_buffer(
+            bytes.len() as u64,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        )?;
+        self.write_bytes(&buffer, 0, &bytes)?;
+        Ok(buffer)
+    }
+
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.write_bytes(buffer, 0, &uniform_bytes(value))
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_bytes(buffer, 0, bytemuck::try_cast_slice_mut(data)?)
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let mut bytes = vec![0u8; buffer.size as usize];
+        self.read_bytes(buffer, 0, &mut bytes)?;
+
+        let mut result = vec![];
+        let encase_buffer = StorageBuffer::new(&bytes);
+        encase_buffer.read(&mut result).unwrap(); // TODO: propagate error
+        data[..result.len()].copy_from_slice(&result);
+        Ok(())
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        if buffer.heap != Dx12Heap::Default {
+            return self.read_buffer(buffer, data).await;
+        }
+
+        let staging = self.create_buffer(buffer.size, BufferUsages::MAP_READ)?;
+        let mut encoder = self.begin_encoding();
+        encoder.copy_bytes(buffer, 0, &staging, 0, buffer.size);
+        self.submit(encoder)?;
+        self.read_buffer(&staging, data).await
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let sz = size_of::<T>();
+        let range = buffer_range(range, buffer.size as usize / sz);
+        let out = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        self.read_bytes(buffer, (range.start * sz) as u64, out)
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        if buffer.heap != Dx12Heap::Default {
+            return self.read_buffer_range(buffer, range, out).await;
+        }
+
+        let sz = size_of::<T>();
+        let range = buffer_range(range, buffer.size as usize / sz);
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        // Only copy the requested range to the staging buffer.
+        let len = (range.len() * sz) as u64;
+        let staging = self.create_buffer(len, BufferUsages::MAP_READ)?;
+        let mut encoder = self.begin_encoding();
+        encoder.copy_bytes(buffer, (range.start * sz) as u64, &staging, 0, len);
+        self.submit(encoder)?;
+        self.read_bytes(
+            &staging,
+            0,
+            bytemuck::try_cast_slice_mut(&mut out[..range.len()])?,
+        )
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        // NOTE: clearing with `ClearUnorderedAccessViewUint` would require descriptor heaps, which
+        //       this backend doesn’t use, so we upload the filled range instead.
+        let sz = size_of::<T>();
+        let range = buffer_range(range, buffer.size as usize / sz);
+        self.write_bytes(
+            buffer,
+            (range.start * sz) as u64,
+            &repeat_bytes(&value, range.len()),
+        )
+    }
+}
+
+impl Encoder<Dx12> for Dx12Encoder {
+    fn begin_pass(&mut self, label: &str) -> Dx12Pass {
+        if !label.is_empty() {
+            begin_event(&self.list, label);
+        }
+        Dx12Pass {
+            list: self.list.clone(),
+            states: self.states.clone(),
+            retained: self.retained.clone(),
+            event: !label.is_empty(),
+        }
+    }
+
+    fn push_debug_group(&mut self, label: &str) {
+        begin_event(&self.list, label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        unsafe { self.list.EndEvent() }
+    }
+
+    fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
+        &mut self,
+        source: &Dx12Buffer,
+        source_offset: usize,
+        target: &mut Dx12Buffer,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), Dx12BackendError> {
+        let sz = size_of::<T>() as u64;
+        self.copy_bytes(
+            source,
+            source_offset as u64 * sz,
+            target,
+            target_offset as u64 * sz,
+            copy_len as u64 * sz,
+        );
+        Ok(())
+    }
+
+    fn copy_buffer_to_buffer_encased<T: DeviceValue + ShaderType>(
+        &mut self,
+        source: &Dx12Buffer,
+        source_offset: usize,
+        target: &mut Dx12Buffer,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), Dx12BackendError> {
+        let sz = T::min_size().get();
+        self.copy_bytes(
+            source,
+            source_offset as u64 * sz,
+            target,
+            target_offset as u64 * sz,
+            copy_len as u64 * sz,
+        );
+        Ok(())
+    }
+}
+
+pub struct Dx12Dispatch<'a> {
+    backend: &'a Dx12,
+    pass: &'a mut Dx12Pass,
+    function: &'a Dx12Function,
+    label: &'a str,
+    // NOTE: keep up to 10 bindings on the stack, like for WebGPU.
+    args: SmallVec<[(usize, Dx12BufferSlice<'a>); 10]>,
+    values: Vec<u8>,
+    value_buffer: Option<ShaderBinding>,
+}
+
+impl<'a> Dx12Dispatch<'a> {
+    fn push_arg(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        slice: Dx12BufferSlice<'a>,
+    ) -> Result<(), ShaderArgsError> {
+        let param_id = self
+            .function
+            .root_parameter_index(binding, name)
+            .ok_or_else(|| ShaderArgsError::ArgNotFound(name.to_owned()))?;
+        self.args.push((param_id, slice));
+        Ok(())
+    }
+}
+
+impl<'a> Dispatch<'a, Dx12> for Dx12Dispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        if self.value_buffer.is_none() {
+            return Err(ShaderArgsError::NoValueBuffer(name.to_owned()));
+        }
+
+        let bytes = bytemuck::bytes_of(value);
+        let offset = binding.index as usize;
+        if self.values.len() < offset + bytes.len() {
+            self.values.resize(offset + bytes.len(), 0);
+        }
+        self.values[offset..offset + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn set_value_buffer_binding(&mut self, binding: ShaderBinding) {
+        self.value_buffer = Some(binding);
+    }
+
+    // NOTE: the block_dim is configured in the shader…
+    fn launch<'b>(
+        self,
+        grid: impl Into<DispatchGrid<'b, Dx12>>,
+        _block_dim: [u32; 3],
+    ) -> Result<(), Dx12BackendError> {
+        let list = &self.pass.list;
+        let grid = grid.into();
+
+        if let DispatchGrid::Direct(grid_dim) = grid {
+            // NOTE: we don’t need to queue if the workgroup is empty.
+            if grid_dim[0] * grid_dim[1] * grid_dim[2] == 0 {
+                return Ok(());
+            }
+        }
+
+        // NOTE: Slang’s DXIL output reads the entry-point parameters passed by value from a
+        //       constant buffer, so we upload them into a new buffer for each dispatch.
+        let mut value_buffer = None;
+        if let Some(binding) = self.value_buffer.filter(|_| !self.values.is_empty()) {
+            let param_id = self
+                .function
+                .resources
+                .iter()
+                .position(|res| {
+                    res.kind == Dx12ResourceKind::ConstantBuffer
+                        && res.space == binding.space
+                        && res.register == binding.index
+                })
+                .ok_or_else(|| ShaderArgsError::ArgNotFound("entry-point values".to_owned()))?;
+            // Constant buffer sizes must be multiples of 256 bytes.
+            let size = (self.values.len() as u64).next_multiple_of(256);
+            let buffer = self.backend.create_buffer(size, BufferUsages::MAP_WRITE)?;
+            self.backend.write_bytes(&buffer, 0, &self.values)?;
+            let address = unsafe { buffer.resource.GetGPUVirtualAddress() };
+            self.pass.retained.lock().unwrap().push(buffer);
+            value_buffer = Some((param_id as u32, address));
+        }
+
+        let mut states = self.pass.states.lock().unwrap();
+        for (param_id, slice) in &self.args {
+            let kind = self.function.resources[*param_id].kind;
+            states.transition(list, slice.buffer, kind.resource_state());
+        }
+        if let DispatchGrid::Indirect(grid_indirect, _) = grid {
+            states.transition(list, grid_indirect, D3D12_RESOURCE_STATE_INDIRECT_ARGUMENT);
+        }
+
+        unsafe {
+            list.SetComputeRootSignature(&self.function.root_signature.0);
+            list.SetPipelineState(&self.function.pipeline.0);
+
+            for (param_id, slice) in &self.args {
+                let address = slice.buffer.resource.GetGPUVirtualAddress() + slice.offset;
+                let param_id = *param_id as u32;
+                match self.function.resources[param_id as usize].kind {
+                    Dx12ResourceKind::ConstantBuffer => {
+                        list.SetComputeRootConstantBufferView(param_id, address)
+                    }
+                    Dx12ResourceKind::ShaderResource => {
+                        list.SetComputeRootShaderResourceView(param_id, address)
+                    }
+                    Dx12ResourceKind::UnorderedAccess => {
+                        list.SetComputeRootUnorderedAccessView(param_id, address)
+                    }
+                }
+            }
+            if let Some((param_id, address)) = value_buffer {
+                list.SetComputeRootConstantBufferView(param_id, address);
+            }
+
+            if !self.label.is_empty() {
+                begin_event(list, self.label);
+            }
+            match grid {
+                DispatchGrid::Direct(grid_dim) => {
+                    list.Dispatch(grid_dim[0], grid_dim[1], grid_dim[2]);
+                }
+                DispatchGrid::Indirect(grid_indirect, offset) => {
+                    list.ExecuteIndirect(
+                        &self.backend.dispatch_indirect_signature.0,
+                        1,
+                        &grid_indirect.resource.0,
+                        (offset * size_of::<[u32; 3]>()) as u64,
+                        None,
+                        0,
+                    );
+                }
+            }
+            if !self.label.is_empty() {
+                list.EndEvent();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b> ShaderArgs<'b, Dx12> for Dx12Buffer {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut Dx12Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        let slice = Dx12BufferSlice {
+            buffer: self,
+            offset: 0,
+        };
+        dispatch.push_arg(binding, name, slice)
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: None,
+            size_bytes: Some(self.size as usize),
+            writable: None,
+        })
+    }
+}
+
+impl<'b> ShaderArgs<'b, Dx12> for Dx12BufferSlice<'_> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut Dx12Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.push_arg(binding, name, *self)
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: None,
+            size_bytes: Some((self.buffer.size - self.offset) as usize),
+            writable: None,
+        })
+    }
+}
+
+impl<T: DeviceValue> crate::backend::Buffer<Dx12, T> for Dx12Buffer {
+    fn len(&self) -> usize {
+        self.size as usize / size_of::<T>()
+    }
+
+    fn slice(&self, range: impl RangeBounds<usize>) -> Dx12BufferSlice<'_> {
+        let sz = size_of::<T>() as u64;
+        // NOTE: root descriptors don’t have a size so only the start of the range matters.
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(i) => *i as u64 * sz,
+            std::ops::Bound::Excluded(i) => (*i as u64 + 1) * sz,
+            std::ops::Bound::Unbounded => 0,
+        };
+        Dx12BufferSlice {
+            buffer: self,
+            offset: start,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.size as usize
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<Dx12BufferSlice<'_>, BufferCastError> {
+        let range = view_bytes::<U>(self.size as usize, byte_offset, len)?;
+        Ok(Dx12BufferSlice {
+            buffer: self,
+            offset: range.start as u64,
+        })
+    }
+}
+
+/// Opens a PIX event labeled with `label`, closed by the next `EndEvent` on `list`.
+fn begin_event(list: &ID3D12GraphicsCommandList, label: &str) {
+    // NOTE: a metadata of 0 means that the event data is a nul-terminated UTF-16 string, which
+    //       is what PIX expects from applications that don’t use WinPixEventRuntime.
+    let label: Vec<u16> = label.encode_utf16().chain(Some(0)).collect();
+    unsafe {
+        list.BeginEvent(0, Some(label.as_ptr().cast()), (label.len() * 2) as u32);
+    }
+}
+
+unsafe fn blob_bytes(blob: &ID3DBlob) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize())
+    }
+}
+
+/// Queries the feature support data `T` associated to `feature`.
+///
+/// # Safety
+///
+/// `T` must be the support data structure matching `feature`.
+unsafe fn check_feature_support<T>(
+    device: &ID3D12Device,
+    feature: D3D12_FEATURE,
+    data: &mut T,
+) -> windows::core::Result<()> {
+    unsafe { device.CheckFeatureSupport(feature, (data as *mut T).cast(), size_of::<T>() as u32) }
+}
@@ -0,0 +1,1235 @@
+use crate::ShaderArgs;
+use crate::backend::{
+    Backend, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid, EncaseType,
+    Encoder, Fence, SamplerDescriptor, ShaderBinding, SubmissionFuture, TextureDataError,
+    UnsupportedBundle, UnsupportedError, UnsupportedTimestamps, buffer_range, check_texture_data,
+    elements_len, repeat_bytes, texture_bytes_len, uniform_bytes, view_bytes,
+};
+use crate::shader::{ArgInfo, ShaderArgsError};
+use bytemuck::Pod;
+use encase::{ShaderType, StorageBuffer};
+use minislang::shader_slang;
+use std::marker::PhantomData;
+use std::ops::{Bound, Range, RangeBounds};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use wgpu::{BufferUsages, TextureFormat, TextureUsages};
+
+/// Unique identifier of a buffer created by the [`Mock`] backend.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MockBufferId(pub usize);
+
+/// Unique identifier of a texture created by the [`Mock`] backend.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MockTextureId(pub usize);
+
+/// Unique identifier of a sampler created by the [`Mock`] backend.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MockSamplerId(pub usize);
+
+/// Unique identifier of a fence created by the [`Mock`] backend.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MockFenceId(pub usize);
+
+/// A shader argument bound to a dispatch recorded by the [`Mock`] backend.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MockArg {
+    /// The argument’s name, as declared in the shader.
+    pub name: String,
+    /// The argument’s binding.
+    pub binding: ShaderBinding,
+    /// The resource bound to this argument.
+    pub resource: MockResource,
+}
+
+/// A resource bound to a dispatch recorded by the [`Mock`] backend.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MockResource {
+    Buffer {
+        id: MockBufferId,
+        /// The byte range of the buffer bound to the argument.
+        range: Range<usize>,
+    },
+    Texture(MockTextureId),
+    Sampler(MockSamplerId),
+    /// The bytes of an argument passed by value.
+    Value(Vec<u8>),
+}
+
+/// The dispatch grid of a dispatch recorded by the [`Mock`] backend.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MockGrid {
+    /// Dispatch with a workgroup count known on the host.
+    Direct([u32; 3]),
+    /// Dispatch with a workgroup count read from a buffer, at the given element offset.
+    Indirect(MockBufferId, usize),
+}
+
+/// An operation recorded by the [`Mock`] backend.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MockEvent {
+    LoadModule {
+        /// Size of the module’s compiled code, in bytes.
+        size: usize,
+    },
+    LoadFunction {
+        entry_point: String,
+    },
+    CreateBuffer {
+        id: MockBufferId,
+        /// Number of elements in the buffer.
+        len: usize,
+        usage: BufferUsages,
+        /// Whether the buffer was created with initial data.
+        initialized: bool,
+    },
+    WriteBuffer {
+        id: MockBufferId,
+        /// Index of the first element written.
+        offset: usize,
+        /// Number of elements written.
+        len: usize,
+    },
+    ReadBuffer {
+        id: MockBufferId,
+        /// Index of the first element read.
+        offset: usize,
+        /// Number of elements read.
+        len: usize,
+    },
+    FillBuffer {
+        id: MockBufferId,
+        /// The bytes of the value the elements were set to.
+        value: Vec<u8>,
+        /// The range of elements filled.
+        range: Range<usize>,
+    },
+    CopyBuffer {
+        source: MockBufferId,
+        source_offset: usize,
+        target: MockBufferId,
+        target_offset: usize,
+        /// Number of elements copied.
+        len: usize,
+    },
+    CreateTexture {
+        id: MockTextureId,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+        /// Whether the texture was created with initial data.
+        initialized: bool,
+    },
+    WriteTexture {
+        id: MockTextureId,
+    },
+    ReadTexture {
+        id: MockTextureId,
+    },
+    CreateSampler {
+        id: MockSamplerId,
+        desc: SamplerDescriptor,
+    },
+    Dispatch {
+        entry_point: String,
+        /// The label given to [`Backend::begin_dispatch`].
+        label: String,
+        args: Vec<MockArg>,
+        grid: MockGrid,
+        block_dim: [u32; 3],
+    },
+    SignalFence {
+        id: MockFenceId,
+    },
+    WaitFence {
+        id: MockFenceId,
+    },
+    PushDebugGroup {
+        label: String,
+    },
+    PopDebugGroup,
+    Submit,
+    Synchronize,
+}
+
+/// A backend recording every operation instead of running them on actual hardware.
+///
+/// Buffers are stored in host memory so they can be written and read back (as well as copied),
+/// but kernels are never executed: dispatches are only recorded, along with their bound arguments
+/// and grid sizes. This is useful for unit-testing the `Shader`/`ShaderArgs` wiring of an
+/// application without a GPU. The recorded operations can be inspected with [`Mock::events`].
+///
+/// Note that shaders still need to be compiled by Slang since the argument names are obtained
+/// from its reflection data.
+#[derive(Clone, Default)]
+pub struct Mock {
+    events: Arc<Mutex<Vec<MockEvent>>>,
+    next_buffer_id: Arc<AtomicUsize>,
+    next_texture_id: Arc<AtomicUsize>,
+    next_sampler_id: Arc<AtomicUsize>,
+    next_fence_id: Arc<AtomicUsize>,
+}
+
+impl Mock {
+    /// Initializes a mock backend with an empty event log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All the events recorded so far.
+    pub fn events(&self) -> Vec<MockEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Returns all the events recorded so far and clears the event log.
+    pub fn take_events(&self) -> Vec<MockEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    /// All the dispatches recorded so far.
+    pub fn dispatches(&self) -> Vec<MockEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| matches!(e, MockEvent::Dispatch { .. }))
+            .cloned()
+            .collect()
+    }
+
+    /// Clears the event log.
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, event: MockEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn create_buffer<T>(
+        &self,
+        bytes: Vec<u8>,
+        stride: usize,
+        usage: BufferUsages,
+        initialized: bool,
+    ) -> MockBuffer<T> {
+        let id = MockBufferId(self.next_buffer_id.fetch_add(1, Ordering::Relaxed));
+        let buffer = MockBuffer {
+            id,
+            bytes,
+            stride,
+            _phantom: PhantomData,
+        };
+        self.record(MockEvent::CreateBuffer {
+            id,
+            len: buffer.len(),
+            usage,
+            initialized,
+        });
+        buffer
+    }
+
+    fn create_texture(
+        &self,
+        bytes: Vec<u8>,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+        initialized: bool,
+    ) -> MockTexture {
+        let id = MockTextureId(self.next_texture_id.fetch_add(1, Ordering::Relaxed));
+        self.record(MockEvent::CreateTexture {
+            id,
+            size,
+            format,
+            usage,
+            initialized,
+        });
+        MockTexture {
+            id,
+            bytes,
+            size,
+            format,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MockBackendError {
+    #[error(transparent)]
+    ShaderArg(#[from] ShaderArgsError),
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
+    BytemuckPod(#[from] bytemuck::PodCastError),
+    #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error(transparent)]
+    TextureData(#[from] TextureDataError),
+    #[error(
+        "the write of {len} bytes at byte {start} is out of the bounds of the buffer ({size} bytes)"
+    )]
+    OutOfBounds {
+        start: usize,
+        len: usize,
+        size: usize,
+    },
+}
+
+/// A buffer created by the [`Mock`] backend, stored in host memory.
+pub struct MockBuffer<T> {
+    id: MockBufferId,
+    bytes: Vec<u8>,
+    stride: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> MockBuffer<T> {
+    /// The identifier of this buffer, as referenced by the recorded [`MockEvent`]s.
+    pub fn id(&self) -> MockBufferId {
+        self.id
+    }
+
+    /// The content of this buffer, as bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len() / self.stride.max(1)
+    }
+
+    /// The `len` bytes of this buffer starting at `start`, or an error if they are out of bounds.
+    fn bytes_mut(&mut self, start: usize, len: usize) -> Result<&mut [u8], MockBackendError> {
+        let size = self.bytes.len();
+        match start.checked_add(len) {
+            Some(end) if end <= size => Ok(&mut self.bytes[start..end]),
+            _ => Err(MockBackendError::OutOfBounds { start, len, size }),
+        }
+    }
+}
+
+/// A 2D texture created by the [`Mock`] backend, stored in host memory.
+pub struct MockTexture {
+    id: MockTextureId,
+    bytes: Vec<u8>,
+    size: [u32; 2],
+    format: TextureFormat,
+}
+
+impl MockTexture {
+    /// The identifier of this texture, as referenced by the recorded [`MockEvent`]s.
+    pub fn id(&self) -> MockTextureId {
+        self.id
+    }
+
+    /// The content of this texture, as tightly packed bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// A texture sampler created by the [`Mock`] backend.
+pub struct MockSampler {
+    id: MockSamplerId,
+    desc: SamplerDescriptor,
+}
+
+impl MockSampler {
+    /// The identifier of this sampler, as referenced by the recorded [`MockEvent`]s.
+    pub fn id(&self) -> MockSamplerId {
+        self.id
+    }
+}
+
+/// A fence created by the [`Mock`] backend.
+///
+/// Nothing is executed asynchronously so the fence is always signaled. Signals and waits are
+/// recorded as [`MockEvent`]s.
+pub struct MockFence {
+    id: MockFenceId,
+}
+
+impl MockFence {
+    /// The identifier of this fence, as referenced by the recorded [`MockEvent`]s.
+    pub fn id(&self) -> MockFenceId {
+        self.id
+    }
+}
+
+impl Fence<Mock> for MockFence {
+    fn is_signaled(&self) -> bool {
+        true
+    }
+}
+
+/// A range of elements of a [`MockBuffer`], possibly reinterpreted as another type.
+pub struct MockBufferSlice<'b, T> {
+    id: MockBufferId,
+    // The byte range of the buffer.
+    range: Range<usize>,
+    _phantom: PhantomData<&'b T>,
+}
+
+/// A module "loaded" by the [`Mock`] backend.
+#[derive(Clone)]
+pub struct MockModule;
+
+/// A function "loaded" by the [`Mock`] backend.
+pub struct MockFunction {
+    entry_point: String,
+}
+
+impl MockFunction {
+    /// The name of the entry-point this function was loaded from.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+}
+
+pub struct MockEncoder {
+    backend: Mock,
+}
+
+pub struct MockPass;
+
+#[async_trait::async_trait]
+impl Backend for Mock {
+    const NAME: &'static str = "mock";
+    const TARGET: shader_slang::CompileTarget = shader_slang::CompileTarget::Wgsl;
+
+    type Error = MockBackendError;
+    type Buffer<T: DeviceValue> = MockBuffer<T>;
+    type Texture = MockTexture;
+    type Sampler = MockSampler;
+    type Timestamps = UnsupportedTimestamps;
+    type Bundle = UnsupportedBundle;
+    type Fence = MockFence;
+    type BufferSlice<'b, T: DeviceValue> = MockBufferSlice<'b, T>;
+    type Encoder = MockEncoder;
+    type Pass = MockPass;
+    type Module = MockModule;
+    type Function = MockFunction;
+    type Dispatch<'a> = MockDispatch<'a>;
+
+    fn as_mock(&self) -> Option<&Mock> {
+        Some(self)
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        Ok(DeviceLimits::default())
+    }
+
+    /*
+     * Module/function loading.
+     */
+    fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
+        self.record(MockEvent::LoadModule { size: bytes.len() });
+        Ok(MockModule)
+    }
+
+    fn load_function(
+        &self,
+        _module: &Self::Module,
+        entry_point: &str,
+    ) -> Result<Self::Function, Self::Error> {
+        self.record(MockEvent::LoadFunction {
+            entry_point: entry_point.to_owned(),
+        });
+        Ok(MockFunction {
+            entry_point: entry_point.to_owned(),
+        })
+    }
+
+    /*
+     * Kernel dispatch.
+     */
+    fn begin_encoding(&self) -> Self::Encoder {
+        MockEncoder {
+            backend: self.clone(),
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        // NOTE: the new queue records into the same event log.
+        Ok(self.clone())
+    }
+
+    fn begin_dispatch<'a>(
+        &'a self,
+        _pass: &'a mut Self::Pass,
+        function: &'a Self::Function,
+        label: &'a str,
+    ) -> Self::Dispatch<'a> {
+        MockDispatch {
+            backend: self,
+            function,
+            label,
+            args: vec![],
+        }
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        self.record(MockEvent::Synchronize);
+        Ok(())
+    }
+
+    fn submit(&self, _encoder: Self::Encoder) -> Result<(), Self::Error> {
+        self.record(MockEvent::Submit);
+        Ok(())
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        self.submit(encoder)?;
+        Ok(SubmissionFuture::ready())
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        let id = MockFenceId(self.next_fence_id.fetch_add(1, Ordering::Relaxed));
+        Ok(MockFence { id })
+    }
+
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        self.record(MockEvent::SignalFence { id: fence.id });
+        Ok(())
+    }
+
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error> {
+        self.record(MockEvent::WaitFence { id: fence.id });
+        Ok(())
+    }
+
+    /*
+     * Buffer handling.
+     */
+    fn init_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes = bytemuck::try_cast_slice(data)?.to_vec();
+        Ok(self.create_buffer(bytes, size_of::<T>(), usage, true))
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        elements_len::<T>(bytes.len())?;
+        Ok(self.create_buffer(bytes.to_vec(), size_of::<T>(), usage, true))
+    }
+
+    fn init_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let mut bytes = vec![];
+        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
+        bytes_buffer.write(data).unwrap();
+        Ok(self.create_buffer(bytes, T::min_size().get() as usize, usage, true))
+    }
+
+    unsafe fn uninit_buffer<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes = vec![0; len * size_of::<T>()];
+        Ok(self.create_buffer(bytes, size_of::<T>(), usage, false))
+    }
+
+    unsafe fn uninit_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let stride = T::min_size().get() as usize;
+        Ok(self.create_buffer(vec![0; len * stride], stride, usage, false))
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_at(buffer, 0, data)
+    }
+
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let start = offset.saturating_mul(buffer.stride);
+        buffer.bytes_mut(start, bytes.len())?.copy_from_slice(bytes);
+        self.record(MockEvent::WriteBuffer {
+            id: buffer.id,
+            offset,
+            len: data.len(),
+        });
+        Ok(())
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let mut bytes = vec![];
+        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
+        bytes_buffer.write(data).unwrap();
+        let start = offset.saturating_mul(buffer.stride);
+        buffer
+            .bytes_mut(start, bytes.len())?
+            .copy_from_slice(&bytes);
+        self.record(MockEvent::WriteBuffer {
+            id: buffer.id,
+            offset,
+            len: data.len(),
+        });
+        Ok(())
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        write(buffer.bytes_mut(byte_offset, len)?);
+        self.record(MockEvent::WriteBuffer {
+            id: buffer.id,
+            offset: byte_offset / buffer.stride,
+            len: len.div_ceil(buffer.stride),
+        });
+        Ok(())
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(data)?;
+        let len = out.len().min(buffer.bytes.len());
+        out[..len].copy_from_slice(&buffer.bytes[..len]);
+        self.record(MockEvent::ReadBuffer {
+            id: buffer.id,
+            offset: 0,
+            len: len / size_of::<T>().max(1),
+        });
+        Ok(())
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let mut result = vec![];
+        let encase_buffer = StorageBuffer::new(&buffer.bytes);
+        encase_buffer.read(&mut result).unwrap(); // TODO: propagate error
+        let len = result.len().min(data.len());
+        data[..len].copy_from_slice(&result[..len]);
+        self.record(MockEvent::ReadBuffer {
+            id: buffer.id,
+            offset: 0,
+            len,
+        });
+        Ok(())
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_buffer(buffer, data).await
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, buffer.len());
+        let sz = size_of::<T>();
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        out.copy_from_slice(&buffer.bytes[range.start * sz..range.end * sz]);
+        self.record(MockEvent::ReadBuffer {
+            id: buffer.id,
+            offset: range.start,
+            len: range.len(),
+        });
+        Ok(())
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        self.read_buffer_range(buffer, range, out).await
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, buffer.len());
+        let sz = size_of::<T>();
+        buffer.bytes[range.start * sz..range.end * sz]
+            .copy_from_slice(&repeat_bytes(&value, range.len()));
+        self.record(MockEvent::FillBuffer {
+            id: buffer.id,
+            value: bytemuck::bytes_of(&value).to_vec(),
+            range,
+        });
+        Ok(())
+    }
+
+    /*
+     * Uniform handling.
+     */
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes = uniform_bytes(value);
+        let stride = bytes.len();
+        let usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        Ok(self.create_buffer(bytes, stride, usage, true))
+    }
+
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let bytes = uniform_bytes(value);
+        buffer.bytes[..bytes.len()].copy_from_slice(&bytes);
+        self.record(MockEvent::WriteBuffer {
+            id: buffer.id,
+            offset: 0,
+            len: 1,
+        });
+        Ok(())
+    }
+
+    /*
+     * Texture handling.
+     */
+    fn init_texture<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let len = texture_bytes_len::<Self>(size, format)?;
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        check_texture_data(bytes.len(), len)?;
+        let bytes = bytes[..len].to_vec();
+        Ok(self.create_texture(bytes, size, format, usage, true))
+    }
+
+    unsafe fn uninit_texture(
+        &self,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let bytes = vec![0; texture_bytes_len::<Self>(size, format)?];
+        Ok(self.create_texture(bytes, size, format, usage, false))
+    }
+
+    fn write_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &mut Self::Texture,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let len = texture.bytes.len();
+        check_texture_data(bytes.len(), len)?;
+        texture.bytes.copy_from_slice(&bytes[..len]);
+        self.record(MockEvent::WriteTexture { id: texture.id });
+        Ok(())
+    }
+
+    async fn read_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &Self::Texture,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(data)?;
+        let len = out.len().min(texture.bytes.len());
+        out[..len].copy_from_slice(&texture.bytes[..len]);
+        self.record(MockEvent::ReadTexture { id: texture.id });
+        Ok(())
+    }
+
+    fn create_sampler(&self, desc: &SamplerDescriptor) -> Result<Self::Sampler, Self::Error> {
+        let id = MockSamplerId(self.next_sampler_id.fetch_add(1, Ordering::Relaxed));
+        self.record(MockEvent::CreateSampler { id, desc: *desc });
+        Ok(MockSampler { id, desc: *desc })
+    }
+}
+
+impl MockEncoder {
+    fn copy_elements<T>(
+        &mut self,
+        source: &MockBuffer<T>,
+        source_offset: usize,
+        target: &mut MockBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) {
+        let sz = source.stride;
+        target.bytes[target_offset * sz..(target_offset + copy_len) * sz]
+            .copy_from_slice(&source.bytes[source_offset * sz..(source_offset + copy_len) * sz]);
+        self.backend.record(MockEvent::CopyBuffer {
+            source: source.id,
+            source_offset,
+            target: target.id,
+            target_offset,
+            len: copy_len,
+        });
+    }
+}
+
+impl Encoder<Mock> for MockEncoder {
+    fn begin_pass(&mut self, _label: &str) -> MockPass {
+        MockPass
+    }
+
+    fn push_debug_group(&mut self, label: &str) {
+        self.backend.record(MockEvent::PushDebugGroup {
+            label: label.to_string(),
+        });
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.backend.record(MockEvent::PopDebugGroup);
+    }
+
+    fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
+        &mut self,
+        source: &MockBuffer<T>,
+        source_offset: usize,
+        target: &mut MockBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), MockBackendError> {
+        self.copy_elements(source, source_offset, target, target_offset, copy_len);
+        Ok(())
+    }
+
+    fn copy_buffer_to_buffer_encased<T: DeviceValue + ShaderType>(
+        &mut self,
+        source: &MockBuffer<T>,
+        source_offset: usize,
+        target: &mut MockBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), MockBackendError> {
+        self.copy_elements(source, source_offset, target, target_offset, copy_len);
+        Ok(())
+    }
+}
+
+pub struct MockDispatch<'a> {
+    backend: &'a Mock,
+    function: &'a MockFunction,
+    label: &'a str,
+    args: Vec<MockArg>,
+}
+
+impl<'a> Dispatch<'a, Mock> for MockDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        self.args.push(MockArg {
+            name: name.to_owned(),
+            binding,
+            resource: MockResource::Value(bytemuck::bytes_of(value).to_vec()),
+        });
+        Ok(())
+    }
+
+    fn launch<'b>(
+        self,
+        grid: impl Into<DispatchGrid<'b, Mock>>,
+        block_dim: [u32; 3],
+    ) -> Result<(), MockBackendError> {
+        let grid = match grid.into() {
+            DispatchGrid::Direct(grid) => MockGrid::Direct(grid),
+            DispatchGrid::Indirect(grid, offset) => MockGrid::Indirect(grid.id, offset),
+        };
+        self.backend.record(MockEvent::Dispatch {
+            entry_point: self.function.entry_point.clone(),
+            label: self.label.to_string(),
+            args: self.args,
+            grid,
+            block_dim,
+        });
+        Ok(())
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Mock> for MockBuffer<T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut MockDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.args.push(MockArg {
+            name: name.to_owned(),
+            binding,
+            resource: MockResource::Buffer {
+                id: self.id,
+                range: 0..self.bytes.len(),
+            },
+        });
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(self.stride),
+            size_bytes: Some(self.bytes.len()),
+            writable: None,
+        })
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Mock> for MockBufferSlice<'_, T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut MockDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.args.push(MockArg {
+            name: name.to_owned(),
+            binding,
+            resource: MockResource::Buffer {
+                id: self.id,
+                range: self.range.clone(),
+            },
+        });
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(size_of::<T>()),
+            size_bytes: Some(self.range.len()),
+            writable: None,
+        })
+    }
+}
+
+impl<'b> ShaderArgs<'b, Mock> for MockTexture {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut MockDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.args.push(MockArg {
+            name: name.to_owned(),
+            binding,
+            resource: MockResource::Texture(self.id),
+        });
+        Ok(())
+    }
+}
+
+impl<'b> ShaderArgs<'b, Mock> for MockSampler {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut MockDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.args.push(MockArg {
+            name: name.to_owned(),
+            binding,
+            resource: MockResource::Sampler(self.id),
+        });
+        Ok(())
+    }
+}
+
+impl crate::backend::Sampler<Mock> for MockSampler {
+    fn descriptor(&self) -> SamplerDescriptor {
+        self.desc
+    }
+}
+
+impl crate::backend::Texture<Mock> for MockTexture {
+    fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.format
+    }
+}
+
+impl<T: DeviceValue> crate::backend::Buffer<Mock, T> for MockBuffer<T> {
+    fn len(&self) -> usize {
+        MockBuffer::len(self)
+    }
+
+    fn slice(&self, range: impl RangeBounds<usize>) -> MockBufferSlice<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(i) => *i,
+            Bound::Excluded(i) => *i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(i) => *i + 1,
+            Bound::Excluded(i) => *i,
+            Bound::Unbounded => MockBuffer::len(self),
+        };
+        MockBufferSlice {
+            id: self.id,
+            range: start * self.stride..end * self.stride,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<MockBufferSlice<'_, U>, BufferCastError> {
+        Ok(MockBufferSlice {
+            id: self.id,
+            range: view_bytes::<U>(self.bytes.len(), byte_offset, len)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Buffer;
+
+    #[test]
+    fn buffers_are_written_and_read_back() {
+        let backend = Mock::new();
+        let mut buffer = backend
+            .init_buffer(&[1u32, 2, 3, 4], BufferUsages::STORAGE)
+            .unwrap();
+        backend.write_buffer_at(&mut buffer, 2, &[30, 40]).unwrap();
+        backend.fill_buffer(&mut buffer, 10, ..1).unwrap();
+
+        let result = futures::executor::block_on(backend.slow_read_vec(&buffer)).unwrap();
+        assert_eq!(result, [10, 2, 30, 40]);
+        let id = buffer.id();
+        assert_eq!(
+            backend.take_events(),
+            [
+                MockEvent::CreateBuffer {
+                    id,
+                    len: 4,
+                    usage: BufferUsages::STORAGE,
+                    initialized: true,
+                },
+                MockEvent::WriteBuffer {
+                    id,
+                    offset: 2,
+                    len: 2,
+                },
+                MockEvent::FillBuffer {
+                    id,
+                    value: 10u32.to_ne_bytes().to_vec(),
+                    range: 0..1,
+                },
+                MockEvent::ReadBuffer {
+                    id,
+                    offset: 0,
+                    len: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_writes_fail() {
+        let backend = Mock::new();
+        let mut buffer = backend
+            .init_buffer(&[0u32; 4], BufferUsages::STORAGE)
+            .unwrap();
+        backend.clear();
+
+        let result = backend.write_buffer_at(&mut buffer, 3, &[1, 2]);
+        assert!(matches!(
+            result,
+            Err(MockBackendError::OutOfBounds {
+                start: 12,
+                len: 8,
+                size: 16,
+            })
+        ));
+        let result = backend.write_buffer_at(&mut buffer, usize::MAX, &[1]);
+        assert!(matches!(result, Err(MockBackendError::OutOfBounds { .. })));
+        let result = backend.write_buffer_with(&mut buffer, 8, 12, |_| unreachable!());
+        assert!(matches!(result, Err(MockBackendError::OutOfBounds { .. })));
+        assert_eq!(buffer.bytes(), [0; 16]);
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn mismatched_buffer_bytes_fail() {
+        let backend = Mock::new();
+        let result = backend.init_buffer_bytes::<u32>(&[0; 6], BufferUsages::STORAGE);
+        assert!(matches!(
+            result,
+            Err(MockBackendError::BufferCast(
+                BufferCastError::SizeMismatch {
+                    size: 6,
+                    elt_size: 4,
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn overflowing_fill_ranges_are_rejected() {
+        let backend = Mock::new();
+        let mut buffer = backend
+            .init_buffer(&[0u32; 4], BufferUsages::STORAGE)
+            .unwrap();
+        let _ = backend.fill_buffer(&mut buffer, 1, ..=usize::MAX);
+    }
+
+    #[test]
+    fn short_texture_data_fails() {
+        let backend = Mock::new();
+        let result = backend.init_texture(
+            &[0u8; 12],
+            [2, 2],
+            TextureFormat::Rgba8Unorm,
+            TextureUsages::TEXTURE_BINDING,
+        );
+        assert!(matches!(
+            result,
+            Err(MockBackendError::TextureData(TextureDataError {
+                len: 12,
+                texture_len: 16,
+            }))
+        ));
+
+        let mut texture = backend
+            .init_texture(
+                &[0u8; 16],
+                [2, 2],
+                TextureFormat::Rgba8Unorm,
+                TextureUsages::TEXTURE_BINDING,
+            )
+            .unwrap();
+        let result = backend.write_texture(&mut texture, &[1u8; 8]);
+        assert!(matches!(result, Err(MockBackendError::TextureData(_))));
+    }
+
+    #[test]
+    fn overflowing_views_fail() {
+        let backend = Mock::new();
+        let buffer = backend
+            .init_buffer(&[0u32; 4], BufferUsages::STORAGE)
+            .unwrap();
+
+        assert!(buffer.reinterpret::<u32>(4, 3).is_ok());
+        assert_eq!(
+            buffer.reinterpret::<u32>(4, usize::MAX / 2).err(),
+            Some(BufferCastError::OutOfBounds {
+                start: 4,
+                end: usize::MAX,
+                size: 16,
+            })
+        );
+        assert!(matches!(
+            buffer.reinterpret::<u8>(usize::MAX, 1),
+            Err(BufferCastError::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn copies_are_recorded() {
+        let backend = Mock::new();
+        let source = backend
+            .init_buffer(&[1u32, 2, 3], BufferUsages::STORAGE)
+            .unwrap();
+        let mut target = backend
+            .init_buffer(&[0u32; 4], BufferUsages::STORAGE)
+            .unwrap();
+        backend.clear();
+
+        let mut encoder = backend.begin_encoding();
+        encoder
+            .copy_buffer_to_buffer(&source, 1, &mut target, 2, 2)
+            .unwrap();
+        backend.submit(encoder).unwrap();
+
+        assert_eq!(
+            target.bytes(),
+            bytemuck::cast_slice::<u32, u8>(&[0, 0, 2, 3])
+        );
+        assert_eq!(
+            backend.events(),
+            [
+                MockEvent::CopyBuffer {
+                    source: source.id(),
+                    source_offset: 1,
+                    target: target.id(),
+                    target_offset: 2,
+                    len: 2,
+                },
+                MockEvent::Submit,
+            ]
+        );
+    }
+}
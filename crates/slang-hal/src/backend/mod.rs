@@ -1,20 +1,48 @@
 use crate::ShaderArgs;
+use crate::layout::StructLayout;
+use crate::profiler::ProfilingReport;
 use crate::shader::ShaderArgsError;
 use bytemuck::Pod;
 use encase::internal::{CreateFrom, WriteInto};
 use encase::private::ReadFrom;
-use encase::{ShaderSize, ShaderType};
+use encase::{ShaderSize, ShaderType, UniformBuffer};
+use futures::future::BoxFuture;
+use minislang::SlangError;
 use minislang::shader_slang::CompileTarget;
+use nalgebra::{Point, Quaternion, SMatrix, Scalar};
 use std::error::Error;
-use std::ops::RangeBounds;
-use wgpu::BufferUsages;
+use std::ops::{Bound, Range, RangeBounds};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wgpu::{AddressMode, BufferUsages, FilterMode, TextureFormat, TextureUsages};
 
+pub use any::{
+    AnyBackend, AnyBackendError, AnyBuffer, AnyBufferSlice, AnyBundle, AnyDispatch, AnyEncoder,
+    AnyFunction, AnyModule, AnyPass, AnySampler, AnyTexture, BackendSelectionError,
+};
+#[cfg(feature = "cpu")]
+pub use cpu::Cpu;
 #[cfg(feature = "cuda")]
-pub use cuda::Cuda;
-pub use webgpu::WebGpu;
+pub use cuda::{Cuda, CudaDispatch, CudaManagedBuffer, CudaModuleOptions, NvrtcOptions, PinnedVec};
+#[cfg(all(feature = "dx12", target_os = "windows"))]
+pub use dx12::Dx12;
+pub use mock::{
+    Mock, MockArg, MockBufferId, MockEvent, MockGrid, MockResource, MockSamplerId, MockTextureId,
+};
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+pub use remote::{Remote, RemoteServer};
+pub use webgpu::{WebGpu, WebGpuOptions};
 
+mod any;
+#[cfg(feature = "cpu")]
+mod cpu;
 #[cfg(feature = "cuda")]
 mod cuda;
+#[cfg(all(feature = "dx12", target_os = "windows"))]
+mod dx12;
+mod mock;
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+mod remote;
 mod webgpu;
 
 // TODO: define our own buffer usages if we want to make wgpu optional.
@@ -28,26 +56,115 @@ pub struct ShaderBinding {
     pub index: u32,
 }
 
+impl From<minislang::reflection::Binding> for ShaderBinding {
+    fn from(binding: minislang::reflection::Binding) -> Self {
+        Self {
+            space: binding.space,
+            index: binding.index,
+        }
+    }
+}
+
+/// The layout of a resource parameter of a kernel, as reflected by Slang.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BindingLayout {
+    /// The binding of the parameter.
+    pub binding: ShaderBinding,
+    /// The type of resource bound to the parameter, or `None` if it can’t be determined from
+    /// the reflection data alone (e.g. storage textures, whose format isn’t reflected).
+    pub ty: Option<wgpu::BindingType>,
+}
+
+/// Options for loading a function with [`Backend::load_function_with_options`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct FunctionOptions<'a> {
+    /// The layout of the function’s resource parameters, as reflected by Slang.
+    pub bindings: &'a [BindingLayout],
+    /// The binding of the constant buffer holding the parameters passed by value, if any. It is
+    /// also part of [`Self::bindings`].
+    pub value_buffer: Option<ShaderBinding>,
+    /// Values of the pipeline-overridable constants (WGSL `override` declarations), keyed by name
+    /// or by numeric id.
+    ///
+    /// These are set when creating the pipeline, without recompiling the module.
+    pub constants: &'a [(&'a str, f64)],
+}
+
 /// A value that can be sent to the GPU.
 ///
 /// # Safety
 ///
 /// The value must comply to the safety requirements of all the backends it is implemented for.
-pub unsafe trait DeviceValue: 'static + Clone + Copy + Send + Sync {}
+///
+/// Structs should implement this trait with `#[derive(DeviceValue)]` (with the `derive` feature),
+/// which checks that they are `#[repr(C)]` and that all their fields implement `DeviceValue`.
+pub unsafe trait DeviceValue: 'static + Clone + Copy + Send + Sync {
+    /// The layout of the fields of this type, if it is a struct deriving `DeviceValue`.
+    ///
+    /// See [`check_layout`](crate::layout::check_layout) for comparing it with a Slang struct.
+    fn struct_layout() -> Option<StructLayout> {
+        None
+    }
+}
+
+macro_rules! impl_device_value {
+    ($($t: ty),*) => {$(
+        // SAFETY: scalars have the same representation on the host and on all the backends.
+        unsafe impl DeviceValue for $t {}
+    )*};
+}
+
+impl_device_value!(
+    u8,
+    u16,
+    u32,
+    u64,
+    i8,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    half::f16,
+    half::bf16
+);
+
+// SAFETY: arrays, vectors, matrices, and points are laid out contiguously like Slang arrays.
+unsafe impl<T: DeviceValue, const N: usize> DeviceValue for [T; N] {}
+unsafe impl<T: DeviceValue + Scalar, const R: usize, const C: usize> DeviceValue
+    for SMatrix<T, R, C>
+{
+}
+unsafe impl<T: DeviceValue + Scalar, const D: usize> DeviceValue for Point<T, D> {}
+unsafe impl<T: DeviceValue + Scalar> DeviceValue for Quaternion<T> {}
 
 pub trait EncaseType: ShaderType + ShaderSize + WriteInto + CreateFrom + ReadFrom {}
 impl<T: ShaderType + ShaderSize + WriteInto + CreateFrom + ReadFrom> EncaseType for T {}
 
-// TODO: don’t do a blanket impl?
-unsafe impl<T: 'static + Clone + Copy + Send + Sync> DeviceValue for T {}
-
 #[async_trait::async_trait]
 pub trait Backend: 'static + Sized + Send + Sync {
     const NAME: &'static str;
     const TARGET: CompileTarget;
 
-    type Error: Error + Send + Sync + 'static + From<ShaderArgsError>;
+    type Error: Error
+        + Send
+        + Sync
+        + 'static
+        + From<ShaderArgsError>
+        + From<UnsupportedError>
+        + From<SlangError>;
     type Buffer<T: DeviceValue>: Buffer<Self, T>;
+    /// A 2D texture, or [`UnsupportedTexture`] if the backend doesn’t support textures.
+    type Texture: Texture<Self>;
+    /// A texture sampler, or [`UnsupportedSampler`] if the backend doesn’t support textures.
+    type Sampler: Sampler<Self>;
+    /// A set of timestamp queries, or [`UnsupportedTimestamps`] if the backend doesn’t support
+    /// GPU timing.
+    type Timestamps: Timestamps<Self>;
+    /// A pre-recorded sequence of commands, or [`UnsupportedBundle`] if the backend can’t replay
+    /// recorded commands.
+    type Bundle;
+    type Fence: Fence<Self>;
     type BufferSlice<'b, T: DeviceValue>: Send + Sync + for<'c> ShaderArgs<'c, Self>;
     type Encoder: Encoder<Self> + Send + Sync;
     type Pass: Send + Sync;
@@ -57,6 +174,15 @@ pub trait Backend: 'static + Sized + Send + Sync {
     where
         Self: 'a;
 
+    /// The compilation target of the shaders loaded by this backend.
+    ///
+    /// This is [`Self::TARGET`] for all backends except [`AnyBackend`], for which it depends on
+    /// the backend selected at runtime, and `Cuda` when compiling with NVRTC (see
+    /// `Cuda::set_nvrtc`).
+    fn compile_target(&self) -> CompileTarget {
+        Self::TARGET
+    }
+
     #[cfg(feature = "cuda")]
     fn as_cuda(&self) -> Option<&crate::backend::Cuda> {
         None
@@ -64,6 +190,36 @@ pub trait Backend: 'static + Sized + Send + Sync {
     fn as_webgpu(&self) -> Option<&WebGpu> {
         None
     }
+    #[cfg(all(feature = "dx12", target_os = "windows"))]
+    fn as_dx12(&self) -> Option<&crate::backend::Dx12> {
+        None
+    }
+    #[cfg(feature = "cpu")]
+    fn as_cpu(&self) -> Option<&crate::backend::Cpu> {
+        None
+    }
+    fn as_mock(&self) -> Option<&Mock> {
+        None
+    }
+    #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+    fn as_remote(&self) -> Option<&crate::backend::Remote> {
+        None
+    }
+
+    /// The limits and optional shader features of the device this backend runs on.
+    ///
+    /// Dispatches exceeding these limits, or kernels relying on unsupported features, will fail.
+    fn limits(&self) -> Result<DeviceLimits, Self::Error>;
+
+    /// The memory usage of the device this backend runs on.
+    ///
+    /// This is device-wide on CUDA, i.e., it accounts for the memory used by other processes. On
+    /// WebGpu, only the memory allocated by this device is known, and only on native backends
+    /// with an allocator report (e.g. DirectX 12). Fails for backends that can’t track memory
+    /// usage.
+    fn memory_info(&self) -> Result<MemoryInfo, Self::Error> {
+        Err(UnsupportedError::new::<Self>("memory usage queries").into())
+    }
 
     /*
      * Module/function loading.
@@ -71,24 +227,108 @@ pub trait Backend: 'static + Sized + Send + Sync {
     fn load_module(&self, data: &str) -> Result<Self::Module, Self::Error> {
         self.load_module_bytes(data.as_bytes())
     }
+    /// Loads a module from its compiled code, e.g. WGSL source or PTX.
+    ///
+    /// The CPU backend refuses to load native code this way since it can’t tell whether it can
+    /// be trusted (see `Cpu::load_library`).
     fn load_module_bytes(&self, data: &[u8]) -> Result<Self::Module, Self::Error>;
+    /// Loads a module from code compiled by Slang from this crate, as attested by `compiled`.
+    ///
+    /// This is how the functions compiled by [`GpuFunction`](crate::function::GpuFunction) and
+    /// the [`ShaderRegistry`](crate::registry::ShaderRegistry) are loaded. Backends loading native
+    /// code (the CPU backend) only trust code loaded this way. The default implementation calls
+    /// [`Self::load_module_bytes`].
+    fn load_compiled_module(
+        &self,
+        data: &[u8],
+        compiled: SlangCompiled,
+    ) -> Result<Self::Module, Self::Error> {
+        let _ = compiled;
+        self.load_module_bytes(data)
+    }
     fn load_function(
         &self,
         module: &Self::Module,
         entry_point: &str,
     ) -> Result<Self::Function, Self::Error>;
+    /// Loads the function named `entry_point` from `module`, with the binding layout and
+    /// pipeline-overridable constants described by `options`.
+    ///
+    /// Backends relying on explicit binding layouts (WebGpu) use `options.bindings` instead of
+    /// inferring them from the compiled module. The default implementation ignores
+    /// `options.bindings`, and fails if `options.constants` isn’t empty.
+    fn load_function_with_options(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+        options: FunctionOptions<'_>,
+    ) -> Result<Self::Function, Self::Error> {
+        if !options.constants.is_empty() {
+            return Err(UnsupportedError::new::<Self>("pipeline-overridable constants").into());
+        }
+        self.load_function(module, entry_point)
+    }
+    /// The block size (number of threads per workgroup) maximizing the occupancy of `function`
+    /// on this device, assuming it doesn’t use dynamic shared memory.
+    ///
+    /// This is only supported on CUDA. The other backends return `None`.
+    fn occupancy_block_size(&self, function: &Self::Function) -> Result<Option<u32>, Self::Error> {
+        let _ = function;
+        Ok(None)
+    }
 
     /*
      * Kernel dispatch.
      */
+    /// Begins recording commands to be submitted with [`Backend::submit`].
+    ///
+    /// On CUDA, each encoder records on its own stream, so its kernels can overlap with transfers
+    /// performed on the backend until it is submitted.
     fn begin_encoding(&self) -> Self::Encoder;
+    /// Creates a handle to this backend whose encoders and submissions go to a new queue (a new
+    /// stream on CUDA).
+    ///
+    /// Resources are shared between both handles, so work submitted to different queues can
+    /// overlap, e.g., uploading data while a kernel is running. Use fences to order work across
+    /// queues.
+    ///
+    /// Only CUDA and DirectX create actual queues. The other backends (including WebGpu, since
+    /// wgpu exposes a single queue per device) return an alias of this backend submitting to the
+    /// same queue, so work submitted through both handles is executed in submission order and
+    /// never overlaps.
+    fn create_queue(&self) -> Result<Self, Self::Error>;
+    /// Begins recording a dispatch of `function` into `pass`.
+    ///
+    /// The `label` identifies the dispatch in GPU captures (e.g. RenderDoc or Nsight). It is
+    /// ignored if empty.
     fn begin_dispatch<'a>(
         &'a self,
         pass: &'a mut Self::Pass,
         function: &'a Self::Function,
+        label: &'a str,
     ) -> Self::Dispatch<'a>;
     fn synchronize(&self) -> Result<(), Self::Error>;
     fn submit(&self, encoder: Self::Encoder) -> Result<(), Self::Error>;
+    /// Submits the commands recorded by `encoder`, like [`Self::submit`], and returns a future
+    /// resolving once they finished executing.
+    ///
+    /// Unlike [`Self::synchronize`], awaiting the future only waits for this submission.
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error>;
+
+    /*
+     * Fences.
+     */
+    /// Creates a fence, initially signaled.
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error>;
+    /// Makes `fence` unsignaled until all the work submitted to this backend so far finished
+    /// executing.
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error>;
+    /// Makes the work submitted to this backend after this call wait for `fence` to be
+    /// signaled, without blocking the current thread when the backend supports it.
+    ///
+    /// The fence may have been signaled by another backend instance of the same type, e.g., to
+    /// wait for an upload done on another queue before a dispatch.
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error>;
 
     /*
      * Buffer handling.
@@ -104,7 +344,16 @@ pub trait Backend: 'static + Sized + Send + Sync {
         usage: BufferUsages,
     ) -> Result<Self::Buffer<T>, Self::Error>;
 
-    // fn init_buffer_bytes<T: Copy>(&self, bytes: &[u8], usage: BufferUsages) -> Result<Self::Buffer<T>, Self::Error>;
+    /// Creates a buffer initialized with raw bytes.
+    ///
+    /// The buffer contains `bytes.len() / size_of::<T>()` elements, so `bytes.len()` must be a
+    /// multiple of `size_of::<T>()` or a [`BufferCastError::SizeMismatch`] is returned. Together with [`Buffer::reinterpret`], a `Buffer<u8>` can be
+    /// used as an untyped allocation backing views of different types.
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error>;
 
     /// # Safety
     /// The returned buffer must be initialized before being read from.
@@ -131,6 +380,41 @@ pub trait Backend: 'static + Sized + Send + Sync {
         buffer: &mut Self::Buffer<T>,
         data: &[T],
     ) -> Result<(), Self::Error>;
+    /// Writes `data` into `buffer`, starting at the element `offset`.
+    ///
+    /// Only the written range is uploaded, which makes this suitable for appending to or
+    /// patching part of a buffer. On WebGpu, the written byte range must start and end at
+    /// multiples of 4 bytes.
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error>;
+    /// Writes `data` into `buffer` with the `encase` layout, starting at the element `offset`.
+    ///
+    /// Fails with an [`UnsupportedError`] on CUDA, which doesn’t support encased buffers yet.
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error>;
+    /// Writes `len` bytes into `buffer`, starting at the byte `byte_offset`, by letting `write`
+    /// fill a view of these bytes.
+    ///
+    /// This avoids the intermediate host allocation of [`Self::write_buffer_at`] for data that
+    /// has to be generated or serialized first (e.g. with `encase`): the view points to the
+    /// memory the upload is performed from (a staging buffer managed by the queue on WebGpu,
+    /// pinned host memory on CUDA, the buffer itself on CPU). The initial content of the view is
+    /// unspecified. On WebGpu, `byte_offset` and `len` must be multiples of 4.
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error>;
     async fn read_buffer<T: DeviceValue + Pod>(
         &self,
         buffer: &Self::Buffer<T>,
@@ -161,10 +445,374 @@ pub trait Backend: 'static + Sized + Send + Sync {
         self.slow_read_buffer(buffer, &mut result).await?;
         Ok(result)
     }
+
+    /// Reads the elements of `buffer` within `range` into the first `range.len()` elements of
+    /// `out`.
+    ///
+    /// Like [`Self::read_buffer`], this requires `buffer` to be a mapped staging buffer on
+    /// backends that have them, but only the requested range is mapped.
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error>;
+
+    /// Slower version of [`Self::read_buffer_range`] that doesn’t require `buffer` to be a
+    /// mapped staging buffer.
+    ///
+    /// Only the requested range is copied to the staging buffer and to `out`, so this is
+    /// suitable for reading back a header, a counter, or a window of a large buffer.
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error>;
+
+    /// Sets every element of `buffer` within the given range to `value`.
+    ///
+    /// The fill is complete once this returns, or ordered before any work submitted afterward.
+    /// On WebGpu, the buffer must have the `COPY_DST` usage, and the filled range must start and
+    /// end at multiples of 4 bytes.
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error>;
+
+    /// Sets every byte of `buffer` to zero.
+    fn clear_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+    ) -> Result<(), Self::Error> {
+        self.fill_buffer(buffer, T::zeroed(), ..)
+    }
+
+    /*
+     * Uniform handling.
+     */
+    /// Creates a uniform buffer containing `value`, for binding to `ConstantBuffer<T>` shader
+    /// parameters.
+    ///
+    /// The buffer is created with the `UNIFORM | COPY_DST` usages. Its content follows the
+    /// uniform layout rules of the backend: the std140-like layout computed by `encase` for WGSL
+    /// and DXIL targets, and the C layout of `T` for the CUDA and CPU targets (where constant
+    /// buffers are passed as pointers).
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        self.init_buffer(
+            std::slice::from_ref(value),
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        )
+    }
+
+    /// Overwrites the content of a uniform buffer created with [`Self::init_uniform`].
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.write_buffer(buffer, std::slice::from_ref(value))
+    }
+
+    /*
+     * Texture handling.
+     */
+    /// Creates a 2D texture initialized with `data`.
+    ///
+    /// The texels of `data` must be tightly packed, row after row, and match `format`. If `data`
+    /// is smaller than the texture, a [`TextureDataError`] is returned.
+    ///
+    /// On CUDA, textures with the `STORAGE_BINDING` usage are bound to kernels as surface objects
+    /// (for `RWTexture2D` parameters). Other textures are bound as texture objects (for
+    /// `Texture2D` parameters).
+    fn init_texture<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let _ = (data, size, format, usage);
+        Err(UnsupportedError::new::<Self>("textures").into())
+    }
+
+    /// Creates a 2D texture without initializing its content.
+    ///
+    /// # Safety
+    /// The returned texture must be initialized before being read from.
+    unsafe fn uninit_texture(
+        &self,
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let _ = (size, format, usage);
+        Err(UnsupportedError::new::<Self>("textures").into())
+    }
+
+    /// Overwrites the whole content of `texture` with `data`.
+    ///
+    /// The texels of `data` must be tightly packed, row after row. If `data` is smaller than the
+    /// texture, a [`TextureDataError`] is returned.
+    fn write_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &mut Self::Texture,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let _ = (texture, data);
+        Err(UnsupportedError::new::<Self>("textures").into())
+    }
+
+    /// Reads the whole content of `texture` into `data`, with tightly packed rows.
+    async fn read_texture<T: DeviceValue + Pod>(
+        &self,
+        texture: &Self::Texture,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let _ = (texture, data);
+        Err(UnsupportedError::new::<Self>("textures").into())
+    }
+
+    /// Creates a sampler that can be bound to `SamplerState` shader parameters.
+    ///
+    /// On CUDA, filtering and addressing are properties of the texture object itself, so the
+    /// sampler only binds a placeholder argument. Use `CudaTexture::set_sampler` to configure
+    /// how a texture is sampled on CUDA.
+    fn create_sampler(&self, desc: &SamplerDescriptor) -> Result<Self::Sampler, Self::Error> {
+        let _ = desc;
+        Err(UnsupportedError::new::<Self>("samplers").into())
+    }
+
+    /*
+     * Timestamp queries.
+     */
+    /// Creates a set of timestamp queries for timing up to `capacity` passes started with
+    /// [`Encoder::begin_timed_pass`].
+    ///
+    /// On WebGpu, this requires the device to be created with `wgpu::Features::TIMESTAMP_QUERY`.
+    fn create_timestamps(&self, capacity: usize) -> Result<Self::Timestamps, Self::Error> {
+        let _ = capacity;
+        Err(UnsupportedError::new::<Self>("timestamp queries").into())
+    }
+
+    /// Waits for the passes timed with `timestamps` to complete and returns their timings, in
+    /// the order the passes were started.
+    ///
+    /// The encoders of the timed passes must have been submitted. This resets `timestamps` so it
+    /// can be used to time new passes.
+    async fn resolve_timestamps(
+        &self,
+        timestamps: &mut Self::Timestamps,
+    ) -> Result<Vec<PassTimestamps>, Self::Error> {
+        let _ = timestamps;
+        Err(UnsupportedError::new::<Self>("timestamp queries").into())
+    }
+
+    /*
+     * Profiling.
+     */
+    /// Enables profiling: every dispatch launched afterwards is counted and timed on the GPU,
+    /// per kernel, until profiling is disabled. See [`Self::profiling_report`].
+    ///
+    /// Up to `capacity` dispatches are timed between two reports, the next ones are only
+    /// counted. Enabling profiling again resets the statistics. The handles created afterwards
+    /// with [`Self::create_queue`] share the statistics of this backend.
+    ///
+    /// Only the WebGpu and CUDA backends support profiling. On WebGpu, timing the dispatches
+    /// requires the device to be created with `wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES`,
+    /// otherwise they are only counted.
+    fn enable_profiling(&mut self, capacity: usize) -> Result<(), Self::Error> {
+        let _ = capacity;
+        Err(UnsupportedError::new::<Self>("profiling").into())
+    }
+
+    /// Disables profiling, discarding the statistics recorded so far.
+    fn disable_profiling(&mut self) {}
+
+    /// Waits for the dispatches timed since the last report to complete, and returns the
+    /// statistics of each kernel launched since profiling was enabled.
+    ///
+    /// The encoders of the profiled dispatches must have been submitted. The report is empty if
+    /// profiling isn’t enabled.
+    async fn profiling_report(&self) -> Result<ProfilingReport, Self::Error> {
+        Ok(ProfilingReport::default())
+    }
+
+    /*
+     * Command bundles.
+     */
+    /// Records the commands of `record` into a bundle, replayed with [`Self::replay_bundle`].
+    ///
+    /// `record` is given the backend and the encoder to record the commands with. The commands
+    /// aren’t executed while recording, and the arguments of the recorded dispatches are bound
+    /// once: values passed by value are captured when recording, while the contents of the bound
+    /// buffers are read when replaying. See [`CommandBundle`](crate::bundle::CommandBundle).
+    ///
+    /// Only the WebGpu and CUDA backends support bundles. On WebGpu, only the commands of the
+    /// given encoder (and of its passes) are recorded, and beginning a timed pass or replaying
+    /// another bundle with it fails.
+    fn record_bundle(
+        &self,
+        record: &mut dyn FnMut(&Self, &mut Self::Encoder) -> Result<(), Self::Error>,
+    ) -> Result<Self::Bundle, Self::Error> {
+        let _ = record;
+        Err(UnsupportedError::new::<Self>("command bundles").into())
+    }
+
+    /// Records the commands of `bundle` into `encoder`.
+    fn replay_bundle(
+        &self,
+        encoder: &mut Self::Encoder,
+        bundle: &Self::Bundle,
+    ) -> Result<(), Self::Error> {
+        let _ = (encoder, bundle);
+        Err(UnsupportedError::new::<Self>("command bundles").into())
+    }
+}
+
+/// Error returned by operations a backend doesn’t support.
+#[derive(thiserror::Error, Debug)]
+#[error("{feature} aren’t supported by the {backend} backend")]
+pub struct UnsupportedError {
+    /// The name of the backend.
+    pub backend: &'static str,
+    /// The unsupported feature.
+    pub feature: &'static str,
+}
+
+impl UnsupportedError {
+    pub fn new<B: Backend>(feature: &'static str) -> Self {
+        Self {
+            backend: B::NAME,
+            feature,
+        }
+    }
+}
+
+/// Proof that the code given to [`Backend::load_compiled_module`] was compiled by Slang from this
+/// crate.
+///
+/// This can only be created by this crate, so that code of unknown origin can’t be passed off
+/// as compiled by Slang.
+#[derive(Copy, Clone, Debug)]
+pub struct SlangCompiled(pub(crate) ());
+
+/// Memory usage of a device, in bytes, as returned by [`Backend::memory_info`].
+///
+/// Values a backend can’t determine are `None`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryInfo {
+    /// The amount of device memory in use.
+    pub used: Option<u64>,
+    /// The amount of device memory still available for allocation.
+    pub free: Option<u64>,
+    /// The total amount of device memory.
+    pub total: Option<u64>,
+}
+
+/// Limits and optional shader features of a device, as returned by [`Backend::limits`].
+///
+/// Limits a backend doesn’t enforce are set to `u32::MAX`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeviceLimits {
+    /// The maximum number of workgroups of a single dispatch, along each axis.
+    pub max_workgroup_count: [u32; 3],
+    /// The maximum size of a workgroup, along each axis.
+    pub max_workgroup_size: [u32; 3],
+    /// The maximum number of invocations in a single workgroup.
+    pub max_invocations_per_workgroup: u32,
+    /// The maximum number of storage buffers bound to a single kernel.
+    pub max_storage_buffers_per_stage: u32,
+    /// The maximum amount of workgroup-shared memory, in bytes, or zero if the backend doesn’t
+    /// support it.
+    pub max_shared_memory_size: u32,
+    /// Whether kernels can use 16-bit floats.
+    pub shader_f16: bool,
+    /// Whether kernels can use 64-bit floats.
+    pub shader_f64: bool,
+    /// Whether kernels can use atomic operations on 64-bit integers.
+    pub shader_int64_atomics: bool,
+    /// Whether kernels can use subgroup (wave/warp) operations.
+    pub subgroups: bool,
+}
+
+impl Default for DeviceLimits {
+    /// The limits guaranteed by every WebGPU implementation, without any optional feature.
+    fn default() -> Self {
+        Self::from_wgpu(&wgpu::Limits::default(), wgpu::Features::empty())
+    }
+}
+
+impl DeviceLimits {
+    /// Converts the limits and features of a wgpu device.
+    pub fn from_wgpu(limits: &wgpu::Limits, features: wgpu::Features) -> Self {
+        Self {
+            max_workgroup_count: [limits.max_compute_workgroups_per_dimension; 3],
+            max_workgroup_size: [
+                limits.max_compute_workgroup_size_x,
+                limits.max_compute_workgroup_size_y,
+                limits.max_compute_workgroup_size_z,
+            ],
+            max_invocations_per_workgroup: limits.max_compute_invocations_per_workgroup,
+            max_storage_buffers_per_stage: limits.max_storage_buffers_per_shader_stage,
+            max_shared_memory_size: limits.max_compute_workgroup_storage_size,
+            shader_f16: features.contains(wgpu::Features::SHADER_F16),
+            shader_f64: features.contains(wgpu::Features::SHADER_F64),
+            shader_int64_atomics: features.contains(wgpu::Features::SHADER_INT64_ATOMIC_ALL_OPS),
+            subgroups: features.contains(wgpu::Features::SUBGROUP),
+        }
+    }
+}
+
+/// Description of a device that can be selected when initializing a backend.
+///
+/// See `WebGpu::enumerate_devices` and `Cuda::enumerate_devices`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// The index of the device, as expected by the backend’s `with_device` constructor.
+    pub index: usize,
+    /// The name of the device.
+    pub name: String,
+    /// Whether the device is a discrete GPU, an integrated GPU, a CPU, etc.
+    pub device_type: wgpu::DeviceType,
+    /// The API driving the device and, if relevant, its version or the device’s capability level (e.g.
+    /// `"Vulkan"` or `"CUDA sm_86"`).
+    pub api: String,
+    /// The total amount of device memory in bytes, if known.
+    pub memory: Option<u64>,
 }
 
 pub trait Encoder<B: Backend> {
-    fn begin_pass(&mut self) -> B::Pass;
+    /// Begins a pass, labeled with `label` in GPU captures (unless it is empty).
+    fn begin_pass(&mut self, label: &str) -> B::Pass;
+    /// Begins a pass whose beginning and end are timed with the next unused queries of
+    /// `timestamps`. The pass ends when it is dropped.
+    ///
+    /// Fails if `timestamps` already timed as many passes as its capacity since it was created
+    /// or last resolved.
+    fn begin_timed_pass(
+        &mut self,
+        label: &str,
+        timestamps: &mut B::Timestamps,
+    ) -> Result<B::Pass, B::Error> {
+        let _ = (label, timestamps);
+        Err(UnsupportedError::new::<B>("timestamp queries").into())
+    }
+    /// Opens a debug group: the commands recorded until the matching [`Self::pop_debug_group`]
+    /// are grouped under `label` in GPU captures.
+    ///
+    /// This maps to wgpu debug groups on WebGpu, NVTX ranges on CUDA, and PIX events on DirectX.
+    /// It does nothing on backends without debugging tools.
+    fn push_debug_group(&mut self, label: &str) {
+        let _ = label;
+    }
+    /// Closes the debug group opened by the last call to [`Self::push_debug_group`].
+    fn pop_debug_group(&mut self) {}
     fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
         &mut self,
         source: &B::Buffer<T>,
@@ -184,6 +832,65 @@ pub trait Encoder<B: Backend> {
 }
 
 pub trait Dispatch<'a, B: Backend> {
+    /// Binds an argument passed by value, like a `uniform uint` entry-point parameter.
+    ///
+    /// The value is uploaded when the dispatch is launched: as a kernel argument on CUDA, as part
+    /// of the entry-point parameters on CPU, and through a uniform buffer created for this
+    /// dispatch on WebGPU and DirectX (see [`Self::set_value_buffer_binding`]).
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError>;
+
+    /// Sets the binding of the constant buffer generated by Slang for the entry-point parameters
+    /// passed by value.
+    ///
+    /// This is only needed by targets (WGSL, DXIL) that can’t pass values to kernels directly,
+    /// and is called automatically by [`GpuFunction`](crate::function::GpuFunction).
+    fn set_value_buffer_binding(&mut self, binding: ShaderBinding) {
+        let _ = binding;
+    }
+
+    /// Sets the amount of dynamic shared memory, in bytes, allocated to each workgroup of this
+    /// dispatch.
+    ///
+    /// This is only supported on CUDA, where it is needed by kernels using dynamically sized
+    /// shared memory. It is ignored by the other backends, since their targets can only declare
+    /// statically sized shared memory.
+    fn set_dynamic_shared_memory(&mut self, bytes: u32) {
+        let _ = bytes;
+    }
+
+    /// The binding of the element `index` of an array of resources (e.g. a
+    /// `StructuredBuffer<float> buffers[4]` parameter) bound at `binding`.
+    ///
+    /// By default, the elements of an array occupy consecutive binding indices, like the
+    /// registers of HLSL arrays.
+    fn array_element_binding(&self, binding: ShaderBinding, index: usize) -> ShaderBinding {
+        ShaderBinding {
+            space: binding.space,
+            index: binding.index + index as u32,
+        }
+    }
+
+    /// Groups the last `len` resources bound (at the bindings given by
+    /// [`Self::array_element_binding`]) into the array of resources `name` bound at `binding`.
+    ///
+    /// This is called by the [`ShaderArgs`] implementation of slices once all their elements are
+    /// bound. It does nothing by default. On WebGpu, the elements are bound as a binding array,
+    /// which requires the `BUFFER_BINDING_ARRAY` and `STORAGE_RESOURCE_BINDING_ARRAY` features.
+    fn bind_array(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        len: usize,
+    ) -> Result<(), ShaderArgsError> {
+        let _ = (binding, name, len);
+        Ok(())
+    }
+
     fn launch<'b>(
         self,
         grid: impl Into<DispatchGrid<'b, B>>,
@@ -197,15 +904,358 @@ pub trait Buffer<B: Backend, T: DeviceValue>: Send + Sync + for<'b> ShaderArgs<'
     }
 
     fn len(&self) -> usize;
+    /// The size of this buffer, in bytes.
+    fn size_bytes(&self) -> usize;
     fn as_slice(&self) -> B::BufferSlice<'_, T> {
         self.slice(..)
     }
+    /// A slice of the elements of this buffer in `range`.
+    ///
+    /// Panics if `range` is out of the bounds of the buffer on the CPU backend, where it would
+    /// let kernels access memory outside of the buffer.
     fn slice(&self, range: impl RangeBounds<usize>) -> B::BufferSlice<'_, T>;
+
+    /// A view of `len` elements of type `U`, starting at the byte `byte_offset` of this buffer.
+    ///
+    /// This lets a single allocation (e.g. a scratch `Buffer<u8>`) back several views of
+    /// different types. `byte_offset` must be a multiple of the alignment of `U`, and the view
+    /// must fit in the buffer. Note that WebGpu additionally requires views bound to storage
+    /// buffers to start at a multiple of the device’s `min_storage_buffer_offset_alignment`.
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<B::BufferSlice<'_, U>, BufferCastError>;
+
+    /// A view of the whole buffer as elements of type `U`.
+    ///
+    /// The size of the buffer must be a multiple of the size of `U`.
+    fn cast<U: DeviceValue + Pod>(&self) -> Result<B::BufferSlice<'_, U>, BufferCastError> {
+        let size = self.size_bytes();
+        let elt_size = size_of::<U>();
+        if elt_size == 0 || !size.is_multiple_of(elt_size) {
+            return Err(BufferCastError::SizeMismatch { size, elt_size });
+        }
+        self.reinterpret(0, size / elt_size)
+    }
+}
+
+/// Error returned when reinterpreting a buffer as elements of another type.
+#[derive(thiserror::Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferCastError {
+    #[error(
+        "the byte offset {offset} isn’t a multiple of the alignment of the view type ({align})"
+    )]
+    Misaligned { offset: usize, align: usize },
+    #[error("the view (bytes {start}..{end}) is out of the bounds of the buffer ({size} bytes)")]
+    OutOfBounds {
+        start: usize,
+        end: usize,
+        size: usize,
+    },
+    #[error(
+        "the buffer size ({size} bytes) isn’t a multiple of the view element size ({elt_size})"
+    )]
+    SizeMismatch { size: usize, elt_size: usize },
+}
+
+/// Error returned when the data written to a texture, or the output of a texture read, is
+/// smaller than the texture.
+#[derive(thiserror::Error, Copy, Clone, Debug, PartialEq, Eq)]
+#[error("the data ({len} bytes) is smaller than the texture ({texture_len} bytes)")]
+pub struct TextureDataError {
+    pub len: usize,
+    pub texture_len: usize,
 }
 
+/// The number of elements of type `T` stored in `size` bytes, or an error if `size` isn’t a
+/// multiple of the size of `T`.
+pub(crate) fn elements_len<T>(size: usize) -> Result<usize, BufferCastError> {
+    let elt_size = size_of::<T>();
+    if !size.is_multiple_of(elt_size) {
+        return Err(BufferCastError::SizeMismatch { size, elt_size });
+    }
+    Ok(size.checked_div(elt_size).unwrap_or(0))
+}
+
+/// Checks that a view of `len` elements of type `U` at `byte_offset` fits in a buffer of `size`
+/// bytes, and returns its byte range.
+pub(crate) fn view_bytes<U>(
+    size: usize,
+    byte_offset: usize,
+    len: usize,
+) -> Result<Range<usize>, BufferCastError> {
+    let align = align_of::<U>();
+    if !byte_offset.is_multiple_of(align) {
+        return Err(BufferCastError::Misaligned {
+            offset: byte_offset,
+            align,
+        });
+    }
+    let end = len
+        .checked_mul(size_of::<U>())
+        .and_then(|n| byte_offset.checked_add(n));
+    match end {
+        Some(end) if end <= size => Ok(byte_offset..end),
+        // NOTE: on overflow, `usize::MAX` is the closest representable end.
+        _ => Err(BufferCastError::OutOfBounds {
+            start: byte_offset,
+            end: end.unwrap_or(usize::MAX),
+            size,
+        }),
+    }
+}
+
+pub trait Texture<B: Backend>: Send + Sync + for<'b> ShaderArgs<'b, B> {
+    /// The width and height of the texture, in texels.
+    fn size(&self) -> [u32; 2];
+    fn format(&self) -> TextureFormat;
+}
+
+/// The texture type of backends that don’t support textures.
+///
+/// This type can’t be instantiated.
+pub enum UnsupportedTexture {}
+
+impl<B: Backend> Texture<B> for UnsupportedTexture {
+    fn size(&self) -> [u32; 2] {
+        match *self {}
+    }
+
+    fn format(&self) -> TextureFormat {
+        match *self {}
+    }
+}
+
+impl<'b, B: Backend> ShaderArgs<'b, B> for UnsupportedTexture {
+    fn write_arg<'a>(
+        &'b self,
+        _binding: ShaderBinding,
+        _name: &str,
+        _dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match *self {}
+    }
+}
+
+/// The bytes of `value` laid out with the uniform buffer layout rules of WGSL.
+pub(crate) fn uniform_bytes<T: EncaseType>(value: &T) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut bytes_buffer = UniformBuffer::new(&mut bytes);
+    bytes_buffer.write(value).unwrap();
+    bytes
+}
+
+/// Converts a range of buffer elements into a `Range`, with unbounded ends clamped to `[0, len]`.
+pub(crate) fn buffer_range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(i) => Some(*i),
+        Bound::Excluded(i) => i.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(i) => i.checked_add(1),
+        Bound::Excluded(i) => Some(*i),
+        Bound::Unbounded => Some(len),
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end && end <= len => start..end,
+        _ => panic!(
+            "range ({:?}, {:?}) out of bounds for a buffer of length {len}",
+            range.start_bound(),
+            range.end_bound()
+        ),
+    }
+}
+
+/// The bytes of `count` consecutive copies of `value`.
+pub(crate) fn repeat_bytes<T: Pod>(value: &T, count: usize) -> Vec<u8> {
+    bytemuck::bytes_of(value).repeat(count)
+}
+
+/// The size of a single texel of the given format, in bytes.
+pub(crate) fn texel_size<B: Backend>(format: TextureFormat) -> Result<u32, UnsupportedError> {
+    match format.block_copy_size(None) {
+        Some(size) if format.block_dimensions() == (1, 1) => Ok(size),
+        _ => Err(UnsupportedError::new::<B>(
+            "compressed, depth, or stencil textures",
+        )),
+    }
+}
+
+/// The size of a tightly packed 2D texture, in bytes.
+pub(crate) fn texture_bytes_len<B: Backend>(
+    size: [u32; 2],
+    format: TextureFormat,
+) -> Result<usize, UnsupportedError> {
+    Ok(size[0] as usize * size[1] as usize * texel_size::<B>(format)? as usize)
+}
+
+/// Checks that `len` bytes of data cover a texture of `texture_len` bytes.
+pub(crate) fn check_texture_data(len: usize, texture_len: usize) -> Result<(), TextureDataError> {
+    if len < texture_len {
+        return Err(TextureDataError { len, texture_len });
+    }
+    Ok(())
+}
+
+/// The filtering and addressing modes of a [`Backend::Sampler`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SamplerDescriptor {
+    /// How to address texels outside of the texture along the horizontal axis.
+    pub address_mode_u: AddressMode,
+    /// How to address texels outside of the texture along the vertical axis.
+    pub address_mode_v: AddressMode,
+    /// How to filter the texture when it is magnified.
+    pub mag_filter: FilterMode,
+    /// How to filter the texture when it is minified.
+    pub min_filter: FilterMode,
+}
+
+impl Default for SamplerDescriptor {
+    fn default() -> Self {
+        Self {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+        }
+    }
+}
+
+pub trait Sampler<B: Backend>: Send + Sync + for<'b> ShaderArgs<'b, B> {
+    fn descriptor(&self) -> SamplerDescriptor;
+}
+
+/// The sampler type of backends that don’t support textures.
+///
+/// This type can’t be instantiated.
+pub enum UnsupportedSampler {}
+
+impl<B: Backend> Sampler<B> for UnsupportedSampler {
+    fn descriptor(&self) -> SamplerDescriptor {
+        match *self {}
+    }
+}
+
+impl<'b, B: Backend> ShaderArgs<'b, B> for UnsupportedSampler {
+    fn write_arg<'a>(
+        &'b self,
+        _binding: ShaderBinding,
+        _name: &str,
+        _dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match *self {}
+    }
+}
+
+pub trait Fence<B: Backend>: Send + Sync {
+    /// Whether the work preceding the last [`Backend::signal_fence`] on this fence finished
+    /// executing.
+    fn is_signaled(&self) -> bool;
+}
+
+pub trait Timestamps<B: Backend>: Send + Sync {
+    /// The maximum number of passes that can be timed with this set.
+    fn capacity(&self) -> usize;
+    /// The number of passes timed since this set was created or last resolved.
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The timestamps type of backends that don’t support GPU timing.
+///
+/// This type can’t be instantiated.
+pub enum UnsupportedTimestamps {}
+
+impl<B: Backend> Timestamps<B> for UnsupportedTimestamps {
+    fn capacity(&self) -> usize {
+        match *self {}
+    }
+
+    fn len(&self) -> usize {
+        match *self {}
+    }
+}
+
+/// The bundle type of backends that can’t replay recorded commands.
+///
+/// This type can’t be instantiated.
+pub enum UnsupportedBundle {}
+
+/// When a pass timed with [`Encoder::begin_timed_pass`] began and ended on the GPU.
+///
+/// Times are in nanoseconds, relative to the beginning of the first pass timed by the same
+/// [`Backend::Timestamps`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PassTimestamps {
+    pub begin_ns: u64,
+    pub end_ns: u64,
+}
+
+impl PassTimestamps {
+    /// The duration of the pass, in nanoseconds.
+    pub fn duration_ns(&self) -> u64 {
+        self.end_ns.saturating_sub(self.begin_ns)
+    }
+}
+
+/// A future resolving once the commands of a submission made with [`Backend::submit_async`]
+/// finished executing.
+///
+/// Except on the remote backend, polling the future never blocks the thread polling it, so it
+/// can be polled with e.g. `futures::FutureExt::now_or_never`:
+/// - on CUDA, it is woken up by the driver once the submission completes.
+/// - on DX12, it is woken up by a thread-pool wait on the completion event of the submission.
+/// - on WebGpu, each poll polls the device without blocking, and the future reschedules itself
+///   until the submission completes (on the web, it is woken up by the browser instead).
+/// - on the CPU and mock backends, submissions run synchronously so the future is already
+///   resolved.
+/// - on the remote backend, polling the future waits for the server’s answer.
+#[must_use = "futures do nothing unless awaited"]
+pub struct SubmissionFuture<B: Backend> {
+    future: BoxFuture<'static, Result<(), B::Error>>,
+}
+
+impl<B: Backend> SubmissionFuture<B> {
+    pub fn new(future: impl Future<Output = Result<(), B::Error>> + Send + 'static) -> Self {
+        Self {
+            future: Box::pin(future),
+        }
+    }
+
+    /// A future that is already resolved, for backends executing submissions synchronously.
+    pub fn ready() -> Self {
+        Self::new(async { Ok(()) })
+    }
+}
+
+impl<B: Backend> Future for SubmissionFuture<B> {
+    type Output = Result<(), B::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// The number of workgroups of a dispatch.
 pub enum DispatchGrid<'a, B: Backend> {
+    /// A workgroup count known on the host.
     Direct([u32; 3]),
-    Indirect(&'a B::Buffer<[u32; 3]>),
+    /// A workgroup count read by the device from a buffer, at the given element offset.
+    ///
+    /// This lets a single buffer hold the indirect arguments of many dispatches.
+    ///
+    /// Launches with an indirect grid fail with an [`UnsupportedError`] on CUDA.
+    Indirect(&'a B::Buffer<[u32; 3]>, usize),
 }
 
 impl<'a, B: Backend> From<u32> for DispatchGrid<'a, B> {
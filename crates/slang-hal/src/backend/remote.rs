@@ -0,0 +1,1887 @@
+//! Backend forwarding all the work to a server running another backend on a remote machine.
+
+use crate::ShaderArgs;
+use crate::backend::{
+    Backend, Buffer, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid,
+    EncaseType, Encoder, Fence, MemoryInfo, ShaderBinding, SubmissionFuture, UnsupportedBundle,
+    UnsupportedError, UnsupportedSampler, UnsupportedTexture, UnsupportedTimestamps, buffer_range,
+    elements_len, uniform_bytes, view_bytes,
+};
+use crate::shader::{ArgInfo, ShaderArgsError};
+use bytemuck::Pod;
+use encase::{ShaderType, StorageBuffer};
+use minislang::shader_slang::CompileTarget;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::{Bound, Range, RangeBounds};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use wgpu::BufferUsages;
+
+const PROTOCOL_MAGIC: &[u8; 8] = b"slanghal";
+const PROTOCOL_VERSION: u32 = 1;
+
+/// The maximum size of a message, in bytes.
+///
+/// This bounds the memory a peer can make the other side allocate with a single message.
+const MAX_FRAME_SIZE: usize = 1 << 30;
+
+/*
+ * Wire format.
+ *
+ * Every message is a little-endian `u32` byte length followed by the message itself. Only the
+ * requests that need a result (module/function loading, buffer reads, synchronization, and
+ * device limits) are answered by the server. Errors from the other requests are deferred and reported with the
+ * next answered request.
+ */
+#[derive(Default)]
+struct WireWriter {
+    bytes: Vec<u8>,
+}
+
+impl WireWriter {
+    fn u8(&mut self, val: u8) -> &mut Self {
+        self.bytes.push(val);
+        self
+    }
+
+    fn u32(&mut self, val: u32) -> &mut Self {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+
+    fn u64(&mut self, val: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
+        self
+    }
+
+    fn bytes(&mut self, val: &[u8]) -> &mut Self {
+        self.u64(val.len() as u64);
+        self.bytes.extend_from_slice(val);
+        self
+    }
+
+    fn str(&mut self, val: &str) -> &mut Self {
+        self.bytes(val.as_bytes())
+    }
+}
+
+struct WireReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(invalid_data("truncated message"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u64()? as usize;
+        self.take(len)
+    }
+
+    fn str(&mut self) -> io::Result<&'a str> {
+        std::str::from_utf8(self.bytes()?).map_err(|_| invalid_data("invalid string"))
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn write_frame(writer: &mut impl Write, frame: &[u8]) -> io::Result<()> {
+    if frame.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "message too large ({} bytes, the maximum is {MAX_FRAME_SIZE})",
+                frame.len()
+            ),
+        ));
+    }
+    writer.write_all(&(frame.len() as u32).to_le_bytes())?;
+    writer.write_all(frame)
+}
+
+fn read_frame(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(invalid_data("message too large"));
+    }
+    // NOTE: the frame grows as its bytes arrive instead of being allocated upfront, so a peer
+    //       announcing a large frame without sending it doesn’t make us allocate it.
+    let mut frame = vec![];
+    reader.take(len as u64).read_to_end(&mut frame)?;
+    if frame.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(frame)
+}
+
+/// The compile targets that can be sent over the wire.
+fn target_to_wire(target: CompileTarget) -> Option<u8> {
+    match target {
+        CompileTarget::Wgsl => Some(0),
+        CompileTarget::Ptx => Some(1),
+        CompileTarget::Dxil => Some(2),
+        CompileTarget::ShaderSharedLibrary => Some(3),
+        CompileTarget::Spirv => Some(4),
+        CompileTarget::Metal => Some(5),
+        CompileTarget::CudaSource => Some(6),
+        _ => None,
+    }
+}
+
+fn target_from_wire(target: u8) -> Option<CompileTarget> {
+    match target {
+        0 => Some(CompileTarget::Wgsl),
+        1 => Some(CompileTarget::Ptx),
+        2 => Some(CompileTarget::Dxil),
+        3 => Some(CompileTarget::ShaderSharedLibrary),
+        4 => Some(CompileTarget::Spirv),
+        5 => Some(CompileTarget::Metal),
+        6 => Some(CompileTarget::CudaSource),
+        _ => None,
+    }
+}
+
+/// The kind of buffer allocated on the server.
+///
+/// Buffers are untyped on the server, except for the ones used for indirect dispatches.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum WireBufferKind {
+    Bytes = 0,
+    Grid = 1,
+}
+
+struct WireArg {
+    name: String,
+    binding: ShaderBinding,
+    resource: WireResource,
+}
+
+enum WireResource {
+    Buffer {
+        id: u64,
+        /// Byte range of the buffer bound to this argument.
+        range: Range<u64>,
+    },
+    /// The bytes of an argument passed by value.
+    Value(Vec<u8>),
+}
+
+enum WireGrid {
+    Direct([u32; 3]),
+    Indirect(u64, u64),
+}
+
+enum Request {
+    LoadModule {
+        id: u64,
+        bytes: Vec<u8>,
+    },
+    LoadFunction {
+        id: u64,
+        module: u64,
+        entry_point: String,
+    },
+    CreateBuffer {
+        id: u64,
+        kind: WireBufferKind,
+        size: u64,
+        usage: u32,
+        data: Option<Vec<u8>>,
+    },
+    WriteBuffer {
+        id: u64,
+        /// Byte offset of the write.
+        offset: u64,
+        bytes: Vec<u8>,
+    },
+    ReadBuffer {
+        id: u64,
+        /// Byte offset of the read.
+        offset: u64,
+        /// Number of bytes read.
+        len: u64,
+    },
+    FillBuffer {
+        id: u64,
+        /// Byte offset of the first element to fill.
+        offset: u64,
+        /// The bytes of the value to fill the elements with.
+        value: Vec<u8>,
+        /// Number of elements to fill.
+        count: u64,
+    },
+    CopyBuffer {
+        source: u64,
+        source_offset: u64,
+        target: u64,
+        target_offset: u64,
+        len: u64,
+    },
+    Dispatch {
+        function: u64,
+        label: String,
+        args: Vec<WireArg>,
+        value_buffer: Option<ShaderBinding>,
+        grid: WireGrid,
+        block_dim: [u32; 3],
+        shared_memory_bytes: u32,
+    },
+    Synchronize,
+    DropBuffer {
+        id: u64,
+    },
+    Limits,
+    MemoryInfo,
+}
+
+impl Request {
+    fn expects_response(&self) -> bool {
+        matches!(
+            self,
+            Request::LoadModule { .. }
+                | Request::LoadFunction { .. }
+                | Request::ReadBuffer { .. }
+                | Request::Synchronize
+                | Request::Limits
+                | Request::MemoryInfo
+        )
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut w = WireWriter::default();
+        match self {
+            Request::LoadModule { id, bytes } => {
+                w.u8(0).u64(*id).bytes(bytes);
+            }
+            Request::LoadFunction {
+                id,
+                module,
+                entry_point,
+            } => {
+                w.u8(1).u64(*id).u64(*module).str(entry_point);
+            }
+            Request::CreateBuffer {
+                id,
+                kind,
+                size,
+                usage,
+                data,
+            } => {
+                w.u8(2).u64(*id).u8(*kind as u8).u64(*size).u32(*usage);
+                match data {
+                    Some(data) => w.u8(1).bytes(data),
+                    None => w.u8(0),
+                };
+            }
+            Request::WriteBuffer { id, offset, bytes } => {
+                w.u8(3).u64(*id).u64(*offset).bytes(bytes);
+            }
+            Request::ReadBuffer { id, offset, len } => {
+                w.u8(4).u64(*id).u64(*offset).u64(*len);
+            }
+            Request::CopyBuffer {
+                source,
+                source_offset,
+                target,
+                target_offset,
+                len,
+            } => {
+                w.u8(5)
+                    .u64(*source)
+                    .u64(*source_offset)
+                    .u64(*target)
+                    .u64(*target_offset)
+                    .u64(*len);
+            }
+            Request::Dispatch {
+                function,
+                label,
+                args,
+                value_buffer,
+                grid,
+                block_dim,
+                shared_memory_bytes,
+            } => {
+                w.u8(6).u64(*function).str(label).u64(args.len() as u64);
+                for arg in args {
+                    w.str(&arg.name)
+                        .u32(arg.binding.space)
+                        .u32(arg.binding.index);
+                    match &arg.resource {
+                        WireResource::Buffer { id, range } => {
+                            w.u8(0).u64(*id).u64(range.start).u64(range.end)
+                        }
+                        WireResource::Value(bytes) => w.u8(1).bytes(bytes),
+                    };
+                }
+                match value_buffer {
+                    Some(binding) => w.u8(1).u32(binding.space).u32(binding.index),
+                    None => w.u8(0),
+                };
+                match grid {
+                    WireGrid::Direct(grid) => w.u8(0).u32(grid[0]).u32(grid[1]).u32(grid[2]),
+                    WireGrid::Indirect(id, offset) => w.u8(1).u64(*id).u64(*offset),
+                };
+                w.u32(block_dim[0]).u32(block_dim[1]).u32(block_dim[2]);
+                w.u32(*shared_memory_bytes);
+            }
+            Request::Synchronize => {
+                w.u8(7);
+            }
+            Request::DropBuffer { id } => {
+                w.u8(8).u64(*id);
+            }
+            Request::FillBuffer {
+                id,
+                offset,
+                value,
+                count,
+            } => {
+                w.u8(9).u64(*id).u64(*offset).bytes(value).u64(*count);
+            }
+            Request::Limits => {
+                w.u8(10);
+            }
+            Request::MemoryInfo => {
+                w.u8(11);
+            }
+        }
+        w.bytes
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = WireReader { bytes };
+        let request = match r.u8()? {
+            0 => Request::LoadModule {
+                id: r.u64()?,
+                bytes: r.bytes()?.to_vec(),
+            },
+            1 => Request::LoadFunction {
+                id: r.u64()?,
+                module: r.u64()?,
+                entry_point: r.str()?.to_string(),
+            },
+            2 => Request::CreateBuffer {
+                id: r.u64()?,
+                kind: match r.u8()? {
+                    0 => WireBufferKind::Bytes,
+                    1 => WireBufferKind::Grid,
+                    _ => return Err(invalid_data("unknown buffer kind")),
+                },
+                size: r.u64()?,
+                usage: r.u32()?,
+                data: match r.u8()? {
+                    0 => None,
+                    _ => Some(r.bytes()?.to_vec()),
+                },
+            },
+            3 => Request::WriteBuffer {
+                id: r.u64()?,
+                offset: r.u64()?,
+                bytes: r.bytes()?.to_vec(),
+            },
+            4 => Request::ReadBuffer {
+                id: r.u64()?,
+                offset: r.u64()?,
+                len: r.u64()?,
+            },
+            5 => Request::CopyBuffer {
+                source: r.u64()?,
+                source_offset: r.u64()?,
+                target: r.u64()?,
+                target_offset: r.u64()?,
+                len: r.u64()?,
+            },
+            6 => {
+                let function = r.u64()?;
+                let label = r.str()?.to_string();
+                let num_args = r.u64()?;
+                let mut args = vec![];
+                for _ in 0..num_args {
+                    args.push(WireArg {
+                        name: r.str()?.to_string(),
+                        binding: ShaderBinding {
+                            space: r.u32()?,
+                            index: r.u32()?,
+                        },
+                        resource: match r.u8()? {
+                            0 => WireResource::Buffer {
+                                id: r.u64()?,
+                                range: r.u64()?..r.u64()?,
+                            },
+                            _ => WireResource::Value(r.bytes()?.to_vec()),
+                        },
+                    });
+                }
+                let value_buffer = match r.u8()? {
+                    0 => None,
+                    _ => Some(ShaderBinding {
+                        space: r.u32()?,
+                        index: r.u32()?,
+                    }),
+                };
+                let grid = match r.u8()? {
+                    0 => WireGrid::Direct([r.u32()?, r.u32()?, r.u32()?]),
+                    _ => WireGrid::Indirect(r.u64()?, r.u64()?),
+                };
+                Request::Dispatch {
+                    function,
+                    label,
+                    args,
+                    value_buffer,
+                    grid,
+                    block_dim: [r.u32()?, r.u32()?, r.u32()?],
+                    shared_memory_bytes: r.u32()?,
+                }
+            }
+            7 => Request::Synchronize,
+            8 => Request::DropBuffer { id: r.u64()? },
+            9 => Request::FillBuffer {
+                id: r.u64()?,
+                offset: r.u64()?,
+                value: r.bytes()?.to_vec(),
+                count: r.u64()?,
+            },
+            10 => Request::Limits,
+            11 => Request::MemoryInfo,
+            _ => return Err(invalid_data("unknown request")),
+        };
+        Ok(request)
+    }
+}
+
+fn encode_limits(limits: &DeviceLimits) -> Vec<u8> {
+    let mut w = WireWriter::default();
+    for val in limits
+        .max_workgroup_count
+        .iter()
+        .chain(&limits.max_workgroup_size)
+    {
+        w.u32(*val);
+    }
+    w.u32(limits.max_invocations_per_workgroup)
+        .u32(limits.max_storage_buffers_per_stage)
+        .u32(limits.max_shared_memory_size)
+        .u8(limits.shader_f16 as u8)
+        .u8(limits.shader_f64 as u8)
+        .u8(limits.shader_int64_atomics as u8)
+        .u8(limits.subgroups as u8);
+    w.bytes
+}
+
+fn decode_limits(bytes: &[u8]) -> io::Result<DeviceLimits> {
+    let mut r = WireReader { bytes };
+    Ok(DeviceLimits {
+        max_workgroup_count: [r.u32()?, r.u32()?, r.u32()?],
+        max_workgroup_size: [r.u32()?, r.u32()?, r.u32()?],
+        max_invocations_per_workgroup: r.u32()?,
+        max_storage_buffers_per_stage: r.u32()?,
+        max_shared_memory_size: r.u32()?,
+        shader_f16: r.u8()? != 0,
+        shader_f64: r.u8()? != 0,
+        shader_int64_atomics: r.u8()? != 0,
+        subgroups: r.u8()? != 0,
+    })
+}
+
+fn encode_memory_info(info: &MemoryInfo) -> Vec<u8> {
+    let mut w = WireWriter::default();
+    for val in [info.used, info.free, info.total] {
+        match val {
+            Some(val) => w.u8(1).u64(val),
+            None => w.u8(0),
+        };
+    }
+    w.bytes
+}
+
+fn decode_memory_info(bytes: &[u8]) -> io::Result<MemoryInfo> {
+    let mut r = WireReader { bytes };
+    let mut val = || {
+        Ok::<_, io::Error>(match r.u8()? {
+            0 => None,
+            _ => Some(r.u64()?),
+        })
+    };
+    Ok(MemoryInfo {
+        used: val()?,
+        free: val()?,
+        total: val()?,
+    })
+}
+
+fn encode_response(response: &Result<Vec<u8>, String>) -> Vec<u8> {
+    let mut w = WireWriter::default();
+    match response {
+        Ok(bytes) => w.u8(0).bytes(bytes),
+        Err(msg) => w.u8(1).str(msg),
+    };
+    w.bytes
+}
+
+fn decode_response(bytes: &[u8]) -> io::Result<Result<Vec<u8>, String>> {
+    let mut r = WireReader { bytes };
+    match r.u8()? {
+        0 => Ok(Ok(r.bytes()?.to_vec())),
+        _ => Ok(Err(r.str()?.to_string())),
+    }
+}
+
+/*
+ * Client.
+ */
+struct RemoteConnection {
+    writer: BufWriter<TcpStream>,
+    reader: BufReader<TcpStream>,
+}
+
+struct RemoteShared {
+    connection: Mutex<RemoteConnection>,
+    next_id: AtomicU64,
+    /// The server’s device limits, queried on first use.
+    limits: OnceLock<DeviceLimits>,
+}
+
+impl RemoteShared {
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a request, waiting for the server’s answer if it expects one.
+    fn send(&self, request: Request) -> Result<Vec<u8>, RemoteBackendError> {
+        let mut connection = self.connection.lock().unwrap();
+        write_frame(&mut connection.writer, &request.encode())?;
+
+        if request.expects_response() {
+            connection.writer.flush()?;
+            let frame = read_frame(&mut connection.reader)?;
+            decode_response(&frame)?.map_err(RemoteBackendError::Server)
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+/// A backend forwarding all its operations to a [`RemoteServer`] through a TCP connection.
+///
+/// Shaders are still compiled locally (for the compile target of the server’s backend) since
+/// their reflection data is needed for binding arguments, but modules, buffers, and dispatches
+/// all live on the server. This allows thin clients to drive GPU work on another machine while
+/// reusing the exact same `Shader`/`GpuFunction` code.
+///
+/// Operations are pipelined: only module/function loading, buffer reads, device limits, and
+/// [`Backend::synchronize`] wait for the server. Errors from the other operations are reported
+/// by the next operation that waits for the server.
+#[derive(Clone)]
+pub struct Remote {
+    shared: Arc<RemoteShared>,
+    target: CompileTarget,
+}
+
+impl Remote {
+    /// Connects to a [`RemoteServer`].
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, RemoteBackendError> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let writer = BufWriter::new(stream);
+
+        // Handshake.
+        let frame = read_frame(&mut reader)?;
+        let mut r = WireReader { bytes: &frame };
+        if r.take(PROTOCOL_MAGIC.len())? != PROTOCOL_MAGIC {
+            return Err(RemoteBackendError::Protocol(
+                "the server isn’t a slang-hal server".to_string(),
+            ));
+        }
+        let version = r.u32()?;
+        if version != PROTOCOL_VERSION {
+            return Err(RemoteBackendError::Protocol(format!(
+                "unsupported protocol version {version} (expected {PROTOCOL_VERSION})"
+            )));
+        }
+        let target = target_from_wire(r.u8()?).ok_or_else(|| {
+            RemoteBackendError::Protocol("unsupported compile target".to_string())
+        })?;
+
+        Ok(Self {
+            shared: Arc::new(RemoteShared {
+                connection: Mutex::new(RemoteConnection { writer, reader }),
+                next_id: AtomicU64::new(0),
+                limits: OnceLock::new(),
+            }),
+            target,
+        })
+    }
+
+    fn create_buffer<T: DeviceValue>(
+        &self,
+        len: usize,
+        stride: usize,
+        usage: BufferUsages,
+        data: Option<Vec<u8>>,
+    ) -> Result<RemoteBuffer<T>, RemoteBackendError> {
+        let id = self.shared.next_id();
+        let kind = if TypeId::of::<T>() == TypeId::of::<[u32; 3]>() {
+            WireBufferKind::Grid
+        } else {
+            WireBufferKind::Bytes
+        };
+        self.shared.send(Request::CreateBuffer {
+            id,
+            kind,
+            size: (len * stride) as u64,
+            usage: usage.bits(),
+            data,
+        })?;
+        Ok(RemoteBuffer {
+            id,
+            len,
+            stride,
+            shared: self.shared.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The bytes of `value` laid out with the uniform layout rules of the server’s backend.
+    fn uniform_bytes<T: EncaseType + Pod>(&self, value: &T) -> Vec<u8> {
+        match self.compile_target() {
+            CompileTarget::Ptx | CompileTarget::CudaSource | CompileTarget::ShaderSharedLibrary => {
+                bytemuck::bytes_of(value).to_vec()
+            }
+            _ => uniform_bytes(value),
+        }
+    }
+
+    fn read_bytes<T>(
+        &self,
+        buffer: &RemoteBuffer<T>,
+        range: Range<usize>,
+    ) -> Result<Vec<u8>, RemoteBackendError> {
+        self.shared.send(Request::ReadBuffer {
+            id: buffer.id,
+            offset: range.start as u64,
+            len: range.len() as u64,
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteBackendError {
+    #[error(transparent)]
+    ShaderArg(#[from] ShaderArgsError),
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
+    BytemuckPod(#[from] bytemuck::PodCastError),
+    #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error(transparent)]
+    Encase(#[from] encase::internal::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("protocol error: {0}")]
+    Protocol(String),
+    #[error("server error: {0}")]
+    Server(String),
+}
+
+/// A buffer living on the server.
+pub struct RemoteBuffer<T> {
+    id: u64,
+    len: usize,
+    stride: usize,
+    shared: Arc<RemoteShared>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Drop for RemoteBuffer<T> {
+    fn drop(&mut self) {
+        // NOTE: if this fails, the connection is broken anyway.
+        let _ = self.shared.send(Request::DropBuffer { id: self.id });
+    }
+}
+
+/// A range of elements of a [`RemoteBuffer`].
+pub struct RemoteBufferSlice<'b, T> {
+    id: u64,
+    // The byte range of the buffer.
+    range: Range<usize>,
+    _phantom: PhantomData<&'b T>,
+}
+
+/// A module loaded on the server.
+pub struct RemoteModule {
+    id: u64,
+}
+
+/// A function loaded on the server.
+pub struct RemoteFunction {
+    id: u64,
+}
+
+/// Operations are forwarded to the server immediately, so encoders don’t record anything.
+pub struct RemoteEncoder {
+    shared: Arc<RemoteShared>,
+}
+
+/// Operations are forwarded to the server immediately, so passes don’t record anything.
+pub struct RemotePass;
+
+/// A fence of the [`Remote`] backend.
+///
+/// Signaling the fence waits for the server to complete all the work forwarded so far, so the
+/// fence is always signaled.
+pub struct RemoteFence;
+
+impl Fence<Remote> for RemoteFence {
+    fn is_signaled(&self) -> bool {
+        true
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for Remote {
+    const NAME: &'static str = "remote";
+    // NOTE: this is only a placeholder, the actual target depends on the server’s backend. See
+    //       `Remote::compile_target`.
+    const TARGET: CompileTarget = CompileTarget::Wgsl;
+
+    type Error = RemoteBackendError;
+    type Buffer<T: DeviceValue> = RemoteBuffer<T>;
+    type Texture = UnsupportedTexture;
+    type Sampler = UnsupportedSampler;
+    type Timestamps = UnsupportedTimestamps;
+    type Bundle = UnsupportedBundle;
+    type Fence = RemoteFence;
+    type BufferSlice<'b, T: DeviceValue> = RemoteBufferSlice<'b, T>;
+    type Encoder = RemoteEncoder;
+    type Pass = RemotePass;
+    type Module = RemoteModule;
+    type Function = RemoteFunction;
+    type Dispatch<'a> = RemoteDispatch<'a>;
+
+    fn compile_target(&self) -> CompileTarget {
+        self.target
+    }
+
+    fn as_remote(&self) -> Option<&Remote> {
+        Some(self)
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        if let Some(limits) = self.shared.limits.get() {
+            return Ok(*limits);
+        }
+        let limits = decode_limits(&self.shared.send(Request::Limits)?)?;
+        Ok(*self.shared.limits.get_or_init(|| limits))
+    }
+
+    fn memory_info(&self) -> Result<MemoryInfo, Self::Error> {
+        Ok(decode_memory_info(&self.shared.send(Request::MemoryInfo)?)?)
+    }
+
+    /*
+     * Module/function loading.
+     */
+    fn load_module(&self, data: &str) -> Result<Self::Module, Self::Error> {
+        // NOTE: the server takes care of any backend-specific preprocessing.
+        self.load_module_bytes(data.as_bytes())
+    }
+
+    fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
+        let id = self.shared.next_id();
+        self.shared.send(Request::LoadModule {
+            id,
+            bytes: bytes.to_vec(),
+        })?;
+        Ok(RemoteModule { id })
+    }
+
+    fn load_function(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+    ) -> Result<Self::Function, Self::Error> {
+        let id = self.shared.next_id();
+        self.shared.send(Request::LoadFunction {
+            id,
+            module: module.id,
+            entry_point: entry_point.to_string(),
+        })?;
+        Ok(RemoteFunction { id })
+    }
+
+    /*
+     * Kernel dispatch.
+     */
+    fn begin_encoding(&self) -> Self::Encoder {
+        RemoteEncoder {
+            shared: self.shared.clone(),
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        // NOTE: operations are forwarded through the same connection, so they are executed in
+        //       order by the server anyway.
+        Ok(self.clone())
+    }
+
+    fn begin_dispatch<'a>(
+        &'a self,
+        _pass: &'a mut Self::Pass,
+        function: &'a Self::Function,
+        label: &'a str,
+    ) -> Self::Dispatch<'a> {
+        RemoteDispatch {
+            backend: self,
+            function,
+            label,
+            args: vec![],
+            value_buffer: None,
+            shared_memory_bytes: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        self.shared.send(Request::Synchronize)?;
+        Ok(())
+    }
+
+    fn submit(&self, _encoder: Self::Encoder) -> Result<(), Self::Error> {
+        // Operations were already forwarded to the server.
+        Ok(())
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        self.submit(encoder)?;
+        // NOTE: the server doesn’t track submissions, so this waits for all the forwarded work.
+        let shared = self.shared.clone();
+        Ok(SubmissionFuture::new(async move {
+            shared.send(Request::Synchronize)?;
+            Ok(())
+        }))
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        Ok(RemoteFence)
+    }
+
+    fn signal_fence(&self, _fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        // NOTE: the server doesn’t track submissions so we wait for all the forwarded work,
+        //       which leaves the fence signaled.
+        self.synchronize()
+    }
+
+    fn wait_fence(&self, _fence: &Self::Fence) -> Result<(), Self::Error> {
+        // The work preceding the fence’s signal already completed.
+        Ok(())
+    }
+
+    /*
+     * Buffer handling.
+     */
+    fn init_buffer<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes = bytemuck::try_cast_slice(data)?.to_vec();
+        self.create_buffer(data.len(), size_of::<T>(), usage, Some(bytes))
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let len = elements_len::<T>(bytes.len())?;
+        self.create_buffer(len, size_of::<T>(), usage, Some(bytes.to_vec()))
+    }
+
+    fn init_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let mut bytes = vec![];
+        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
+        bytes_buffer.write(data).unwrap();
+        let stride = T::min_size().get() as usize;
+        self.create_buffer(data.len(), stride, usage, Some(bytes))
+    }
+
+    unsafe fn uninit_buffer<T: DeviceValue + Pod>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        self.create_buffer(len, size_of::<T>(), usage, None)
+    }
+
+    unsafe fn uninit_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        self.create_buffer(len, T::min_size().get() as usize, usage, None)
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_at(buffer, 0, data)
+    }
+
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.shared.send(Request::WriteBuffer {
+            id: buffer.id,
+            offset: (offset * buffer.stride) as u64,
+            bytes: bytemuck::try_cast_slice(data)?.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let mut bytes = vec![];
+        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
+        bytes_buffer.write(data).unwrap();
+        self.shared.send(Request::WriteBuffer {
+            id: buffer.id,
+            offset: (offset * buffer.stride) as u64,
+            bytes,
+        })?;
+        Ok(())
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        // The bytes have to be serialized into the request anyway.
+        let mut bytes = vec![0; len];
+        write(&mut bytes);
+        self.shared.send(Request::WriteBuffer {
+            id: buffer.id,
+            offset: byte_offset as u64,
+            bytes,
+        })?;
+        Ok(())
+    }
+
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let bytes = self.uniform_bytes(value);
+        let usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        self.create_buffer(1, bytes.len(), usage, Some(bytes))
+    }
+
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.shared.send(Request::WriteBuffer {
+            id: buffer.id,
+            offset: 0,
+            bytes: self.uniform_bytes(value),
+        })?;
+        Ok(())
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let bytes = self.read_bytes(buffer, 0..buffer.len * buffer.stride)?;
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(data)?;
+        let len = out.len().min(bytes.len());
+        out[..len].copy_from_slice(&bytes[..len]);
+        Ok(())
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let bytes = self.read_bytes(buffer, 0..buffer.len * buffer.stride)?;
+        let mut result = vec![];
+        let encase_buffer = StorageBuffer::new(&bytes);
+        encase_buffer.read(&mut result)?;
+        let len = result.len().min(data.len());
+        data[..len].copy_from_slice(&result[..len]);
+        Ok(())
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        data: &mut [T],
+    ) -> Result<(), Self::Error> {
+        // The server always reads through a staging buffer.
+        self.read_buffer(buffer, data).await
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, buffer.len);
+        let bytes = self.read_bytes(
+            buffer,
+            range.start * buffer.stride..range.end * buffer.stride,
+        )?;
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        let len = out.len().min(bytes.len());
+        out[..len].copy_from_slice(&bytes[..len]);
+        Ok(())
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        // The server always reads through a staging buffer.
+        self.read_buffer_range(buffer, range, out).await
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        let range = buffer_range(range, buffer.len);
+        self.shared.send(Request::FillBuffer {
+            id: buffer.id,
+            offset: (range.start * buffer.stride) as u64,
+            value: bytemuck::bytes_of(&value).to_vec(),
+            count: range.len() as u64,
+        })?;
+        Ok(())
+    }
+}
+
+impl RemoteEncoder {
+    fn copy_elements<T>(
+        &mut self,
+        source: &RemoteBuffer<T>,
+        source_offset: usize,
+        target: &mut RemoteBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), RemoteBackendError> {
+        let sz = source.stride as u64;
+        self.shared.send(Request::CopyBuffer {
+            source: source.id,
+            source_offset: source_offset as u64 * sz,
+            target: target.id,
+            target_offset: target_offset as u64 * sz,
+            len: copy_len as u64 * sz,
+        })?;
+        Ok(())
+    }
+}
+
+impl Encoder<Remote> for RemoteEncoder {
+    fn begin_pass(&mut self, _label: &str) -> RemotePass {
+        RemotePass
+    }
+
+    fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
+        &mut self,
+        source: &RemoteBuffer<T>,
+        source_offset: usize,
+        target: &mut RemoteBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), RemoteBackendError> {
+        self.copy_elements(source, source_offset, target, target_offset, copy_len)
+    }
+
+    fn copy_buffer_to_buffer_encased<T: DeviceValue + ShaderType>(
+        &mut self,
+        source: &RemoteBuffer<T>,
+        source_offset: usize,
+        target: &mut RemoteBuffer<T>,
+        target_offset: usize,
+        copy_len: usize,
+    ) -> Result<(), RemoteBackendError> {
+        self.copy_elements(source, source_offset, target, target_offset, copy_len)
+    }
+}
+
+pub struct RemoteDispatch<'a> {
+    backend: &'a Remote,
+    function: &'a RemoteFunction,
+    label: &'a str,
+    args: Vec<WireArg>,
+    value_buffer: Option<ShaderBinding>,
+    shared_memory_bytes: u32,
+    // The bound buffers must outlive the dispatch, same as for the other backends.
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl RemoteDispatch<'_> {
+    fn push_arg(&mut self, binding: ShaderBinding, name: &str, id: u64, range: Range<usize>) {
+        self.args.push(WireArg {
+            name: name.to_string(),
+            binding,
+            resource: WireResource::Buffer {
+                id,
+                range: range.start as u64..range.end as u64,
+            },
+        });
+    }
+}
+
+impl<'a> Dispatch<'a, Remote> for RemoteDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        self.args.push(WireArg {
+            name: name.to_string(),
+            binding,
+            resource: WireResource::Value(bytemuck::bytes_of(value).to_vec()),
+        });
+        Ok(())
+    }
+
+    fn set_value_buffer_binding(&mut self, binding: ShaderBinding) {
+        self.value_buffer = Some(binding);
+    }
+
+    fn set_dynamic_shared_memory(&mut self, bytes: u32) {
+        self.shared_memory_bytes = bytes;
+    }
+
+    fn bind_array(
+        &mut self,
+        _binding: ShaderBinding,
+        name: &str,
+        _len: usize,
+    ) -> Result<(), ShaderArgsError> {
+        // NOTE: the wire protocol only forwards individual resources, so the server wouldn’t
+        //       know how to group them.
+        Err(ShaderArgsError::UnsupportedArray(name.to_owned()))
+    }
+
+    fn launch<'b>(
+        self,
+        grid: impl Into<DispatchGrid<'b, Remote>>,
+        block_dim: [u32; 3],
+    ) -> Result<(), RemoteBackendError> {
+        let grid = match grid.into() {
+            DispatchGrid::Direct(grid) => WireGrid::Direct(grid),
+            DispatchGrid::Indirect(grid, offset) => WireGrid::Indirect(grid.id, offset as u64),
+        };
+        self.backend.shared.send(Request::Dispatch {
+            function: self.function.id,
+            label: self.label.to_string(),
+            args: self.args,
+            value_buffer: self.value_buffer,
+            grid,
+            block_dim,
+            shared_memory_bytes: self.shared_memory_bytes,
+        })?;
+        Ok(())
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Remote> for RemoteBuffer<T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut RemoteDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.push_arg(binding, name, self.id, 0..self.len * self.stride);
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(self.stride),
+            size_bytes: Some(self.len * self.stride),
+            writable: None,
+        })
+    }
+}
+
+impl<'b, T: DeviceValue> ShaderArgs<'b, Remote> for RemoteBufferSlice<'_, T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut RemoteDispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.push_arg(binding, name, self.id, self.range.clone());
+        Ok(())
+    }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: Some(size_of::<T>()),
+            size_bytes: Some(self.range.len()),
+            writable: None,
+        })
+    }
+}
+
+impl<T: DeviceValue> Buffer<Remote, T> for RemoteBuffer<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn slice(&self, range: impl RangeBounds<usize>) -> RemoteBufferSlice<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(i) => *i,
+            Bound::Excluded(i) => *i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(i) => *i + 1,
+            Bound::Excluded(i) => *i,
+            Bound::Unbounded => self.len,
+        };
+        RemoteBufferSlice {
+            id: self.id,
+            range: start * self.stride..end * self.stride,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.len * self.stride
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<RemoteBufferSlice<'_, U>, BufferCastError> {
+        Ok(RemoteBufferSlice {
+            id: self.id,
+            range: view_bytes::<U>(self.len * self.stride, byte_offset, len)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/*
+ * Server.
+ */
+enum ServerBuffer<B: Backend> {
+    Bytes(B::Buffer<u8>),
+    Grid(B::Buffer<[u32; 3]>),
+}
+
+impl<B: Backend> ServerBuffer<B> {
+    /// The range of elements covering the `len` bytes starting at the byte `offset`.
+    ///
+    /// Fails if the range is out of bounds, or if it doesn’t cover whole elements.
+    fn element_range(&self, id: u64, offset: u64, len: u64) -> Result<Range<usize>, String> {
+        let (num_elements, sz) = match self {
+            ServerBuffer::Bytes(buffer) => (buffer.len(), 1),
+            ServerBuffer::Grid(buffer) => (buffer.len(), size_of::<[u32; 3]>()),
+        };
+        let size = (num_elements * sz) as u64;
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= size)
+            .ok_or_else(|| {
+                format!("byte range {offset}+{len} out of bounds of buffer {id} ({size} bytes)")
+            })?;
+        if offset % sz as u64 != 0 || len % sz as u64 != 0 {
+            return Err(format!(
+                "byte range {offset}+{len} of buffer {id} isn’t aligned to its elements"
+            ));
+        }
+        Ok(offset as usize / sz..end as usize / sz)
+    }
+}
+
+enum ServerBufferSlice<'b, B: Backend> {
+    Bytes(B::BufferSlice<'b, u8>),
+    Grid(B::BufferSlice<'b, [u32; 3]>),
+}
+
+/// The resources created by a client.
+struct ServerSession<B: Backend> {
+    modules: HashMap<u64, B::Module>,
+    functions: HashMap<u64, B::Function>,
+    buffers: HashMap<u64, ServerBuffer<B>>,
+}
+
+impl<B: Backend> ServerSession<B> {
+    fn buffer(&self, id: u64) -> Result<&ServerBuffer<B>, String> {
+        self.buffers
+            .get(&id)
+            .ok_or_else(|| format!("unknown buffer {id}"))
+    }
+
+    fn buffer_mut(&mut self, id: u64) -> Result<&mut ServerBuffer<B>, String> {
+        self.buffers
+            .get_mut(&id)
+            .ok_or_else(|| format!("unknown buffer {id}"))
+    }
+}
+
+/// A server running the operations sent by [`Remote`] clients on its own backend.
+///
+/// Clients aren’t authenticated, and the modules they send are loaded and run on the server’s
+/// device as-is, so the server must only be reachable by trusted clients (e.g. on a private
+/// network, or through an authenticated tunnel). Backends loading native code (the CPU backend)
+/// can’t be served since clients could run any code on the server.
+pub struct RemoteServer<B: Backend> {
+    backend: B,
+}
+
+impl<B: Backend> RemoteServer<B> {
+    /// Creates a server running all the client operations on `backend`.
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    /// The backend running the client operations.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Accepts and serves clients, one at a time, until `listener` fails.
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, addr) = listener.accept()?;
+            log::info!("Remote client connected: {addr}");
+            if let Err(e) = self.serve_client(stream) {
+                log::info!("Remote client {addr} disconnected: {e}");
+            }
+        }
+    }
+
+    /// Serves a single client until it disconnects.
+    ///
+    /// All the resources created by the client are released once it disconnects.
+    pub fn serve_client(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+
+        // Handshake.
+        // NOTE: the shared libraries sent by clients would be loaded as native code, giving them
+        //       full control over the server.
+        let target = self.backend.compile_target();
+        if matches!(target, CompileTarget::ShaderSharedLibrary) {
+            return Err(invalid_data(
+                "serving backends loading native code isn’t supported",
+            ));
+        }
+        let target =
+            target_to_wire(target).ok_or_else(|| invalid_data("unsupported compile target"))?;
+        let mut handshake = WireWriter::default();
+        handshake.u32(PROTOCOL_VERSION).u8(target);
+        let mut frame = PROTOCOL_MAGIC.to_vec();
+        frame.extend_from_slice(&handshake.bytes);
+        write_frame(&mut writer, &frame)?;
+        writer.flush()?;
+
+        let mut session = ServerSession {
+            modules: HashMap::new(),
+            functions: HashMap::new(),
+            buffers: HashMap::new(),
+        };
+        let mut deferred_error = None;
+
+        loop {
+            let frame = match read_frame(&mut reader) {
+                Ok(frame) => frame,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            let request = Request::decode(&frame)?;
+            let expects_response = request.expects_response();
+            let result = self.execute(&mut session, request);
+
+            if expects_response {
+                let response = match deferred_error.take() {
+                    Some(e) => Err(e),
+                    None => result,
+                };
+                write_frame(&mut writer, &encode_response(&response))?;
+                writer.flush()?;
+            } else if let Err(e) = result {
+                // Only keep the first error, since the following ones are likely caused by it.
+                deferred_error.get_or_insert(e);
+            }
+        }
+    }
+
+    fn execute(&self, session: &mut ServerSession<B>, request: Request) -> Result<Vec<u8>, String> {
+        let backend = &self.backend;
+        match request {
+            Request::LoadModule { id, bytes } => {
+                let module = match std::str::from_utf8(&bytes) {
+                    // Text modules go through `load_module` for backend-specific preprocessing.
+                    Ok(text) => backend.load_module(text),
+                    Err(_) => backend.load_module_bytes(&bytes),
+                }
+                .map_err(|e| e.to_string())?;
+                session.modules.insert(id, module);
+            }
+            Request::LoadFunction {
+                id,
+                module,
+                entry_point,
+            } => {
+                let module = session
+                    .modules
+                    .get(&module)
+                    .ok_or_else(|| format!("unknown module {module}"))?;
+                let function = backend
+                    .load_function(module, &entry_point)
+                    .map_err(|e| e.to_string())?;
+                session.functions.insert(id, function);
+            }
+            Request::CreateBuffer {
+                id,
+                kind,
+                size,
+                usage,
+                data,
+            } => {
+                // Buffers are never mapped by the client so we make them usable for copies.
+                let usage = (BufferUsages::from_bits_truncate(usage)
+                    - BufferUsages::MAP_READ
+                    - BufferUsages::MAP_WRITE)
+                    | BufferUsages::COPY_SRC
+                    | BufferUsages::COPY_DST;
+                let buffer = match (kind, data) {
+                    (WireBufferKind::Bytes, Some(data)) => {
+                        backend.init_buffer(&data, usage).map(ServerBuffer::Bytes)
+                    }
+                    (WireBufferKind::Bytes, None) => {
+                        // SAFETY: the client is responsible for initializing the buffer.
+                        unsafe { backend.uninit_buffer(size as usize, usage) }
+                            .map(ServerBuffer::Bytes)
+                    }
+                    (WireBufferKind::Grid, Some(data)) => backend
+                        .init_buffer(&bytemuck::pod_collect_to_vec(&data), usage)
+                        .map(ServerBuffer::Grid),
+                    (WireBufferKind::Grid, None) => {
+                        let len = size as usize / size_of::<[u32; 3]>();
+                        // SAFETY: the client is responsible for initializing the buffer.
+                        unsafe { backend.uninit_buffer(len, usage) }.map(ServerBuffer::Grid)
+                    }
+                }
+                .map_err(|e| e.to_string())?;
+                session.buffers.insert(id, buffer);
+            }
+            Request::WriteBuffer { id, offset, bytes } => {
+                let buffer = session.buffer_mut(id)?;
+                let range = buffer.element_range(id, offset, bytes.len() as u64)?;
+                match buffer {
+                    ServerBuffer::Bytes(buffer) => {
+                        backend.write_buffer_at(buffer, range.start, &bytes)
+                    }
+                    ServerBuffer::Grid(buffer) => backend.write_buffer_at(
+                        buffer,
+                        range.start,
+                        &bytemuck::pod_collect_to_vec(&bytes),
+                    ),
+                }
+                .map_err(|e| e.to_string())?;
+            }
+            Request::FillBuffer {
+                id,
+                offset,
+                value,
+                count,
+            } => {
+                let buffer = session.buffer_mut(id)?;
+                let len = (value.len() as u64)
+                    .checked_mul(count)
+                    .ok_or_else(|| format!("fill of buffer {id} too large"))?;
+                let range = buffer.element_range(id, offset, len)?;
+                match buffer {
+                    ServerBuffer::Bytes(buffer) => {
+                        if value.iter().all(|b| Some(b) == value.first()) {
+                            let byte = value.first().copied().unwrap_or_default();
+                            backend.fill_buffer(buffer, byte, range)
+                        } else {
+                            // The buffer is untyped here, so copy the filled range from a
+                            // temporary buffer instead.
+                            copy_bytes_to(
+                                backend,
+                                &value.repeat(count as usize),
+                                buffer,
+                                range.start,
+                            )
+                        }
+                    }
+                    ServerBuffer::Grid(buffer) => {
+                        let grid = bytemuck::try_pod_read_unaligned(&value)
+                            .map_err(|_| "invalid grid fill value".to_string())?;
+                        backend.fill_buffer(buffer, grid, range)
+                    }
+                }
+                .map_err(|e| e.to_string())?;
+            }
+            Request::ReadBuffer { id, offset, len } => {
+                let buffer = session.buffer(id)?;
+                let range = buffer.element_range(id, offset, len)?;
+                let bytes = match buffer {
+                    ServerBuffer::Bytes(buffer) => {
+                        let mut bytes = vec![0u8; range.len()];
+                        futures::executor::block_on(
+                            backend.slow_read_range(buffer, range, &mut bytes),
+                        )
+                        .map(|_| bytes)
+                    }
+                    ServerBuffer::Grid(buffer) => {
+                        let mut grid = vec![[0u32; 3]; range.len()];
+                        futures::executor::block_on(
+                            backend.slow_read_range(buffer, range, &mut grid),
+                        )
+                        .map(|_| bytemuck::cast_slice(&grid).to_vec())
+                    }
+                }
+                .map_err(|e| e.to_string())?;
+                return Ok(bytes);
+            }
+            Request::CopyBuffer {
+                source,
+                source_offset,
+                target,
+                target_offset,
+                len,
+            } => {
+                // Temporarily remove the target so we can borrow the source at the same time.
+                let mut target_buffer = session
+                    .buffers
+                    .remove(&target)
+                    .ok_or_else(|| format!("unknown buffer {target}"))?;
+                let mut encoder = backend.begin_encoding();
+                let result = session.buffer(source).and_then(|source_buffer| {
+                    let source_range = source_buffer.element_range(source, source_offset, len)?;
+                    let target_range = target_buffer.element_range(target, target_offset, len)?;
+                    match (source_buffer, &mut target_buffer) {
+                        (ServerBuffer::Bytes(src), ServerBuffer::Bytes(dst)) => encoder
+                            .copy_buffer_to_buffer(
+                                src,
+                                source_range.start,
+                                dst,
+                                target_range.start,
+                                source_range.len(),
+                            ),
+                        (ServerBuffer::Grid(src), ServerBuffer::Grid(dst)) => encoder
+                            .copy_buffer_to_buffer(
+                                src,
+                                source_range.start,
+                                dst,
+                                target_range.start,
+                                source_range.len(),
+                            ),
+                        _ => return Err("copy between incompatible buffers".to_string()),
+                    }
+                    .map_err(|e| e.to_string())
+                });
+                session.buffers.insert(target, target_buffer);
+                result?;
+                backend.submit(encoder).map_err(|e| e.to_string())?;
+            }
+            Request::Dispatch {
+                function,
+                label,
+                args,
+                value_buffer,
+                grid,
+                block_dim,
+                shared_memory_bytes,
+            } => {
+                let function = session
+                    .functions
+                    .get(&function)
+                    .ok_or_else(|| format!("unknown function {function}"))?;
+                let slices = args
+                    .iter()
+                    .map(|arg| {
+                        let WireResource::Buffer { id, range } = &arg.resource else {
+                            return Ok(None);
+                        };
+                        let buffer = session.buffer(*id)?;
+                        let len = range.end.checked_sub(range.start).ok_or_else(|| {
+                            format!("invalid byte range {range:?} of buffer {id}")
+                        })?;
+                        let range = buffer.element_range(*id, range.start, len)?;
+                        Ok(Some(match buffer {
+                            ServerBuffer::Bytes(buffer) => {
+                                ServerBufferSlice::<B>::Bytes(buffer.slice(range))
+                            }
+                            ServerBuffer::Grid(buffer) => {
+                                ServerBufferSlice::Grid(buffer.slice(range))
+                            }
+                        }))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let grid = match grid {
+                    WireGrid::Direct(grid) => DispatchGrid::Direct(grid),
+                    WireGrid::Indirect(id, offset) => match session.buffers.get(&id) {
+                        Some(ServerBuffer::Grid(grid)) if offset < grid.len() as u64 => {
+                            DispatchGrid::Indirect(grid, offset as usize)
+                        }
+                        _ => return Err(format!("invalid indirect dispatch buffer {id}")),
+                    },
+                };
+
+                let mut encoder = backend.begin_encoding();
+                {
+                    let mut pass = encoder.begin_pass("");
+                    let mut dispatch = backend.begin_dispatch(&mut pass, function, &label);
+                    if let Some(binding) = value_buffer {
+                        dispatch.set_value_buffer_binding(binding);
+                    }
+                    dispatch.set_dynamic_shared_memory(shared_memory_bytes);
+                    for (arg, slice) in args.iter().zip(slices.iter()) {
+                        match (slice, &arg.resource) {
+                            (Some(ServerBufferSlice::Bytes(s)), _) => {
+                                s.write_arg(arg.binding, &arg.name, &mut dispatch)
+                            }
+                            (Some(ServerBufferSlice::Grid(s)), _) => {
+                                s.write_arg(arg.binding, &arg.name, &mut dispatch)
+                            }
+                            (None, WireResource::Value(bytes)) => {
+                                write_value_bytes::<B>(&mut dispatch, arg, bytes)?
+                            }
+                            (None, WireResource::Buffer { .. }) => unreachable!(),
+                        }
+                        .map_err(|e| e.to_string())?;
+                    }
+                    dispatch
+                        .launch(grid, block_dim)
+                        .map_err(|e| e.to_string())?;
+                }
+                backend.submit(encoder).map_err(|e| e.to_string())?;
+            }
+            Request::Synchronize => {
+                backend.synchronize().map_err(|e| e.to_string())?;
+            }
+            Request::DropBuffer { id } => {
+                session.buffers.remove(&id);
+            }
+            Request::Limits => {
+                let limits = backend.limits().map_err(|e| e.to_string())?;
+                return Ok(encode_limits(&limits));
+            }
+            Request::MemoryInfo => {
+                let info = backend.memory_info().map_err(|e| e.to_string())?;
+                return Ok(encode_memory_info(&info));
+            }
+        }
+
+        Ok(vec![])
+    }
+}
+
+/// Writes `bytes` into `target`, starting at the byte `offset`, through a buffer-to-buffer copy.
+fn copy_bytes_to<B: Backend>(
+    backend: &B,
+    bytes: &[u8],
+    target: &mut B::Buffer<u8>,
+    offset: usize,
+) -> Result<(), B::Error> {
+    let source = backend.init_buffer(bytes, BufferUsages::COPY_SRC)?;
+    let mut encoder = backend.begin_encoding();
+    encoder.copy_buffer_to_buffer(&source, 0, target, offset, bytes.len())?;
+    backend.submit(encoder)?;
+    // Don’t drop the source buffer while the copy is still running.
+    backend.synchronize()
+}
+
+/// Binds the bytes of an argument passed by value to a dispatch on the server.
+///
+/// The value type isn’t known on the server so it is bound as a byte array of the same size.
+fn write_value_bytes<'a, B: Backend>(
+    dispatch: &mut B::Dispatch<'a>,
+    arg: &WireArg,
+    bytes: &'a [u8],
+) -> Result<Result<(), ShaderArgsError>, String> {
+    macro_rules! write_sized {
+        ($($len: literal),*) => {
+            match bytes.len() {
+                $($len => {
+                    let value: &[u8; $len] = bytes.try_into().unwrap();
+                    Ok(dispatch.write_value(arg.binding, &arg.name, value))
+                })*
+                len => Err(format!("unsupported size ({len} bytes) for argument {}", arg.name)),
+            }
+        };
+    }
+
+    write_sized!(
+        1, 2, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44, 48, 52, 56, 60, 64
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Mock;
+
+    /// Connects a client to a server running on the mock backend, on a loopback socket.
+    fn loopback() -> (Remote, std::thread::JoinHandle<io::Result<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept()?;
+            RemoteServer::new(Mock::new()).serve_client(stream)
+        });
+        (Remote::connect(addr).unwrap(), server)
+    }
+
+    #[test]
+    fn buffer_round_trip() {
+        let (remote, server) = loopback();
+        assert_eq!(remote.compile_target(), CompileTarget::Wgsl);
+
+        let mut buffer = remote
+            .init_buffer(&[1u32, 2, 3, 4], BufferUsages::STORAGE)
+            .unwrap();
+        remote.write_buffer_at(&mut buffer, 1, &[20, 30]).unwrap();
+        remote.fill_buffer(&mut buffer, 40, 3..).unwrap();
+
+        let mut data = [0u32; 4];
+        futures::executor::block_on(remote.read_buffer(&buffer, &mut data)).unwrap();
+        assert_eq!(data, [1, 20, 30, 40]);
+
+        drop(buffer);
+        drop(remote);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn invalid_requests_are_reported() {
+        let (remote, server) = loopback();
+        let buffer = remote
+            .init_buffer(&[1u32, 2, 3, 4], BufferUsages::STORAGE)
+            .unwrap();
+
+        // Out-of-bounds and unknown buffers are reported by the next answered request instead
+        // of taking down the server.
+        let invalid = [
+            Request::WriteBuffer {
+                id: buffer.id,
+                offset: 12,
+                bytes: vec![0; 8],
+            },
+            Request::WriteBuffer {
+                id: buffer.id,
+                offset: u64::MAX,
+                bytes: vec![0; 8],
+            },
+            Request::FillBuffer {
+                id: buffer.id,
+                offset: 0,
+                value: vec![0; 4],
+                count: u64::MAX,
+            },
+            Request::CopyBuffer {
+                source: buffer.id,
+                source_offset: 8,
+                target: buffer.id + 1000,
+                target_offset: 0,
+                len: 16,
+            },
+            Request::ReadBuffer {
+                id: buffer.id,
+                offset: 0,
+                len: 32,
+            },
+        ];
+        for request in invalid {
+            let error = if request.expects_response() {
+                remote.shared.send(request)
+            } else {
+                remote.shared.send(request).unwrap();
+                remote.synchronize().map(|_| vec![])
+            };
+            assert!(matches!(error, Err(RemoteBackendError::Server(_))));
+            assert!(remote.synchronize().is_ok());
+        }
+
+        // The server is still up.
+        let mut data = [0u32; 4];
+        futures::executor::block_on(remote.read_buffer(&buffer, &mut data)).unwrap();
+        assert_eq!(data, [1, 2, 3, 4]);
+
+        drop(buffer);
+        drop(remote);
+        server.join().unwrap().unwrap();
+    }
+}
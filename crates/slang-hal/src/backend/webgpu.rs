@@ -1,8 +1,15 @@
 use crate::ShaderArgs;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backend::DeviceInfo;
 use crate::backend::{
-    Backend, DeviceValue, Dispatch, DispatchGrid, EncaseType, Encoder, ShaderBinding,
+    Backend, BindingLayout, BufferCastError, DeviceLimits, DeviceValue, Dispatch, DispatchGrid,
+    EncaseType, Encoder, Fence, FunctionOptions, MemoryInfo, PassTimestamps, SamplerDescriptor,
+    ShaderBinding, SubmissionFuture, Texture as _, TextureDataError, UnsupportedError,
+    buffer_range, check_texture_data, elements_len, repeat_bytes, texel_size, texture_bytes_len,
+    uniform_bytes, view_bytes,
 };
-use crate::shader::ShaderArgsError;
+use crate::profiler::{Profiler, ProfilingReport};
+use crate::shader::{ArgInfo, ShaderArgsError};
 use async_channel::RecvError;
 use bytemuck::Pod;
 use encase::{ShaderType, StorageBuffer};
@@ -10,22 +17,31 @@ use minislang::shader_slang;
 use regex::Regex;
 use smallvec::SmallVec;
 use std::borrow::Cow;
-use std::ops::RangeBounds;
+use std::collections::HashMap;
+use std::ops::{Range, RangeBounds};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::wgt::CommandEncoderDescriptor;
 use wgpu::{
     Adapter, Buffer, BufferAddress, BufferDescriptor, BufferSlice, BufferUsages, BufferView,
-    CommandEncoder, ComputePass, ComputePassDescriptor, ComputePipeline, ComputePipelineDescriptor,
-    Device, Instance, PipelineCompilationOptions, PollError, Queue, ShaderModule,
-    ShaderRuntimeChecks,
+    CommandEncoder, ComputePass, ComputePassDescriptor, ComputePassTimestampWrites,
+    ComputePipeline, ComputePipelineDescriptor, Device, Extent3d, Instance,
+    PipelineCompilationOptions, PollError, QuerySetDescriptor, QueryType, Queue, ShaderModule,
+    ShaderRuntimeChecks, TextureDescriptor, TextureFormat, TextureUsages, TextureView,
 };
 
 /// Helper struct to initialize a device and its queue.
 pub struct WebGpu {
-    _instance: Instance, // TODO: do we have to keep this around?
-    _adapter: Adapter,   // TODO: do we have to keep this around?
+    // NOTE: these are `None` if the backend was created from an existing device.
+    _instance: Option<Instance>, // TODO: do we have to keep this around?
+    adapter: Option<Adapter>,
     device: Device,
     queue: Queue,
+    staging_cache: Arc<StagingCache>,
+    pipeline_cache: Option<Arc<WebGpuPipelineCache>>,
+    /// The profiling state, if profiling is enabled.
+    profiler: Option<Arc<Profiler<WebGpu>>>,
     hacks: Vec<(Regex, String)>,
     /// If this flag is set, every buffer created by this backend will have the
     /// `BufferUsages::COPY_SRC` flag. Useful for debugging.
@@ -38,15 +54,90 @@ impl WebGpu {
     }
 
     /// Initializes a wgpu instance and create its queue.
+    ///
+    /// This picks the default high-performance adapter. Use [`WebGpu::with_options`] to
+    /// configure the adapter selection, or [`WebGpu::with_device`] and
+    /// [`WebGpu::with_device_matching`] to target a specific device.
     pub async fn new(features: wgpu::Features, limits: wgpu::Limits) -> anyhow::Result<Self> {
-        let instance = wgpu::Instance::default();
+        Self::with_options(WebGpuOptions {
+            features,
+            limits,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Initializes a wgpu instance and create its queue, selecting the adapter according to
+    /// `options`.
+    pub async fn with_options(options: WebGpuOptions<'_>) -> anyhow::Result<Self> {
+        let instance = options.instance.unwrap_or_else(|| {
+            wgpu::Instance::new(&wgpu::InstanceDescriptor {
+                backends: options.backends,
+                ..Default::default()
+            })
+        });
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                ..Default::default()
+                power_preference: options.power_preference,
+                force_fallback_adapter: options.force_fallback_adapter,
+                compatible_surface: options.compatible_surface,
             })
             .await
             .map_err(|_| anyhow::anyhow!("Failed to initialize gpu adapter."))?;
+        Self::from_adapter(instance, adapter, options.features, options.limits).await
+    }
+
+    /// Lists the adapters available to wgpu, in the order expected by [`WebGpu::with_device`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enumerate_devices() -> Vec<DeviceInfo> {
+        wgpu::Instance::default()
+            .enumerate_adapters(wgpu::Backends::all())
+            .iter()
+            .enumerate()
+            .map(|(index, adapter)| device_info(index, &adapter.get_info()))
+            .collect()
+    }
+
+    /// Initializes a wgpu instance on the `index`-th adapter listed by [`WebGpu::enumerate_devices`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn with_device(
+        index: usize,
+        features: wgpu::Features,
+        limits: wgpu::Limits,
+    ) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| anyhow::anyhow!("No gpu adapter with index {index}."))?;
+        Self::from_adapter(instance, adapter, features, limits).await
+    }
+
+    /// Initializes a wgpu instance on the first adapter for which `predicate` returns `true`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn with_device_matching(
+        mut predicate: impl FnMut(&DeviceInfo) -> bool,
+        features: wgpu::Features,
+        limits: wgpu::Limits,
+    ) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .into_iter()
+            .enumerate()
+            .find(|(index, adapter)| predicate(&device_info(*index, &adapter.get_info())))
+            .map(|(_, adapter)| adapter)
+            .ok_or_else(|| anyhow::anyhow!("No gpu adapter matches the device predicate."))?;
+        Self::from_adapter(instance, adapter, features, limits).await
+    }
+
+    async fn from_adapter(
+        instance: Instance,
+        adapter: Adapter,
+        features: wgpu::Features,
+        limits: wgpu::Limits,
+    ) -> anyhow::Result<Self> {
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
@@ -58,14 +149,41 @@ impl WebGpu {
             .await
             .map_err(|e| anyhow::anyhow!("{:?}", e))?;
 
-        Ok(Self {
-            _instance: instance,
-            _adapter: adapter,
+        Ok(Self::from_raw_parts(instance, adapter, device, queue))
+    }
+
+    /// Creates a backend running on an existing `wgpu` device and queue.
+    ///
+    /// This lets compute kernels run inside an application already using `wgpu` (e.g. for
+    /// rendering), sharing buffers with it. The adapter isn’t known in this case so
+    /// [`Self::load_pipeline_cache`] is a no-op. Use [`Self::from_raw_parts`] if the adapter is
+    /// available.
+    pub fn from_device(device: Device, queue: Queue) -> Self {
+        Self {
+            _instance: None,
+            adapter: None,
             device,
             queue,
+            staging_cache: Arc::default(),
+            pipeline_cache: None,
+            profiler: None,
             force_buffer_copy_src: false,
             hacks: vec![],
-        })
+        }
+    }
+
+    /// Creates a backend running on an existing `wgpu` device and queue, created from `adapter`.
+    pub fn from_raw_parts(
+        instance: Instance,
+        adapter: Adapter,
+        device: Device,
+        queue: Queue,
+    ) -> Self {
+        Self {
+            _instance: Some(instance),
+            adapter: Some(adapter),
+            ..Self::from_device(device, queue)
+        }
     }
 
     pub fn append_hack(&mut self, regex: Regex, replace_pattern: String) {
@@ -81,6 +199,473 @@ impl WebGpu {
     pub fn queue(&self) -> &Queue {
         &self.queue
     }
+
+    /// The `wgpu` adapter, unless this backend was created with [`Self::from_device`].
+    pub fn adapter(&self) -> Option<&Adapter> {
+        self.adapter.as_ref()
+    }
+
+    /// Loads the persistent pipeline cache of this device’s adapter from the directory `dir`,
+    /// and uses it for all the pipelines created afterward.
+    ///
+    /// The compiled pipelines are keyed by shader module and entry point by the driver, so
+    /// subsequent runs calling [`Self::save_pipeline_cache`] then this function skip the
+    /// compilation of the pipelines that didn’t change. The cache file is named after the
+    /// adapter and driver so multiple devices can share the same directory.
+    ///
+    /// Returns `false` if pipeline caching isn’t available, i.e., if the device wasn’t created
+    /// with [`wgpu::Features::PIPELINE_CACHE`] (currently only supported on Vulkan), or if the
+    /// adapter is unknown.
+    ///
+    /// # Safety
+    ///
+    /// The cache file in `dir`, if it exists, must have been written by
+    /// [`Self::save_pipeline_cache`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn load_pipeline_cache(
+        &mut self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> anyhow::Result<bool> {
+        if !self
+            .device
+            .features()
+            .contains(wgpu::Features::PIPELINE_CACHE)
+        {
+            return Ok(false);
+        }
+        let Some(key) = self
+            .adapter
+            .as_ref()
+            .and_then(|adapter| wgpu::util::pipeline_cache_key(&adapter.get_info()))
+        else {
+            return Ok(false);
+        };
+
+        let path = dir.as_ref().join(key);
+        let data = match std::fs::read(&path) {
+            Ok(data) => Some(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        // SAFETY: the data was produced by `PipelineCache::get_data` according to our own safety
+        //         contract. Invalid or outdated data are discarded thanks to `fallback`.
+        let cache = unsafe {
+            self.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("slang-hal pipeline cache"),
+                    data: data.as_deref(),
+                    fallback: true,
+                })
+        };
+        self.pipeline_cache = Some(Arc::new(WebGpuPipelineCache { cache, path }));
+        Ok(true)
+    }
+
+    /// Writes the pipeline cache loaded by [`Self::load_pipeline_cache`] back to disk, including
+    /// the pipelines created since.
+    ///
+    /// Does nothing if no pipeline cache was loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_pipeline_cache(&self) -> anyhow::Result<()> {
+        let Some(pipeline_cache) = &self.pipeline_cache else {
+            return Ok(());
+        };
+        let Some(data) = pipeline_cache.cache.get_data() else {
+            return Ok(());
+        };
+
+        // Write to a temporary file first so a crash can’t leave a truncated cache behind.
+        if let Some(dir) = pipeline_cache.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = pipeline_cache.path.with_extension("tmp");
+        std::fs::write(&tmp_path, &data)?;
+        std::fs::rename(&tmp_path, &pipeline_cache.path)?;
+        Ok(())
+    }
+
+    fn create_command_encoder(&self) -> CommandEncoder {
+        self.device
+            .create_command_encoder(&CommandEncoderDescriptor::default())
+    }
+
+    /// Submits the commands encoded into a wgpu encoder.
+    fn submit_commands(&self, encoder: CommandEncoder) -> Result<(), WebGpuBackendError> {
+        error_scope(&self.device, || {
+            let _ = self.queue.submit(Some(encoder.finish()));
+        })
+    }
+
+    /// The size of the push constants available to the pipelines, or zero if the device doesn’t
+    /// support them.
+    fn max_push_constant_size(&self) -> u32 {
+        if self
+            .device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS)
+        {
+            self.device.limits().max_push_constant_size
+        } else {
+            0
+        }
+    }
+
+    fn create_shader_module(&self, wgsl: &str) -> Result<ShaderModule, WebGpuBackendError> {
+        error_scope(&self.device, || unsafe {
+            self.device.create_shader_module_trusted(
+                wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(wgsl)),
+                },
+                ShaderRuntimeChecks::unchecked(),
+            )
+        })
+    }
+
+    /// Creates a pipeline with bind group layouts (one per binding space) built from
+    /// `options.bindings`, or inferred by wgpu if some bindings can’t be described.
+    ///
+    /// The parameters passed by value are read from push constants if `module` supports them
+    /// and the layout can be built, and from a uniform buffer otherwise.
+    fn create_pipeline(
+        &self,
+        module: &WebGpuModule,
+        entry_point: &str,
+        options: FunctionOptions,
+    ) -> Result<WebGpuFunction, WebGpuBackendError> {
+        if module.uniform_source.is_some() {
+            let bindings: Vec<_> = options
+                .bindings
+                .iter()
+                .filter(|b| Some(b.binding) != options.value_buffer)
+                .copied()
+                .collect();
+            // NOTE: wgpu can’t infer layouts with push constants.
+            if bindings.iter().all(|b| b.ty.is_some()) {
+                let layout = self.create_pipeline_layout(
+                    entry_point,
+                    &bindings,
+                    self.max_push_constant_size(),
+                );
+                // NOTE: this fails if the parameters don’t fit in the push constants, in which
+                //       case they are read from a uniform buffer instead.
+                let pipeline = error_scope(&self.device, || {
+                    self.create_compute_pipeline(
+                        &module.module,
+                        entry_point,
+                        Some(&layout),
+                        &options,
+                    )
+                });
+                if let Ok(pipeline) = pipeline {
+                    return Ok(WebGpuFunction {
+                        pipeline,
+                        push_constants: true,
+                    });
+                }
+            }
+        }
+
+        let shader_module = match &module.uniform_source {
+            Some(source) => match module.uniform_module.get() {
+                Some(uniform_module) => uniform_module,
+                None => {
+                    let uniform_module = self.create_shader_module(source)?;
+                    module.uniform_module.get_or_init(|| uniform_module)
+                }
+            },
+            None => &module.module,
+        };
+        let bindings = options.bindings;
+        let layout = (!bindings.is_empty() && bindings.iter().all(|b| b.ty.is_some()))
+            .then(|| self.create_pipeline_layout(entry_point, bindings, 0));
+        Ok(WebGpuFunction {
+            pipeline: self.create_compute_pipeline(
+                shader_module,
+                entry_point,
+                layout.as_ref(),
+                &options,
+            ),
+            push_constants: false,
+        })
+    }
+
+    fn create_compute_pipeline(
+        &self,
+        module: &ShaderModule,
+        entry_point: &str,
+        layout: Option<&wgpu::PipelineLayout>,
+        options: &FunctionOptions,
+    ) -> ComputePipeline {
+        self.device
+            .create_compute_pipeline(&ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout,
+                module,
+                entry_point: Some(entry_point),
+                compilation_options: PipelineCompilationOptions {
+                    constants: options.constants,
+                    zero_initialize_workgroup_memory: false,
+                },
+                cache: self.pipeline_cache.as_ref().map(|c| &c.cache),
+            })
+    }
+
+    /// Creates a pipeline layout with one bind group layout per binding space, and a push
+    /// constant range of `push_constant_size` bytes if it isn’t zero.
+    fn create_pipeline_layout(
+        &self,
+        entry_point: &str,
+        bindings: &[BindingLayout],
+        push_constant_size: u32,
+    ) -> wgpu::PipelineLayout {
+        let num_groups = bindings
+            .iter()
+            .map(|b| b.binding.space + 1)
+            .max()
+            .unwrap_or(0);
+        let group_layouts: Vec<_> = (0..num_groups)
+            .map(|space| {
+                let entries: Vec<_> = bindings
+                    .iter()
+                    .filter(|b| b.binding.space == space)
+                    .filter_map(|b| {
+                        Some(wgpu::BindGroupLayoutEntry {
+                            binding: b.binding.index,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: b.ty?,
+                            count: None,
+                        })
+                    })
+                    .collect();
+                self.device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: Some(entry_point),
+                        entries: &entries,
+                    })
+            })
+            .collect();
+        let push_constant_ranges: SmallVec<[_; 1]> = (push_constant_size > 0)
+            .then_some(wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::COMPUTE,
+                range: 0..push_constant_size,
+            })
+            .into_iter()
+            .collect();
+        self.device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(entry_point),
+                bind_group_layouts: &group_layouts.iter().collect::<Vec<_>>(),
+                push_constant_ranges: &push_constant_ranges,
+            })
+    }
+
+    /// Frees the staging buffers kept around for reuse by readbacks.
+    pub fn clear_staging_cache(&self) {
+        self.staging_cache.buffers.lock().unwrap().clear();
+    }
+
+    /// Returns a `MAP_READ | COPY_DST` staging buffer of at least `size` bytes, reusing a cached
+    /// one if possible.
+    fn take_staging(&self, size: BufferAddress) -> Buffer {
+        let bucket = size.next_power_of_two().max(StagingCache::MIN_SIZE);
+        let cached = self
+            .staging_cache
+            .buffers
+            .lock()
+            .unwrap()
+            .get_mut(&bucket)
+            .and_then(|buffers| buffers.pop());
+        cached.unwrap_or_else(|| {
+            self.device.create_buffer(&BufferDescriptor {
+                label: Some("slang-hal staging"),
+                size: bucket,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        })
+    }
+
+    /// Gives back a staging buffer returned by [`Self::take_staging`] once it is unmapped.
+    fn recycle_staging(&self, staging: Buffer) {
+        self.staging_cache
+            .buffers
+            .lock()
+            .unwrap()
+            .entry(staging.size())
+            .or_default()
+            .push(staging);
+    }
+}
+
+/// Runs `f`, returning the validation or out-of-memory error it raised (if any) instead of
+/// reporting it to the device’s uncaptured error handler.
+///
+/// On the web, error scopes are resolved asynchronously so the errors are still reported to the
+/// uncaptured error handler.
+///
+/// Error scopes are shared by all the threads using the device, so an error raised concurrently
+/// by another thread may be captured by this scope instead.
+fn error_scope<R>(device: &Device, f: impl FnOnce() -> R) -> Result<R, WebGpuBackendError> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = device;
+        Ok(f())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use futures::FutureExt;
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        let result = f();
+        // NOTE: error scopes are resolved immediately on native.
+        let out_of_memory = device.pop_error_scope().now_or_never().flatten();
+        let validation = device.pop_error_scope().now_or_never().flatten();
+        match out_of_memory.or(validation) {
+            Some(error) => Err(error.into()),
+            None => Ok(result),
+        }
+    }
+}
+
+/// Rewrites the half-precision types and literals of a WGSL shader to single precision.
+fn demote_f16(wgsl: &str) -> String {
+    static ENABLE_F16: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"enable\s+f16\s*;").unwrap());
+    static F16: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bf16\b").unwrap());
+    static F16_LITERAL: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"\b(\d+(?:\.\d*)?(?:[eE][+-]?\d+)?|\.\d+(?:[eE][+-]?\d+)?)h\b").unwrap()
+    });
+
+    let wgsl = ENABLE_F16.replace_all(wgsl, "");
+    let wgsl = F16.replace_all(&wgsl, "f32");
+    F16_LITERAL.replace_all(&wgsl, "${1}f").into_owned()
+}
+
+/// Moves the parameters passed by value of the entry points of a WGSL shader generated by Slang
+/// from uniform buffers to push constants.
+///
+/// Returns `None` if the shader has no such parameters.
+fn use_push_constants(wgsl: &str) -> Option<String> {
+    static ENTRY_POINT_PARAMS: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?:@binding\(\d+\)\s*@group\(\d+\)|@group\(\d+\)\s*@binding\(\d+\))\s*var<uniform>\s*(entryPointParams\w*)",
+        )
+        .unwrap()
+    });
+
+    ENTRY_POINT_PARAMS.is_match(wgsl).then(|| {
+        ENTRY_POINT_PARAMS
+            .replace_all(wgsl, "var<push_constant> ${1}")
+            .into_owned()
+    })
+}
+
+/// A WGSL shader module.
+pub struct WebGpuModule {
+    module: ShaderModule,
+    /// If the parameters passed by value were moved to push constants (see
+    /// [`WebGpuFunction::push_constants`]), the original source reading them from uniform buffers.
+    uniform_source: Option<String>,
+    /// The module compiled from `uniform_source`, for the pipelines that can’t use push
+    /// constants.
+    uniform_module: OnceLock<ShaderModule>,
+}
+
+/// A compute pipeline.
+#[derive(Clone)]
+pub struct WebGpuFunction {
+    pipeline: ComputePipeline,
+    /// See [`Self::push_constants`].
+    push_constants: bool,
+}
+
+impl WebGpuFunction {
+    /// The wgpu pipeline of this function.
+    pub fn pipeline(&self) -> &ComputePipeline {
+        &self.pipeline
+    }
+
+    /// Whether the parameters passed by value are set with push constants rather than uploaded
+    /// to a uniform buffer for each dispatch.
+    ///
+    /// Push constants are used if the device was created with [`wgpu::Features::PUSH_CONSTANTS`]
+    /// and a large enough [`wgpu::Limits::max_push_constant_size`], and if the bindings of all the
+    /// resource parameters of the function were reflected.
+    pub fn push_constants(&self) -> bool {
+        self.push_constants
+    }
+}
+
+/// Options for initializing a [`WebGpu`] backend with [`WebGpu::with_options`].
+#[derive(Clone, Debug)]
+pub struct WebGpuOptions<'a> {
+    /// The graphics APIs the adapter may be picked from.
+    ///
+    /// Ignored if [`Self::instance`] is set. Use [`wgpu::Backends::from_env`] to let the
+    /// `WGPU_BACKEND` environment variable decide.
+    pub backends: wgpu::Backends,
+    /// Whether to prefer a low-power or a high-performance adapter.
+    pub power_preference: wgpu::PowerPreference,
+    /// Forces the use of a fallback (software) adapter, e.g. llvmpipe or WARP.
+    pub force_fallback_adapter: bool,
+    /// A surface the adapter must be able to present to.
+    ///
+    /// The surface must have been created from [`Self::instance`].
+    pub compatible_surface: Option<&'a wgpu::Surface<'a>>,
+    /// An existing instance to pick the adapter from, instead of creating a new one.
+    pub instance: Option<Instance>,
+    /// The features required from the device.
+    pub features: wgpu::Features,
+    /// The limits required from the device.
+    pub limits: wgpu::Limits,
+}
+
+impl Default for WebGpuOptions<'_> {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+            instance: None,
+            features: wgpu::Features::default(),
+            limits: wgpu::Limits::default(),
+        }
+    }
+}
+
+/// A pipeline cache and the file it is persisted to.
+struct WebGpuPipelineCache {
+    cache: wgpu::PipelineCache,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    path: std::path::PathBuf,
+}
+
+/// Staging buffers used for readbacks, bucketed by power-of-two sizes.
+///
+/// This avoids allocating a new staging buffer each time a buffer without the `MAP_READ` usage
+/// is read back (e.g. every frame).
+#[derive(Default)]
+struct StagingCache {
+    buffers: Mutex<HashMap<BufferAddress, Vec<Buffer>>>,
+}
+
+impl StagingCache {
+    /// The size of the smallest bucket, in bytes.
+    const MIN_SIZE: BufferAddress = 256;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn device_info(index: usize, info: &wgpu::AdapterInfo) -> DeviceInfo {
+    DeviceInfo {
+        index,
+        name: info.name.clone(),
+        device_type: info.device_type,
+        api: info.backend.to_string(),
+        memory: None,
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -88,13 +673,25 @@ pub enum WebGpuBackendError {
     #[error(transparent)]
     ShaderArg(#[from] ShaderArgsError),
     #[error(transparent)]
+    Unsupported(#[from] UnsupportedError),
+    #[error(transparent)]
+    Slang(#[from] minislang::SlangError),
+    #[error(transparent)]
     Wgpu(#[from] wgpu::Error),
     #[error(transparent)]
     BytemuckPod(#[from] bytemuck::PodCastError),
+    #[error(transparent)]
+    BufferCast(#[from] BufferCastError),
+    #[error(transparent)]
+    TextureData(#[from] TextureDataError),
     #[error("Failed to read buffer from GPU: {0}")]
     BufferRead(RecvError),
+    #[error("Failed to wait for a submission: {0}")]
+    SubmissionWait(RecvError),
     #[error(transparent)]
     DevicePoll(#[from] PollError),
+    #[error("all the timestamp queries of this set are already used")]
+    TimestampsFull,
 }
 
 #[async_trait::async_trait]
@@ -104,40 +701,75 @@ impl Backend for WebGpu {
 
     type Error = WebGpuBackendError;
     type Buffer<T: DeviceValue> = Buffer;
+    type Texture = WebGpuTexture;
+    type Sampler = WebGpuSampler;
+    type Timestamps = WebGpuTimestamps;
+    type Bundle = WebGpuBundle;
+    type Fence = WebGpuFence;
     type BufferSlice<'b, T: DeviceValue> = BufferSlice<'b>;
-    type Encoder = wgpu::CommandEncoder;
-    type Pass = ComputePass<'static>;
-    type Module = ShaderModule;
-    type Function = wgpu::ComputePipeline;
+    type Encoder = WebGpuEncoder;
+    type Pass = WebGpuPass;
+    type Module = WebGpuModule;
+    type Function = WebGpuFunction;
     type Dispatch<'a> = WebGpuDispatch<'a>;
 
     fn as_webgpu(&self) -> Option<&WebGpu> {
         Some(self)
     }
 
+    fn memory_info(&self) -> Result<MemoryInfo, Self::Error> {
+        // NOTE: wgpu doesn’t expose the memory budget of the adapter, so only the memory
+        //       allocated by this device can be reported, when the backend supports it.
+        let report = self
+            .device
+            .generate_allocator_report()
+            .ok_or_else(|| UnsupportedError::new::<Self>("memory usage queries"))?;
+        Ok(MemoryInfo {
+            used: Some(report.total_allocated_bytes),
+            free: None,
+            total: None,
+        })
+    }
+
+    fn limits(&self) -> Result<DeviceLimits, Self::Error> {
+        Ok(DeviceLimits::from_wgpu(
+            &self.device.limits(),
+            self.device.features(),
+        ))
+    }
+
     /*
      * Module/function loading.
      */
     fn load_module(&self, data: &str) -> Result<Self::Module, Self::Error> {
         // HACK: slang tends to introduce some useless conversions when unpacking, resulting in
-        //       the SHADER_F16 feature being needed for no good reasons.
-        let mut data = data.replace("enable f16;", "").replace("f16", "f32");
+        //       the SHADER_F16 feature being needed for no good reasons. If the device doesn’t
+        //       support it, demote half-precision types and literals to single precision.
+        let mut data = if self.device.features().contains(wgpu::Features::SHADER_F16) {
+            data.to_string()
+        } else {
+            demote_f16(data)
+        };
 
         // Apply other user-defined hacks.
         for (reg, replace) in &self.hacks {
             data = reg.replace_all(&data, replace).to_string();
         }
 
-        let module = unsafe {
-            self.device.create_shader_module_trusted(
-                wgpu::ShaderModuleDescriptor {
-                    label: None,
-                    source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(&data)),
-                },
-                ShaderRuntimeChecks::unchecked(),
-            )
+        // NOTE: Slang’s WGSL output reads the parameters passed by value from a uniform buffer,
+        //       which has to be created for each dispatch. Read them from push constants instead
+        //       if the device supports them.
+        let push_constants_source =
+            (self.max_push_constant_size() > 0).then(|| use_push_constants(&data));
+        let (source, uniform_source) = match push_constants_source.flatten() {
+            Some(source) => (source, Some(data)),
+            None => (data, None),
         };
-        Ok(module)
+        Ok(WebGpuModule {
+            module: self.create_shader_module(&source)?,
+            uniform_source,
+            uniform_module: OnceLock::new(),
+        })
     }
 
     fn load_module_bytes(&self, bytes: &[u8]) -> Result<Self::Module, Self::Error> {
@@ -149,44 +781,123 @@ impl Backend for WebGpu {
         module: &Self::Module,
         entry_point: &str,
     ) -> Result<Self::Function, Self::Error> {
-        /*
-         * Create the pipeline.
-         */
-        let pipeline = self
-            .device
-            .create_compute_pipeline(&ComputePipelineDescriptor {
-                label: Some(entry_point),
-                layout: None,
-                module,
-                entry_point: Some(entry_point),
-                compilation_options: PipelineCompilationOptions {
-                    zero_initialize_workgroup_memory: false,
-                    ..Default::default()
-                },
-                cache: None,
-            });
+        self.load_function_with_options(module, entry_point, FunctionOptions::default())
+    }
 
-        Ok(pipeline)
+    fn load_function_with_options(
+        &self,
+        module: &Self::Module,
+        entry_point: &str,
+        options: FunctionOptions<'_>,
+    ) -> Result<Self::Function, Self::Error> {
+        error_scope(&self.device, || {
+            self.create_pipeline(module, entry_point, options)
+        })?
     }
 
     /*
      * Kernel dispatch.
      */
     fn begin_encoding(&self) -> Self::Encoder {
-        self.device
-            .create_command_encoder(&CommandEncoderDescriptor::default())
+        WebGpuEncoder {
+            encoder: Some(self.create_command_encoder()),
+            recording: None,
+        }
+    }
+    fn create_queue(&self) -> Result<Self, Self::Error> {
+        // NOTE: wgpu only exposes a single queue per device, so this returns an alias sharing it:
+        //       submissions through both handles are executed in order.
+        Ok(Self {
+            _instance: self._instance.clone(),
+            adapter: self.adapter.clone(),
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            staging_cache: self.staging_cache.clone(),
+            pipeline_cache: self.pipeline_cache.clone(),
+            profiler: self.profiler.clone(),
+            hacks: self.hacks.clone(),
+            force_buffer_copy_src: self.force_buffer_copy_src,
+        })
     }
 
     fn begin_dispatch<'a>(
         &'a self,
         pass: &'a mut Self::Pass,
         function: &'a Self::Function,
+        label: &'a str,
     ) -> WebGpuDispatch<'a> {
-        WebGpuDispatch::new(&self.device, pass, function)
+        let mut dispatch = WebGpuDispatch::new(&self.device, pass, function, label);
+        dispatch.profiler = self.profiler.as_deref();
+        dispatch
     }
 
     fn submit(&self, encoder: Self::Encoder) -> Result<(), Self::Error> {
-        let _ = self.queue.submit(Some(encoder.finish()));
+        match encoder.encoder {
+            Some(encoder) => self.submit_commands(encoder),
+            None => Ok(()),
+        }
+    }
+
+    fn submit_async(&self, encoder: Self::Encoder) -> Result<SubmissionFuture<Self>, Self::Error> {
+        error_scope(&self.device, || {
+            let _ = self
+                .queue
+                .submit(encoder.encoder.map(|encoder| encoder.finish()));
+        })?;
+        let (sender, receiver) = async_channel::bounded(1);
+        self.queue.on_submitted_work_done(move || {
+            let _ = sender.force_send(());
+        });
+        let device = self.device.clone();
+        Ok(SubmissionFuture::new(async move {
+            let mut done = std::pin::pin!(receiver.recv());
+            std::future::poll_fn(|cx| {
+                // NOTE: on the web, the callback is triggered by the browser. Native devices only
+                //       trigger it when polled, so poll without blocking, and reschedule the
+                //       future until the submission completes instead of blocking the executor.
+                #[cfg(not(target_arch = "wasm32"))]
+                device.poll(wgpu::PollType::Poll)?;
+                match done.as_mut().poll(cx) {
+                    std::task::Poll::Ready(result) => {
+                        std::task::Poll::Ready(result.map_err(WebGpuBackendError::SubmissionWait))
+                    }
+                    std::task::Poll::Pending => {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        cx.waker().wake_by_ref();
+                        std::task::Poll::Pending
+                    }
+                }
+            })
+            .await
+        }))
+    }
+    fn create_fence(&self) -> Result<Self::Fence, Self::Error> {
+        Ok(WebGpuFence {
+            device: self.device.clone(),
+            requested: 0,
+            completed: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn signal_fence(&self, fence: &mut Self::Fence) -> Result<(), Self::Error> {
+        fence.device = self.device.clone();
+        fence.requested += 1;
+        let value = fence.requested;
+        let completed = fence.completed.clone();
+        self.queue.on_submitted_work_done(move || {
+            completed.fetch_max(value, Ordering::Release);
+        });
+        Ok(())
+    }
+
+    fn wait_fence(&self, fence: &Self::Fence) -> Result<(), Self::Error> {
+        // NOTE: submissions are executed in order by the device’s single queue, so only fences
+        //       signaled by another device need to be waited for. This is done on the CPU since
+        //       wgpu has no way to synchronize devices.
+        #[cfg(not(target_arch = "wasm32"))]
+        if fence.device != self.device && !fence.is_signaled() {
+            fence.device.poll(wgpu::PollType::wait())?;
+        }
         Ok(())
     }
 
@@ -202,11 +913,33 @@ impl Backend for WebGpu {
             usage |= BufferUsages::COPY_SRC;
         }
 
-        Ok(self.device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: bytemuck::try_cast_slice(data)?,
-            usage,
-        }))
+        let contents = bytemuck::try_cast_slice(data)?;
+        error_scope(&self.device, || {
+            self.device.create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents,
+                usage,
+            })
+        })
+    }
+
+    fn init_buffer_bytes<T: DeviceValue>(
+        &self,
+        bytes: &[u8],
+        mut usage: BufferUsages,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        elements_len::<T>(bytes.len())?;
+        if self.force_buffer_copy_src && !usage.contains(BufferUsages::MAP_READ) {
+            usage |= BufferUsages::COPY_SRC;
+        }
+
+        error_scope(&self.device, || {
+            self.device.create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents: bytes,
+                usage,
+            })
+        })
     }
 
     fn init_buffer_encased<T: DeviceValue + EncaseType>(
@@ -218,15 +951,24 @@ impl Backend for WebGpu {
             usage |= BufferUsages::COPY_SRC;
         }
 
-        let mut bytes = vec![]; // TODO PERF: can we avoid the allocation somehow?
-        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
-        bytes_buffer.write(data).unwrap();
-
-        Ok(self.device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: &bytes,
-            usage,
-        }))
+        // Serialize directly into the buffer’s memory, mapped at creation.
+        let len = data.len() as BufferAddress * T::min_size().get();
+        let buffer = error_scope(&self.device, || {
+            self.device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: len.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+                usage,
+                mapped_at_creation: true,
+            })
+        })?;
+        {
+            let mut view = buffer.slice(..).get_mapped_range_mut();
+            StorageBuffer::new(&mut view[..len as usize])
+                .write(data)
+                .unwrap();
+        }
+        buffer.unmap();
+        Ok(buffer)
     }
 
     // fn init_buffer_bytes<T: Copy>(&self, data: &[u8], usage: BufferUsages) -> Result<Self::Buffer<T>, Self::Error> {
@@ -247,12 +989,14 @@ impl Backend for WebGpu {
         }
 
         let bytes_len = std::mem::size_of::<T>() as u64 * len as u64;
-        Ok(self.device.create_buffer(&BufferDescriptor {
-            label: None,
-            size: bytes_len,
-            usage,
-            mapped_at_creation: false,
-        }))
+        error_scope(&self.device, || {
+            self.device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: bytes_len,
+                usage,
+                mapped_at_creation: false,
+            })
+        })
     }
 
     unsafe fn uninit_buffer_encased<T: DeviceValue + ShaderType>(
@@ -265,99 +1009,690 @@ impl Backend for WebGpu {
         }
 
         let bytes_len = T::min_size().get() * len as u64;
-        Ok(self.device.create_buffer(&BufferDescriptor {
+        error_scope(&self.device, || {
+            self.device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: bytes_len,
+                usage,
+                mapped_at_creation: false,
+            })
+        })
+    }
+
+    fn write_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_at(buffer, 0, data)
+    }
+    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        self.write_buffer_encased_at(buffer, 0, data)
+    }
+
+    fn write_buffer_at<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let sz = size_of::<T>() as BufferAddress;
+        error_scope(&self.device, || {
+            self.queue.write_buffer(
+                buffer,
+                offset as BufferAddress * sz,
+                bytemuck::cast_slice(data),
+            )
+        })
+    }
+
+    fn write_buffer_encased_at<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        offset: usize,
+        data: &[T],
+    ) -> Result<(), Self::Error> {
+        let sz = T::min_size().get() as usize;
+        self.write_buffer_with::<T>(buffer, offset * sz, data.len() * sz, |view| {
+            StorageBuffer::new(view).write(data).unwrap()
+        })
+    }
+
+    fn write_buffer_with<T: DeviceValue>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        byte_offset: usize,
+        len: usize,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), Self::Error> {
+        let Some(size) = wgpu::BufferSize::new(len as BufferAddress) else {
+            write(&mut []);
+            return Ok(());
+        };
+        // NOTE: if this returns `None`, the validation error is reported to the error scope.
+        let view = error_scope(&self.device, || {
+            self.queue
+                .write_buffer_with(buffer, byte_offset as BufferAddress, size)
+        })?;
+        if let Some(mut view) = view {
+            write(&mut view);
+        }
+        Ok(())
+    }
+
+    fn synchronize(&self) -> Result<(), Self::Error> {
+        self.device.poll(wgpu::PollType::wait())?;
+        Ok(())
+    }
+
+    async fn read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let data = read_bytes(&self.device, buffer, 0..buffer.size()).await?;
+        let result = bytemuck::try_cast_slice(&data)?;
+        out[..result.len()].copy_from_slice(result);
+        drop(data);
+        buffer.unmap();
+        Ok(())
+    }
+
+    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let data = read_bytes(&self.device, buffer, 0..buffer.size()).await?;
+
+        let mut result = vec![];
+        let bytes = data.as_ref();
+        let encase_buffer = StorageBuffer::new(&bytes);
+        encase_buffer.read(&mut result).unwrap(); // TODO: propagate error
+        out[..result.len()].copy_from_slice(&result);
+
+        drop(data);
+        buffer.unmap();
+        Ok(())
+    }
+
+    async fn slow_read_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        let bytes_len = buffer.size();
+        let staging = self.take_staging(bytes_len);
+        let mut encoder = self.create_command_encoder();
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, bytes_len);
+        self.submit_commands(encoder)?;
+
+        // Read the buffer.
+        let data = read_bytes(&self.device, &staging, 0..bytes_len).await?;
+        let result = bytemuck::try_cast_slice(&data)?;
+        out[..result.len()].copy_from_slice(result);
+        drop(data);
+        staging.unmap();
+        self.recycle_staging(staging);
+        Ok(())
+    }
+
+    async fn read_buffer_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        // Mapped ranges must start at a multiple of `MAP_ALIGNMENT` and have a size multiple of
+        // `COPY_BUFFER_ALIGNMENT`.
+        let sz = size_of::<T>() as BufferAddress;
+        let (start, end) = (
+            range.start as BufferAddress * sz,
+            range.end as BufferAddress * sz,
+        );
+        let mapped_start = start - start % wgpu::MAP_ALIGNMENT;
+        let mapped_end = end
+            .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+            .min(buffer.size());
+
+        let data = read_bytes(&self.device, buffer, mapped_start..mapped_end).await?;
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        out.copy_from_slice(&data[(start - mapped_start) as usize..(end - mapped_start) as usize]);
+        drop(data);
+        buffer.unmap();
+        Ok(())
+    }
+
+    async fn slow_read_range<T: DeviceValue + Pod>(
+        &self,
+        buffer: &Self::Buffer<T>,
+        range: Range<usize>,
+        out: &mut [T],
+    ) -> Result<(), Self::Error> {
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        // Copy only the requested range (extended to the copy alignment) to the staging buffer.
+        let sz = size_of::<T>() as BufferAddress;
+        let (start, end) = (
+            range.start as BufferAddress * sz,
+            range.end as BufferAddress * sz,
+        );
+        let copy_start = start - start % wgpu::COPY_BUFFER_ALIGNMENT;
+        let copy_end = end
+            .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+            .min(buffer.size());
+        let staging = self.take_staging(copy_end - copy_start);
+        let mut encoder = self.create_command_encoder();
+        encoder.copy_buffer_to_buffer(buffer, copy_start, &staging, 0, copy_end - copy_start);
+        self.submit_commands(encoder)?;
+
+        let data = read_bytes(&self.device, &staging, 0..copy_end - copy_start).await?;
+        let out: &mut [u8] = bytemuck::try_cast_slice_mut(&mut out[..range.len()])?;
+        out.copy_from_slice(&data[(start - copy_start) as usize..(end - copy_start) as usize]);
+        drop(data);
+        staging.unmap();
+        self.recycle_staging(staging);
+        Ok(())
+    }
+
+    fn fill_buffer<T: DeviceValue + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: T,
+        range: impl RangeBounds<usize>,
+    ) -> Result<(), Self::Error> {
+        let sz = size_of::<T>() as BufferAddress;
+        let len = (buffer.size() as usize)
+            .checked_div(size_of::<T>())
+            .unwrap_or(0);
+        let range = buffer_range(range, len);
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        let offset = range.start as BufferAddress * sz;
+        let len_bytes = range.len() as BufferAddress * sz;
+        // NOTE: clears and queue writes both operate on multiples of 4 bytes.
+        if !offset.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+            || !len_bytes.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+        {
+            return Err(UnsupportedError::new::<Self>("fills not aligned to 4 bytes").into());
+        }
+
+        error_scope(&self.device, || {
+            if bytemuck::bytes_of(&value).iter().all(|b| *b == 0) {
+                let mut encoder = self
+                    .device
+                    .create_command_encoder(&CommandEncoderDescriptor::default());
+                encoder.clear_buffer(buffer, offset, Some(len_bytes));
+                let _ = self.queue.submit(Some(encoder.finish()));
+            } else {
+                self.queue
+                    .write_buffer(buffer, offset, &repeat_bytes(&value, range.len()));
+            }
+        })
+    }
+
+    /*
+     * Uniform handling.
+     */
+    fn init_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        value: &T,
+    ) -> Result<Self::Buffer<T>, Self::Error> {
+        let mut usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        if self.force_buffer_copy_src {
+            usage |= BufferUsages::COPY_SRC;
+        }
+
+        Ok(self.device.create_buffer_init(&BufferInitDescriptor {
             label: None,
-            size: bytes_len,
+            contents: &uniform_bytes(value),
             usage,
-            mapped_at_creation: false,
         }))
     }
 
-    fn write_buffer<T: DeviceValue + Pod>(
+    fn write_uniform<T: DeviceValue + EncaseType + Pod>(
+        &self,
+        buffer: &mut Self::Buffer<T>,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.queue.write_buffer(buffer, 0, &uniform_bytes(value));
+        Ok(())
+    }
+
+    /*
+     * Texture handling.
+     */
+    fn init_texture<T: DeviceValue + Pod>(
+        &self,
+        data: &[T],
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        check_texture_data(bytes.len(), texture_bytes_len::<WebGpu>(size, format)?)?;
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &texture_descriptor(size, format, usage),
+            wgpu::util::TextureDataOrder::LayerMajor,
+            bytes,
+        );
+        Ok(WebGpuTexture::new(texture))
+    }
+
+    unsafe fn uninit_texture(
         &self,
-        buffer: &mut Self::Buffer<T>,
-        data: &[T],
-    ) -> Result<(), Self::Error> {
-        self.queue
-            .write_buffer(buffer, 0, bytemuck::cast_slice(data));
-        Ok(())
+        size: [u32; 2],
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Result<Self::Texture, Self::Error> {
+        let _ = texel_size::<WebGpu>(format)?;
+        let texture = self
+            .device
+            .create_texture(&texture_descriptor(size, format, usage));
+        Ok(WebGpuTexture::new(texture))
     }
-    fn write_buffer_encased<T: DeviceValue + EncaseType>(
+
+    fn write_texture<T: DeviceValue + Pod>(
         &self,
-        buffer: &mut Self::Buffer<T>,
+        texture: &mut Self::Texture,
         data: &[T],
     ) -> Result<(), Self::Error> {
-        let mut bytes = vec![]; // TODO: can we avoid the allocation?
-        let mut bytes_buffer = StorageBuffer::new(&mut bytes);
-        bytes_buffer.write(data).unwrap();
-
-        self.queue.write_buffer(buffer, 0, &bytes);
-        Ok(())
-    }
-
-    fn synchronize(&self) -> Result<(), Self::Error> {
-        self.device.poll(wgpu::PollType::wait())?;
+        let bytes: &[u8] = bytemuck::try_cast_slice(data)?;
+        let size = texture.size();
+        check_texture_data(
+            bytes.len(),
+            texture_bytes_len::<WebGpu>(size, texture.format())?,
+        )?;
+        self.queue.write_texture(
+            texture.texture.as_image_copy(),
+            bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size[0] * texel_size::<WebGpu>(texture.format())?),
+                rows_per_image: Some(size[1]),
+            },
+            texture.texture.size(),
+        );
         Ok(())
     }
 
-    async fn read_buffer<T: DeviceValue + Pod>(
+    async fn read_texture<T: DeviceValue + Pod>(
         &self,
-        buffer: &Self::Buffer<T>,
+        texture: &Self::Texture,
         out: &mut [T],
     ) -> Result<(), Self::Error> {
-        let data = read_bytes(&self.device, buffer).await?;
-        let result = bytemuck::try_cast_slice(&data)?;
-        out[..result.len()].copy_from_slice(result);
+        // Rows copied to a buffer must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`, so we copy
+        // to a padded staging buffer and remove the padding when reading it back.
+        let size = texture.size();
+        let row_len = (size[0] * texel_size::<WebGpu>(texture.format())?) as usize;
+        let padded_row_len = row_len.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+        let staging_len = (padded_row_len * size[1] as usize) as BufferAddress;
+        let staging = self.take_staging(staging_len);
+
+        let mut encoder = self.create_command_encoder();
+        encoder.copy_texture_to_buffer(
+            texture.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &staging,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_len as u32),
+                    rows_per_image: Some(size[1]),
+                },
+            },
+            texture.texture.size(),
+        );
+        self.submit_commands(encoder)?;
+
+        let data = read_bytes(&self.device, &staging, 0..staging_len).await?;
+        let out_bytes: &mut [u8] = bytemuck::try_cast_slice_mut(out)?;
+        for (out_row, row) in out_bytes
+            .chunks_exact_mut(row_len)
+            .zip(data.chunks_exact(padded_row_len))
+        {
+            out_row.copy_from_slice(&row[..row_len]);
+        }
         drop(data);
-        buffer.unmap();
+        staging.unmap();
+        self.recycle_staging(staging);
         Ok(())
     }
 
-    async fn read_buffer_encased<T: DeviceValue + EncaseType>(
+    fn create_sampler(&self, desc: &SamplerDescriptor) -> Result<Self::Sampler, Self::Error> {
+        let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: None,
+            address_mode_u: desc.address_mode_u,
+            address_mode_v: desc.address_mode_v,
+            mag_filter: desc.mag_filter,
+            min_filter: desc.min_filter,
+            ..Default::default()
+        });
+        Ok(WebGpuSampler {
+            sampler,
+            desc: *desc,
+        })
+    }
+
+    fn create_timestamps(&self, capacity: usize) -> Result<Self::Timestamps, Self::Error> {
+        if !self
+            .device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+        {
+            return Err(UnsupportedError::new::<Self>("timestamp queries").into());
+        }
+
+        // Each pass writes one timestamp when it begins, and one when it ends.
+        // NOTE: query sets can’t be empty, and are validated against the maximum query count.
+        let count = capacity
+            .max(1)
+            .checked_mul(2)
+            .and_then(|count| u32::try_from(count).ok())
+            .unwrap_or(u32::MAX);
+        let size = count as BufferAddress * wgpu::QUERY_SIZE as BufferAddress;
+        error_scope(&self.device, || WebGpuTimestamps {
+            query_set: self.device.create_query_set(&QuerySetDescriptor {
+                label: None,
+                ty: QueryType::Timestamp,
+                count,
+            }),
+            resolve_buffer: self.device.create_buffer(&BufferDescriptor {
+                label: None,
+                size,
+                usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            staging: self.device.create_buffer(&BufferDescriptor {
+                label: None,
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            capacity,
+            len: 0,
+        })
+    }
+
+    async fn resolve_timestamps(
         &self,
-        buffer: &Self::Buffer<T>,
-        out: &mut [T],
-    ) -> Result<(), Self::Error> {
-        let data = read_bytes(&self.device, buffer).await?;
+        timestamps: &mut Self::Timestamps,
+    ) -> Result<Vec<PassTimestamps>, Self::Error> {
+        let count = 2 * timestamps.len as u32;
+        timestamps.len = 0;
+        if count == 0 {
+            return Ok(vec![]);
+        }
 
-        let mut result = vec![];
-        let bytes = data.as_ref();
-        let encase_buffer = StorageBuffer::new(&bytes);
-        encase_buffer.read(&mut result).unwrap(); // TODO: propagate error
-        out[..result.len()].copy_from_slice(&result);
+        let size = count as BufferAddress * wgpu::QUERY_SIZE as BufferAddress;
+        let mut encoder = self.create_command_encoder();
+        encoder.resolve_query_set(
+            &timestamps.query_set,
+            0..count,
+            &timestamps.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(&timestamps.resolve_buffer, 0, &timestamps.staging, 0, size);
+        self.submit_commands(encoder)?;
 
+        let data = read_bytes(&self.device, &timestamps.staging, 0..size).await?;
+        let ticks: Vec<u64> = bytemuck::pod_collect_to_vec(&data);
         drop(data);
-        buffer.unmap();
+        timestamps.staging.unmap();
+
+        let period = self.queue.get_timestamp_period() as f64;
+        let to_ns = |t: u64| (t.saturating_sub(ticks[0]) as f64 * period) as u64;
+        Ok(ticks
+            .chunks_exact(2)
+            .map(|t| PassTimestamps {
+                begin_ns: to_ns(t[0]),
+                end_ns: to_ns(t[1]),
+            })
+            .collect())
+    }
+
+    fn enable_profiling(&mut self, capacity: usize) -> Result<(), Self::Error> {
+        // NOTE: dispatches are timed from inside their pass, which needs an extra feature.
+        let timestamps = self
+            .device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES)
+            .then(|| self.create_timestamps(capacity))
+            .transpose()?;
+        self.profiler = Some(Arc::new(Profiler::new(timestamps)));
         Ok(())
     }
 
-    async fn slow_read_buffer<T: DeviceValue + Pod>(
+    fn disable_profiling(&mut self) {
+        self.profiler = None;
+    }
+
+    async fn profiling_report(&self) -> Result<ProfilingReport, Self::Error> {
+        match &self.profiler {
+            Some(profiler) => profiler.report(self).await,
+            None => Ok(ProfilingReport::default()),
+        }
+    }
+
+    fn record_bundle(
         &self,
-        buffer: &Self::Buffer<T>,
-        out: &mut [T],
+        record: &mut dyn FnMut(&Self, &mut Self::Encoder) -> Result<(), Self::Error>,
+    ) -> Result<WebGpuBundle, Self::Error> {
+        // NOTE: the commands of this encoder, and of its passes, are recorded instead of being
+        //       encoded.
+        let recording = Recording::default();
+        let mut encoder = WebGpuEncoder {
+            encoder: None,
+            recording: Some(recording.clone()),
+        };
+        record(self, &mut encoder)?;
+        drop(encoder);
+        let commands = std::mem::take(&mut *recording.lock().unwrap());
+        Ok(WebGpuBundle { commands })
+    }
+
+    fn replay_bundle(
+        &self,
+        encoder: &mut Self::Encoder,
+        bundle: &WebGpuBundle,
     ) -> Result<(), Self::Error> {
-        // Create staging buffer.
-        // SAFETY: the buffer will be initialized by a buffer-to-buffer copy.
-        let bytes_len = buffer.size() as usize;
-        let staging = unsafe {
-            // TODO: not using `u8` because it doesn’t implement ShaderType
-            self.uninit_buffer::<u32>(
-                bytes_len.div_ceil(4),
-                BufferUsages::MAP_READ | BufferUsages::COPY_DST,
-            )?
+        let Some(encoder) = &mut encoder.encoder else {
+            return Err(UnsupportedError::new::<Self>("nested bundles").into());
         };
-        let mut encoder = self.begin_encoding();
-        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, bytes_len as u64);
-        self.submit(encoder)?;
+        let mut pass = None;
+        for command in &bundle.commands {
+            match command {
+                WebGpuCommand::BeginPass(label) => {
+                    drop(pass.take());
+                    pass = Some(encoder.compute_pass(label, None).forget_lifetime());
+                }
+                WebGpuCommand::Dispatch {
+                    label,
+                    pipeline,
+                    push_constants,
+                    bind_groups,
+                    grid,
+                } => {
+                    // NOTE: a pass may have been used after a copy ended it.
+                    let pass = pass
+                        .get_or_insert_with(|| encoder.compute_pass("", None).forget_lifetime());
+                    if !label.is_empty() {
+                        pass.push_debug_group(label);
+                    }
+                    pass.set_pipeline(pipeline);
+                    if !push_constants.is_empty() {
+                        pass.set_push_constants(0, push_constants);
+                    }
+                    for (space, bind_group) in bind_groups.iter().enumerate() {
+                        pass.set_bind_group(space as u32, bind_group, &[]);
+                    }
+                    match grid {
+                        RecordedGrid::Direct(grid_dim) => {
+                            if grid_dim[0] * grid_dim[1] * grid_dim[2] > 0 {
+                                pass.dispatch_workgroups(grid_dim[0], grid_dim[1], grid_dim[2]);
+                            }
+                        }
+                        RecordedGrid::Indirect(buffer, offset) => {
+                            pass.dispatch_workgroups_indirect(buffer, *offset)
+                        }
+                    }
+                    if !label.is_empty() {
+                        pass.pop_debug_group();
+                    }
+                }
+                WebGpuCommand::Copy {
+                    source,
+                    source_offset,
+                    target,
+                    target_offset,
+                    size,
+                } => {
+                    drop(pass.take());
+                    encoder.copy_buffer_to_buffer(
+                        source,
+                        *source_offset,
+                        target,
+                        *target_offset,
+                        *size,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
 
-        // Read the buffer.
-        Ok(self.read_buffer(&staging, out).await?)
+/// A sequence of commands recorded with [`Backend::record_bundle`].
+///
+/// WebGPU command buffers can’t be submitted more than once, so the commands are encoded again
+/// when replayed, but with their pipelines and bind groups (including the uniform buffers or push
+/// constants of the arguments passed by value) created once when recording.
+pub struct WebGpuBundle {
+    commands: Vec<WebGpuCommand>,
+}
+
+/// A command recorded into a [`WebGpuBundle`].
+enum WebGpuCommand {
+    BeginPass(String),
+    Dispatch {
+        label: String,
+        pipeline: ComputePipeline,
+        /// The parameters passed by value, if they are set with push constants.
+        push_constants: Vec<u8>,
+        /// The bind group of each binding space.
+        bind_groups: Vec<wgpu::BindGroup>,
+        grid: RecordedGrid,
+    },
+    Copy {
+        source: Buffer,
+        source_offset: BufferAddress,
+        target: Buffer,
+        target_offset: BufferAddress,
+        size: BufferAddress,
+    },
+}
+
+/// The workgroup count of a recorded dispatch.
+enum RecordedGrid {
+    Direct([u32; 3]),
+    /// The buffer and byte offset of the workgroup count.
+    Indirect(Buffer, BufferAddress),
+}
+
+/// The commands of a bundle being recorded, shared by the encoder given to
+/// [`WebGpu::record_bundle`] and its passes.
+type Recording = Arc<Mutex<Vec<WebGpuCommand>>>;
+
+/// A command encoder, or the recorder of a [`WebGpuBundle`].
+pub struct WebGpuEncoder {
+    /// The wgpu encoder, or `None` if the commands are recorded into a bundle.
+    encoder: Option<CommandEncoder>,
+    recording: Option<Recording>,
+}
+
+impl WebGpuEncoder {
+    /// The wgpu encoder the commands are encoded into, or `None` if they are recorded into a
+    /// bundle (see [`Backend::record_bundle`]).
+    pub fn encoder(&mut self) -> Option<&mut CommandEncoder> {
+        self.encoder.as_mut()
+    }
+}
+
+/// A compute pass, or a pass of a [`WebGpuBundle`] being recorded.
+pub struct WebGpuPass {
+    /// The wgpu pass, or `None` if the commands are recorded into a bundle.
+    pass: Option<ComputePass<'static>>,
+    recording: Option<Recording>,
+}
+
+impl WebGpuPass {
+    /// The wgpu pass the dispatches are encoded into, or `None` if they are recorded into a
+    /// bundle (see [`Backend::record_bundle`]).
+    pub fn pass(&mut self) -> Option<&mut ComputePass<'static>> {
+        self.pass.as_mut()
     }
 }
 
-impl Encoder<WebGpu> for wgpu::CommandEncoder {
-    fn begin_pass(&mut self) -> ComputePass<'static> {
-        self.compute_pass("").forget_lifetime()
+impl Encoder<WebGpu> for WebGpuEncoder {
+    fn begin_pass(&mut self, label: &str) -> WebGpuPass {
+        if let Some(recording) = &self.recording {
+            recording
+                .lock()
+                .unwrap()
+                .push(WebGpuCommand::BeginPass(label.to_string()));
+        }
+        WebGpuPass {
+            pass: self
+                .encoder
+                .as_mut()
+                .map(|encoder| encoder.compute_pass(label, None).forget_lifetime()),
+            recording: self.recording.clone(),
+        }
+    }
+
+    fn begin_timed_pass(
+        &mut self,
+        label: &str,
+        timestamps: &mut WebGpuTimestamps,
+    ) -> Result<WebGpuPass, WebGpuBackendError> {
+        // NOTE: the queries would be written by each replay, before being resolved.
+        let Some(encoder) = &mut self.encoder else {
+            return Err(UnsupportedError::new::<WebGpu>("timed passes in bundles").into());
+        };
+        let timestamp_writes = timestamps.next_compute_pass_timestamp_writes()?;
+        Ok(WebGpuPass {
+            pass: Some(
+                encoder
+                    .compute_pass(label, Some(timestamp_writes))
+                    .forget_lifetime(),
+            ),
+            recording: None,
+        })
+    }
+
+    // NOTE: debug groups aren’t recorded into bundles.
+    fn push_debug_group(&mut self, label: &str) {
+        if let Some(encoder) = &mut self.encoder {
+            encoder.push_debug_group(label);
+        }
+    }
+
+    fn pop_debug_group(&mut self) {
+        if let Some(encoder) = &mut self.encoder {
+            encoder.pop_debug_group();
+        }
     }
 
     fn copy_buffer_to_buffer<T: DeviceValue + Pod>(
@@ -368,13 +1703,14 @@ impl Encoder<WebGpu> for wgpu::CommandEncoder {
         target_offset: usize,
         copy_len: usize,
     ) -> Result<(), WebGpuBackendError> {
-        wgpu::CommandEncoder::copy_buffer_to_buffer(
+        let sz = size_of::<T>() as BufferAddress;
+        copy_buffer(
             self,
             source,
-            source_offset as BufferAddress * size_of::<T>() as BufferAddress,
+            source_offset as BufferAddress * sz,
             target,
-            target_offset as BufferAddress * size_of::<T>() as BufferAddress,
-            copy_len as BufferAddress * size_of::<T>() as BufferAddress,
+            target_offset as BufferAddress * sz,
+            copy_len as BufferAddress * sz,
         );
         Ok(())
     }
@@ -387,60 +1723,240 @@ impl Encoder<WebGpu> for wgpu::CommandEncoder {
         target_offset: usize,
         copy_len: usize,
     ) -> Result<(), WebGpuBackendError> {
-        let sz = T::min_size().get() as usize;
-        wgpu::CommandEncoder::copy_buffer_to_buffer(
+        let sz = T::min_size().get() as BufferAddress;
+        copy_buffer(
             self,
             source,
-            source_offset as BufferAddress * sz as BufferAddress,
+            source_offset as BufferAddress * sz,
             target,
-            target_offset as BufferAddress * sz as BufferAddress,
-            copy_len as BufferAddress * sz as BufferAddress,
+            target_offset as BufferAddress * sz,
+            copy_len as BufferAddress * sz,
         );
         Ok(())
     }
 }
 
+/// Encodes a copy of `size` bytes into `encoder`, or records it into its bundle.
+fn copy_buffer(
+    encoder: &mut WebGpuEncoder,
+    source: &Buffer,
+    source_offset: BufferAddress,
+    target: &Buffer,
+    target_offset: BufferAddress,
+    size: BufferAddress,
+) {
+    if let Some(recording) = &encoder.recording {
+        recording.lock().unwrap().push(WebGpuCommand::Copy {
+            source: source.clone(),
+            source_offset,
+            target: target.clone(),
+            target_offset,
+            size,
+        });
+    }
+    if let Some(encoder) = &mut encoder.encoder {
+        encoder.copy_buffer_to_buffer(source, source_offset, target, target_offset, size);
+    }
+}
+
 impl<'a> Dispatch<'a, WebGpu> for WebGpuDispatch<'a> {
+    fn write_value<T: DeviceValue + Pod>(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        value: &'a T,
+    ) -> Result<(), ShaderArgsError> {
+        if self.value_buffer.is_none() {
+            return Err(ShaderArgsError::NoValueBuffer(name.to_owned()));
+        }
+
+        let bytes = bytemuck::bytes_of(value);
+        let offset = binding.index as usize;
+        if self.values.len() < offset + bytes.len() {
+            self.values.resize(offset + bytes.len(), 0);
+        }
+        self.values[offset..offset + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn set_value_buffer_binding(&mut self, binding: ShaderBinding) {
+        self.value_buffer = Some(binding);
+    }
+
+    fn bind_array(
+        &mut self,
+        binding: ShaderBinding,
+        name: &str,
+        len: usize,
+    ) -> Result<(), ShaderArgsError> {
+        let elements = self.args.drain(self.args.len() - len..);
+        let buffers = elements
+            .map(|(_, elt)| match elt {
+                WebGpuBinding::Buffer(slice) => Ok(slice.into()),
+                _ => Err(ShaderArgsError::UnsupportedArray(name.to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+        self.args
+            .push((binding, WebGpuBinding::BufferArray(buffers)));
+        Ok(())
+    }
+
     // NOTE: the block_dim is configured in the shader…
     fn launch<'b>(
         self,
         grid: impl Into<DispatchGrid<'b, WebGpu>>,
-        _block_dim: [u32; 3],
+        block_dim: [u32; 3],
     ) -> Result<(), WebGpuBackendError> {
         if !self.launchable {
             return Ok(());
         }
 
-        self.pass.set_pipeline(&self.pipeline);
+        // NOTE: push constants are set with a size multiple of 4 bytes.
+        let push_constants = if self.push_constants {
+            let mut values = self.values.clone();
+            values.resize(values.len().next_multiple_of(4), 0);
+            values
+        } else {
+            vec![]
+        };
+
+        // NOTE: without push constants (which aren’t available on the web), Slang’s WGSL output
+        //       reads the entry-point parameters passed by value from a uniform buffer, so we
+        //       upload them into a new buffer for each dispatch.
+        let value_buffer = self
+            .value_buffer
+            .filter(|_| !self.push_constants && !self.values.is_empty())
+            .map(|binding| {
+                let mut contents = self.values.clone();
+                contents.resize(contents.len().next_multiple_of(16), 0);
+                let buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: &contents,
+                    usage: BufferUsages::UNIFORM,
+                });
+                (binding, buffer)
+            });
 
-        // TODO: we could store the BindGroupEntry directly?
-        let entries: SmallVec<[_; 10]> = self
+        // NOTE: each binding space maps to its own bind group. Every group up to the largest
+        //       space used must be set, even if empty, for the pipeline layout to be satisfied.
+        let max_space = self
             .args
             .iter()
-            .map(|(id, input)| wgpu::BindGroupEntry {
-                binding: id.index,
-                resource: (*input).into(),
-            })
-            .collect();
-        let layout = self.pipeline.get_bind_group_layout(0);
-        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &layout,
-            entries: &entries,
-        });
-        self.pass.set_bind_group(0, &bind_group, &[]);
-
-        match grid.into() {
-            DispatchGrid::Direct(grid_dim) => {
-                // NOTE: we don’t need to queue if the workgroup is empty.
-                if grid_dim[0] * grid_dim[1] * grid_dim[2] > 0 {
-                    self.pass
-                        .dispatch_workgroups(grid_dim[0], grid_dim[1], grid_dim[2]);
+            .map(|(id, _)| id.space)
+            .chain(value_buffer.as_ref().map(|(id, _)| id.space))
+            .max()
+            .unwrap_or(0);
+
+        let mut bind_groups = Vec::with_capacity(max_space as usize + 1);
+        for space in 0..=max_space {
+            // TODO: we could store the BindGroupEntry directly?
+            let mut entries: SmallVec<[_; 10]> = self
+                .args
+                .iter()
+                .filter(|(id, _)| id.space == space)
+                .map(|(id, input)| wgpu::BindGroupEntry {
+                    binding: id.index,
+                    resource: match input {
+                        WebGpuBinding::Buffer(slice) => (*slice).into(),
+                        WebGpuBinding::BufferArray(buffers) => {
+                            wgpu::BindingResource::BufferArray(buffers)
+                        }
+                        WebGpuBinding::Texture(view) => wgpu::BindingResource::TextureView(view),
+                        WebGpuBinding::Sampler(sampler) => wgpu::BindingResource::Sampler(sampler),
+                    },
+                })
+                .collect();
+            if let Some((binding, buffer)) = &value_buffer
+                && binding.space == space
+            {
+                entries.push(wgpu::BindGroupEntry {
+                    binding: binding.index,
+                    resource: buffer.as_entire_binding(),
+                });
+            }
+
+            let layout = self.pipeline.get_bind_group_layout(space);
+            let bind_group = error_scope(&self.device, || {
+                self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &layout,
+                    entries: &entries,
+                })
+            })?;
+            bind_groups.push(bind_group);
+        }
+
+        let grid = grid.into();
+
+        // NOTE: the passes of a bundle being recorded have no wgpu pass, so the dispatch is only
+        //       recorded, with the bind groups it needs.
+        let Some(pass) = &mut self.pass.pass else {
+            if let Some(recording) = &self.pass.recording {
+                recording.lock().unwrap().push(WebGpuCommand::Dispatch {
+                    label: self.label.to_string(),
+                    pipeline: self.pipeline.clone(),
+                    push_constants,
+                    bind_groups,
+                    grid: match &grid {
+                        DispatchGrid::Direct(grid_dim) => RecordedGrid::Direct(*grid_dim),
+                        DispatchGrid::Indirect(grid_indirect, offset) => RecordedGrid::Indirect(
+                            (*grid_indirect).clone(),
+                            (offset * size_of::<[u32; 3]>()) as BufferAddress,
+                        ),
+                    },
+                });
+            }
+            return Ok(());
+        };
+
+        if !self.label.is_empty() {
+            pass.push_debug_group(self.label);
+        }
+        pass.set_pipeline(&self.pipeline);
+        if !push_constants.is_empty() {
+            pass.set_push_constants(0, &push_constants);
+        }
+        for (space, bind_group) in bind_groups.iter().enumerate() {
+            pass.set_bind_group(space as u32, bind_group, &[]);
+        }
+
+        let mut dispatch = |timestamps: Option<&mut WebGpuTimestamps>| {
+            // NOTE: the profiler only passes timestamps with unused queries.
+            let queries =
+                timestamps.and_then(|ts| Some((ts.next_query_index().ok()?, &ts.query_set)));
+            if let Some((index, query_set)) = queries {
+                pass.write_timestamp(query_set, index);
+            }
+            match &grid {
+                DispatchGrid::Direct(grid_dim) => {
+                    // NOTE: we don’t need to queue if the workgroup is empty.
+                    if grid_dim[0] * grid_dim[1] * grid_dim[2] > 0 {
+                        pass.dispatch_workgroups(grid_dim[0], grid_dim[1], grid_dim[2]);
+                    }
+                }
+                DispatchGrid::Indirect(grid_indirect, offset) => {
+                    let byte_offset = (offset * size_of::<[u32; 3]>()) as BufferAddress;
+                    pass.dispatch_workgroups_indirect(grid_indirect, byte_offset);
                 }
             }
-            DispatchGrid::Indirect(grid_indirect) => {
-                self.pass.dispatch_workgroups_indirect(grid_indirect, 0);
+            if let Some((index, query_set)) = queries {
+                pass.write_timestamp(query_set, index + 1);
+            }
+        };
+
+        match self.profiler {
+            Some(profiler) => {
+                let grid_dim = match &grid {
+                    DispatchGrid::Direct(grid_dim) => Some(*grid_dim),
+                    DispatchGrid::Indirect(..) => None,
+                };
+                profiler.record(self.label, grid_dim, block_dim, dispatch);
             }
+            None => dispatch(None),
+        }
+
+        if !self.label.is_empty() {
+            pass.pop_debug_group();
         }
 
         Ok(())
@@ -451,33 +1967,55 @@ pub struct WebGpuDispatch<'a> {
     // NOTE: keep up to 10 bindings on the stack. This number was chosen to match
     //       the current (06/2025) max storage bindings on the browser.
     device: Device,
-    pass: &'a mut ComputePass<'static>,
+    pass: &'a mut WebGpuPass,
     pipeline: ComputePipeline,
-    args: SmallVec<[(ShaderBinding, BufferSlice<'a>); 10]>,
+    push_constants: bool,
+    label: &'a str,
+    args: SmallVec<[(ShaderBinding, WebGpuBinding<'a>); 10]>,
+    values: Vec<u8>,
+    value_buffer: Option<ShaderBinding>,
     launchable: bool,
+    profiler: Option<&'a Profiler<WebGpu>>,
+}
+
+/// A resource bound to a [`WebGpuDispatch`].
+enum WebGpuBinding<'a> {
+    Buffer(BufferSlice<'a>),
+    /// The elements of a binding array.
+    BufferArray(Vec<wgpu::BufferBinding<'a>>),
+    Texture(&'a TextureView),
+    Sampler(&'a wgpu::Sampler),
 }
 
 impl<'a> WebGpuDispatch<'a> {
     fn new(
         device: &Device,
-        pass: &'a mut ComputePass<'static>,
-        pipeline: &ComputePipeline,
+        pass: &'a mut WebGpuPass,
+        function: &WebGpuFunction,
+        label: &'a str,
     ) -> WebGpuDispatch<'a> {
         WebGpuDispatch {
             device: device.clone(),
             pass,
-            pipeline: pipeline.clone(),
+            pipeline: function.pipeline.clone(),
+            push_constants: function.push_constants,
+            label,
             args: SmallVec::default(),
+            values: vec![],
+            value_buffer: None,
             launchable: true,
+            profiler: None,
         }
     }
 }
 
 pub trait CommandEncoderExt {
+    /// Begins a compute pass, timed with `timestamp_writes` (see
+    /// [`WebGpuTimestamps::next_compute_pass_timestamp_writes`]).
     fn compute_pass<'encoder>(
         &'encoder mut self,
         label: &str,
-        // timestamps: Option<&mut GpuTimestamps>,
+        timestamp_writes: Option<ComputePassTimestampWrites<'_>>,
     ) -> ComputePass<'encoder>;
 }
 
@@ -485,11 +2023,11 @@ impl CommandEncoderExt for CommandEncoder {
     fn compute_pass<'encoder>(
         &'encoder mut self,
         label: &str,
-        // timestamps: Option<&mut GpuTimestamps>,
+        timestamp_writes: Option<ComputePassTimestampWrites<'_>>,
     ) -> ComputePass<'encoder> {
         let desc = ComputePassDescriptor {
             label: Some(label),
-            timestamp_writes: None, // timestamps.and_then(|ts| ts.next_compute_pass_timestamp_writes()),
+            timestamp_writes,
         };
         self.begin_compute_pass(&desc)
     }
@@ -498,8 +2036,9 @@ impl CommandEncoderExt for CommandEncoder {
 async fn read_bytes<'a>(
     device: &Device,
     buffer: &'a Buffer,
+    range: Range<BufferAddress>,
 ) -> Result<BufferView<'a>, WebGpuBackendError> {
-    let buffer_slice = buffer.slice(..);
+    let buffer_slice = buffer.slice(range);
 
     #[cfg(not(target_arch = "wasm32"))]
     {
@@ -538,9 +2077,21 @@ impl<'b> ShaderArgs<'b, WebGpu> for Buffer {
     where
         'b: 'a,
     {
-        dispatch.args.push((binding, self.slice(..)));
+        dispatch
+            .args
+            .push((binding, WebGpuBinding::Buffer(self.slice(..))));
         Ok(())
     }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        // NOTE: the element type of buffers is erased, and their size might be padded to the copy
+        //       alignment, so only their usage can be checked.
+        Some(ArgInfo {
+            element_size: None,
+            size_bytes: None,
+            writable: Some(self.usage().contains(BufferUsages::STORAGE)),
+        })
+    }
 }
 
 impl<'b> ShaderArgs<'b, WebGpu> for BufferSlice<'_> {
@@ -553,9 +2104,17 @@ impl<'b> ShaderArgs<'b, WebGpu> for BufferSlice<'_> {
     where
         'b: 'a,
     {
-        dispatch.args.push((binding, *self));
+        dispatch.args.push((binding, WebGpuBinding::Buffer(*self)));
         Ok(())
     }
+
+    fn arg_info(&self, _name: &str) -> Option<ArgInfo> {
+        Some(ArgInfo {
+            element_size: None,
+            size_bytes: None,
+            writable: Some(self.buffer().usage().contains(BufferUsages::STORAGE)),
+        })
+    }
 }
 
 impl<T: DeviceValue> crate::backend::Buffer<WebGpu, T> for Buffer {
@@ -572,4 +2131,213 @@ impl<T: DeviceValue> crate::backend::Buffer<WebGpu, T> for Buffer {
             .map(|val| *val as u64 * std::mem::size_of::<T>() as u64);
         self.slice((start, end))
     }
+
+    fn size_bytes(&self) -> usize {
+        self.size() as usize
+    }
+
+    fn reinterpret<U: DeviceValue + Pod>(
+        &self,
+        byte_offset: usize,
+        len: usize,
+    ) -> Result<BufferSlice<'_>, BufferCastError> {
+        let range = view_bytes::<U>(self.size() as usize, byte_offset, len)?;
+        Ok(self.slice(range.start as u64..range.end as u64))
+    }
+}
+
+/// A 2D texture created by the [`WebGpu`] backend.
+pub struct WebGpuTexture {
+    texture: wgpu::Texture,
+    view: TextureView,
+}
+
+impl WebGpuTexture {
+    fn new(texture: wgpu::Texture) -> Self {
+        let view = texture.create_view(&Default::default());
+        Self { texture, view }
+    }
+
+    /// The underlying `wgpu` texture.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// The view of the whole texture, as bound to shaders.
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+}
+
+impl crate::backend::Texture<WebGpu> for WebGpuTexture {
+    fn size(&self) -> [u32; 2] {
+        [self.texture.width(), self.texture.height()]
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.texture.format()
+    }
+}
+
+impl<'b> ShaderArgs<'b, WebGpu> for WebGpuTexture {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut <WebGpu as Backend>::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch
+            .args
+            .push((binding, WebGpuBinding::Texture(&self.view)));
+        Ok(())
+    }
+}
+
+/// A texture sampler created by the [`WebGpu`] backend.
+pub struct WebGpuSampler {
+    sampler: wgpu::Sampler,
+    desc: SamplerDescriptor,
+}
+
+impl WebGpuSampler {
+    /// The underlying `wgpu` sampler.
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+}
+
+impl crate::backend::Sampler<WebGpu> for WebGpuSampler {
+    fn descriptor(&self) -> SamplerDescriptor {
+        self.desc
+    }
+}
+
+impl<'b> ShaderArgs<'b, WebGpu> for WebGpuSampler {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        _name: &str,
+        dispatch: &mut <WebGpu as Backend>::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch
+            .args
+            .push((binding, WebGpuBinding::Sampler(&self.sampler)));
+        Ok(())
+    }
+}
+
+pub struct WebGpuTimestamps {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: Buffer,
+    staging: Buffer,
+    capacity: usize,
+    len: usize,
+}
+
+impl WebGpuTimestamps {
+    /// The underlying `wgpu` query set.
+    ///
+    /// The beginning and end of the `i`-th timed pass are written to the queries `2 * i` and
+    /// `2 * i + 1`.
+    pub fn query_set(&self) -> &wgpu::QuerySet {
+        &self.query_set
+    }
+
+    /// The timestamp writes of the next timed compute pass.
+    ///
+    /// Fails if all the queries of this set are already used.
+    pub fn next_compute_pass_timestamp_writes(
+        &mut self,
+    ) -> Result<ComputePassTimestampWrites<'_>, WebGpuBackendError> {
+        let index = self.next_query_index()?;
+        Ok(ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        })
+    }
+
+    /// Reserves the next pair of queries, and returns the index of the first one.
+    fn next_query_index(&mut self) -> Result<u32, WebGpuBackendError> {
+        if self.len >= self.capacity {
+            return Err(WebGpuBackendError::TimestampsFull);
+        }
+        let index = 2 * self.len as u32;
+        self.len += 1;
+        Ok(index)
+    }
+}
+
+/// A fence of the [`WebGpu`] backend, signaled from `Queue::on_submitted_work_done`.
+pub struct WebGpuFence {
+    device: Device,
+    /// The number of times this fence was signaled.
+    requested: u64,
+    /// The number of signals whose work completed.
+    completed: Arc<AtomicU64>,
+}
+
+impl Fence<WebGpu> for WebGpuFence {
+    fn is_signaled(&self) -> bool {
+        // NOTE: on native platforms, the callbacks are only triggered when polling the device.
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = self.device.poll(wgpu::PollType::Poll);
+        self.completed.load(Ordering::Acquire) >= self.requested
+    }
+}
+
+impl crate::backend::Timestamps<WebGpu> for WebGpuTimestamps {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+fn texture_descriptor(
+    size: [u32; 2],
+    format: TextureFormat,
+    usage: TextureUsages,
+) -> TextureDescriptor<'static> {
+    TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: size[0],
+            height: size[1],
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage,
+        view_formats: &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_point_params_use_push_constants() {
+        let wgsl = "struct EntryPointParams_std140_0 { @align(16) len_0 : u32, };\n\
+                    @binding(2) @group(0) var<uniform> entryPointParams_0 : EntryPointParams_std140_0;\n\
+                    @binding(0) @group(0) var<storage, read_write> data_0 : array<u32>;\n";
+        let expected = "struct EntryPointParams_std140_0 { @align(16) len_0 : u32, };\n\
+                        var<push_constant> entryPointParams_0 : EntryPointParams_std140_0;\n\
+                        @binding(0) @group(0) var<storage, read_write> data_0 : array<u32>;\n";
+        assert_eq!(use_push_constants(wgsl).as_deref(), Some(expected));
+
+        let wgsl = "@binding(0) @group(0) var<uniform> params_0 : Params_std140_0;\n";
+        assert_eq!(use_push_constants(wgsl), None);
+    }
 }
@@ -0,0 +1,76 @@
+//! Pre-recorded sequences of commands, replayed with little CPU overhead.
+//!
+//! Encoding the same dispatches every frame spends CPU time binding their arguments (looking up
+//! the kernel parameters, creating bind groups, and uploading the values passed by value). A
+//! [`CommandBundle`] records a fixed sequence of dispatches and copies once, with their
+//! arguments bound, and replays it every frame:
+//!
+//! - on CUDA, the commands are captured into a CUDA graph, launched at once when replayed.
+//! - on WebGpu, command buffers can’t be submitted twice, so the commands are encoded again when
+//!   replayed, but with their pipelines and bind groups created once.
+//!
+//! The other backends don’t support bundles.
+//!
+//! ```ignore
+//! let bundle = CommandBundle::record(&backend, |backend, encoder| {
+//!     let mut pass = encoder.begin_pass("step");
+//!     shaders.forces.launch(backend, &mut pass, &forces_args, [n, 1, 1])?;
+//!     shaders.integrate.launch(backend, &mut pass, &integrate_args, [n, 1, 1])?;
+//!     drop(pass);
+//!     encoder.copy_buffer_to_buffer(&positions, 0, &mut prev_positions, 0, n as usize)
+//! })?;
+//!
+//! for _ in 0..num_steps {
+//!     bundle.replay(&backend)?;
+//! }
+//! ```
+//!
+//! A [`ComputeGraph`](crate::graph::ComputeGraph) can be recorded into a bundle with
+//! [`ComputeGraph::encode`](crate::graph::ComputeGraph::encode).
+
+use crate::backend::Backend;
+
+/// A fixed sequence of dispatches and copies, recorded once and replayed any number of times.
+///
+/// See the [module documentation](self) for details.
+pub struct CommandBundle<B: Backend> {
+    bundle: B::Bundle,
+}
+
+impl<B: Backend> CommandBundle<B> {
+    /// Records the commands of `record` into a new bundle.
+    ///
+    /// `record` must record its commands with the backend and encoder it is given, and not with
+    /// other handles to the same backend. The commands aren’t executed while recording. Values
+    /// passed by value to the dispatches are captured when recording, while the contents of the
+    /// bound buffers are read each time the bundle is replayed.
+    ///
+    /// Fails if the backend doesn’t support bundles (see [`Backend::record_bundle`]), or if
+    /// `record` fails.
+    pub fn record(
+        backend: &B,
+        mut record: impl FnMut(&B, &mut B::Encoder) -> Result<(), B::Error>,
+    ) -> Result<Self, B::Error> {
+        Ok(Self {
+            bundle: backend.record_bundle(&mut record)?,
+        })
+    }
+
+    /// Records the commands of this bundle into `encoder`, after the commands it already
+    /// contains.
+    pub fn encode(&self, backend: &B, encoder: &mut B::Encoder) -> Result<(), B::Error> {
+        backend.replay_bundle(encoder, &self.bundle)
+    }
+
+    /// Replays the commands of this bundle, submitting them to `backend`.
+    pub fn replay(&self, backend: &B) -> Result<(), B::Error> {
+        let mut encoder = backend.begin_encoding();
+        self.encode(backend, &mut encoder)?;
+        backend.submit(encoder)
+    }
+
+    /// The backend-specific bundle.
+    pub fn inner(&self) -> &B::Bundle {
+        &self.bundle
+    }
+}
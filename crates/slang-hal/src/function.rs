@@ -1,91 +1,709 @@
-use crate::backend::{Backend, Dispatch, DispatchGrid, ShaderBinding};
-use crate::shader::ShaderArgs;
-use minislang::{SlangCompiler, SlangProgram};
+use crate::backend::{
+    Backend, BindingLayout, Dispatch, DispatchGrid, FunctionOptions, ShaderBinding, SlangCompiled,
+};
+use crate::grid::{FoldedArgs, fold_grid};
+use crate::shader::{ArgInfo, DynArgs, ShaderArgs, ShaderArgsError};
+use minislang::pack::{PackedArgument, PackedEntryPoint, PackedResource, SlangPack, TexelType};
+use minislang::reflection::EntryPointReflection;
+use minislang::shader_slang::CompileTarget;
+use minislang::{CODE_CACHE_ENTRY, CompileOptions, SlangCompiler, SlangError};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex, OnceLock};
+use wgpu::{
+    BindingType, BufferBindingType, SamplerBindingType, TextureSampleType, TextureViewDimension,
+};
 
 struct ShaderArgsDesc {
     buffers: Vec<(String, ShaderBinding)>,
+    /// Binding of the constant buffer holding the parameters passed by value, if the target needs
+    /// one.
+    value_buffer: Option<ShaderBinding>,
+}
+
+/// The data reflected by Slang about an entry point.
+struct Reflection {
+    block_dim: [u32; 3],
+    args: ShaderArgsDesc,
+    /// Layout of the resource parameters, including the constant buffer of the parameters passed
+    /// by value.
+    bindings: Vec<BindingLayout>,
+    /// The reflection data this is derived from.
+    entry_point: PackedEntryPoint,
+}
+
+impl Reflection {
+    fn new(entry_point: &EntryPointReflection) -> Self {
+        Self::from_packed(&PackedEntryPoint::from(entry_point))
+    }
+
+    fn from_packed(entry_point: &PackedEntryPoint) -> Self {
+        let mut buffers = vec![];
+        let mut bindings = vec![];
+        let value_buffer = entry_point.value_buffer.map(ShaderBinding::from);
+        if let Some(binding) = value_buffer {
+            // NOTE: the value buffer is padded when uploaded, so don’t constrain its size.
+            bindings.push(BindingLayout {
+                binding,
+                ty: Some(BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                }),
+            });
+        }
+
+        for param in &entry_point.arguments {
+            let binding = ShaderBinding::from(param.binding);
+            // Parameters passed by value live in the value buffer.
+            if let Some(resource) = param.resource {
+                bindings.push(BindingLayout {
+                    binding,
+                    ty: binding_type(resource),
+                });
+            }
+            buffers.push((param.name.clone(), binding));
+        }
+
+        Self {
+            block_dim: entry_point.thread_group_size,
+            args: ShaderArgsDesc {
+                buffers,
+                value_buffer,
+            },
+            bindings,
+            entry_point: entry_point.clone(),
+        }
+    }
+
+    /// Serializes this reflection data for the on-disk compilation cache.
+    fn to_cache_bytes(&self) -> Vec<u8> {
+        self.entry_point.to_bytes()
+    }
+
+    /// Deserializes reflection data serialized with [`Reflection::to_cache_bytes`].
+    ///
+    /// Returns `None` if `bytes` is malformed.
+    fn from_cache_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_packed(&PackedEntryPoint::from_bytes(bytes)?))
+    }
+}
+
+/// Name of the on-disk cache entries holding the serialized [`Reflection`] of a kernel.
+const REFLECTION_CACHE_ENTRY: &str = concat!("reflection-", env!("CARGO_PKG_VERSION"));
+
+/// The wgpu binding type matching a resource parameter, if it can be inferred from its type.
+fn binding_type(resource: PackedResource) -> Option<BindingType> {
+    match resource {
+        PackedResource::ConstantBuffer { size } => Some(BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: NonZeroU64::new(size),
+        }),
+        PackedResource::Sampler => Some(BindingType::Sampler(SamplerBindingType::Filtering)),
+        PackedResource::StructuredBuffer {
+            read_only,
+            element_size,
+        } => Some(BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: NonZeroU64::new(element_size),
+        }),
+        PackedResource::ByteAddressBuffer { read_only } => Some(BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        }),
+        PackedResource::Texture2d {
+            read_only: true,
+            texel,
+        } => {
+            let sample_type = match texel? {
+                TexelType::Float => TextureSampleType::Float { filterable: true },
+                TexelType::Sint => TextureSampleType::Sint,
+                TexelType::Uint => TextureSampleType::Uint,
+            };
+            Some(BindingType::Texture {
+                sample_type,
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            })
+        }
+        // Storage textures need their format, which isn’t part of the reflection data.
+        _ => None,
+    }
+}
+
+/// The code and reflection data of a kernel compiled by [`compile_in_background`], ready to be
+/// loaded with [`GpuFunction::from_compiled`].
+pub struct CompiledFunction {
+    path: String,
+    entry_point: String,
+    code: Vec<u8>,
+    reflection: Reflection,
+}
+
+impl CompiledFunction {
+    /// Compiles the entry point `entry_point_name` of the shader at `path`, or reads it from the
+    /// on-disk cache of `compiler`.
+    fn compile(
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        target: CompileTarget,
+        options: &CompileOptions,
+    ) -> Result<Self, SlangError> {
+        let cache_key = compiler.cache_key(path, target, Some(entry_point_name), options);
+        let cached = cache_key.and_then(|key| {
+            let code = compiler.read_cache(key, CODE_CACHE_ENTRY)?;
+            let reflection = compiler.read_cache(key, REFLECTION_CACHE_ENTRY)?;
+            Some((code, Reflection::from_cache_bytes(&reflection)?))
+        });
+
+        let (code, reflection) = match cached {
+            Some(cached) => cached,
+            None => {
+                let program = compiler.compile(path, target, Some(entry_point_name), options)?;
+                let code = program.target_bytes(0)?;
+                let program_reflection = program.reflection()?;
+                let entry_point = program_reflection
+                    .entry_point(entry_point_name)
+                    .ok_or_else(|| SlangError::EntryPointNotFound {
+                        module: path.to_string(),
+                        entry_point: entry_point_name.to_string(),
+                    })?;
+                let reflection = Reflection::new(entry_point);
+                if let Some(key) = cache_key {
+                    compiler.write_cache(key, CODE_CACHE_ENTRY, &code)?;
+                    compiler.write_cache(
+                        key,
+                        REFLECTION_CACHE_ENTRY,
+                        &reflection.to_cache_bytes(),
+                    )?;
+                }
+                (code, reflection)
+            }
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            entry_point: entry_point_name.to_string(),
+            code,
+            reflection,
+        })
+    }
+
+    /// The path of the compiled shader.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The name of the compiled entry point.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+}
+
+/// Compiles the `kernels`, given as pairs of a shader path and an entry point name, for `target`
+/// on a worker thread, so that the caller isn’t blocked while Slang compiles them.
+///
+/// Because the Slang compiler can’t be shared across threads, `make_compiler` is called from the
+/// worker thread to create the compiler. The result of each kernel is given at the same index as
+/// in `kernels`, to be loaded with [`GpuFunction::from_compiled`]. Every result is an error if
+/// the worker thread can’t be spawned or panics. On WASM, where threads aren’t available, the
+/// kernels are compiled when the future is first polled.
+pub fn compile_in_background<const N: usize>(
+    make_compiler: impl FnOnce() -> SlangCompiler + Send + 'static,
+    target: CompileTarget,
+    kernels: [(String, String); N],
+    options: CompileOptions,
+) -> impl Future<Output = [Result<CompiledFunction, SlangError>; N]> {
+    let compile = move || {
+        let compiler = make_compiler();
+        kernels.each_ref().map(|(path, entry_point)| {
+            CompiledFunction::compile(&compiler, path, entry_point, target, &options)
+        })
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let spawned = std::thread::Builder::new()
+            .name("slang-compiler".to_string())
+            .spawn(move || {
+                let _ = sender.send(compile());
+            });
+        async move {
+            if let Err(e) = spawned {
+                return std::array::from_fn(|_| {
+                    Err(SlangError::Io(std::io::Error::new(e.kind(), e.to_string())))
+                });
+            }
+            // NOTE: the sender is only dropped without sending if the thread panicked.
+            receiver
+                .await
+                .unwrap_or_else(|_| std::array::from_fn(|_| Err(SlangError::CompilationPanicked)))
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        async move { compile() }
+    }
 }
 
 // TODO: find a better name… "GpuFunction" perhaps?
 pub struct GpuFunction<B: Backend> {
+    entry_point: String,
+    /// The path of the Slang module the function was loaded from, if known.
+    module: Option<String>,
     block_dim: [u32; 3],
     args: ShaderArgsDesc,
+    /// Layout of the resource parameters, for validating the arguments bound to them.
+    bindings: Vec<BindingLayout>,
+    /// The parameters of the entry point, as reflected by Slang.
+    parameters: Vec<PackedArgument>,
     function: B::Function,
+    shared_memory_bytes: u32,
 }
 
 impl<B: Backend> GpuFunction<B> {
-    pub const MAX_NUM_WORKGROUPS: u32 = 65535;
-
     pub fn from_file(
         backend: &B,
         compiler: &SlangCompiler,
         path: &str,
         entry_point_name: &str,
     ) -> Result<Self, B::Error> {
-        let program = compiler.compile(path, B::TARGET, Some(entry_point_name), &[]);
-        let module_bytes = program.target_code(0).unwrap();
-        let module = backend.load_module_bytes(module_bytes.as_slice())?;
-        let function = backend.load_function(&module, entry_point_name)?;
-        Self::from_function(entry_point_name, &program, function)
+        Self::from_file_with_constants(backend, compiler, path, entry_point_name, &[])
     }
 
-    fn from_function(
+    /// Compiles and loads the entry point `entry_point_name` of the shader at `path`, setting the
+    /// values of its pipeline-overridable constants (e.g. tile sizes) to `constants`.
+    ///
+    /// See [`FunctionOptions::constants`] for details. Fails if the backend doesn’t support
+    /// overridable constants and `constants` isn’t empty.
+    pub fn from_file_with_constants(
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
         entry_point_name: &str,
-        program: &SlangProgram,
-        function: B::Function,
+        constants: &[(&str, f64)],
     ) -> Result<Self, B::Error> {
-        let shader = program.layout(0).unwrap();
-        let entry_point = shader.find_entry_point_by_name(entry_point_name).unwrap();
-        let block_dim = entry_point.compute_thread_group_size().map(|e| e as u32);
-        let mut buffers = vec![];
+        let options = CompileOptions::default();
+        Self::compile_and_load(
+            backend,
+            compiler,
+            path,
+            entry_point_name,
+            constants,
+            &options,
+        )
+    }
 
-        for param in entry_point.parameters() {
-            let Some(param_var) = param.variable() else {
-                continue;
-            };
-            if param.semantic_name().is_some() {
-                continue;
-            }
-            let binding = ShaderBinding {
-                space: param.binding_space(),
-                index: param.binding_index(),
-            };
-            buffers.push((
-                param_var
-                    .name()
-                    // .expect("unnamed parameters not supported yet")
-                    .to_string(),
-                binding,
-            ));
+    /// Compiles and loads the entry point `entry_point_name` of the shader at `path` with the given
+    /// preprocessor macros defined, e.g. `&[("TILE", "16")]`.
+    ///
+    /// Use [`GpuFunctionVariants`] to cache the functions compiled with different sets of macros.
+    pub fn from_file_with_defines(
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Self, B::Error> {
+        let options = defines
+            .iter()
+            .fold(CompileOptions::default(), |options, (name, value)| {
+                options.macro_define(name, value)
+            });
+        Self::compile_and_load(backend, compiler, path, entry_point_name, &[], &options)
+    }
+
+    /// Compiles and loads the entry point `entry_point_name` of the shader at `path` with the given
+    /// compilation options (e.g. [`CompileOptions::debug`] to generate debuggable code, or
+    /// [`CompileOptions::type_argument`] to instantiate a generic entry point).
+    pub fn from_file_with_options(
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        options: &CompileOptions,
+    ) -> Result<Self, B::Error> {
+        Self::compile_and_load(backend, compiler, path, entry_point_name, &[], options)
+    }
+
+    /// Compiles and loads the entry point `entry_point_name` of the shader at `path`, with the
+    /// block size maximizing the occupancy of the device.
+    ///
+    /// The shader’s thread-group size must be set from the `block_size_macro` macro, e.g.,
+    /// `[numthreads(BLOCK_SIZE, 1, 1)]`. The shader is first compiled with `default_block_size`,
+    /// then compiled again with the block size given by [`Backend::occupancy_block_size`] if it
+    /// differs. Only CUDA reports such a block size, so the other backends keep
+    /// `default_block_size`.
+    pub fn from_file_with_occupancy_block_size(
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        block_size_macro: &str,
+        default_block_size: u32,
+    ) -> Result<Self, B::Error> {
+        let defines =
+            |block_size: u32| CompileOptions::default().macro_define(block_size_macro, block_size);
+        let function = Self::compile_and_load(
+            backend,
+            compiler,
+            path,
+            entry_point_name,
+            &[],
+            &defines(default_block_size),
+        )?;
+
+        match backend.occupancy_block_size(&function.function)? {
+            Some(block_size) if block_size != default_block_size => Self::compile_and_load(
+                backend,
+                compiler,
+                path,
+                entry_point_name,
+                &[],
+                &defines(block_size),
+            ),
+            _ => Ok(function),
         }
+    }
+
+    /// Loads the entry point `entry_point_name` of the module packed under `module` in `pack`,
+    /// without compiling anything.
+    ///
+    /// The pack must contain the code of the module for the backend’s compilation target (see
+    /// [`Backend::compile_target`]), as generated by [`SlangCompiler::pack`].
+    pub fn from_pack(
+        backend: &B,
+        pack: &SlangPack,
+        module: &str,
+        entry_point_name: &str,
+    ) -> Result<Self, B::Error> {
+        let target = backend.compile_target();
+        let packed = pack
+            .target(module, target)
+            .ok_or_else(|| SlangError::NotInPack {
+                module: module.to_string(),
+                target,
+            })?;
+        let entry_point =
+            packed
+                .entry_point(entry_point_name)
+                .ok_or_else(|| SlangError::EntryPointNotFound {
+                    module: module.to_string(),
+                    entry_point: entry_point_name.to_string(),
+                })?;
+        Self::load(
+            backend,
+            backend.load_module_bytes(&packed.code)?,
+            Reflection::from_packed(entry_point),
+            Some(module),
+            &entry_point.name,
+            &[],
+        )
+    }
+
+    /// Loads the entry point described by `reflection` from the target `code` of a module
+    /// compiled ahead of time, e.g. by a build script.
+    ///
+    /// This doesn’t need a [`SlangCompiler`], so shaders loaded this way run without the Slang
+    /// libraries. `code` must have been compiled for the backend’s compilation target (see
+    /// [`Backend::compile_target`]), and `reflection` must be the reflection of the entry point
+    /// for that same target (see [`PackedTarget::entry_points`]).
+    ///
+    /// [`PackedTarget::entry_points`]: minislang::pack::PackedTarget::entry_points
+    pub fn from_precompiled(
+        backend: &B,
+        code: &[u8],
+        reflection: &PackedEntryPoint,
+    ) -> Result<Self, B::Error> {
+        Self::load(
+            backend,
+            backend.load_module_bytes(code)?,
+            Reflection::from_packed(reflection),
+            None,
+            &reflection.name,
+            &[],
+        )
+    }
+
+    /// Loads the entry point described by `reflection` from a module already loaded by the
+    /// backend, e.g. a shared library loaded with `Cpu::load_library`.
+    ///
+    /// See [`Self::from_precompiled`] for the requirements on `reflection`.
+    pub fn from_precompiled_module(
+        backend: &B,
+        module: &B::Module,
+        reflection: &PackedEntryPoint,
+    ) -> Result<Self, B::Error> {
+        Self::from_module(
+            backend,
+            module,
+            Reflection::from_packed(reflection),
+            None,
+            &reflection.name,
+            &[],
+        )
+    }
+
+    /// Loads a kernel compiled by [`compile_in_background`].
+    pub fn from_compiled(backend: &B, compiled: CompiledFunction) -> Result<Self, B::Error> {
+        Self::load(
+            backend,
+            backend.load_compiled_module(&compiled.code, SlangCompiled(()))?,
+            compiled.reflection,
+            Some(&compiled.path),
+            &compiled.entry_point,
+            &[],
+        )
+    }
+
+    fn compile_and_load(
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        constants: &[(&str, f64)],
+        options: &CompileOptions,
+    ) -> Result<Self, B::Error> {
+        let target = backend.compile_target();
+        let compiled =
+            CompiledFunction::compile(compiler, path, entry_point_name, target, options)?;
+        Self::load(
+            backend,
+            backend.load_compiled_module(&compiled.code, SlangCompiled(()))?,
+            compiled.reflection,
+            Some(path),
+            entry_point_name,
+            constants,
+        )
+    }
+
+    fn load(
+        backend: &B,
+        module: B::Module,
+        reflection: Reflection,
+        path: Option<&str>,
+        entry_point_name: &str,
+        constants: &[(&str, f64)],
+    ) -> Result<Self, B::Error> {
+        Self::from_module(
+            backend,
+            &module,
+            reflection,
+            path,
+            entry_point_name,
+            constants,
+        )
+    }
 
+    /// Loads the entry point `entry_point` of a module already loaded by the backend, e.g. shared
+    /// with other functions by a [`ShaderRegistry`](crate::registry::ShaderRegistry).
+    pub(crate) fn from_module_reflection(
+        backend: &B,
+        module: &B::Module,
+        path: &str,
+        entry_point: &EntryPointReflection,
+    ) -> Result<Self, B::Error> {
+        let reflection = Reflection::new(entry_point);
+        Self::from_module(
+            backend,
+            module,
+            reflection,
+            Some(path),
+            &entry_point.name,
+            &[],
+        )
+    }
+
+    fn from_module(
+        backend: &B,
+        module: &B::Module,
+        reflection: Reflection,
+        path: Option<&str>,
+        entry_point_name: &str,
+        constants: &[(&str, f64)],
+    ) -> Result<Self, B::Error> {
+        let options = FunctionOptions {
+            bindings: &reflection.bindings,
+            value_buffer: reflection.args.value_buffer,
+            constants,
+        };
+        let function = backend.load_function_with_options(module, entry_point_name, options)?;
         Ok(Self {
-            block_dim,
-            args: ShaderArgsDesc { buffers },
+            entry_point: entry_point_name.to_string(),
+            module: path.map(str::to_string),
+            block_dim: reflection.block_dim,
+            args: reflection.args,
+            bindings: reflection.bindings,
+            parameters: reflection.entry_point.arguments,
             function,
+            shared_memory_bytes: 0,
         })
     }
 
+    /// Sets the amount of dynamic shared memory, in bytes, allocated to each workgroup when this
+    /// function is launched.
+    ///
+    /// This is needed by kernels using dynamically sized shared memory (e.g. an unsized
+    /// `groupshared` array). See [`Dispatch::set_dynamic_shared_memory`] for details.
+    pub fn with_dynamic_shared_memory(mut self, bytes: u32) -> Self {
+        self.shared_memory_bytes = bytes;
+        self
+    }
+
+    /// The amount of dynamic shared memory, in bytes, allocated to each workgroup when this
+    /// function is launched.
+    pub fn dynamic_shared_memory(&self) -> u32 {
+        self.shared_memory_bytes
+    }
+
+    /// The thread-group size of this function, as declared with `[numthreads(…)]`.
     pub fn block_dim(&self) -> [u32; 3] {
         self.block_dim
     }
 
+    /// The name of the entry point of this function.
+    ///
+    /// Dispatches of this function are labeled with this name in GPU captures.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// The path of the Slang module this function was loaded from (or its name in a
+    /// [`SlangPack`]), or `None` if it was loaded with [`Self::from_precompiled`].
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    /// The parameters of this function that aren’t system values, in declaration order.
+    ///
+    /// Each parameter comes with its binding (space and index) for the backend’s compilation
+    /// target, and the kind of resource bound to it (e.g. a structured buffer with the stride of
+    /// its elements), or `None` if it is passed by value.
+    pub fn parameters(&self) -> &[PackedArgument] {
+        &self.parameters
+    }
+
+    /// The parameter named `name`, if any.
+    pub fn parameter(&self, name: &str) -> Option<&PackedArgument> {
+        self.parameters.iter().find(|param| param.name == name)
+    }
+
+    /// Binds `args` to the parameters of this function.
+    ///
+    /// On failure, the error is a [`ShaderArgsError::Kernel`] naming this function, its module,
+    /// the parameters `args` has no argument for, and the arguments of `args` (if it can list
+    /// them, see [`ShaderArgs::arg_names`]) matching no parameter.
     pub fn bind<'a, 'b: 'a>(
         &self,
         dispatch: &mut B::Dispatch<'a>,
         args: &'b impl ShaderArgs<'b, B>,
     ) -> Result<(), B::Error> {
+        if let Some(binding) = self.args.value_buffer {
+            dispatch.set_value_buffer_binding(binding);
+        }
+        if self.shared_memory_bytes != 0 {
+            dispatch.set_dynamic_shared_memory(self.shared_memory_bytes);
+        }
+
+        // NOTE: binding goes on after a missing argument, so they are all reported at once.
+        let mut missing = vec![];
+        let mut error = None;
         for (arg_name, arg_binding) in &self.args.buffers {
-            args.write_arg(*arg_binding, arg_name, dispatch).unwrap(); // TODO: don't unwrap!
+            let result = match args.arg_info(arg_name) {
+                Some(info) => self.check_arg(arg_name, *arg_binding, info),
+                None => Ok(()),
+            }
+            .and_then(|()| args.write_arg(*arg_binding, arg_name, dispatch));
+            match result {
+                Ok(()) => {}
+                Err(ShaderArgsError::ArgNotFound(_)) => missing.push(arg_name.clone()),
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        let Some(error) = error.or_else(|| {
+            let name = missing.first()?;
+            Some(ShaderArgsError::ArgNotFound(name.clone()))
+        }) else {
+            return Ok(());
+        };
+        let extra = args
+            .arg_names()
+            .into_iter()
+            .filter(|name| !self.args.buffers.iter().any(|(param, _)| param == name))
+            .collect();
+        Err(ShaderArgsError::Kernel {
+            kernel: self.entry_point.clone(),
+            module: self.module.clone(),
+            missing,
+            extra,
+            error: Box::new(error),
+        }
+        .into())
+    }
+
+    /// Checks that the resource described by `info` matches the type of the buffer parameter
+    /// `name` bound at `binding`.
+    fn check_arg(
+        &self,
+        name: &str,
+        binding: ShaderBinding,
+        info: ArgInfo,
+    ) -> Result<(), ShaderArgsError> {
+        let layout = self
+            .bindings
+            .iter()
+            .find(|layout| layout.binding == binding);
+        let Some(BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only },
+            min_binding_size,
+            ..
+        }) = layout.and_then(|layout| layout.ty)
+        else {
+            return Ok(());
+        };
+
+        // Structured buffers have a minimum binding size of one element, and byte-address
+        // buffers are made of 32-bit words.
+        let element_size = min_binding_size.map_or(4, |size| size.get() as usize);
+        if min_binding_size.is_some()
+            && let Some(found) = info.element_size
+            && found != element_size
+        {
+            return Err(ShaderArgsError::ElementSizeMismatch {
+                name: name.to_string(),
+                expected: element_size,
+                found,
+            });
+        }
+        if let Some(size) = info.size_bytes
+            && !size.is_multiple_of(element_size)
+        {
+            return Err(ShaderArgsError::SizeMismatch {
+                name: name.to_string(),
+                size,
+                element_size,
+            });
+        }
+        if !read_only && info.writable == Some(false) {
+            return Err(ShaderArgsError::NotWritable(name.to_string()));
         }
         Ok(())
     }
 
-    /// Launches the function, clamping the dispatch size so it doesn’t exceed WebGPU’s 65535
-    /// workgroup count limit.
+    /// Launches the function, clamping the dispatch size so it doesn’t exceed the backend’s
+    /// workgroup count limit (see [`Backend::limits`]).
     ///
     /// Only use this is your shader is capable of handling the case where it should have exceeded
-    /// 65535 * WORKGROUP_SIZE.
+    /// `max_workgroup_count[0] * WORKGROUP_SIZE`.
     ///
     /// Panics if the shader’s block dimension isn’t `1` along the second and third axes, i.e.,
     /// it should be `[anything, 1, 1]`.
@@ -105,10 +723,10 @@ impl<B: Backend> GpuFunction<B> {
             "launch_capped isn’t applicable in this case"
         );
 
-        // TODO: this is the WebGpu limit. Ideally, we should adjust it depending on the target
-        //       platform. And if we do adjust it, the shaders should be adjusted too (because
-        //       Slang doesn’t have any way to know the total number of dispatched workgroups).
-        let max_num_threads = Self::MAX_NUM_WORKGROUPS * self.block_dim[0];
+        // NOTE: Slang doesn’t have any way to know the total number of dispatched workgroups, so
+        //       the shader has to be told about this limit if it needs it.
+        let max_num_workgroups = backend.limits()?.max_workgroup_count[0];
+        let max_num_threads = max_num_workgroups.saturating_mul(self.block_dim[0]);
         self.launch(
             backend,
             pass,
@@ -117,6 +735,53 @@ impl<B: Backend> GpuFunction<B> {
         )
     }
 
+    /// Launches `num_threads` threads along x, folding the workgroups into a 2D or 3D grid if
+    /// they exceed the backend’s workgroup count limit along x (see [`Backend::limits`]).
+    ///
+    /// The folded grid is bound to the kernel parameter named
+    /// [`FOLDED_GRID_PARAM`](crate::grid::FOLDED_GRID_PARAM), from which
+    /// the kernel computes the linear index of each thread and checks it against `num_threads`.
+    /// See the [`grid`](crate::grid) module for the Slang side of this convention.
+    ///
+    /// Fails with [`ShaderArgsError::TooManyThreads`] if `num_threads` exceeds the largest 3D
+    /// grid of the backend. Panics if the shader’s block dimension isn’t `1` along the second and
+    /// third axes, i.e., it should be `[anything, 1, 1]`.
+    pub fn launch_folded<'b>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        args: &'b impl ShaderArgs<'b, B>,
+        num_threads: u32,
+    ) -> Result<(), B::Error> {
+        assert_eq!(
+            [self.block_dim[1], self.block_dim[2]],
+            [1, 1],
+            "launch_folded isn’t applicable in this case"
+        );
+
+        let num_workgroups = num_threads.div_ceil(self.block_dim[0]);
+        let max_workgroup_count = backend.limits()?.max_workgroup_count;
+        let grid = fold_grid(num_workgroups, max_workgroup_count).ok_or_else(|| {
+            let max_workgroups = max_workgroup_count
+                .iter()
+                .fold(1u64, |acc, count| acc.saturating_mul(*count as u64));
+            ShaderArgsError::TooManyThreads {
+                num_threads: num_threads as u64,
+                max_threads: max_workgroups.saturating_mul(self.block_dim[0] as u64),
+            }
+        })?;
+        let args = FoldedArgs {
+            grid: [
+                grid[0].saturating_mul(self.block_dim[0]),
+                grid[1],
+                num_threads,
+                0,
+            ],
+            args,
+        };
+        self.launch_grid(backend, pass, &args, DispatchGrid::Direct(grid))
+    }
+
     pub fn launch<'b>(
         &self,
         backend: &B,
@@ -128,6 +793,10 @@ impl<B: Backend> GpuFunction<B> {
         self.launch_grid(backend, pass, args, DispatchGrid::Direct(grid))
     }
 
+    /// Launches the function with the workgroup count read by the device from the first element
+    /// of `grid`.
+    ///
+    /// Use [`Self::launch_grid`] with [`DispatchGrid::Indirect`] to read it at another offset.
     pub fn launch_indirect<'b>(
         &self,
         backend: &B,
@@ -135,7 +804,7 @@ impl<B: Backend> GpuFunction<B> {
         args: &'b impl ShaderArgs<'b, B>,
         grid: &'b B::Buffer<[u32; 3]>,
     ) -> Result<(), B::Error> {
-        self.launch_grid(backend, pass, args, DispatchGrid::Indirect(grid))
+        self.launch_grid(backend, pass, args, DispatchGrid::Indirect(grid, 0))
     }
 
     pub fn launch_grid<'b>(
@@ -145,9 +814,238 @@ impl<B: Backend> GpuFunction<B> {
         args: &'b impl ShaderArgs<'b, B>,
         grid: impl Into<DispatchGrid<'b, B>>,
     ) -> Result<(), B::Error> {
-        let mut dispatch = backend.begin_dispatch(pass, &self.function);
+        let mut dispatch = backend.begin_dispatch(pass, &self.function, &self.entry_point);
         self.bind(&mut dispatch, args)?;
         dispatch.launch(grid, self.block_dim)?;
         Ok(())
     }
+
+    /// Starts a launch of this function whose arguments are given one by one, see
+    /// [`LaunchBuilder`].
+    pub fn dispatch<'f, 'a>(
+        &'f self,
+        backend: &'f B,
+        pass: &'f mut B::Pass,
+    ) -> LaunchBuilder<'f, 'a, B> {
+        LaunchBuilder {
+            function: self,
+            backend,
+            pass,
+            args: DynArgs::new(),
+            grid: DispatchGrid::Direct([1, 1, 1]),
+        }
+    }
+}
+
+/// A launch of a [`GpuFunction`] with its arguments given one by one, created by
+/// [`GpuFunction::dispatch`].
+///
+/// This avoids declaring a struct deriving `ShaderArgs` for one-off kernels:
+///
+/// ```ignore
+/// function
+///     .dispatch(&backend, &mut pass)
+///     .arg("a", &buf_a)
+///     .arg("b", &buf_b)
+///     .threads([n, 1, 1])
+///     .launch()?;
+/// ```
+pub struct LaunchBuilder<'f, 'a, B: Backend> {
+    function: &'f GpuFunction<B>,
+    backend: &'f B,
+    pass: &'f mut B::Pass,
+    args: DynArgs<'a, B>,
+    grid: DispatchGrid<'a, B>,
+}
+
+impl<'f, 'a, B: Backend> LaunchBuilder<'f, 'a, B> {
+    /// Binds `arg` to the parameter `name`.
+    pub fn arg(mut self, name: impl Into<String>, arg: &'a dyn ShaderArgs<'a, B>) -> Self {
+        self.args.insert(name, arg);
+        self
+    }
+
+    /// Launches enough workgroups to cover `num_threads` threads, like [`GpuFunction::launch`].
+    pub fn threads(mut self, num_threads: [u32; 3]) -> Self {
+        let block_dim = self.function.block_dim;
+        let grid = [0, 1, 2].map(|i| num_threads[i].div_ceil(block_dim[i]));
+        self.grid = DispatchGrid::Direct(grid);
+        self
+    }
+
+    /// Launches the given number of workgroups, like [`GpuFunction::launch_grid`].
+    pub fn grid(mut self, grid: impl Into<DispatchGrid<'a, B>>) -> Self {
+        self.grid = grid.into();
+        self
+    }
+
+    /// Launches the function.
+    ///
+    /// A single workgroup is launched if neither [`Self::threads`] nor [`Self::grid`] was called.
+    pub fn launch(self) -> Result<(), B::Error> {
+        self.function
+            .launch_grid(self.backend, self.pass, &self.args, self.grid)
+    }
+}
+
+/// A [`GpuFunction`] compiled and loaded on first use rather than on creation.
+///
+/// This avoids paying for the compilation of kernels an application might never run. Note that
+/// compilation errors are only reported on first use too.
+pub struct LazyGpuFunction<B: Backend> {
+    path: String,
+    entry_point: String,
+    options: CompileOptions,
+    function: OnceLock<GpuFunction<B>>,
+}
+
+impl<B: Backend> LazyGpuFunction<B> {
+    /// A function loading the entry point `entry_point_name` of the shader at `path` on first use,
+    /// as [`GpuFunction::from_file_with_options`] would.
+    pub fn new(path: &str, entry_point_name: &str, options: CompileOptions) -> Self {
+        Self {
+            path: path.to_string(),
+            entry_point: entry_point_name.to_string(),
+            options,
+            function: OnceLock::new(),
+        }
+    }
+
+    /// The name of the entry point of this function.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Was this function already compiled and loaded?
+    pub fn is_loaded(&self) -> bool {
+        self.function.get().is_some()
+    }
+
+    /// The loaded function, compiling and loading it first if this is the first call.
+    ///
+    /// If this is called concurrently before the function is loaded, it might be compiled more
+    /// than once, but only one of the results is kept.
+    pub fn get(&self, backend: &B, compiler: &SlangCompiler) -> Result<&GpuFunction<B>, B::Error> {
+        if let Some(function) = self.function.get() {
+            return Ok(function);
+        }
+
+        let function = GpuFunction::from_file_with_options(
+            backend,
+            compiler,
+            &self.path,
+            &self.entry_point,
+            &self.options,
+        )?;
+        Ok(self.function.get_or_init(|| function))
+    }
+
+    /// Launches the function after loading it if needed. See [`GpuFunction::launch`].
+    pub fn launch<'b>(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        pass: &mut B::Pass,
+        args: &'b impl ShaderArgs<'b, B>,
+        num_threads: [u32; 3],
+    ) -> Result<(), B::Error> {
+        self.get(backend, compiler)?
+            .launch(backend, pass, args, num_threads)
+    }
+
+    /// Launches the function after loading it if needed. See [`GpuFunction::launch_grid`].
+    pub fn launch_grid<'b>(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        pass: &mut B::Pass,
+        args: &'b impl ShaderArgs<'b, B>,
+        grid: impl Into<DispatchGrid<'b, B>>,
+    ) -> Result<(), B::Error> {
+        self.get(backend, compiler)?
+            .launch_grid(backend, pass, args, grid)
+    }
+}
+
+/// The variants of an entry point compiled with different sets of preprocessor macros (e.g. for
+/// different precisions or tile sizes), each compiled and loaded on first use.
+///
+/// Variants are identified by their set of macros, regardless of the order they are given in.
+pub struct GpuFunctionVariants<B: Backend> {
+    path: String,
+    entry_point: String,
+    options: CompileOptions,
+    variants: Mutex<HashMap<Vec<(String, String)>, Arc<GpuFunction<B>>>>,
+}
+
+impl<B: Backend> GpuFunctionVariants<B> {
+    /// The variants of the entry point `entry_point_name` of the shader at `path`, compiled with
+    /// `options` in addition to the macros of each variant.
+    pub fn new(path: &str, entry_point_name: &str, options: CompileOptions) -> Self {
+        Self {
+            path: path.to_string(),
+            entry_point: entry_point_name.to_string(),
+            options,
+            variants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The name of the entry point of these variants.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// The number of variants loaded so far.
+    pub fn len(&self) -> usize {
+        self.variants.lock().unwrap().len()
+    }
+
+    /// Was no variant loaded yet?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The variant with the macros `defines`, compiling and loading it first if this is the first
+    /// call with this set of macros.
+    ///
+    /// Like [`LazyGpuFunction::get`], a variant requested concurrently before being loaded might
+    /// be compiled more than once, but only one of the results is kept.
+    pub fn get(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        defines: &[(&str, &str)],
+    ) -> Result<Arc<GpuFunction<B>>, B::Error> {
+        let mut key: Vec<_> = defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        key.sort();
+        key.dedup();
+        if let Some(function) = self.variants.lock().unwrap().get(&key) {
+            return Ok(function.clone());
+        }
+
+        let options = key
+            .iter()
+            .fold(self.options.clone(), |options, (name, value)| {
+                options.macro_define(name, value)
+            });
+        let function = GpuFunction::from_file_with_options(
+            backend,
+            compiler,
+            &self.path,
+            &self.entry_point,
+            &options,
+        )?;
+        let mut variants = self.variants.lock().unwrap();
+        Ok(variants.entry(key).or_insert(Arc::new(function)).clone())
+    }
+
+    /// Removes all the loaded variants.
+    ///
+    /// Variants still referenced elsewhere aren’t affected.
+    pub fn clear(&self) {
+        self.variants.lock().unwrap().clear();
+    }
 }
@@ -0,0 +1,336 @@
+//! Scheduling of interdependent dispatches from the resources they access.
+//!
+//! A [`ComputeGraph`] holds a sequence of nodes (kernel launches or encoder commands like
+//! copies), each declaring the resources it reads and writes. The graph derives the
+//! dependencies between nodes from these accesses, as if the nodes ran one after the other in
+//! declaration order, then groups the nodes that don’t depend on each other into the same pass.
+//! Each group starts a new pass, which is where backends synchronize dependent work, and the
+//! graph can be split across several submissions. The graph is recorded again each time it is
+//! executed, e.g. once per simulation step.
+//!
+//! ```ignore
+//! let mut graph = ComputeGraph::new("step");
+//! graph
+//!     .node("forces")
+//!     .reads(&positions)
+//!     .writes(&forces)
+//!     .dispatch(|backend, pass| shaders.forces.launch(backend, pass, &forces_args, [n, 1, 1]));
+//! graph
+//!     .node("integrate")
+//!     .reads(&forces)
+//!     .writes(&positions)
+//!     .dispatch(|backend, pass| shaders.integrate.launch(backend, pass, &integrate_args, [n, 1, 1]));
+//!
+//! for _ in 0..num_steps {
+//!     graph.execute(&backend)?;
+//! }
+//! ```
+
+use crate::backend::{Backend, Encoder};
+
+/// The identifier of a node of a [`ComputeGraph`], in declaration order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+/// The identity of a resource accessed by a node: its address.
+type ResourceId = usize;
+
+type DispatchFn<'a, B> =
+    Box<dyn FnMut(&B, &mut <B as Backend>::Pass) -> Result<(), <B as Backend>::Error> + 'a>;
+type EncodeFn<'a, B> =
+    Box<dyn FnMut(&B, &mut <B as Backend>::Encoder) -> Result<(), <B as Backend>::Error> + 'a>;
+
+enum NodeKind<'a, B: Backend> {
+    /// Kernel launches recorded into a pass.
+    Dispatch(DispatchFn<'a, B>),
+    /// Commands recorded directly into the encoder, e.g. buffer copies.
+    Encode(EncodeFn<'a, B>),
+}
+
+struct Node<'a, B: Backend> {
+    label: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    after: Vec<NodeId>,
+    kind: NodeKind<'a, B>,
+}
+
+impl<B: Backend> Node<'_, B> {
+    /// Whether this node must run after `prev`, declared before it.
+    fn depends_on(&self, id: NodeId, prev: &Self) -> bool {
+        let overlap = |a: &[ResourceId], b: &[ResourceId]| a.iter().any(|r| b.contains(r));
+        self.after.contains(&id)
+            || overlap(&prev.writes, &self.reads)
+            || overlap(&prev.writes, &self.writes)
+            || overlap(&prev.reads, &self.writes)
+    }
+}
+
+/// A sequence of dispatches and encoder commands, scheduled from the resources they access.
+///
+/// See the [module documentation](self) for details. Resources are identified by their address,
+/// so the same buffer (not a clone of its handle) must be given to every node accessing it.
+pub struct ComputeGraph<'a, B: Backend> {
+    label: String,
+    nodes: Vec<Node<'a, B>>,
+    /// The nodes grouped by pass, computed on first execution.
+    levels: Option<Vec<Vec<NodeId>>>,
+    max_nodes_per_submission: usize,
+}
+
+impl<'a, B: Backend> ComputeGraph<'a, B> {
+    /// An empty graph, whose passes are labeled with `label` in GPU captures.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            nodes: vec![],
+            levels: None,
+            max_nodes_per_submission: usize::MAX,
+        }
+    }
+
+    /// Submits the commands recorded by [`Self::execute`] each time at least `max_nodes` nodes
+    /// were recorded, instead of once at the end.
+    ///
+    /// Splitting large graphs lets the device start working before the whole graph is recorded.
+    /// Submissions only happen between passes.
+    pub fn with_max_nodes_per_submission(mut self, max_nodes: usize) -> Self {
+        self.max_nodes_per_submission = max_nodes.max(1);
+        self
+    }
+
+    /// The number of nodes of this graph.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Does this graph have no node?
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The label of the node `id`.
+    pub fn label(&self, id: NodeId) -> &str {
+        &self.nodes[id.0].label
+    }
+
+    /// Starts declaring a node labeled `label`, added to the graph once its commands are given
+    /// with [`NodeBuilder::dispatch`] or [`NodeBuilder::encode`].
+    pub fn node<'g>(&'g mut self, label: impl Into<String>) -> NodeBuilder<'g, 'a, B> {
+        NodeBuilder {
+            graph: self,
+            label: label.into(),
+            reads: vec![],
+            writes: vec![],
+            after: vec![],
+        }
+    }
+
+    /// The nodes of this graph, grouped by pass in execution order.
+    ///
+    /// Each node is in the group following the last group containing one of its dependencies,
+    /// and the nodes of a group are in declaration order.
+    pub fn levels(&mut self) -> &[Vec<NodeId>] {
+        self.levels.get_or_insert_with(|| {
+            let mut node_levels: Vec<usize> = Vec::with_capacity(self.nodes.len());
+            let mut levels: Vec<Vec<NodeId>> = vec![];
+            for (j, node) in self.nodes.iter().enumerate() {
+                let level = (0..j)
+                    .filter(|i| node.depends_on(NodeId(*i), &self.nodes[*i]))
+                    .map(|i| node_levels[i] + 1)
+                    .max()
+                    .unwrap_or(0);
+                node_levels.push(level);
+                if level == levels.len() {
+                    levels.push(vec![]);
+                }
+                levels[level].push(NodeId(j));
+            }
+            levels
+        })
+    }
+
+    /// Records all the nodes of this graph into `encoder`, without submitting it.
+    pub fn encode(&mut self, backend: &B, encoder: &mut B::Encoder) -> Result<(), B::Error> {
+        let levels = self.levels().to_vec();
+        for level in &levels {
+            self.encode_level(backend, encoder, level)?;
+        }
+        Ok(())
+    }
+
+    /// Records all the nodes of this graph and submits them to `backend`.
+    pub fn execute(&mut self, backend: &B) -> Result<(), B::Error> {
+        let levels = self.levels().to_vec();
+        let mut encoder = backend.begin_encoding();
+        let mut num_recorded = 0;
+        for level in &levels {
+            if num_recorded >= self.max_nodes_per_submission {
+                let full = std::mem::replace(&mut encoder, backend.begin_encoding());
+                backend.submit(full)?;
+                num_recorded = 0;
+            }
+            self.encode_level(backend, &mut encoder, level)?;
+            num_recorded += level.len();
+        }
+        backend.submit(encoder)
+    }
+
+    fn encode_level(
+        &mut self,
+        backend: &B,
+        encoder: &mut B::Encoder,
+        level: &[NodeId],
+    ) -> Result<(), B::Error> {
+        // NOTE: the nodes of a level are independent, so the dispatches are grouped into a
+        //       single pass, and the encoder commands are recorded once the pass ended.
+        let mut pass = None;
+        for id in level {
+            if let NodeKind::Dispatch(dispatch) = &mut self.nodes[id.0].kind {
+                let pass = pass.get_or_insert_with(|| encoder.begin_pass(&self.label));
+                dispatch(backend, pass)?;
+            }
+        }
+        drop(pass);
+
+        for id in level {
+            if let NodeKind::Encode(encode) = &mut self.nodes[id.0].kind {
+                encode(backend, encoder)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A node being declared with [`ComputeGraph::node`].
+pub struct NodeBuilder<'g, 'a, B: Backend> {
+    graph: &'g mut ComputeGraph<'a, B>,
+    label: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    after: Vec<NodeId>,
+}
+
+impl<'a, B: Backend> NodeBuilder<'_, 'a, B> {
+    /// Declares that the node reads `resource` (e.g. a buffer or texture).
+    pub fn reads<T: ?Sized>(mut self, resource: &T) -> Self {
+        self.reads.push(resource_id(resource));
+        self
+    }
+
+    /// Declares that the node writes to `resource` (e.g. a buffer or texture).
+    pub fn writes<T: ?Sized>(mut self, resource: &T) -> Self {
+        self.writes.push(resource_id(resource));
+        self
+    }
+
+    /// Declares that the node must run after the node `id`, regardless of the resources they
+    /// access.
+    ///
+    /// Panics if `id` isn’t a node already added to the graph.
+    pub fn after(mut self, id: NodeId) -> Self {
+        assert!(
+            id.0 < self.graph.nodes.len(),
+            "node `{}` can’t run after {id:?}, which isn’t in the graph yet",
+            self.label
+        );
+        self.after.push(id);
+        self
+    }
+
+    /// Adds the node to the graph, recording its kernel launches into a pass with `dispatch`.
+    pub fn dispatch(
+        self,
+        dispatch: impl FnMut(&B, &mut B::Pass) -> Result<(), B::Error> + 'a,
+    ) -> NodeId {
+        self.add(NodeKind::Dispatch(Box::new(dispatch)))
+    }
+
+    /// Adds the node to the graph, recording its commands (e.g. buffer copies) directly into the
+    /// encoder with `encode`.
+    pub fn encode(
+        self,
+        encode: impl FnMut(&B, &mut B::Encoder) -> Result<(), B::Error> + 'a,
+    ) -> NodeId {
+        self.add(NodeKind::Encode(Box::new(encode)))
+    }
+
+    fn add(self, kind: NodeKind<'a, B>) -> NodeId {
+        let id = NodeId(self.graph.nodes.len());
+        self.graph.nodes.push(Node {
+            label: self.label,
+            reads: self.reads,
+            writes: self.writes,
+            after: self.after,
+            kind,
+        });
+        self.graph.levels = None;
+        id
+    }
+}
+
+fn resource_id<T: ?Sized>(resource: &T) -> ResourceId {
+    resource as *const T as *const () as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Mock;
+    use std::cell::RefCell;
+
+    /// A dispatch appending `label` to `order` when recorded.
+    fn record<'a>(
+        order: &'a RefCell<Vec<&'static str>>,
+        label: &'static str,
+    ) -> DispatchFn<'a, Mock> {
+        Box::new(move |_, _| {
+            order.borrow_mut().push(label);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn nodes_are_scheduled_by_dependencies() {
+        let backend = Mock::new();
+        let (a, b, c) = (0u32, 0u32, 0u32);
+        let order = RefCell::new(vec![]);
+        let mut graph = ComputeGraph::<Mock>::new("test");
+        let node = |label| record(&order, label);
+
+        let write_a = graph.node("write_a").writes(&a).dispatch(node("write_a"));
+        let write_b = graph.node("write_b").writes(&b).dispatch(node("write_b"));
+        let read_a = graph
+            .node("read_a")
+            .reads(&a)
+            .writes(&c)
+            .dispatch(node("read_a"));
+        let read_b = graph.node("read_b").reads(&b).dispatch(node("read_b"));
+        let last = graph.node("last").after(write_b).encode(|_, _| {
+            order.borrow_mut().push("last");
+            Ok(())
+        });
+        let read_c = graph.node("read_c").reads(&c).dispatch(node("read_c"));
+
+        assert_eq!(
+            graph.levels(),
+            [
+                vec![write_a, write_b],
+                vec![read_a, read_b, last],
+                vec![read_c]
+            ]
+        );
+        graph.execute(&backend).unwrap();
+        assert_eq!(
+            *order.borrow(),
+            ["write_a", "write_b", "read_a", "read_b", "last", "read_c"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "isn’t in the graph yet")]
+    fn forward_dependencies_are_rejected() {
+        let mut graph = ComputeGraph::<Mock>::new("test");
+        graph.node("first").after(NodeId(0)).encode(|_, _| Ok(()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Launching 1D workloads larger than a single row of workgroups.
+//!
+//! Backends limit the number of workgroups along each axis of a dispatch (e.g. 65535 on WebGPU),
+//! which caps 1D workloads to a few million threads. [`GpuFunction::launch_folded`] lifts this
+//! limit by folding the workgroups into the rows and layers of a 2D or 3D grid, and passes the
+//! folded grid to the kernel as the [`FOLDED_GRID_PARAM`] parameter. Kernels recover the linear
+//! index of each thread with the `FoldedGrid` struct of the `slang_hal_grid` Slang module, which
+//! must be added to the compiler with [`register_shaders`]:
+//!
+//! ```slang
+//! import slang_hal_grid;
+//!
+//! [shader("compute")]
+//! [numthreads(64, 1, 1)]
+//! void integrate(
+//!     uint3 invocation_id: SV_DispatchThreadID,
+//!     uniform FoldedGrid folded_grid,
+//!     RWStructuredBuffer<float4> positions,
+//! ) {
+//!     let i = folded_grid.index(invocation_id);
+//!     if (i >= folded_grid.num_threads) {
+//!         return;
+//!     }
+//!     // …
+//! }
+//! ```
+//!
+//! [`GpuFunction::launch_folded`]: crate::function::GpuFunction::launch_folded
+
+use crate::backend::{Backend, Dispatch, ShaderBinding};
+use crate::shader::{ArgInfo, ShaderArgs, ShaderArgsError};
+use minislang::SlangCompiler;
+
+/// The Slang module defining the `FoldedGrid` struct.
+pub const SHADERS: include_dir::Dir<'static> =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/shaders/grid");
+
+/// Adds the `slang_hal_grid` Slang module to `compiler`.
+pub fn register_shaders(compiler: &mut SlangCompiler) {
+    compiler.add_dir(SHADERS);
+}
+
+/// The name of the entry-point parameter the folded grid is bound to, declared as
+/// `uniform FoldedGrid folded_grid`.
+pub const FOLDED_GRID_PARAM: &str = "folded_grid";
+
+/// The grid covering `num_workgroups` workgroups without exceeding `max_workgroup_count` along
+/// any axis.
+///
+/// The rows (along x) are filled first, then the layers (along y). The grid may contain up to a
+/// row and a layer of extra workgroups. Returns `None` if even the largest 3D grid is too small.
+pub fn fold_grid(num_workgroups: u32, max_workgroup_count: [u32; 3]) -> Option<[u32; 3]> {
+    let x = num_workgroups.min(max_workgroup_count[0]).max(1);
+    let rows = num_workgroups.div_ceil(x);
+    let y = rows.min(max_workgroup_count[1]).max(1);
+    let z = rows.div_ceil(y);
+    (z <= max_workgroup_count[2]).then_some([x, y, z])
+}
+
+/// The arguments of a folded launch: the folded grid, bound to [`FOLDED_GRID_PARAM`], followed
+/// by the user’s arguments.
+pub(crate) struct FoldedArgs<'b, A> {
+    /// The `FoldedGrid` passed to the kernel.
+    pub grid: [u32; 4],
+    pub args: &'b A,
+}
+
+// NOTE: like `DynArgs`, this can be bound without being borrowed for as long as its arguments.
+impl<'c, 'b: 'c, B: Backend, A: ShaderArgs<'b, B>> ShaderArgs<'c, B> for FoldedArgs<'b, A> {
+    fn write_arg<'a>(
+        &'c self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'c: 'a,
+    {
+        if name == FOLDED_GRID_PARAM {
+            dispatch.write_value(binding, name, &self.grid)
+        } else {
+            self.args.write_arg(binding, name, dispatch)
+        }
+    }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        self.args.arg_info(name)
+    }
+
+    fn arg_names(&self) -> Vec<String> {
+        let mut names = self.args.arg_names();
+        names.push(FOLDED_GRID_PARAM.to_string());
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_are_folded() {
+        let max = [65535; 3];
+        assert_eq!(fold_grid(0, max), Some([1, 1, 0]));
+        assert_eq!(fold_grid(100, max), Some([100, 1, 1]));
+        assert_eq!(fold_grid(65536, max), Some([65535, 2, 1]));
+        assert_eq!(fold_grid(u32::MAX, max), Some([65535, 65535, 2]));
+        assert_eq!(fold_grid(1000, [10, 10, 10]), Some([10, 10, 10]));
+        assert_eq!(fold_grid(1001, [10, 10, 10]), None);
+    }
+}
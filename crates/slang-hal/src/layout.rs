@@ -0,0 +1,140 @@
+//! Validation of the memory layout of Rust structs against their Slang counterparts.
+//!
+//! ```ignore
+//! #[derive(Copy, Clone, DeviceValue)]
+//! #[repr(C)]
+//! struct Particle {
+//!     position: [f32; 3],
+//!     mass: f32,
+//! }
+//!
+//! let program = compiler.compile("particles", backend.compile_target(), None, &options)?;
+//! slang_hal::layout::check_layout::<Particle>(&program.type_reflection("Particle")?)?;
+//! ```
+
+use crate::backend::DeviceValue;
+use minislang::reflection::TypeReflection;
+use minislang::shader_slang::TypeKind;
+
+/// The memory layout of a Rust struct, as generated by `derive(DeviceValue)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructLayout {
+    /// The name of the struct.
+    pub name: &'static str,
+    /// The size of the struct, in bytes, including trailing padding.
+    pub size: usize,
+    /// The fields of the struct, in declaration order.
+    pub fields: Vec<FieldLayout>,
+}
+
+/// The memory layout of a field of a Rust struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// The name of the field (its index for tuple structs).
+    pub name: &'static str,
+    /// Byte offset of the field from the start of the struct.
+    pub offset: usize,
+    /// The size of the field, in bytes.
+    pub size: usize,
+}
+
+/// A difference between the layouts of a Rust struct and of a Slang struct.
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    #[error("`{0}` doesn’t describe its layout; derive `DeviceValue` to validate it")]
+    UnknownLayout(&'static str),
+    #[error("the slang type matched against `{0}` isn’t a struct")]
+    NotAStruct(&'static str),
+    #[error("`{ty}` takes {rust} bytes in Rust but {slang} bytes in Slang")]
+    Size {
+        ty: &'static str,
+        rust: usize,
+        slang: usize,
+    },
+    #[error("the field `{field}` of `{ty}` doesn’t exist in Slang")]
+    MissingInSlang { ty: &'static str, field: String },
+    #[error("the field `{field}` of the slang struct matched against `{ty}` doesn’t exist in Rust")]
+    MissingInRust { ty: &'static str, field: String },
+    #[error("the field `{field}` of `{ty}` is at offset {rust} in Rust but {slang} in Slang")]
+    FieldOffset {
+        ty: &'static str,
+        field: String,
+        rust: usize,
+        slang: usize,
+    },
+    #[error("the field `{field}` of `{ty}` takes {rust} bytes in Rust but {slang} bytes in Slang")]
+    FieldSize {
+        ty: &'static str,
+        field: String,
+        rust: usize,
+        slang: usize,
+    },
+}
+
+/// Checks that the layout of `T` matches the layout of the Slang struct `ty`.
+///
+/// Fields are matched by name, except for the Rust fields whose name starts with `_pad`, which
+/// are ignored (e.g. the padding of the structs generated by `minislang::codegen`). The size of
+/// `T` is compared to the stride of `ty`, i.e., the distance between consecutive elements of a
+/// buffer. Only the struct itself is checked: nested structs must be checked separately.
+///
+/// The reflection data of a Slang struct is given by
+/// [`SlangProgram::type_reflection`](minislang::SlangProgram::type_reflection), or by the
+/// [`element_type`](TypeReflection::element_type) of the buffer parameters it is stored in.
+pub fn check_layout<T: DeviceValue>(ty: &TypeReflection) -> Result<(), LayoutError> {
+    let layout =
+        T::struct_layout().ok_or(LayoutError::UnknownLayout(std::any::type_name::<T>()))?;
+    if ty.kind != TypeKind::Struct {
+        return Err(LayoutError::NotAStruct(layout.name));
+    }
+    if layout.size != ty.stride {
+        return Err(LayoutError::Size {
+            ty: layout.name,
+            rust: layout.size,
+            slang: ty.stride,
+        });
+    }
+
+    let fields: Vec<_> = layout
+        .fields
+        .iter()
+        .filter(|field| !field.name.starts_with("_pad"))
+        .collect();
+    for field in &fields {
+        let Some(slang_field) = ty.fields.iter().find(|f| f.name == field.name) else {
+            return Err(LayoutError::MissingInSlang {
+                ty: layout.name,
+                field: field.name.to_string(),
+            });
+        };
+        if field.offset != slang_field.offset {
+            return Err(LayoutError::FieldOffset {
+                ty: layout.name,
+                field: field.name.to_string(),
+                rust: field.offset,
+                slang: slang_field.offset,
+            });
+        }
+        if field.size != slang_field.ty.size {
+            return Err(LayoutError::FieldSize {
+                ty: layout.name,
+                field: field.name.to_string(),
+                rust: field.size,
+                slang: slang_field.ty.size,
+            });
+        }
+    }
+
+    if let Some(extra) = ty
+        .fields
+        .iter()
+        .find(|f| fields.iter().all(|field| field.name != f.name))
+    {
+        return Err(LayoutError::MissingInRust {
+            ty: layout.name,
+            field: extra.name.clone(),
+        });
+    }
+
+    Ok(())
+}
@@ -2,10 +2,20 @@
 // #![warn(missing_docs)]
 #![allow(clippy::result_large_err)]
 
+pub mod autotune;
 pub mod backend;
+pub mod bundle;
 
 pub mod function;
+pub mod graph;
+pub mod grid;
+pub mod layout;
+pub mod linalg;
+pub mod pool;
+pub mod profiler;
+pub mod registry;
 pub mod shader;
+pub mod utils;
 // mod kernel;
 
 pub use shader::{Shader, ShaderArgs};
@@ -16,6 +26,7 @@ pub use slang_hal_derive::*;
 pub mod re_exports {
     pub use bytemuck;
     pub use encase;
+    pub use half;
     pub use include_dir;
     pub use minislang;
     pub use paste;
@@ -0,0 +1,583 @@
+//! Portable BLAS-like operations on backend buffers.
+//!
+//! Matrices are stored in column-major order, like in `nalgebra` and cuBLAS. On CUDA, the
+//! operations are performed by cuBLAS if the `cublas` feature is enabled (see
+//! `Cuda::cublas_enabled`). Otherwise, they are performed by the Slang kernels bundled with this
+//! crate, which must be added to the compiler with [`register_shaders`] before creating a
+//! [`Linalg`].
+
+use crate::backend::{Backend, DeviceValue, ShaderBinding, UnsupportedError};
+use crate::function::GpuFunction;
+use crate::shader::{Shader, ShaderArgs, ShaderArgsError, ShaderValue};
+use bytemuck::Pod;
+use minislang::SlangCompiler;
+use minislang::shader_slang::CompileTarget;
+use std::any::TypeId;
+
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+use crate::backend::{AnyBackendError, AnyBuffer, Cuda};
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+use cudarc::cublas::{result::CublasError, sys as cublas_sys};
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+use cudarc::driver::{DevicePtr, DevicePtrMut};
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+use std::any::Any;
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+use std::ffi::c_int;
+
+/// The Slang kernels used by [`Linalg`].
+pub const SHADERS: include_dir::Dir<'static> =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/shaders/linalg");
+
+/// Adds the Slang kernels used by [`Linalg`] to `compiler`.
+pub fn register_shaders(compiler: &mut SlangCompiler) {
+    compiler.add_dir(SHADERS);
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A scalar type supported by [`Linalg`], i.e., `f32` or `f64`.
+pub trait LinalgScalar: DeviceValue + Pod + sealed::Sealed {
+    #[cfg(all(feature = "cuda", feature = "cublas"))]
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn cublas_gemm(
+        handle: cublas_sys::cublasHandle_t,
+        m: c_int,
+        n: c_int,
+        k: c_int,
+        alpha: &Self,
+        a: *const Self,
+        b: *const Self,
+        beta: &Self,
+        c: *mut Self,
+    ) -> Result<(), CublasError>;
+
+    #[cfg(all(feature = "cuda", feature = "cublas"))]
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn cublas_gemv(
+        handle: cublas_sys::cublasHandle_t,
+        m: c_int,
+        n: c_int,
+        alpha: &Self,
+        a: *const Self,
+        x: *const Self,
+        beta: &Self,
+        y: *mut Self,
+    ) -> Result<(), CublasError>;
+
+    #[cfg(all(feature = "cuda", feature = "cublas"))]
+    #[doc(hidden)]
+    unsafe fn cublas_axpy(
+        handle: cublas_sys::cublasHandle_t,
+        n: c_int,
+        alpha: &Self,
+        x: *const Self,
+        y: *mut Self,
+    ) -> Result<(), CublasError>;
+
+    #[cfg(all(feature = "cuda", feature = "cublas"))]
+    #[doc(hidden)]
+    unsafe fn cublas_dot(
+        handle: cublas_sys::cublasHandle_t,
+        n: c_int,
+        x: *const Self,
+        y: *const Self,
+        result: *mut Self,
+    ) -> Result<(), CublasError>;
+}
+
+macro_rules! impl_linalg_scalar {
+    ($t: ty, $gemm: ident, $gemv: ident, $axpy: ident, $dot: ident) => {
+        impl sealed::Sealed for $t {}
+
+        impl LinalgScalar for $t {
+            #[cfg(all(feature = "cuda", feature = "cublas"))]
+            unsafe fn cublas_gemm(
+                handle: cublas_sys::cublasHandle_t,
+                m: c_int,
+                n: c_int,
+                k: c_int,
+                alpha: &Self,
+                a: *const Self,
+                b: *const Self,
+                beta: &Self,
+                c: *mut Self,
+            ) -> Result<(), CublasError> {
+                use cublas_sys::cublasOperation_t::CUBLAS_OP_N;
+                unsafe {
+                    cudarc::cublas::result::$gemm(
+                        handle,
+                        CUBLAS_OP_N,
+                        CUBLAS_OP_N,
+                        m,
+                        n,
+                        k,
+                        alpha,
+                        a,
+                        m,
+                        b,
+                        k,
+                        beta,
+                        c,
+                        m,
+                    )
+                }
+            }
+
+            #[cfg(all(feature = "cuda", feature = "cublas"))]
+            unsafe fn cublas_gemv(
+                handle: cublas_sys::cublasHandle_t,
+                m: c_int,
+                n: c_int,
+                alpha: &Self,
+                a: *const Self,
+                x: *const Self,
+                beta: &Self,
+                y: *mut Self,
+            ) -> Result<(), CublasError> {
+                use cublas_sys::cublasOperation_t::CUBLAS_OP_N;
+                unsafe {
+                    cudarc::cublas::result::$gemv(
+                        handle,
+                        CUBLAS_OP_N,
+                        m,
+                        n,
+                        alpha,
+                        a,
+                        m,
+                        x,
+                        1,
+                        beta,
+                        y,
+                        1,
+                    )
+                }
+            }
+
+            #[cfg(all(feature = "cuda", feature = "cublas"))]
+            unsafe fn cublas_axpy(
+                handle: cublas_sys::cublasHandle_t,
+                n: c_int,
+                alpha: &Self,
+                x: *const Self,
+                y: *mut Self,
+            ) -> Result<(), CublasError> {
+                unsafe { cublas_sys::$axpy(handle, n, alpha, x, 1, y, 1).result() }
+            }
+
+            #[cfg(all(feature = "cuda", feature = "cublas"))]
+            unsafe fn cublas_dot(
+                handle: cublas_sys::cublasHandle_t,
+                n: c_int,
+                x: *const Self,
+                y: *const Self,
+                result: *mut Self,
+            ) -> Result<(), CublasError> {
+                unsafe { cublas_sys::$dot(handle, n, x, 1, y, 1, result).result() }
+            }
+        }
+    };
+}
+
+impl_linalg_scalar!(f32, sgemm, sgemv, cublasSaxpy_v2, cublasSdot_v2);
+impl_linalg_scalar!(f64, dgemm, dgemv, cublasDaxpy_v2, cublasDdot_v2);
+
+macro_rules! linalg_args {
+    ($name: ident { $($value: ident: $ty: ty),*; $($buffer: ident),* }) => {
+        struct $name<'c, B: Backend, T: LinalgScalar> {
+            $($value: $ty,)*
+            $($buffer: &'c B::Buffer<T>,)*
+        }
+
+        impl<'b, B: Backend, T: LinalgScalar> ShaderArgs<'b, B> for $name<'_, B, T> {
+            fn write_arg<'a>(
+                &'b self,
+                binding: ShaderBinding,
+                name: &str,
+                dispatch: &mut B::Dispatch<'a>,
+            ) -> Result<(), ShaderArgsError>
+            where
+                'b: 'a,
+            {
+                match name {
+                    $(stringify!($value) => {
+                        ShaderArgs::<'b, B>::write_arg(&self.$value, binding, name, dispatch)
+                    })*
+                    $(stringify!($buffer) => {
+                        ShaderArgs::<'b, B>::write_arg(self.$buffer, binding, name, dispatch)
+                    })*
+                    _ => Err(ShaderArgsError::ArgNotFound(name.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+linalg_args!(GemmArgs {
+    m: u32, n: u32, k: u32, alpha: ShaderValue<T>, beta: ShaderValue<T>;
+    a, b, c
+});
+
+linalg_args!(GemvArgs {
+    m: u32, n: u32, alpha: ShaderValue<T>, beta: ShaderValue<T>;
+    a, x, y
+});
+
+linalg_args!(AxpyArgs {
+    len: u32, alpha: ShaderValue<T>;
+    x, y
+});
+
+linalg_args!(DotArgs {
+    len: u32;
+    x, y, result
+});
+
+/// The workgroup size of the `linalg_dot` kernel, which runs a single workgroup.
+const DOT_WORKGROUP_SIZE: u32 = 256;
+
+struct LinalgKernels<B: Backend> {
+    gemm: GpuFunction<B>,
+    gemv: GpuFunction<B>,
+    axpy: GpuFunction<B>,
+    dot: GpuFunction<B>,
+}
+
+impl<B: Backend> LinalgKernels<B> {
+    fn new(backend: &B, compiler: &SlangCompiler, module: &str) -> Result<Self, B::Error> {
+        Ok(Self {
+            gemm: GpuFunction::from_file(backend, compiler, module, "linalg_gemm")?,
+            gemv: GpuFunction::from_file(backend, compiler, module, "linalg_gemv")?,
+            axpy: GpuFunction::from_file(backend, compiler, module, "linalg_axpy")?,
+            dot: GpuFunction::from_file(backend, compiler, module, "linalg_dot")?,
+        })
+    }
+}
+
+/// BLAS-like operations on `f32` and `f64` buffers, portable across backends.
+///
+/// The `f64` operations aren’t supported by backends compiling to WGSL or Metal.
+pub struct Linalg<B: Backend> {
+    f32: LinalgKernels<B>,
+    f64: Option<LinalgKernels<B>>,
+}
+
+impl<B: Backend> Shader<B> for Linalg<B> {
+    fn from_backend(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        let f64 = match backend.compile_target() {
+            // These targets don’t support double-precision floats.
+            CompileTarget::Wgsl | CompileTarget::Metal => None,
+            _ => Some(LinalgKernels::new(
+                backend,
+                compiler,
+                "slang_hal_linalg_f64",
+            )?),
+        };
+
+        Ok(Self {
+            f32: LinalgKernels::new(backend, compiler, "slang_hal_linalg_f32")?,
+            f64,
+        })
+    }
+}
+
+impl<B: Backend> Linalg<B> {
+    fn kernels<T: LinalgScalar>(&self) -> Result<&LinalgKernels<B>, B::Error> {
+        if TypeId::of::<T>() == TypeId::of::<f32>() {
+            Ok(&self.f32)
+        } else {
+            self.f64
+                .as_ref()
+                .ok_or_else(|| UnsupportedError::new::<B>("double-precision linalg").into())
+        }
+    }
+
+    /// Computes `c = alpha * a * b + beta * c`, where `a` is a `m × k` matrix, `b` a `k × n`
+    /// matrix, and `c` a `m × n` matrix, with `[m, n, k] = shape`.
+    ///
+    /// `c` isn’t read if `beta` is zero, so it doesn’t need to be initialized in that case.
+    #[allow(clippy::too_many_arguments)]
+    pub fn gemm<T: LinalgScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        shape: [u32; 3],
+        alpha: T,
+        a: &B::Buffer<T>,
+        b: &B::Buffer<T>,
+        beta: T,
+        c: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        use crate::backend::Buffer;
+
+        let [m, n, k] = shape;
+        assert!(a.len() >= m as usize * k as usize, "`a` is too small");
+        assert!(b.len() >= k as usize * n as usize, "`b` is too small");
+        assert!(c.len() >= m as usize * n as usize, "`c` is too small");
+
+        #[cfg(all(feature = "cuda", feature = "cublas"))]
+        if let Some((cuda, a, b, c)) = cublas_args(backend, a, b, c) {
+            let (a, _record_a) = a.device_ptr(&cuda.stream);
+            let (b, _record_b) = b.device_ptr(&cuda.stream);
+            let (c, _record_c) = c.device_ptr_mut(&cuda.stream);
+            // SAFETY: the buffers are large enough for the given shape.
+            return unsafe {
+                T::cublas_gemm(
+                    *cuda.cublas.handle(),
+                    m as c_int,
+                    n as c_int,
+                    k as c_int,
+                    &alpha,
+                    a as *const T,
+                    b as *const T,
+                    &beta,
+                    c as *mut T,
+                )
+            }
+            .map_err(cuda_error::<B>);
+        }
+
+        let args = GemmArgs::<B, T> {
+            m,
+            n,
+            k,
+            alpha: ShaderValue(alpha),
+            beta: ShaderValue(beta),
+            a,
+            b,
+            c,
+        };
+        self.kernels::<T>()?
+            .gemm
+            .launch(backend, pass, &args, [m, n, 1])
+    }
+
+    /// Computes `y = alpha * a * x + beta * y`, where `a` is a `m × n` matrix, with
+    /// `[m, n] = shape`.
+    ///
+    /// `y` isn’t read if `beta` is zero, so it doesn’t need to be initialized in that case.
+    #[allow(clippy::too_many_arguments)]
+    pub fn gemv<T: LinalgScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        shape: [u32; 2],
+        alpha: T,
+        a: &B::Buffer<T>,
+        x: &B::Buffer<T>,
+        beta: T,
+        y: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        use crate::backend::Buffer;
+
+        let [m, n] = shape;
+        assert!(a.len() >= m as usize * n as usize, "`a` is too small");
+        assert!(x.len() >= n as usize, "`x` is too small");
+        assert!(y.len() >= m as usize, "`y` is too small");
+
+        #[cfg(all(feature = "cuda", feature = "cublas"))]
+        if let Some((cuda, a, x, y)) = cublas_args(backend, a, x, y) {
+            let (a, _record_a) = a.device_ptr(&cuda.stream);
+            let (x, _record_x) = x.device_ptr(&cuda.stream);
+            let (y, _record_y) = y.device_ptr_mut(&cuda.stream);
+            // SAFETY: the buffers are large enough for the given shape.
+            return unsafe {
+                T::cublas_gemv(
+                    *cuda.cublas.handle(),
+                    m as c_int,
+                    n as c_int,
+                    &alpha,
+                    a as *const T,
+                    x as *const T,
+                    &beta,
+                    y as *mut T,
+                )
+            }
+            .map_err(cuda_error::<B>);
+        }
+
+        let args = GemvArgs::<B, T> {
+            m,
+            n,
+            alpha: ShaderValue(alpha),
+            beta: ShaderValue(beta),
+            a,
+            x,
+            y,
+        };
+        self.kernels::<T>()?
+            .gemv
+            .launch(backend, pass, &args, [m, 1, 1])
+    }
+
+    /// Computes `y = alpha * x + y`.
+    ///
+    /// Panics if `x` and `y` don’t have the same length.
+    pub fn axpy<T: LinalgScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        alpha: T,
+        x: &B::Buffer<T>,
+        y: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        use crate::backend::Buffer;
+
+        assert_eq!(x.len(), y.len(), "`x` and `y` must have the same length");
+        let len = x.len() as u32;
+
+        #[cfg(all(feature = "cuda", feature = "cublas"))]
+        if let Some((cuda, x, _, y)) = cublas_args(backend, x, x, y) {
+            let (x, _record_x) = x.device_ptr(&cuda.stream);
+            let (y, _record_y) = y.device_ptr_mut(&cuda.stream);
+            // SAFETY: both buffers have `len` elements.
+            return unsafe {
+                T::cublas_axpy(
+                    *cuda.cublas.handle(),
+                    len as c_int,
+                    &alpha,
+                    x as *const T,
+                    y as *mut T,
+                )
+            }
+            .map_err(cuda_error::<B>);
+        }
+
+        let args = AxpyArgs::<B, T> {
+            len,
+            alpha: ShaderValue(alpha),
+            x,
+            y,
+        };
+        self.kernels::<T>()?
+            .axpy
+            .launch(backend, pass, &args, [len, 1, 1])
+    }
+
+    /// Computes the dot product of `x` and `y`, and writes it to the first element of `result`.
+    ///
+    /// Panics if `x` and `y` don’t have the same length, or if `result` is empty.
+    pub fn dot<T: LinalgScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        x: &B::Buffer<T>,
+        y: &B::Buffer<T>,
+        result: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        use crate::backend::Buffer;
+
+        assert_eq!(x.len(), y.len(), "`x` and `y` must have the same length");
+        assert!(!result.is_empty(), "`result` must not be empty");
+        let len = x.len() as u32;
+
+        #[cfg(all(feature = "cuda", feature = "cublas"))]
+        if let Some((cuda, x, y, result)) = cublas_args(backend, x, y, result) {
+            use cublas_sys::cublasPointerMode_t::*;
+
+            let (x, _record_x) = x.device_ptr(&cuda.stream);
+            let (y, _record_y) = y.device_ptr(&cuda.stream);
+            let (result, _record_result) = result.device_ptr_mut(&cuda.stream);
+            // NOTE: the result is written to device memory, so this doesn’t block.
+            let blas = &cuda.cublas;
+            blas.set_pointer_mode(CUBLAS_POINTER_MODE_DEVICE)
+                .map_err(cuda_error::<B>)?;
+            // SAFETY: both buffers have `len` elements, and `result` isn’t empty.
+            let dot = unsafe {
+                T::cublas_dot(
+                    *blas.handle(),
+                    len as c_int,
+                    x as *const T,
+                    y as *const T,
+                    result as *mut T,
+                )
+            };
+            blas.set_pointer_mode(CUBLAS_POINTER_MODE_HOST)
+                .map_err(cuda_error::<B>)?;
+            return dot.map_err(cuda_error::<B>);
+        }
+
+        let args = DotArgs::<B, T> { len, x, y, result };
+        self.kernels::<T>()?
+            .dot
+            .launch(backend, pass, &args, [DOT_WORKGROUP_SIZE, 1, 1])
+    }
+}
+
+/// The CUDA backend and buffers to run a cuBLAS operation with, if `backend` runs on CUDA with
+/// cuBLAS enabled.
+///
+/// cuBLAS operations are enqueued on the backend’s stream right away, instead of the stream of
+/// the pass. They are still ordered with the kernels accessing the same buffers, since CUDA
+/// buffers track their reads and writes across streams with events.
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+#[allow(clippy::type_complexity)]
+fn cublas_args<'a, B: Backend, T: LinalgScalar>(
+    backend: &'a B,
+    x: &'a B::Buffer<T>,
+    y: &'a B::Buffer<T>,
+    out: &'a mut B::Buffer<T>,
+) -> Option<(
+    &'a Cuda,
+    &'a <Cuda as Backend>::Buffer<T>,
+    &'a <Cuda as Backend>::Buffer<T>,
+    &'a mut <Cuda as Backend>::Buffer<T>,
+)> {
+    let cuda = backend.as_cuda().filter(|cuda| cuda.cublas_enabled)?;
+    Some((
+        cuda,
+        as_cuda_buffer::<B, T>(x)?,
+        as_cuda_buffer::<B, T>(y)?,
+        as_cuda_buffer_mut::<B, T>(out)?,
+    ))
+}
+
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+fn as_cuda_buffer<B: Backend, T: LinalgScalar>(
+    buffer: &B::Buffer<T>,
+) -> Option<&<Cuda as Backend>::Buffer<T>> {
+    let buffer: &dyn Any = buffer;
+    buffer
+        .downcast_ref()
+        .or_else(|| match buffer.downcast_ref::<AnyBuffer<T>>()? {
+            AnyBuffer::Cuda(buffer) => Some(buffer),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
+}
+
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+fn as_cuda_buffer_mut<B: Backend, T: LinalgScalar>(
+    buffer: &mut B::Buffer<T>,
+) -> Option<&mut <Cuda as Backend>::Buffer<T>> {
+    let buffer: &mut dyn Any = buffer;
+    if buffer.is::<<Cuda as Backend>::Buffer<T>>() {
+        return buffer.downcast_mut();
+    }
+    match buffer.downcast_mut::<AnyBuffer<T>>()? {
+        AnyBuffer::Cuda(buffer) => Some(buffer),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Converts a cuBLAS error to the error type of `B`, which is either `Cuda` or `AnyBackend`
+/// when running cuBLAS operations.
+#[cfg(all(feature = "cuda", feature = "cublas"))]
+fn cuda_error<B: Backend>(error: CublasError) -> B::Error {
+    let error = <Cuda as Backend>::Error::from(error);
+    let error: Box<dyn Any> = match (Box::new(error) as Box<dyn Any>).downcast::<B::Error>() {
+        Ok(error) => return *error,
+        Err(error) => error,
+    };
+    let error = *error
+        .downcast::<<Cuda as Backend>::Error>()
+        .unwrap_or_else(|_| unreachable!());
+    *(Box::new(AnyBackendError::from(error)) as Box<dyn Any>)
+        .downcast::<B::Error>()
+        .unwrap_or_else(|_| unreachable!("cuBLAS is only used by the Cuda and AnyBackend backends"))
+}
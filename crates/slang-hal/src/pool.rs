@@ -0,0 +1,283 @@
+//! Recycling of buffer allocations.
+
+use crate::backend::{Backend, Buffer, DeviceValue, UnsupportedError};
+use bytemuck::Pod;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use wgpu::BufferUsages;
+
+/// The smallest size class, in bytes.
+const MIN_SIZE_CLASS: usize = 256;
+
+/// Identifies the buffers that can be recycled for one another.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+struct PoolKey {
+    ty: TypeId,
+    /// Size of the allocation, in bytes.
+    size_class: usize,
+    usage: BufferUsages,
+}
+
+/// Statistics of a [`BufferPool`].
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct BufferPoolStats {
+    /// The number of buffers allocated on the device by the pool.
+    pub allocations: usize,
+    /// The number of requests served by recycling a free buffer.
+    pub reuses: usize,
+    /// The number of buffers currently handed out by the pool.
+    pub buffers_in_use: usize,
+    /// The number of free buffers waiting to be reused.
+    pub buffers_free: usize,
+    /// The total size of the buffers currently handed out, in bytes.
+    pub bytes_in_use: usize,
+    /// The total size of the free buffers, in bytes.
+    pub bytes_free: usize,
+}
+
+#[derive(Default)]
+struct PoolState {
+    free: HashMap<PoolKey, Vec<Box<dyn Any + Send + Sync>>>,
+    stats: BufferPoolStats,
+}
+
+/// A pool recycling buffers by size class and usage, to avoid a device allocation each time a
+/// temporary buffer is needed (e.g. every frame).
+///
+/// Buffer sizes are rounded up to the next power of two (of at least 256 bytes), so a freed
+/// buffer can serve any later request of the same element type and usage within the same size
+/// class. A [`PooledBuffer`] is given back to the pool when dropped.
+///
+/// Since a recycled buffer can be handed out again right away, a [`PooledBuffer`] must only be
+/// dropped once the work reading it was submitted to the same queue as the work reusing it (or
+/// after synchronizing), so that both execute in order.
+pub struct BufferPool<B: Backend> {
+    state: Arc<Mutex<PoolState>>,
+    _backend: std::marker::PhantomData<fn() -> B>,
+}
+
+impl<B: Backend> Clone for BufferPool<B> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            _backend: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<B: Backend> Default for BufferPool<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> BufferPool<B> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PoolState::default())),
+            _backend: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns a buffer with at least `len` elements and the given usage, reusing a free buffer
+    /// of the same size class if there is one.
+    ///
+    /// Fails with an [`UnsupportedError`] if the size class of the buffer overflows `usize`.
+    ///
+    /// # Safety
+    /// The content of the returned buffer is uninitialized (or left over from its previous use)
+    /// and must be initialized before being read from.
+    pub unsafe fn acquire<T: DeviceValue + Pod>(
+        &self,
+        backend: &B,
+        len: usize,
+        usage: BufferUsages,
+    ) -> Result<PooledBuffer<B, T>, B::Error> {
+        let elt_size = size_of::<T>().max(1);
+        let size_class = len
+            .checked_mul(elt_size)
+            .and_then(usize::checked_next_power_of_two)
+            .ok_or_else(|| {
+                UnsupportedError::new::<B>("buffers larger than `usize::MAX / 2` bytes")
+            })?
+            .max(MIN_SIZE_CLASS);
+        let key = PoolKey {
+            ty: TypeId::of::<T>(),
+            size_class,
+            usage,
+        };
+
+        let recycled = {
+            let mut state = self.state.lock().unwrap();
+            let recycled = state.free.get_mut(&key).and_then(|free| free.pop());
+            if recycled.is_some() {
+                state.stats.reuses += 1;
+                state.stats.buffers_free -= 1;
+                state.stats.bytes_free -= size_class;
+            }
+            recycled
+        };
+
+        let buffer = match recycled {
+            Some(buffer) => *buffer
+                .downcast::<B::Buffer<T>>()
+                .expect("buffer pool entries are keyed by type"),
+            None => {
+                // SAFETY: forwarded to the caller.
+                let buffer = unsafe { backend.uninit_buffer(size_class / elt_size, usage)? };
+                self.state.lock().unwrap().stats.allocations += 1;
+                buffer
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.stats.buffers_in_use += 1;
+        state.stats.bytes_in_use += size_class;
+
+        Ok(PooledBuffer {
+            buffer: Some(buffer),
+            len,
+            key,
+            state: self.state.clone(),
+        })
+    }
+
+    /// Returns a buffer initialized with `data`, reusing a free buffer of the same size class
+    /// if there is one.
+    ///
+    /// Only the first `data.len()` elements of the buffer are initialized.
+    pub fn acquire_init<T: DeviceValue + Pod>(
+        &self,
+        backend: &B,
+        data: &[T],
+        usage: BufferUsages,
+    ) -> Result<PooledBuffer<B, T>, B::Error> {
+        // SAFETY: the requested elements are initialized right away.
+        let mut buffer =
+            unsafe { self.acquire(backend, data.len(), usage | BufferUsages::COPY_DST)? };
+        backend.write_buffer_at(&mut buffer, 0, data)?;
+        Ok(buffer)
+    }
+
+    /// Frees all the buffers waiting to be reused.
+    ///
+    /// Buffers currently handed out aren’t affected and will still be given back to the pool.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.free.clear();
+        state.stats.buffers_free = 0;
+        state.stats.bytes_free = 0;
+    }
+
+    /// Allocation and reuse statistics of this pool.
+    pub fn stats(&self) -> BufferPoolStats {
+        self.state.lock().unwrap().stats
+    }
+}
+
+/// A buffer handed out by a [`BufferPool`], given back to the pool when dropped.
+///
+/// This dereferences to the underlying buffer, which may be larger than requested. Use
+/// [`PooledBuffer::as_slice`] for binding only the requested elements.
+pub struct PooledBuffer<B: Backend, T: DeviceValue> {
+    buffer: Option<B::Buffer<T>>,
+    len: usize,
+    key: PoolKey,
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl<B: Backend, T: DeviceValue> PooledBuffer<B, T> {
+    /// The number of elements requested when acquiring this buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether zero elements were requested when acquiring this buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// A slice covering the requested elements of this buffer.
+    pub fn as_slice(&self) -> B::BufferSlice<'_, T> {
+        (**self).slice(..self.len)
+    }
+
+    /// Takes the buffer out of the pool for good.
+    pub fn detach(mut self) -> B::Buffer<T> {
+        let mut state = self.state.lock().unwrap();
+        state.stats.buffers_in_use -= 1;
+        state.stats.bytes_in_use -= self.key.size_class;
+        drop(state);
+        self.buffer.take().unwrap()
+    }
+}
+
+impl<B: Backend, T: DeviceValue> Deref for PooledBuffer<B, T> {
+    type Target = B::Buffer<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_ref().unwrap()
+    }
+}
+
+impl<B: Backend, T: DeviceValue> DerefMut for PooledBuffer<B, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer.as_mut().unwrap()
+    }
+}
+
+impl<B: Backend, T: DeviceValue> Drop for PooledBuffer<B, T> {
+    fn drop(&mut self) {
+        let Some(buffer) = self.buffer.take() else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        state.stats.buffers_in_use -= 1;
+        state.stats.bytes_in_use -= self.key.size_class;
+        state.stats.buffers_free += 1;
+        state.stats.bytes_free += self.key.size_class;
+        state
+            .free
+            .entry(self.key)
+            .or_default()
+            .push(Box::new(buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Mock;
+
+    type MockError = <Mock as Backend>::Error;
+
+    #[test]
+    fn buffers_are_recycled() {
+        let backend = Mock::new();
+        let pool = BufferPool::<Mock>::new();
+        let buffer = unsafe { pool.acquire::<u32>(&backend, 10, BufferUsages::STORAGE) }.unwrap();
+        assert_eq!(buffer.len(), 10);
+        drop(buffer);
+        let buffer = unsafe { pool.acquire::<u32>(&backend, 64, BufferUsages::STORAGE) }.unwrap();
+        assert_eq!(buffer.len(), 64);
+
+        let stats = pool.stats();
+        assert_eq!((stats.allocations, stats.reuses), (1, 1));
+        assert_eq!(stats.bytes_in_use, MIN_SIZE_CLASS);
+    }
+
+    #[test]
+    fn oversized_buffers_are_rejected() {
+        let backend = Mock::new();
+        let pool = BufferPool::<Mock>::new();
+        // The size in bytes overflows, then only its next power of two.
+        for len in [usize::MAX, (1 << (usize::BITS - 3)) + 1] {
+            let result = unsafe { pool.acquire::<u32>(&backend, len, BufferUsages::STORAGE) };
+            assert!(matches!(result, Err(MockError::Unsupported(_))));
+        }
+        assert_eq!(pool.stats(), BufferPoolStats::default());
+    }
+}
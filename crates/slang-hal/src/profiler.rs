@@ -0,0 +1,194 @@
+//! Per-kernel statistics of the dispatches launched by a backend.
+//!
+//! Profiling is enabled with [`Backend::enable_profiling`]. Every dispatch launched afterwards
+//! (e.g. by [`GpuFunction::launch`](crate::function::GpuFunction::launch)) is then counted and,
+//! if the backend supports it, timed on the GPU with timestamp queries. The statistics are
+//! aggregated per kernel, identified by the dispatch label (the entry point name for dispatches
+//! launched by a `GpuFunction`), and retrieved with [`Backend::profiling_report`].
+
+use crate::backend::{Backend, Timestamps};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Statistics of the dispatches of a single kernel.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct KernelStats {
+    /// The number of dispatches launched.
+    pub dispatches: u64,
+    /// The total number of threads launched, excluding indirect dispatches whose workgroup
+    /// count isn’t known on the host.
+    pub threads: u64,
+    /// The number of dispatches whose GPU time was measured.
+    pub timed_dispatches: u64,
+    /// The total GPU time of the timed dispatches, in nanoseconds.
+    pub gpu_time_ns: u64,
+}
+
+impl KernelStats {
+    /// The average GPU time of the timed dispatches, in nanoseconds.
+    pub fn mean_gpu_time_ns(&self) -> Option<f64> {
+        (self.timed_dispatches > 0).then(|| self.gpu_time_ns as f64 / self.timed_dispatches as f64)
+    }
+}
+
+/// The statistics of each kernel launched since profiling was enabled, as returned by
+/// [`Backend::profiling_report`].
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct ProfilingReport {
+    /// The name and statistics of each kernel, sorted by decreasing GPU time.
+    pub kernels: Vec<(String, KernelStats)>,
+}
+
+impl ProfilingReport {
+    /// The statistics of the kernel `name`, if it was launched.
+    pub fn kernel(&self, name: &str) -> Option<&KernelStats> {
+        self.kernels
+            .iter()
+            .find(|(kernel, _)| kernel == name)
+            .map(|(_, stats)| stats)
+    }
+
+    /// The total GPU time of all the timed dispatches, in nanoseconds.
+    pub fn total_gpu_time_ns(&self) -> u64 {
+        self.kernels
+            .iter()
+            .map(|(_, stats)| stats.gpu_time_ns)
+            .sum()
+    }
+}
+
+impl fmt::Display for ProfilingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .kernels
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("kernel".len());
+        writeln!(
+            f,
+            "{:name_width$} {:>10} {:>14} {:>12} {:>12}",
+            "kernel", "dispatches", "threads", "total (ms)", "mean (µs)"
+        )?;
+        for (name, stats) in &self.kernels {
+            let mean = stats
+                .mean_gpu_time_ns()
+                .map(|ns| format!("{:.3}", ns / 1.0e3))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{name:name_width$} {:>10} {:>14} {:>12.3} {mean:>12}",
+                stats.dispatches,
+                stats.threads,
+                stats.gpu_time_ns as f64 / 1.0e6,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct ProfilerState<B: Backend> {
+    /// The queries timing the dispatches, or `None` while they are being resolved.
+    timestamps: Option<B::Timestamps>,
+    /// The kernel of each dispatch timed by `timestamps`, in launch order.
+    timed: Vec<String>,
+    kernels: HashMap<String, KernelStats>,
+}
+
+/// The profiling state of a backend, shared by the handles created with
+/// [`Backend::create_queue`].
+///
+/// This is used by the backend implementations: their dispatches are launched through
+/// [`Profiler::record`] while profiling is enabled.
+pub struct Profiler<B: Backend> {
+    state: Mutex<ProfilerState<B>>,
+}
+
+impl<B: Backend> Profiler<B> {
+    /// Creates a profiler timing dispatches with `timestamps`, or only counting them if
+    /// `timestamps` is `None`.
+    pub fn new(timestamps: Option<B::Timestamps>) -> Self {
+        Self {
+            state: Mutex::new(ProfilerState {
+                timestamps,
+                timed: vec![],
+                kernels: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Records a dispatch of `kernel` with `grid` workgroups (`None` for indirect dispatches) of
+    /// `block_dim` threads, launched by `launch`.
+    ///
+    /// `launch` is given the timestamp queries if they aren’t all used yet. It must then time
+    /// the dispatch with the next two queries, like [`Encoder::begin_timed_pass`] times a pass.
+    ///
+    /// [`Encoder::begin_timed_pass`]: crate::backend::Encoder::begin_timed_pass
+    pub fn record<R>(
+        &self,
+        kernel: &str,
+        grid: Option<[u32; 3]>,
+        block_dim: [u32; 3],
+        launch: impl FnOnce(Option<&mut B::Timestamps>) -> R,
+    ) -> R {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let stats = state.kernels.entry(kernel.to_string()).or_default();
+        stats.dispatches += 1;
+        if let Some(grid) = grid {
+            stats.threads += grid
+                .iter()
+                .zip(block_dim)
+                .map(|(n, dim)| *n as u64 * dim as u64)
+                .product::<u64>();
+        }
+
+        let timestamps = state
+            .timestamps
+            .as_mut()
+            .filter(|timestamps| timestamps.len() < timestamps.capacity());
+        if timestamps.is_some() {
+            state.timed.push(kernel.to_string());
+        }
+        launch(timestamps)
+    }
+
+    /// Resolves the GPU time of the dispatches timed so far, and returns the statistics of all
+    /// the kernels recorded since this profiler was created.
+    ///
+    /// The encoders of the timed dispatches must have been submitted.
+    pub async fn report(&self, backend: &B) -> Result<ProfilingReport, B::Error> {
+        let (timestamps, timed) = {
+            let mut state = self.state.lock().unwrap();
+            (state.timestamps.take(), std::mem::take(&mut state.timed))
+        };
+
+        // NOTE: the lock isn’t held while waiting for the GPU. The dispatches launched in the
+        //       meantime are only counted.
+        if let Some(mut timestamps) = timestamps {
+            let resolved = backend.resolve_timestamps(&mut timestamps).await;
+            let mut state = self.state.lock().unwrap();
+            state.timestamps = Some(timestamps);
+            for (kernel, pass) in timed.iter().zip(resolved?) {
+                let stats = state.kernels.entry(kernel.clone()).or_default();
+                stats.timed_dispatches += 1;
+                stats.gpu_time_ns += pass.duration_ns();
+            }
+        }
+
+        let state = self.state.lock().unwrap();
+        let mut kernels: Vec<_> = state
+            .kernels
+            .iter()
+            .map(|(name, stats)| (name.clone(), *stats))
+            .collect();
+        kernels.sort_by(|(name_a, a), (name_b, b)| {
+            b.gpu_time_ns
+                .cmp(&a.gpu_time_ns)
+                .then_with(|| name_a.cmp(name_b))
+        });
+        Ok(ProfilingReport { kernels })
+    }
+}
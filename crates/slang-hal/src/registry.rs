@@ -0,0 +1,191 @@
+//! Sharing of compiled Slang modules between shaders.
+
+use crate::backend::{Backend, SlangCompiled};
+use crate::function::GpuFunction;
+use minislang::reflection::ProgramReflection;
+use minislang::shader_slang::CompileTarget;
+use minislang::{CompileOptions, SlangCompiler, SlangError, SlangProgram};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Identifies a compilation of a Slang module.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+struct ModuleKey {
+    path: String,
+    /// The compilation target, as its integer value (like in `SlangCompiler::cache_key`).
+    target: i64,
+    options: CompileOptions,
+}
+
+impl ModuleKey {
+    fn new(path: &str, target: CompileTarget, options: &CompileOptions) -> Self {
+        Self {
+            path: path.to_string(),
+            target: target as i64,
+            options: options.clone(),
+        }
+    }
+}
+
+/// Statistics of a [`ShaderRegistry`].
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct ShaderRegistryStats {
+    /// The number of Slang programs currently cached.
+    pub programs: usize,
+    /// The number of backend modules currently cached.
+    pub modules: usize,
+    /// The number of Slang compilations performed by the registry.
+    pub program_misses: usize,
+    /// The number of requests served by a cached Slang program.
+    pub program_hits: usize,
+    /// The number of modules loaded on the backend by the registry.
+    pub module_misses: usize,
+    /// The number of requests served by a cached backend module.
+    pub module_hits: usize,
+}
+
+#[derive(Clone)]
+struct CachedProgram {
+    program: Rc<SlangProgram>,
+    reflection: Rc<ProgramReflection>,
+}
+
+struct RegistryState<B: Backend> {
+    programs: HashMap<ModuleKey, CachedProgram>,
+    modules: HashMap<ModuleKey, Rc<B::Module>>,
+    stats: ShaderRegistryStats,
+}
+
+/// A cache of compiled Slang programs and loaded backend modules, shared by the functions built
+/// from the same module.
+///
+/// Loading each entry point with [`GpuFunction::from_file`] compiles and loads its module once per
+/// entry point. Instead, the registry compiles every entry point of a module at once, and loads
+/// all the functions of that module from a single backend module. Compilations are identified by
+/// the module path, the compilation target, and the [`CompileOptions`] (including the macro
+/// definitions).
+///
+/// Use [`Shader::from_registry`](crate::Shader::from_registry) to build shaders sharing the
+/// modules of a registry. Like [`SlangCompiler`], a registry can’t be shared across threads.
+pub struct ShaderRegistry<B: Backend> {
+    state: RefCell<RegistryState<B>>,
+}
+
+impl<B: Backend> Default for ShaderRegistry<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> ShaderRegistry<B> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            state: RefCell::new(RegistryState {
+                programs: HashMap::new(),
+                modules: HashMap::new(),
+                stats: ShaderRegistryStats::default(),
+            }),
+        }
+    }
+
+    /// The program with all the entry points of the module at `path`, compiled for `target`,
+    /// compiling it if it isn’t cached yet.
+    pub fn program(
+        &self,
+        compiler: &SlangCompiler,
+        path: &str,
+        target: CompileTarget,
+        options: &CompileOptions,
+    ) -> Result<Rc<SlangProgram>, SlangError> {
+        let key = ModuleKey::new(path, target, options);
+        Ok(self.cached_program(compiler, &key, target)?.program)
+    }
+
+    /// Loads the entry point `entry_point_name` of the module at `path`, reusing the compiled
+    /// program and the backend module if they are cached.
+    pub fn function(
+        &self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        path: &str,
+        entry_point_name: &str,
+        options: &CompileOptions,
+    ) -> Result<GpuFunction<B>, B::Error> {
+        let target = backend.compile_target();
+        let key = ModuleKey::new(path, target, options);
+        let program = self.cached_program(compiler, &key, target)?;
+        let entry_point = program
+            .reflection
+            .entry_point(entry_point_name)
+            .ok_or_else(|| SlangError::EntryPointNotFound {
+                module: path.to_string(),
+                entry_point: entry_point_name.to_string(),
+            })?;
+
+        let cached_module = self.state.borrow().modules.get(&key).cloned();
+        let module = match cached_module {
+            Some(module) => {
+                self.state.borrow_mut().stats.module_hits += 1;
+                module
+            }
+            None => {
+                let code = program.program.target_bytes(0)?;
+                let module = Rc::new(backend.load_compiled_module(&code, SlangCompiled(()))?);
+                let mut state = self.state.borrow_mut();
+                state.stats.module_misses += 1;
+                state.modules.insert(key, module.clone());
+                module
+            }
+        };
+
+        GpuFunction::from_module_reflection(backend, &module, path, entry_point)
+    }
+
+    /// Removes all the cached programs and modules.
+    ///
+    /// Functions already loaded from the registry aren’t affected.
+    pub fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+        state.programs.clear();
+        state.modules.clear();
+    }
+
+    /// Cache statistics of this registry.
+    pub fn stats(&self) -> ShaderRegistryStats {
+        let state = self.state.borrow();
+        ShaderRegistryStats {
+            programs: state.programs.len(),
+            modules: state.modules.len(),
+            ..state.stats
+        }
+    }
+
+    fn cached_program(
+        &self,
+        compiler: &SlangCompiler,
+        key: &ModuleKey,
+        target: CompileTarget,
+    ) -> Result<CachedProgram, SlangError> {
+        let cached = self.state.borrow().programs.get(key).cloned();
+        if let Some(cached) = cached {
+            self.state.borrow_mut().stats.program_hits += 1;
+            return Ok(cached);
+        }
+
+        // NOTE: the state isn’t borrowed while compiling, in case the compiler calls back into
+        //       the registry (e.g. from a module resolver).
+        let program = compiler.compile(&key.path, target, None, &key.options)?;
+        let reflection = Rc::new(program.reflection()?);
+        let program = Rc::new(program);
+        let mut state = self.state.borrow_mut();
+        state.stats.program_misses += 1;
+        let cached = CachedProgram {
+            program,
+            reflection,
+        };
+        state.programs.insert(key.clone(), cached.clone());
+        Ok(cached)
+    }
+}
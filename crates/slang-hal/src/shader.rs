@@ -1,15 +1,188 @@
-use crate::backend::{Backend, ShaderBinding};
+use crate::backend::{Backend, DeviceValue, Dispatch, ShaderBinding};
+use crate::registry::ShaderRegistry;
+use bytemuck::Pod;
 use minislang::SlangCompiler;
+use nalgebra::{SMatrix, Scalar};
+use std::collections::HashMap;
 
 pub trait Shader<B: Backend>: Sized + 'static {
     /// Instantiates `Self` and all its compute functions from a backend.
     fn from_backend(b: &B, compiler: &SlangCompiler) -> Result<Self, B::Error>;
+
+    /// Instantiates `Self` like [`Self::from_backend`], but without blocking the calling thread
+    /// while its Slang modules are compiled.
+    ///
+    /// `make_compiler` creates the compiler used for compiling the shaders, which must be
+    /// configured like the one given to [`Self::from_backend`]. `derive(Shader)` compiles the
+    /// kernels on a worker thread (see [`compile_in_background`]) and only loads them into
+    /// `backend` from the calling thread. By default, this calls [`Self::from_backend`] when the
+    /// future is first polled.
+    ///
+    /// [`compile_in_background`]: crate::function::compile_in_background
+    fn from_backend_async<'a>(
+        backend: &'a B,
+        make_compiler: impl FnOnce() -> SlangCompiler + Send + 'static,
+    ) -> impl Future<Output = Result<Self, B::Error>> + 'a {
+        async move { Self::from_backend(backend, &make_compiler()) }
+    }
+
+    /// Instantiates `Self` like [`Self::from_backend`], but reusing the modules compiled and
+    /// loaded by `registry` for other shaders.
+    ///
+    /// `derive(Shader)` loads all the (non-lazy) kernels through the registry, so that each of
+    /// its Slang modules is compiled and loaded once. By default, this ignores the registry and
+    /// calls [`Self::from_backend`].
+    fn from_registry(
+        backend: &B,
+        compiler: &SlangCompiler,
+        registry: &ShaderRegistry<B>,
+    ) -> Result<Self, B::Error> {
+        let _ = registry;
+        Self::from_backend(backend, compiler)
+    }
+
+    /// The paths of the Slang modules the compute functions of `Self` are loaded from.
+    ///
+    /// This is used to find the shaders affected by a module change when hot-reloading. It is
+    /// empty by default.
+    fn modules() -> Vec<String> {
+        vec![]
+    }
+
+    /// Recompiles and reloads all the compute functions of `self`, e.g. after its Slang modules
+    /// were modified.
+    ///
+    /// The session cache of `compiler` is cleared first (see
+    /// [`SlangCompiler::clear_session_cache`]) so that modified modules are parsed again. If
+    /// anything fails to compile, the error is returned and `self` keeps its previous functions.
+    fn reload(&mut self, backend: &B, compiler: &SlangCompiler) -> Result<(), B::Error> {
+        compiler.clear_session_cache();
+        *self = Self::from_backend(backend, compiler)?;
+        Ok(())
+    }
+
+    /// Reloads `self` if `event` reports that one of its [modules](Self::modules) was rebuilt by
+    /// a [`SlangWatcher`](minislang::SlangWatcher), and returns whether it did.
+    ///
+    /// See [`watched_modules`] for setting up the watcher.
+    #[cfg(feature = "watch")]
+    fn reload_on_event(
+        &mut self,
+        backend: &B,
+        compiler: &SlangCompiler,
+        event: &minislang::WatchEvent,
+    ) -> Result<bool, B::Error> {
+        match event {
+            minislang::WatchEvent::Rebuilt { module, .. } if Self::modules().contains(module) => {
+                self.reload(backend, compiler)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// The modules of the shader `S` to watch with a [`SlangWatcher`](minislang::SlangWatcher) for
+/// hot-reloading it on `backend`.
+///
+/// ```ignore
+/// let watcher = SlangWatcher::new(make_compiler, watched_modules::<_, GpuAdd<_>>(&backend));
+/// // Then, e.g. once per frame:
+/// for event in watcher.try_iter() {
+///     gpu_add.reload_on_event(&backend, &compiler, &event)?;
+/// }
+/// ```
+#[cfg(feature = "watch")]
+pub fn watched_modules<B: Backend, S: Shader<B>>(backend: &B) -> Vec<minislang::WatchedModule> {
+    S::modules()
+        .into_iter()
+        .map(|module| minislang::WatchedModule::new(module, backend.compile_target()))
+        .collect()
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum ShaderArgsError {
     #[error("argument not found: {0}")]
     ArgNotFound(String),
+    #[error("argument {0} was created by a different backend than the dispatch")]
+    BackendMismatch(String),
+    #[error("argument {0} is passed by value but the kernel has no constant buffer for it")]
+    NoValueBuffer(String),
+    #[error("argument {0} is an array of resources, which isn’t supported by this backend")]
+    UnsupportedArray(String),
+    #[error(
+        "argument {name} has elements of {found} bytes, but the kernel expects elements of \
+         {expected} bytes"
+    )]
+    ElementSizeMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error(
+        "argument {name} has a size of {size} bytes, which isn’t a multiple of the \
+         {element_size}-byte elements expected by the kernel"
+    )]
+    SizeMismatch {
+        name: String,
+        size: usize,
+        element_size: usize,
+    },
+    #[error("argument {0} is written by the kernel but can only be read")]
+    NotWritable(String),
+    #[error("{num_threads} threads exceed the maximum of {max_threads} threads of a launch")]
+    TooManyThreads { num_threads: u64, max_threads: u64 },
+    #[error(
+        "failed to bind the arguments of kernel {kernel}{}: {error}{}",
+        module_suffix(.module),
+        arg_lists(.missing, .extra)
+    )]
+    Kernel {
+        /// The entry point name of the kernel.
+        kernel: String,
+        /// The path of the module of the kernel, if known.
+        module: Option<String>,
+        /// The parameters of the kernel without an argument.
+        missing: Vec<String>,
+        /// The arguments matching no parameter of the kernel.
+        extra: Vec<String>,
+        /// The first error raised while binding the arguments.
+        error: Box<ShaderArgsError>,
+    },
+}
+
+fn module_suffix(module: &Option<String>) -> String {
+    module
+        .as_ref()
+        .map(|module| format!(" of {module}"))
+        .unwrap_or_default()
+}
+
+fn arg_lists(missing: &[String], extra: &[String]) -> String {
+    let mut lists = String::new();
+    if !missing.is_empty() {
+        lists += &format!(" (missing arguments: {})", missing.join(", "));
+    }
+    if !extra.is_empty() {
+        lists += &format!(" (unexpected arguments: {})", extra.join(", "));
+    }
+    lists
+}
+
+/// The properties of a resource bound by [`ShaderArgs::write_arg`], checked by
+/// [`GpuFunction::bind`](crate::function::GpuFunction::bind) against the type of the parameter it
+/// is bound to.
+///
+/// Each property is `None` if it isn’t known, e.g. the element size of the untyped buffers of the
+/// `WebGpu` backend.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArgInfo {
+    /// The size of the elements of the bound buffer, in bytes.
+    pub element_size: Option<usize>,
+    /// The size of the bound buffer, in bytes.
+    pub size_bytes: Option<usize>,
+    /// Whether kernels can write to the bound resource.
+    pub writable: Option<bool>,
 }
 
 pub trait ShaderArgs<'b, B: Backend> {
@@ -21,6 +194,23 @@ pub trait ShaderArgs<'b, B: Backend> {
     ) -> Result<(), ShaderArgsError>
     where
         'b: 'a;
+
+    /// The properties of the resource [`Self::write_arg`] binds to the parameter `name`, if it is
+    /// a buffer, for validating it against the parameter’s type.
+    ///
+    /// Returns `None` by default, which skips the validation.
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        let _ = name;
+        None
+    }
+
+    /// The names of the parameters [`Self::write_arg`] knows about, for reporting the arguments
+    /// matching no parameter of a kernel when binding fails.
+    ///
+    /// Returns an empty list by default, e.g. for buffers, which bind to any parameter name.
+    fn arg_names(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
 impl<'b, B: Backend> ShaderArgs<'b, B> for () {
@@ -52,9 +242,17 @@ impl<'b, B: Backend, T: ShaderArgs<'b, B>> ShaderArgs<'b, B> for Option<T> {
             None => Err(ShaderArgsError::ArgNotFound(name.to_owned())),
         }
     }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        self.as_ref()?.arg_info(name)
+    }
+
+    fn arg_names(&self) -> Vec<String> {
+        self.as_ref().map(|arg| arg.arg_names()).unwrap_or_default()
+    }
 }
 
-impl<'b, B: Backend, T: ShaderArgs<'b, B>> ShaderArgs<'b, B> for &'b T {
+impl<'b, B: Backend, T: ShaderArgs<'b, B> + ?Sized> ShaderArgs<'b, B> for &'b T {
     fn write_arg<'a>(
         &'b self,
         binding: ShaderBinding,
@@ -66,4 +264,241 @@ impl<'b, B: Backend, T: ShaderArgs<'b, B>> ShaderArgs<'b, B> for &'b T {
     {
         (*self).write_arg(binding, name, dispatch)
     }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        (*self).arg_info(name)
+    }
+
+    fn arg_names(&self) -> Vec<String> {
+        (*self).arg_names()
+    }
+}
+
+/// Binds an array of resources, e.g. a `&[&B::Buffer<T>]` to a
+/// `StructuredBuffer<T> buffers[N]` parameter.
+///
+/// The elements are bound at the bindings given by [`Dispatch::array_element_binding`], then
+/// grouped by [`Dispatch::bind_array`].
+impl<'b, B: Backend, T: ShaderArgs<'b, B>> ShaderArgs<'b, B> for [T] {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        for (i, elt) in self.iter().enumerate() {
+            let elt_binding = dispatch.array_element_binding(binding, i);
+            elt.write_arg(elt_binding, name, dispatch)?;
+        }
+        dispatch.bind_array(binding, name, self.len())
+    }
+}
+
+/// Binds each argument to the first element of the tuple that knows about it.
+///
+/// The elements are tried in order, moving on to the next one when an element returns
+/// [`ShaderArgsError::ArgNotFound`]. Note that buffers, textures, and scalars bind to any
+/// parameter name, so they only make sense as the last element of a tuple: the other elements are
+/// typically structs deriving `ShaderArgs`.
+macro_rules! impl_shader_args_for_tuple {
+    ($($t: ident),*) => {
+        impl<'b, B: Backend, $($t: ShaderArgs<'b, B>),*> ShaderArgs<'b, B> for ($($t,)*) {
+            fn write_arg<'a>(
+                &'b self,
+                binding: ShaderBinding,
+                name: &str,
+                dispatch: &mut B::Dispatch<'a>,
+            ) -> Result<(), ShaderArgsError>
+            where
+                'b: 'a,
+            {
+                #[allow(non_snake_case)]
+                let ($($t,)*) = self;
+                $(
+                    match $t.write_arg(binding, name, dispatch) {
+                        Err(ShaderArgsError::ArgNotFound(_)) => {}
+                        result => return result,
+                    }
+                )*
+                Err(ShaderArgsError::ArgNotFound(name.to_owned()))
+            }
+
+            fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+                #[allow(non_snake_case)]
+                let ($($t,)*) = self;
+                None$(.or_else(|| $t.arg_info(name)))*
+            }
+
+            fn arg_names(&self) -> Vec<String> {
+                #[allow(non_snake_case)]
+                let ($($t,)*) = self;
+                let mut names = vec![];
+                $(names.extend($t.arg_names());)*
+                names
+            }
+        }
+    };
+}
+
+impl_shader_args_for_tuple!(A);
+impl_shader_args_for_tuple!(A, B2);
+impl_shader_args_for_tuple!(A, B2, C);
+impl_shader_args_for_tuple!(A, B2, C, D);
+impl_shader_args_for_tuple!(A, B2, C, D, E);
+impl_shader_args_for_tuple!(A, B2, C, D, E, F);
+impl_shader_args_for_tuple!(A, B2, C, D, E, F, G);
+impl_shader_args_for_tuple!(A, B2, C, D, E, F, G, H);
+
+/// Shader arguments bound by name at runtime, e.g. by scripting layers or editors that can’t
+/// declare a struct deriving `ShaderArgs`.
+///
+/// Each parameter is bound to the argument registered under its name, or fails with
+/// [`ShaderArgsError::ArgNotFound`] if there isn’t any.
+pub struct DynArgs<'a, B: Backend> {
+    args: HashMap<String, &'a dyn ShaderArgs<'a, B>>,
+}
+
+impl<B: Backend> Default for DynArgs<'_, B> {
+    fn default() -> Self {
+        Self {
+            args: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, B: Backend> DynArgs<'a, B> {
+    /// An empty set of arguments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `arg` as the argument bound to the parameter `name`.
+    pub fn with(mut self, name: impl Into<String>, arg: &'a dyn ShaderArgs<'a, B>) -> Self {
+        self.insert(name, arg);
+        self
+    }
+
+    /// Registers `arg` as the argument bound to the parameter `name`, returning the argument
+    /// previously registered under that name.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        arg: &'a dyn ShaderArgs<'a, B>,
+    ) -> Option<&'a dyn ShaderArgs<'a, B>> {
+        self.args.insert(name.into(), arg)
+    }
+
+    /// Unregisters the argument bound to the parameter `name`.
+    pub fn remove(&mut self, name: &str) -> Option<&'a dyn ShaderArgs<'a, B>> {
+        self.args.remove(name)
+    }
+
+    /// The names of the parameters with a registered argument.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.args.keys().map(|name| name.as_str())
+    }
+}
+
+// NOTE: the registered arguments outlive any borrow of `self`, so `DynArgs` can be bound without
+//       being borrowed for as long as its arguments.
+impl<'b, 'c: 'b, B: Backend> ShaderArgs<'b, B> for DynArgs<'c, B> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        match self.args.get(name) {
+            Some(arg) => (*arg).write_arg(binding, name, dispatch),
+            None => Err(ShaderArgsError::ArgNotFound(name.to_owned())),
+        }
+    }
+
+    fn arg_info(&self, name: &str) -> Option<ArgInfo> {
+        self.args.get(name)?.arg_info(name)
+    }
+
+    fn arg_names(&self) -> Vec<String> {
+        self.args.keys().cloned().collect()
+    }
+}
+
+/// Wrapper for binding a plain-old-data value (e.g. a small struct) to an entry-point parameter
+/// passed by value.
+///
+/// Scalars (`u32`, `f32`, etc.), arrays of scalars (e.g. `[f32; 4]`), and nalgebra vectors and
+/// matrices implement [`ShaderArgs`] directly and don’t need this wrapper. Fields of a struct
+/// deriving `ShaderArgs` can also be bound by value with the `#[shader(value)]` attribute.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct ShaderValue<T>(pub T);
+
+impl<'b, B: Backend, T: DeviceValue + Pod> ShaderArgs<'b, B> for ShaderValue<T> {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.write_value(binding, name, &self.0)
+    }
+}
+
+macro_rules! impl_shader_args_for_scalar {
+    ($($t: ty),*) => {$(
+        impl<'b, B: Backend> ShaderArgs<'b, B> for $t {
+            fn write_arg<'a>(
+                &'b self,
+                binding: ShaderBinding,
+                name: &str,
+                dispatch: &mut B::Dispatch<'a>,
+            ) -> Result<(), ShaderArgsError>
+            where
+                'b: 'a,
+            {
+                dispatch.write_value(binding, name, self)
+            }
+        }
+    )*};
+}
+
+impl_shader_args_for_scalar!(u32, i32, f32, u64, i64, f64);
+
+impl<'b, B: Backend, T: DeviceValue + Pod, const N: usize> ShaderArgs<'b, B> for [T; N] {
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.write_value(binding, name, self)
+    }
+}
+
+impl<'b, B: Backend, T: DeviceValue + Pod + Scalar, const R: usize, const C: usize>
+    ShaderArgs<'b, B> for SMatrix<T, R, C>
+{
+    fn write_arg<'a>(
+        &'b self,
+        binding: ShaderBinding,
+        name: &str,
+        dispatch: &mut B::Dispatch<'a>,
+    ) -> Result<(), ShaderArgsError>
+    where
+        'b: 'a,
+    {
+        dispatch.write_value(binding, name, self)
+    }
 }
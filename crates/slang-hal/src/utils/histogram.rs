@@ -0,0 +1,135 @@
+//! Histograms of buffers with a fixed number of bins.
+
+use super::UtilsScalar;
+use crate::backend::{Backend, Buffer, DeviceValue, ShaderBinding};
+use crate::function::GpuFunction;
+use crate::shader::{ShaderArgs, ShaderArgsError};
+use minislang::{CompileOptions, SlangCompiler};
+use std::marker::PhantomData;
+
+/// The workgroup size of the histogram kernels.
+const WORKGROUP_SIZE: u32 = 256;
+
+/// The maximum number of workgroups of the `histogram` kernel, each adding its private bins to
+/// the global ones.
+const MAX_WORKGROUPS: u32 = 1024;
+
+utils_args!(ClearArgs<> {
+    ;
+    bins: B::Buffer<u32>
+});
+
+utils_args!(HistogramArgs<T> {
+    len: u32, num_workgroups: u32, range_min_bits: u32, range_max_bits: u32;
+    input: B::Buffer<T>, bins: B::Buffer<u32>
+});
+
+/// Counts the elements of buffers of `T` falling in each of a fixed number of bins.
+///
+/// The bins split a range `[min, max)` into intervals of equal width. Each workgroup counts its
+/// elements in a private copy of the bins in shared memory, and adds it to the bins of the
+/// output buffer once.
+pub struct Histogram<B: Backend, T: UtilsScalar> {
+    clear: GpuFunction<B>,
+    histogram: GpuFunction<B>,
+    num_bins: u32,
+    _scalar: PhantomData<T>,
+}
+
+impl<B: Backend, T: UtilsScalar> Histogram<B, T> {
+    /// Compiles and loads the histogram kernels for `num_bins` bins.
+    ///
+    /// Panics if `num_bins` is zero, or if the bins don’t fit in the shared memory of a
+    /// workgroup (see [`DeviceLimits::max_shared_memory_size`]).
+    ///
+    /// [`DeviceLimits::max_shared_memory_size`]: crate::backend::DeviceLimits::max_shared_memory_size
+    pub fn new(backend: &B, compiler: &SlangCompiler, num_bins: u32) -> Result<Self, B::Error> {
+        assert_ne!(num_bins, 0, "a histogram needs at least one bin");
+        super::check_shared_memory(backend)?;
+        let max_shared_memory_size = backend.limits()?.max_shared_memory_size;
+        assert!(
+            num_bins.saturating_mul(4) <= max_shared_memory_size,
+            "{num_bins} bins don’t fit in {max_shared_memory_size} bytes of shared memory"
+        );
+
+        let options = CompileOptions::default()
+            .macro_define("HISTOGRAM_TYPE", T::SLANG_TYPE)
+            .macro_define("HISTOGRAM_BINS", num_bins);
+        let load = |entry_point| {
+            GpuFunction::from_file_with_options(
+                backend,
+                compiler,
+                "slang_hal_histogram",
+                entry_point,
+                &options,
+            )
+        };
+        Ok(Self {
+            clear: load("histogram_clear")?,
+            histogram: load("histogram")?,
+            num_bins,
+            _scalar: PhantomData,
+        })
+    }
+
+    /// The number of bins.
+    pub fn num_bins(&self) -> u32 {
+        self.num_bins
+    }
+
+    /// Writes the histogram of the elements of `input` to the first [`Self::num_bins`] elements
+    /// of `bins`.
+    ///
+    /// See [`Self::accumulate`] for details.
+    pub fn compute(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<T>,
+        range: [T; 2],
+        bins: &mut B::Buffer<u32>,
+    ) -> Result<(), B::Error> {
+        assert!(bins.len() >= self.num_bins as usize, "`bins` is too small");
+        let args = ClearArgs::<B> { bins };
+        self.clear
+            .launch(backend, pass, &args, [self.num_bins, 1, 1])?;
+        self.accumulate(backend, pass, input, range, bins)
+    }
+
+    /// Adds the histogram of the elements of `input` to the first [`Self::num_bins`] elements of
+    /// `bins`, e.g. to compute the histogram of several buffers.
+    ///
+    /// `range` is the `[min, max)` range split into the bins. The elements outside of it, and
+    /// NaNs, aren’t counted. The bin of each element is computed in single precision, so
+    /// integers close to the bounds of a bin may be counted in its neighbor.
+    ///
+    /// Panics if `bins` is smaller than [`Self::num_bins`].
+    pub fn accumulate(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<T>,
+        range: [T; 2],
+        bins: &mut B::Buffer<u32>,
+    ) -> Result<(), B::Error> {
+        assert!(bins.len() >= self.num_bins as usize, "`bins` is too small");
+        let len = super::kernel_len(input.len())?;
+        let num_workgroups = len.div_ceil(WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS);
+        // NOTE: the bounds are passed as 32-bit words, like all the scalars of the utilities, and
+        //       cast back to `T` by the kernel.
+        let args = HistogramArgs::<B, T> {
+            len,
+            num_workgroups,
+            range_min_bits: bytemuck::cast(range[0]),
+            range_max_bits: bytemuck::cast(range[1]),
+            input,
+            bins,
+        };
+        self.histogram.launch(
+            backend,
+            pass,
+            &args,
+            [num_workgroups * WORKGROUP_SIZE, 1, 1],
+        )
+    }
+}
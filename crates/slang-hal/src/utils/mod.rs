@@ -0,0 +1,261 @@
+//! Common GPU algorithms (reductions, prefix sums, sorting, histograms, …), portable across
+//! backends.
+//!
+//! The utilities run the Slang kernels bundled with this crate, which must be added to the
+//! compiler with [`register_shaders`] before creating them.
+//!
+//! All the utilities but [`Primitives`] synchronize their workgroups through shared memory, so
+//! creating them fails with an [`UnsupportedError`] on backends without it (i.e., reporting a
+//! zero [`DeviceLimits::max_shared_memory_size`], like the CPU backend).
+//!
+//! [`DeviceLimits::max_shared_memory_size`]: crate::backend::DeviceLimits::max_shared_memory_size
+
+use crate::backend::{Backend, DeviceValue, UnsupportedError};
+use crate::shader::ShaderArgsError;
+use bytemuck::Pod;
+use minislang::SlangCompiler;
+
+pub use histogram::Histogram;
+pub use prefix_sum::PrefixSum;
+pub use primitives::{ConvertScalar, Primitives};
+pub use radix_sort::RadixSort;
+pub use reduce::{Reduce, ReduceOp};
+
+/// The Slang kernels used by the utilities.
+pub const SHADERS: include_dir::Dir<'static> =
+    include_dir::include_dir!("$CARGO_MANIFEST_DIR/shaders/utils");
+
+/// Adds the Slang kernels used by the utilities, and the `slang_hal_grid` module they import,
+/// to `compiler`.
+pub fn register_shaders(compiler: &mut SlangCompiler) {
+    compiler.add_dir(SHADERS);
+    crate::grid::register_shaders(compiler);
+}
+
+/// The length of a buffer processed by the utility kernels, which index elements with 32 bits.
+fn kernel_len(len: usize) -> Result<u32, ShaderArgsError> {
+    u32::try_from(len).map_err(|_| ShaderArgsError::TooManyThreads {
+        num_threads: len as u64,
+        max_threads: u32::MAX as u64,
+    })
+}
+
+/// Fails if `backend` doesn’t support the workgroup shared memory the utility kernels rely on.
+fn check_shared_memory<B: Backend>(backend: &B) -> Result<(), B::Error> {
+    if backend.limits()?.max_shared_memory_size == 0 {
+        return Err(UnsupportedError::new::<B>("workgroup shared memory").into());
+    }
+    Ok(())
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A scalar type supported by the utilities, i.e., `u32`, `i32`, or `f32`.
+pub trait UtilsScalar: DeviceValue + Pod + sealed::Sealed {
+    /// The name of this type in Slang.
+    const SLANG_TYPE: &'static str;
+    /// The smallest value of this type (`-inf` for floats), as a Slang expression.
+    const SLANG_LOWEST: &'static str;
+    /// The largest value of this type (`inf` for floats), as a Slang expression.
+    const SLANG_HIGHEST: &'static str;
+}
+
+macro_rules! impl_utils_scalar {
+    ($($t: ty: $slang: literal, $lowest: literal, $highest: literal);*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl UtilsScalar for $t {
+            const SLANG_TYPE: &'static str = $slang;
+            const SLANG_LOWEST: &'static str = $lowest;
+            const SLANG_HIGHEST: &'static str = $highest;
+        }
+    )*};
+}
+
+impl_utils_scalar!(
+    u32: "uint", "0u", "0xffffffffu";
+    i32: "int", "(-2147483647 - 1)", "2147483647";
+    f32: "float", "asfloat(0xff800000u)", "asfloat(0x7f800000u)"
+);
+
+/// Declares the arguments of a utility kernel: the values passed by value, then the buffers.
+macro_rules! utils_args {
+    ($name: ident<$($t: ident),*> { $($value: ident: $vty: ty),*; $($buffer: ident: $bty: ty),* }) => {
+        struct $name<'c, B: Backend, $($t: DeviceValue),*> {
+            $($value: $vty,)*
+            $($buffer: &'c $bty,)*
+        }
+
+        impl<'b, B: Backend, $($t: DeviceValue),*> ShaderArgs<'b, B> for $name<'_, B, $($t),*> {
+            fn write_arg<'a>(
+                &'b self,
+                binding: ShaderBinding,
+                name: &str,
+                dispatch: &mut B::Dispatch<'a>,
+            ) -> Result<(), ShaderArgsError>
+            where
+                'b: 'a,
+            {
+                match name {
+                    $(stringify!($value) => {
+                        ShaderArgs::<'b, B>::write_arg(&self.$value, binding, name, dispatch)
+                    })*
+                    $(stringify!($buffer) => {
+                        ShaderArgs::<'b, B>::write_arg(self.$buffer, binding, name, dispatch)
+                    })*
+                    _ => Err(ShaderArgsError::ArgNotFound(name.to_owned())),
+                }
+            }
+
+            fn arg_names(&self) -> Vec<String> {
+                vec![$(stringify!($value).to_string(),)* $(stringify!($buffer).to_string()),*]
+            }
+        }
+    };
+}
+
+pub mod histogram;
+pub mod prefix_sum;
+pub mod primitives;
+pub mod radix_sort;
+pub mod reduce;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Encoder, Mock, MockEvent, MockGrid};
+    use wgpu::BufferUsages;
+
+    fn compiler() -> SlangCompiler {
+        let mut compiler = SlangCompiler::new(vec![]);
+        register_shaders(&mut compiler);
+        compiler
+    }
+
+    /// The entry points and workgroup counts of the dispatches recorded by `backend`.
+    fn dispatches(backend: &Mock) -> Vec<(String, [u32; 3])> {
+        backend
+            .dispatches()
+            .into_iter()
+            .map(|event| match event {
+                MockEvent::Dispatch {
+                    entry_point,
+                    grid: MockGrid::Direct(grid),
+                    ..
+                } => (entry_point, grid),
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect()
+    }
+
+    /// Records `f` into a pass of `backend`, and returns the dispatches it recorded.
+    fn record(
+        backend: &Mock,
+        f: impl FnOnce(&mut <Mock as Backend>::Pass),
+    ) -> Vec<(String, [u32; 3])> {
+        backend.clear();
+        let mut encoder = backend.begin_encoding();
+        f(&mut encoder.begin_pass("test"));
+        backend.submit(encoder).unwrap();
+        dispatches(backend)
+    }
+
+    fn dispatch(entry_point: &str, num_workgroups: u32) -> (String, [u32; 3]) {
+        (entry_point.to_string(), [num_workgroups, 1, 1])
+    }
+
+    #[test]
+    fn reduce_runs_in_two_passes() {
+        let backend = Mock::new();
+        let reduce = Reduce::<Mock, f32>::sum(&backend, &compiler()).unwrap();
+        let small = backend
+            .init_buffer(&[1.0; 100], BufferUsages::STORAGE)
+            .unwrap();
+        let large = backend
+            .init_buffer(&[1.0; 1000], BufferUsages::STORAGE)
+            .unwrap();
+        let mut output = backend.init_buffer(&[0.0], BufferUsages::STORAGE).unwrap();
+
+        let recorded = record(&backend, |pass| {
+            reduce.reduce(&backend, pass, &small, &mut output).unwrap()
+        });
+        assert_eq!(recorded, [dispatch("reduce", 1)]);
+        let recorded = record(&backend, |pass| {
+            reduce.reduce(&backend, pass, &large, &mut output).unwrap()
+        });
+        assert_eq!(recorded, [dispatch("reduce", 4), dispatch("reduce", 1)]);
+    }
+
+    #[test]
+    fn prefix_sum_scans_the_block_sums() {
+        let backend = Mock::new();
+        let scan = PrefixSum::<Mock, u32>::new(&backend, &compiler()).unwrap();
+        let input = backend
+            .init_buffer(&[1; 1000], BufferUsages::STORAGE)
+            .unwrap();
+        let mut output = backend
+            .init_buffer(&[0; 1000], BufferUsages::STORAGE)
+            .unwrap();
+
+        let recorded = record(&backend, |pass| {
+            scan.inclusive(&backend, pass, &input, &mut output).unwrap()
+        });
+        assert_eq!(
+            recorded,
+            [
+                dispatch("scan_blocks", 2),
+                dispatch("scan_blocks", 1),
+                dispatch("scan_add_block_offsets", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn radix_sort_runs_a_pass_per_digit() {
+        let backend = Mock::new();
+        let sort = RadixSort::<Mock>::new(&backend, &compiler()).unwrap();
+        let mut keys = backend
+            .init_buffer(&[0; 1000], BufferUsages::STORAGE)
+            .unwrap();
+
+        let recorded = record(&backend, |pass| {
+            sort.sort(&backend, pass, &mut keys).unwrap()
+        });
+        let digit_pass = [
+            dispatch("radix_histogram", 4),
+            dispatch("scan_blocks", 1),
+            dispatch("radix_scatter", 4),
+        ];
+        assert_eq!(recorded, vec![digit_pass; 8].concat());
+
+        let mut single = backend.init_buffer(&[0], BufferUsages::STORAGE).unwrap();
+        let recorded = record(&backend, |pass| {
+            sort.sort(&backend, pass, &mut single).unwrap()
+        });
+        assert!(recorded.is_empty());
+    }
+
+    #[test]
+    fn histogram_clears_the_bins() {
+        let backend = Mock::new();
+        let histogram = Histogram::<Mock, f32>::new(&backend, &compiler(), 16).unwrap();
+        let input = backend
+            .init_buffer(&[0.5; 1000], BufferUsages::STORAGE)
+            .unwrap();
+        let mut bins = backend
+            .init_buffer(&[0; 16], BufferUsages::STORAGE)
+            .unwrap();
+
+        let recorded = record(&backend, |pass| {
+            histogram
+                .compute(&backend, pass, &input, [0.0, 1.0], &mut bins)
+                .unwrap()
+        });
+        assert_eq!(
+            recorded,
+            [dispatch("histogram_clear", 1), dispatch("histogram", 4)]
+        );
+    }
+}
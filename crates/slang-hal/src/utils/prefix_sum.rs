@@ -0,0 +1,171 @@
+//! Prefix sums (scans) of buffers.
+
+use super::UtilsScalar;
+use crate::backend::{Backend, Buffer, DeviceValue, ShaderBinding};
+use crate::function::GpuFunction;
+use crate::pool::BufferPool;
+use crate::shader::{Shader, ShaderArgs, ShaderArgsError};
+use minislang::{CompileOptions, SlangCompiler};
+use std::marker::PhantomData;
+use wgpu::BufferUsages;
+
+/// The workgroup size of the scan kernels.
+const WORKGROUP_SIZE: u32 = 256;
+
+/// The number of elements scanned by each workgroup.
+const BLOCK_SIZE: u32 = 2 * WORKGROUP_SIZE;
+
+utils_args!(ScanBlocksArgs<T> {
+    len: u32, inclusive: u32;
+    input: B::Buffer<T>, output: B::Buffer<T>, block_sums: B::Buffer<T>
+});
+
+utils_args!(AddBlockOffsetsArgs<T> {
+    len: u32;
+    block_offsets: B::Buffer<T>, data: B::Buffer<T>
+});
+
+/// Computes the prefix sums (scans) of buffers of `T`.
+///
+/// Each workgroup scans a block of 512 elements with the work-efficient (Blelloch) algorithm.
+/// The sums of the blocks are then scanned recursively, and added to the elements of the
+/// following blocks. The number of blocks is limited by the maximum workgroup count of the
+/// device along x (e.g. 65535 blocks, or about 33.5 million elements, on WebGpu).
+pub struct PrefixSum<B: Backend, T: UtilsScalar> {
+    scan_blocks: GpuFunction<B>,
+    add_block_offsets: GpuFunction<B>,
+    /// The buffers holding the sums of the blocks.
+    pool: BufferPool<B>,
+    _scalar: PhantomData<T>,
+}
+
+impl<B: Backend, T: UtilsScalar> Shader<B> for PrefixSum<B, T> {
+    fn from_backend(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        super::check_shared_memory(backend)?;
+        let options = CompileOptions::default().macro_define("SCAN_TYPE", T::SLANG_TYPE);
+        let load = |entry_point| {
+            GpuFunction::from_file_with_options(
+                backend,
+                compiler,
+                "slang_hal_prefix_sum",
+                entry_point,
+                &options,
+            )
+        };
+        Ok(Self {
+            scan_blocks: load("scan_blocks")?,
+            add_block_offsets: load("scan_add_block_offsets")?,
+            pool: BufferPool::new(),
+            _scalar: PhantomData,
+        })
+    }
+}
+
+impl<B: Backend, T: UtilsScalar> PrefixSum<B, T> {
+    /// Compiles and loads the scan kernels.
+    pub fn new(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::from_backend(backend, compiler)
+    }
+
+    /// Writes the exclusive prefix sums of `input` to `output`: `output[i]` is the sum of the
+    /// elements of `input` before `i`.
+    ///
+    /// Panics if `output` is smaller than `input`. See [`Self::inclusive`] for details.
+    pub fn exclusive(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<T>,
+        output: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        assert!(output.len() >= input.len(), "`output` is too small");
+        self.scan(
+            backend,
+            pass,
+            super::kernel_len(input.len())?,
+            false,
+            input,
+            output,
+        )
+    }
+
+    /// Writes the inclusive prefix sums of `input` to `output`: `output[i]` is the sum of the
+    /// elements of `input` up to `i`.
+    ///
+    /// `input` and `output` must be different buffers. The sums of the blocks are stored in
+    /// buffers recycled across calls, so scans recorded on different queues must not run
+    /// concurrently.
+    ///
+    /// Panics if `output` is smaller than `input`.
+    pub fn inclusive(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<T>,
+        output: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        assert!(output.len() >= input.len(), "`output` is too small");
+        self.scan(
+            backend,
+            pass,
+            super::kernel_len(input.len())?,
+            true,
+            input,
+            output,
+        )
+    }
+
+    fn scan(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        len: u32,
+        inclusive: bool,
+        input: &B::Buffer<T>,
+        output: &B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        let num_blocks = len.div_ceil(BLOCK_SIZE).max(1);
+        // SAFETY: the sum of each block is written by `scan_blocks` before being read.
+        let block_sums = unsafe {
+            self.pool
+                .acquire::<T>(backend, num_blocks as usize, BufferUsages::STORAGE)?
+        };
+        let args = ScanBlocksArgs::<B, T> {
+            len,
+            inclusive: inclusive as u32,
+            input,
+            output,
+            block_sums: &block_sums,
+        };
+        self.scan_blocks
+            .launch(backend, pass, &args, [num_blocks * WORKGROUP_SIZE, 1, 1])?;
+
+        if num_blocks > 1 {
+            // SAFETY: the offsets are written by the scan of the block sums before being read.
+            let block_offsets = unsafe {
+                self.pool
+                    .acquire::<T>(backend, num_blocks as usize, BufferUsages::STORAGE)?
+            };
+            self.scan(
+                backend,
+                pass,
+                num_blocks,
+                false,
+                &block_sums,
+                &block_offsets,
+            )?;
+            let args = AddBlockOffsetsArgs::<B, T> {
+                len,
+                block_offsets: &block_offsets,
+                data: output,
+            };
+            self.add_block_offsets.launch(
+                backend,
+                pass,
+                &args,
+                [num_blocks * WORKGROUP_SIZE, 1, 1],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+//! Element-wise kernels: fills, gathers, scatters, and conversions.
+
+use super::UtilsScalar;
+use super::sealed::Sealed;
+use crate::backend::{Backend, Buffer, DeviceValue, ShaderBinding, UnsupportedError};
+use crate::function::GpuFunction;
+use crate::shader::{Shader, ShaderArgs, ShaderArgsError};
+use half::f16;
+use minislang::{CompileOptions, SlangCompiler};
+use std::collections::HashMap;
+
+utils_args!(FillArgs<T> {
+    value: u32;
+    data: B::Buffer<T>
+});
+
+utils_args!(IotaArgs<> {
+    ;
+    data: B::Buffer<u32>
+});
+
+utils_args!(IndexedArgs<T> {
+    ;
+    indices: B::Buffer<u32>, input: B::Buffer<T>, output: B::Buffer<T>
+});
+
+utils_args!(ConvertArgs<I, O> {
+    ;
+    input: B::Buffer<I>, output: B::Buffer<O>
+});
+
+impl Sealed for f16 {}
+
+/// A scalar type [`Primitives::convert`] converts from and to, i.e., `u32`, `f32`, or `f16`.
+pub trait ConvertScalar: DeviceValue + Sealed {
+    /// The name of this type in Slang.
+    const SLANG_TYPE: &'static str;
+}
+
+impl ConvertScalar for u32 {
+    const SLANG_TYPE: &'static str = "uint";
+}
+
+impl ConvertScalar for f32 {
+    const SLANG_TYPE: &'static str = "float";
+}
+
+impl ConvertScalar for f16 {
+    const SLANG_TYPE: &'static str = "half";
+}
+
+/// The Slang types of the [`ConvertScalar`] types.
+const CONVERT_TYPES: [&str; 3] = [
+    <u32 as ConvertScalar>::SLANG_TYPE,
+    <f32 as ConvertScalar>::SLANG_TYPE,
+    <f16 as ConvertScalar>::SLANG_TYPE,
+];
+
+/// Trivial element-wise kernels: filling a buffer with a constant or with its indices, gathering
+/// and scattering elements by index, and converting between `u32`, `f32`, and `f16` buffers.
+///
+/// The kernels are launched with [`GpuFunction::launch_folded`], so the buffers aren’t limited
+/// by the maximum workgroup count of the device.
+pub struct Primitives<B: Backend> {
+    fill: GpuFunction<B>,
+    iota: GpuFunction<B>,
+    gather: GpuFunction<B>,
+    scatter: GpuFunction<B>,
+    /// The conversion kernels, by Slang input and output types.
+    convert: HashMap<(&'static str, &'static str), GpuFunction<B>>,
+}
+
+impl<B: Backend> Shader<B> for Primitives<B> {
+    fn from_backend(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        let load = |entry_point| {
+            GpuFunction::from_file(backend, compiler, "slang_hal_primitives", entry_point)
+        };
+
+        // NOTE: kernels using `half` are demoted to `float` by backends without 16-bit floats,
+        //       which would misread `f16` buffers, so these conversions aren’t loaded at all.
+        let shader_f16 = backend.limits()?.shader_f16;
+        let mut convert = HashMap::new();
+        for input in CONVERT_TYPES {
+            for output in CONVERT_TYPES {
+                if input == output
+                    || (!shader_f16 && (input == f16::SLANG_TYPE || output == f16::SLANG_TYPE))
+                {
+                    continue;
+                }
+                let options = CompileOptions::default()
+                    .macro_define("CONVERT_INPUT", input)
+                    .macro_define("CONVERT_OUTPUT", output);
+                let function = GpuFunction::from_file_with_options(
+                    backend,
+                    compiler,
+                    "slang_hal_convert",
+                    "convert",
+                    &options,
+                )?;
+                convert.insert((input, output), function);
+            }
+        }
+
+        Ok(Self {
+            fill: load("fill")?,
+            iota: load("iota")?,
+            gather: load("gather")?,
+            scatter: load("scatter")?,
+            convert,
+        })
+    }
+}
+
+impl<B: Backend> Primitives<B> {
+    /// Compiles and loads the element-wise kernels.
+    ///
+    /// The conversions from and to `f16` are only loaded if the device supports 16-bit floats
+    /// (see [`DeviceLimits::shader_f16`](crate::backend::DeviceLimits::shader_f16)).
+    pub fn new(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::from_backend(backend, compiler)
+    }
+
+    /// Sets every element of `data` to `value`.
+    pub fn fill<T: UtilsScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        data: &mut B::Buffer<T>,
+        value: T,
+    ) -> Result<(), B::Error> {
+        // NOTE: the kernel operates on 32-bit words, like all the scalars of the utilities.
+        let args = FillArgs::<B, T> {
+            value: bytemuck::cast(value),
+            data,
+        };
+        self.fill
+            .launch_folded(backend, pass, &args, super::kernel_len(data.len())?)
+    }
+
+    /// Sets each element of `data` to its index, i.e., writes `0, 1, …, n - 1`.
+    pub fn iota(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        data: &mut B::Buffer<u32>,
+    ) -> Result<(), B::Error> {
+        let args = IotaArgs::<B> { data };
+        self.iota
+            .launch_folded(backend, pass, &args, super::kernel_len(data.len())?)
+    }
+
+    /// Sets `output[i]` to `input[indices[i]]` for each element of `indices`.
+    ///
+    /// The indices must be smaller than the length of `input`. Panics if `output` is smaller
+    /// than `indices`.
+    pub fn gather<T: UtilsScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        indices: &B::Buffer<u32>,
+        input: &B::Buffer<T>,
+        output: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        assert!(output.len() >= indices.len(), "`output` is too small");
+        let args = IndexedArgs::<B, T> {
+            indices,
+            input,
+            output,
+        };
+        self.gather
+            .launch_folded(backend, pass, &args, super::kernel_len(indices.len())?)
+    }
+
+    /// Sets `output[indices[i]]` to `input[i]` for each element of `indices`.
+    ///
+    /// The indices must be smaller than the length of `output`. If several indices are equal,
+    /// which of their elements is written is unspecified. Panics if `input` is smaller than
+    /// `indices`.
+    pub fn scatter<T: UtilsScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        indices: &B::Buffer<u32>,
+        input: &B::Buffer<T>,
+        output: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        assert!(input.len() >= indices.len(), "`input` is too small");
+        let args = IndexedArgs::<B, T> {
+            indices,
+            input,
+            output,
+        };
+        self.scatter
+            .launch_folded(backend, pass, &args, super::kernel_len(indices.len())?)
+    }
+
+    /// Converts each element of `input` to `O`, and writes it to `output`.
+    ///
+    /// Floats are converted to `u32` by rounding toward zero, which is only defined for values
+    /// in the range of `u32`. Fails if `I` or `O` is `f16` and the device doesn’t support 16-bit
+    /// floats.
+    ///
+    /// Panics if `I` and `O` are the same type, or if `output` is smaller than `input`.
+    pub fn convert<I: ConvertScalar, O: ConvertScalar>(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<I>,
+        output: &mut B::Buffer<O>,
+    ) -> Result<(), B::Error> {
+        assert_ne!(
+            I::SLANG_TYPE,
+            O::SLANG_TYPE,
+            "the input and output types must differ"
+        );
+        assert!(output.len() >= input.len(), "`output` is too small");
+        let Some(function) = self.convert.get(&(I::SLANG_TYPE, O::SLANG_TYPE)) else {
+            return Err(UnsupportedError::new::<B>("16-bit floats").into());
+        };
+        let args = ConvertArgs::<B, I, O> { input, output };
+        function.launch_folded(backend, pass, &args, super::kernel_len(input.len())?)
+    }
+}
@@ -0,0 +1,194 @@
+//! Sorting of `u32` keys, optionally with values.
+
+use super::PrefixSum;
+use crate::backend::{Backend, Buffer, DeviceValue, ShaderBinding};
+use crate::function::GpuFunction;
+use crate::pool::BufferPool;
+use crate::shader::{Shader, ShaderArgs, ShaderArgsError};
+use minislang::{CompileOptions, SlangCompiler};
+use wgpu::BufferUsages;
+
+/// The workgroup size of the sort kernels, which is also the number of keys per block.
+const WORKGROUP_SIZE: u32 = 256;
+
+/// The number of bits of the digit sorted by each pass.
+const RADIX_BITS: u32 = 4;
+
+/// The number of values of a digit.
+const RADIX_DIGITS: u32 = 1 << RADIX_BITS;
+
+utils_args!(HistogramArgs<> {
+    len: u32, shift: u32, num_blocks: u32;
+    keys: B::Buffer<u32>, block_histograms: B::Buffer<u32>
+});
+
+utils_args!(ScatterArgs<> {
+    len: u32, shift: u32, num_blocks: u32;
+    keys_in: B::Buffer<u32>, block_offsets: B::Buffer<u32>, keys_out: B::Buffer<u32>
+});
+
+utils_args!(ScatterPairsArgs<> {
+    len: u32, shift: u32, num_blocks: u32;
+    keys_in: B::Buffer<u32>, block_offsets: B::Buffer<u32>, keys_out: B::Buffer<u32>,
+    values_in: B::Buffer<u32>, values_out: B::Buffer<u32>
+});
+
+/// Sorts buffers of `u32` keys, optionally reordering `u32` values (e.g. indices) along with
+/// them.
+///
+/// This is a stable least-significant-digit radix sort, running 8 passes of 4 bits. Each pass
+/// counts the digits of each block of 256 keys, scans the counts with a [`PrefixSum`], and moves
+/// the keys to their sorted position. The temporary buffers are recycled across calls, so sorts
+/// recorded on different queues must not run concurrently. The number of blocks is limited by
+/// the maximum workgroup count of the device along x (e.g. 65535 blocks, or about 16.7 million
+/// keys, on WebGpu).
+pub struct RadixSort<B: Backend> {
+    histogram: GpuFunction<B>,
+    scatter: GpuFunction<B>,
+    scatter_pairs: GpuFunction<B>,
+    scan: PrefixSum<B, u32>,
+    pool: BufferPool<B>,
+}
+
+impl<B: Backend> Shader<B> for RadixSort<B> {
+    fn from_backend(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        super::check_shared_memory(backend)?;
+        let load = |entry_point, options: &CompileOptions| {
+            GpuFunction::from_file_with_options(
+                backend,
+                compiler,
+                "slang_hal_radix_sort",
+                entry_point,
+                options,
+            )
+        };
+        let options = CompileOptions::default();
+        let pairs_options = CompileOptions::default().macro_define("RADIX_SORT_VALUES", 1);
+        Ok(Self {
+            histogram: load("radix_histogram", &options)?,
+            scatter: load("radix_scatter", &options)?,
+            scatter_pairs: load("radix_scatter", &pairs_options)?,
+            scan: PrefixSum::from_backend(backend, compiler)?,
+            pool: BufferPool::new(),
+        })
+    }
+}
+
+impl<B: Backend> RadixSort<B> {
+    /// Compiles and loads the sort kernels.
+    pub fn new(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::from_backend(backend, compiler)
+    }
+
+    /// Sorts `keys` in increasing order.
+    pub fn sort(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        keys: &mut B::Buffer<u32>,
+    ) -> Result<(), B::Error> {
+        self.sort_impl(backend, pass, keys, None)
+    }
+
+    /// Sorts `keys` in increasing order, and moves each element of `values` to the position of
+    /// the key it shares an index with.
+    ///
+    /// Keys that are equal keep their relative order. Panics if `keys` and `values` don’t have
+    /// the same length.
+    pub fn sort_pairs(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        keys: &mut B::Buffer<u32>,
+        values: &mut B::Buffer<u32>,
+    ) -> Result<(), B::Error> {
+        assert_eq!(
+            keys.len(),
+            values.len(),
+            "`keys` and `values` must have the same length"
+        );
+        self.sort_impl(backend, pass, keys, Some(values))
+    }
+
+    fn sort_impl(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        keys: &mut B::Buffer<u32>,
+        values: Option<&mut B::Buffer<u32>>,
+    ) -> Result<(), B::Error> {
+        let len = super::kernel_len(keys.len())?;
+        if len <= 1 {
+            return Ok(());
+        }
+
+        let num_blocks = len.div_ceil(WORKGROUP_SIZE);
+        let num_counts = (RADIX_DIGITS * num_blocks) as usize;
+        // SAFETY: each pass writes every element of the buffers it reads afterwards: the
+        //         histograms, their scan, and the keys (and values) of the next pass.
+        let acquire = |size| unsafe {
+            self.pool
+                .acquire::<u32>(backend, size, BufferUsages::STORAGE)
+        };
+        let block_histograms = acquire(num_counts)?;
+        let mut block_offsets = acquire(num_counts)?;
+        let tmp_keys = acquire(len as usize)?;
+        let tmp_values = values.as_ref().map(|_| acquire(len as usize)).transpose()?;
+
+        // NOTE: the keys go back and forth between `keys` and `tmp_keys`. There is an even number
+        //       of passes, so the sorted keys end up in `keys`.
+        for digit in 0..32 / RADIX_BITS {
+            let shift = digit * RADIX_BITS;
+            let (keys_in, keys_out) = if digit % 2 == 0 {
+                (&*keys, &*tmp_keys)
+            } else {
+                (&*tmp_keys, &*keys)
+            };
+
+            let args = HistogramArgs::<B> {
+                len,
+                shift,
+                num_blocks,
+                keys: keys_in,
+                block_histograms: &block_histograms,
+            };
+            self.histogram.launch(backend, pass, &args, [len, 1, 1])?;
+            self.scan
+                .exclusive(backend, pass, &block_histograms, &mut block_offsets)?;
+
+            match (&values, &tmp_values) {
+                (Some(values), Some(tmp_values)) => {
+                    let (values_in, values_out) = if digit % 2 == 0 {
+                        (&**values, &**tmp_values)
+                    } else {
+                        (&**tmp_values, &**values)
+                    };
+                    let args = ScatterPairsArgs::<B> {
+                        len,
+                        shift,
+                        num_blocks,
+                        keys_in,
+                        block_offsets: &block_offsets,
+                        keys_out,
+                        values_in,
+                        values_out,
+                    };
+                    self.scatter_pairs
+                        .launch(backend, pass, &args, [len, 1, 1])?;
+                }
+                _ => {
+                    let args = ScatterArgs::<B> {
+                        len,
+                        shift,
+                        num_blocks,
+                        keys_in,
+                        block_offsets: &block_offsets,
+                        keys_out,
+                    };
+                    self.scatter.launch(backend, pass, &args, [len, 1, 1])?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
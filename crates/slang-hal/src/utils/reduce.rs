@@ -0,0 +1,150 @@
+//! Reduction of a buffer to a single value.
+
+use super::UtilsScalar;
+use crate::backend::{Backend, Buffer, DeviceValue, ShaderBinding};
+use crate::function::GpuFunction;
+use crate::pool::BufferPool;
+use crate::shader::{ShaderArgs, ShaderArgsError};
+use minislang::{CompileOptions, SlangCompiler};
+use std::marker::PhantomData;
+use wgpu::BufferUsages;
+
+/// The workgroup size of the `reduce` kernel.
+const WORKGROUP_SIZE: u32 = 256;
+
+/// The maximum number of workgroups of the first reduction pass, whose partial results are
+/// reduced by a single workgroup.
+const MAX_PARTIALS: u32 = WORKGROUP_SIZE;
+
+/// The operator of a [`Reduce`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+    /// The sum of the elements.
+    Sum,
+    /// The smallest element.
+    Min,
+    /// The largest element.
+    Max,
+    /// A custom operator.
+    Custom {
+        /// A Slang expression combining the values `a` and `b`, e.g. `a * b`.
+        ///
+        /// The operator must be associative and commutative, since the elements aren’t combined
+        /// in order.
+        op: String,
+        /// A Slang expression of the identity of the operator, e.g. `1`.
+        identity: String,
+    },
+}
+
+utils_args!(ReduceArgs<T> {
+    len: u32, num_workgroups: u32;
+    input: B::Buffer<T>, output: B::Buffer<T>
+});
+
+/// Reduces buffers of `T` to a single value with an associative operator, e.g. their sum.
+///
+/// The reduction runs in two passes: each workgroup of the first pass reduces a part of the
+/// buffer, and a single workgroup reduces their partial results.
+pub struct Reduce<B: Backend, T: UtilsScalar> {
+    kernel: GpuFunction<B>,
+    /// The buffers holding the partial results.
+    pool: BufferPool<B>,
+    _scalar: PhantomData<T>,
+}
+
+impl<B: Backend, T: UtilsScalar> Reduce<B, T> {
+    /// Compiles and loads the reduction kernel for `op`.
+    pub fn new(backend: &B, compiler: &SlangCompiler, op: ReduceOp) -> Result<Self, B::Error> {
+        super::check_shared_memory(backend)?;
+        let (op, identity) = match &op {
+            ReduceOp::Sum => ("a + b", "0"),
+            ReduceOp::Min => ("min(a, b)", T::SLANG_HIGHEST),
+            ReduceOp::Max => ("max(a, b)", T::SLANG_LOWEST),
+            ReduceOp::Custom { op, identity } => (op.as_str(), identity.as_str()),
+        };
+        let options = CompileOptions::default()
+            .macro_define("REDUCE_TYPE", T::SLANG_TYPE)
+            .macro_define("REDUCE_OP", op)
+            .macro_define("REDUCE_IDENTITY", identity);
+        Ok(Self {
+            kernel: GpuFunction::from_file_with_options(
+                backend,
+                compiler,
+                "slang_hal_reduce",
+                "reduce",
+                &options,
+            )?,
+            pool: BufferPool::new(),
+            _scalar: PhantomData,
+        })
+    }
+
+    /// A reduction computing the sum of the elements.
+    pub fn sum(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::new(backend, compiler, ReduceOp::Sum)
+    }
+
+    /// A reduction computing the smallest element.
+    pub fn min(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::new(backend, compiler, ReduceOp::Min)
+    }
+
+    /// A reduction computing the largest element.
+    pub fn max(backend: &B, compiler: &SlangCompiler) -> Result<Self, B::Error> {
+        Self::new(backend, compiler, ReduceOp::Max)
+    }
+
+    /// Reduces the elements of `input`, and writes the result to the first element of `output`.
+    ///
+    /// The result is the identity of the operator if `input` is empty. The partial results are
+    /// stored in buffers recycled across calls, so reductions recorded on different queues must
+    /// not run concurrently.
+    ///
+    /// Panics if `output` is empty.
+    pub fn reduce(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        input: &B::Buffer<T>,
+        output: &mut B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        assert!(!output.is_empty(), "`output` must not be empty");
+        let len = super::kernel_len(input.len())?;
+        let num_workgroups = len.div_ceil(WORKGROUP_SIZE).clamp(1, MAX_PARTIALS);
+        if num_workgroups == 1 {
+            return self.launch(backend, pass, len, 1, input, output);
+        }
+
+        // SAFETY: each partial result is written by the first pass before being read.
+        let partials = unsafe {
+            self.pool
+                .acquire::<T>(backend, num_workgroups as usize, BufferUsages::STORAGE)?
+        };
+        self.launch(backend, pass, len, num_workgroups, input, &partials)?;
+        self.launch(backend, pass, num_workgroups, 1, &partials, output)
+    }
+
+    fn launch(
+        &self,
+        backend: &B,
+        pass: &mut B::Pass,
+        len: u32,
+        num_workgroups: u32,
+        input: &B::Buffer<T>,
+        output: &B::Buffer<T>,
+    ) -> Result<(), B::Error> {
+        let args = ReduceArgs::<B, T> {
+            len,
+            num_workgroups,
+            input,
+            output,
+        };
+        self.kernel.launch(
+            backend,
+            pass,
+            &args,
+            [num_workgroups * WORKGROUP_SIZE, 1, 1],
+        )
+    }
+}